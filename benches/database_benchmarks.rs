@@ -10,6 +10,7 @@ fn create_test_transfer(id: u64) -> ProcessedTransfer {
         block_number: 1000 + id,
         transaction_hash: format!("0x{:064x}", id),
         log_index: 0,
+        transaction_index: 0,
         from_address: format!("0x{:040x}", id),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         amount: format!("{}", (id + 1) * 1000000000000000000),
@@ -0,0 +1,32 @@
+use std::process::Command;
+
+/// Embed the git commit the binary was built from as `GIT_COMMIT`
+///
+/// A `GIT_COMMIT` environment variable takes precedence (for builds without a
+/// `.git` directory, such as Docker); otherwise the short hash of `HEAD` is used,
+/// falling back to "unknown".
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    let commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.trim().is_empty())
+        .or_else(git_head_commit)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit.trim());
+}
+
+fn git_head_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
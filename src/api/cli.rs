@@ -1,7 +1,28 @@
 use clap::{Parser, Subcommand};
 use thiserror::Error;
-use crate::database::Database;
-use std::sync::Arc;
+use crate::blockchain::{is_unconfirmed, BackfillControl, BackfillSummary, BlockMonitor, BlockProcessor, MonitorError, RpcClient, TransferDetector};
+use crate::config::AppConfig;
+use crate::database::{Database, FlowSeriesPoint, TransactionRow, MAX_FLOW_SERIES_BUCKETS};
+use crate::models::AmountStorage;
+use crate::api::http::ErrorStatisticsResponse;
+use crate::api::tail::TransferTail;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+/// Column header of the transaction CSV export
+pub const TRANSACTION_CSV_HEADER: &str =
+    "id,block_number,transaction_hash,log_index,from_address,to_address,amount,timestamp,direction,created_at";
+
+/// Column header of the net-flow history CSV export
+pub const FLOW_CSV_HEADER: &str =
+    "bucket_start,bucket_end,inflow,outflow,net_flow,cumulative_inflow,cumulative_outflow";
+
+/// Rows serialized together by one worker of a parallel export
+const EXPORT_CHUNK_ROWS: usize = 1000;
 
 #[derive(Error, Debug)]
 pub enum CliError {
@@ -11,12 +32,16 @@ pub enum CliError {
     Database(#[from] crate::database::DbError),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("Indexer error: {0}")]
+    Indexer(#[from] crate::error::IndexerError),
+    #[error("Monitor error: {0}")]
+    Monitor(#[from] MonitorError),
 }
 
 #[derive(Parser)]
 #[command(name = "polygon-pol-indexer")]
 #[command(about = "A CLI tool for querying POL token net-flow data\nCreated by Agnivesh Kumar for Alfred Capital assignment")]
-#[command(version = "0.1.0")]
+#[command(version = crate::build_info::LONG_VERSION)]
 #[command(long_about = "Polygon POL Token Indexer - Real-time blockchain data analysis\n\nThis tool provides access to POL token transfer data and net-flow calculations\nfor Binance exchange addresses on the Polygon network.\n\nCreated by Agnivesh Kumar for Alfred Capital assignment")]
 pub struct Cli {
     #[command(subcommand)]
@@ -42,15 +67,230 @@ pub enum Commands {
         #[arg(short, long, default_value = "0")]
         offset: u32,
     },
+    /// List blocks that were skipped after processing failures
+    ListSkipped,
+    /// Verify stored block hashes still match the chain
+    Verify {
+        /// First block to verify
+        #[arg(long)]
+        from: u64,
+        /// Last block to verify (inclusive)
+        #[arg(long)]
+        to: u64,
+    },
+    /// Check the stored net-flow totals against a fresh sum of the transactions
+    Audit,
+    /// Export all stored transactions as CSV
+    Export {
+        /// File to write; prints to stdout when omitted
+        #[arg(long)]
+        output: Option<String>,
+        /// Worker threads serializing rows; the output is identical for any value
+        #[arg(long, default_value_t = 1)]
+        jobs: usize,
+    },
+    /// Export inflow, outflow and net flow per time bucket for analytics
+    ExportFlows {
+        /// Bucket size
+        #[arg(long, value_enum, default_value_t = FlowInterval::Day)]
+        interval: FlowInterval,
+        /// Output file format
+        #[arg(long, value_enum, default_value_t = FlowExportFormat::Csv)]
+        format: FlowExportFormat,
+        /// Start of the range, in Unix seconds of block time
+        #[arg(long)]
+        from: u64,
+        /// End of the range (inclusive), in Unix seconds of block time
+        #[arg(long)]
+        to: u64,
+        /// File to write; CSV is printed to stdout when omitted
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Follow the HTTP API and print new transfers as they are indexed
+    Tail {
+        /// Base URL of the indexer API server
+        #[arg(long, default_value = "http://127.0.0.1:8080")]
+        url: String,
+        /// Polling interval in milliseconds
+        #[arg(long, default_value = "1000")]
+        interval_ms: u64,
+        /// Start after this transaction id instead of the latest one
+        #[arg(long)]
+        since_id: Option<i64>,
+        /// Disable colored inflow/outflow output
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Show the errors recorded by a running indexer, most frequent first
+    Errors {
+        /// Base URL of the indexer API server
+        #[arg(long, default_value = "http://127.0.0.1:8080")]
+        url: String,
+    },
+    /// Show the crate version and git commit of this build
+    Version,
+    /// List the watched exchange addresses and their labels
+    ListAddresses,
+    /// Rebuild transactions and net flow from the raw-log journal
+    Rebuild,
+    /// Refresh query planner statistics (ANALYZE) and optionally compact the database
+    Maintain {
+        /// Also VACUUM; a running indexer's writes wait until it finishes
+        #[arg(long)]
+        vacuum: bool,
+    },
+    /// Index a historical block range; Ctrl-C stops it with a resume point
+    Backfill {
+        /// First block to index
+        #[arg(long, required_unless_present = "resume")]
+        from: Option<u64>,
+        /// Last block to index (inclusive)
+        #[arg(long, required_unless_present = "resume")]
+        to: Option<u64>,
+        /// Continue the latest backfill after the last block it completed
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        resume: bool,
+        /// Stop after this many seconds
+        #[arg(long)]
+        timeout_seconds: Option<u64>,
+    },
+    /// Process one block now and print the transfers detected in it
+    ProcessBlock {
+        /// Block to process
+        block: u64,
+        /// Only print the transfers; store nothing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write a sample configuration file
+    InitConfig {
+        /// Path of the configuration file to create
+        #[arg(long, default_value = "config.toml")]
+        path: String,
+        /// Overwrite the file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration after merging defaults, the config file and environment overrides
+    Show {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ConfigFormat::Toml)]
+        output: ConfigFormat,
+    },
+}
+
+/// Output format of `config show`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+/// Bucket size of `export-flows`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FlowInterval {
+    Hour,
+    Day,
+    Week,
+}
+
+impl FlowInterval {
+    /// Bucket length in seconds; buckets are aligned to multiples of it
+    pub fn seconds(self) -> u64 {
+        match self {
+            FlowInterval::Hour => 3_600,
+            FlowInterval::Day => 86_400,
+            FlowInterval::Week => 604_800,
+        }
+    }
+}
+
+/// Output format of `export-flows`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FlowExportFormat {
+    Csv,
+    /// Requires a build with the `parquet` feature
+    Parquet,
 }
 
 pub struct CliHandler {
     database: Arc<Database>,
+    rpc_client: Option<RpcClient>,
+    amount_storage: AmountStorage,
+    /// Classifies journaled logs when rebuilding
+    transfer_detector: TransferDetector,
+    /// Whether rebuilding keeps zero-amount transfers
+    track_zero_value_transfers: bool,
+    block_monitor: Option<BlockMonitor>,
+    block_processor: Option<BlockProcessor>,
+    /// Blocks needed on top of a processed block before its transfers count in net flow
+    min_confirmations: u64,
 }
 
 impl CliHandler {
     pub fn new(database: Arc<Database>) -> Self {
-        Self { database }
+        Self {
+            database,
+            rpc_client: None,
+            amount_storage: AmountStorage::default(),
+            transfer_detector: TransferDetector::new(),
+            track_zero_value_transfers: false,
+            block_monitor: None,
+            block_processor: None,
+            min_confirmations: 0,
+        }
+    }
+
+    /// Set the RPC client used by commands that query the chain
+    pub fn with_rpc_client(mut self, rpc_client: RpcClient) -> Self {
+        self.rpc_client = Some(rpc_client);
+        self
+    }
+
+    /// Set the block monitor used to backfill historical blocks
+    pub fn with_block_monitor(mut self, block_monitor: BlockMonitor) -> Self {
+        self.block_monitor = Some(block_monitor);
+        self
+    }
+
+    /// Set the processor used to process single blocks on demand
+    pub fn with_block_processor(mut self, block_processor: BlockProcessor) -> Self {
+        self.block_processor = Some(block_processor);
+        self
+    }
+
+    /// Store transfers of processed blocks as pending until `min_confirmations` blocks follow
+    pub fn with_min_confirmations(mut self, min_confirmations: u64) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
+    /// Set the detector used to classify journaled logs on rebuild
+    pub fn with_transfer_detector(mut self, transfer_detector: TransferDetector) -> Self {
+        self.transfer_detector = transfer_detector;
+        self
+    }
+
+    /// Set whether rebuilding keeps zero-amount transfers, matching `processing.track_zero_value_transfers`
+    pub fn with_track_zero_value_transfers(mut self, track_zero_value_transfers: bool) -> Self {
+        self.track_zero_value_transfers = track_zero_value_transfers;
+        self
+    }
+
+    /// Set the representation amounts are stored in, used to print them as POL
+    pub fn with_amount_storage(mut self, amount_storage: AmountStorage) -> Self {
+        self.amount_storage = amount_storage;
+        self
     }
 
     /// Handle net-flow query command
@@ -132,6 +372,661 @@ impl CliHandler {
         Ok(())
     }
 
+    /// Handle rebuild command by replaying the raw-log journal
+    pub async fn handle_rebuild(&self) -> Result<(), CliError> {
+        if self.database.get_raw_log_count()? == 0 {
+            return Err(CliError::Operation(
+                "The raw-log journal is empty; enable processing.journal_raw_logs and index blocks first".to_string(),
+            ));
+        }
+
+        let summary = self
+            .database
+            .rebuild_from_raw_logs(&self.transfer_detector, self.track_zero_value_transfers)?;
+        let net_flow_data = self.database.get_net_flow_data()?;
+
+        println!("=== Rebuild Complete ===");
+        println!("Logs Replayed:     {}", summary.logs_replayed);
+        println!("Transfers Stored:  {}", summary.transfers_stored);
+        println!("Undecodable Logs:  {}", summary.undecodable_logs);
+        println!("Net Flow:          {}", net_flow_data.net_flow);
+
+        Ok(())
+    }
+
+    /// Handle maintain command by running ANALYZE and, with `vacuum`, VACUUM
+    pub async fn handle_maintain(&self, vacuum: bool) -> Result<(), CliError> {
+        let started = std::time::Instant::now();
+        self.database.maintenance(vacuum)?;
+
+        println!("=== Maintenance Complete ===");
+        println!("Steps:             {}", if vacuum { "ANALYZE, VACUUM" } else { "ANALYZE" });
+        println!("Duration:          {:.2?}", started.elapsed());
+
+        Ok(())
+    }
+
+    /// Handle process-block command, storing the transfers not indexed yet unless `dry_run`
+    pub async fn handle_process_block(&self, block_number: u64, dry_run: bool) -> Result<(), CliError> {
+        let block_processor = self.block_processor.as_ref().ok_or_else(|| {
+            CliError::Operation("Processing a block requires an RPC endpoint".to_string())
+        })?;
+
+        let (block_hash, transfers) = block_processor
+            .process_block_with_hash(block_number)
+            .await
+            .map_err(|e| CliError::Operation(format!("Failed to process block {}: {}", block_number, e)))?;
+        let mut pending = false;
+        let stored = if dry_run {
+            0
+        } else {
+            if self.min_confirmations > 0 {
+                let chain_tip = block_processor
+                    .latest_block_number()
+                    .await
+                    .map_err(|e| CliError::Operation(format!("Failed to fetch the latest block: {}", e)))?;
+                pending = is_unconfirmed(block_number, self.min_confirmations, chain_tip);
+            }
+            self.database.store_processed_block(block_number, &block_hash, &transfers, pending)?
+        };
+
+        println!("=== Block {} ===", block_number);
+        println!("Hash:       {}", if block_hash.is_empty() { "(skipped)" } else { &block_hash });
+        println!("Transfers:  {}", transfers.len());
+        if dry_run {
+            println!("Stored:     none (dry run)");
+        } else {
+            println!("Stored:     {} new{}", stored, if pending { ", pending confirmation" } else { "" });
+        }
+        for transfer in &transfers {
+            let amount = AmountStorage::Wei
+                .stored_to_pol(&transfer.amount)
+                .unwrap_or_else(|_| format!("{} wei", transfer.amount));
+            println!();
+            println!("  Tx:        {}:{}", transfer.transaction_hash, transfer.log_index);
+            println!("  From:      {}", transfer.from_address);
+            println!("  To:        {}", transfer.to_address);
+            println!("  Amount:    {} POL", amount);
+            println!("  Direction: {:?}", transfer.direction);
+        }
+
+        Ok(())
+    }
+
+    /// Handle backfill command, stopping early on Ctrl-C or after `timeout_seconds`
+    pub async fn handle_backfill(&self, from: u64, to: u64, timeout_seconds: Option<u64>) -> Result<(), CliError> {
+        if from > to {
+            return Err(CliError::InvalidArgument(format!(
+                "--from ({}) must not be greater than --to ({})",
+                from, to
+            )));
+        }
+        let block_monitor = self.backfill_monitor()?;
+        let existing_rows = self.database.count_transactions_in_range(from, to)?;
+
+        let summary = Self::run_interruptible(timeout_seconds, |control| block_monitor.backfill(from, to, control)).await?;
+        Self::print_backfill_summary(&summary, existing_rows);
+
+        Ok(())
+    }
+
+    /// Handle backfill --resume, continuing the latest backfill where it stopped
+    pub async fn handle_resume_backfill(&self, timeout_seconds: Option<u64>) -> Result<(), CliError> {
+        let block_monitor = self.backfill_monitor()?;
+        let state = self
+            .database
+            .get_backfill_state()?
+            .ok_or_else(|| CliError::Operation("No backfill to resume".to_string()))?;
+        let existing_rows = self.database.count_transactions_in_range(state.resume_from(), state.to_block)?;
+
+        let summary = Self::run_interruptible(timeout_seconds, |control| block_monitor.resume_backfill(control)).await?;
+        Self::print_backfill_summary(&summary, existing_rows);
+
+        Ok(())
+    }
+
+    fn backfill_monitor(&self) -> Result<&BlockMonitor, CliError> {
+        self.block_monitor.as_ref().ok_or_else(|| {
+            CliError::Operation("Backfill requires an RPC endpoint".to_string())
+        })
+    }
+
+    /// Run a backfill that Ctrl-C or `timeout_seconds` stops after the block being written
+    async fn run_interruptible<F, Fut>(timeout_seconds: Option<u64>, backfill: F) -> Result<BackfillSummary, CliError>
+    where
+        F: FnOnce(BackfillControl) -> Fut,
+        Fut: std::future::Future<Output = Result<BackfillSummary, MonitorError>>,
+    {
+        let (cancel, cancelled) = tokio::sync::watch::channel(false);
+        let ctrl_c = tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                let _ = cancel.send(true);
+            }
+        });
+        let mut control = BackfillControl::new().with_cancellation(cancelled);
+        if let Some(timeout_seconds) = timeout_seconds {
+            control = control.with_deadline(Duration::from_secs(timeout_seconds));
+        }
+
+        let summary = backfill(control).await;
+        ctrl_c.abort();
+        Ok(summary?)
+    }
+
+    fn print_backfill_summary(summary: &BackfillSummary, existing_rows: u64) {
+        println!("=== Backfill {} ===", if summary.completed { "Complete" } else { "Stopped" });
+        println!("Range:             {} - {}", summary.from_block, summary.to_block);
+        println!("Existing Rows:     {}", existing_rows);
+        println!("Blocks Processed:  {}", summary.blocks_processed);
+        println!("Transfers Found:   {}", summary.transfers_found);
+        println!("Skipped Blocks:    {}", summary.skipped_blocks);
+        if !summary.completed {
+            println!("Resume With:       --resume (from block {})", summary.resume_from());
+        }
+    }
+
+    /// Handle list-skipped command
+    pub async fn handle_list_skipped(&self) -> Result<(), CliError> {
+        let skipped_blocks = self.database.get_skipped_blocks()?;
+
+        if skipped_blocks.is_empty() {
+            println!("No skipped blocks.");
+            return Ok(());
+        }
+
+        println!("=== Skipped Blocks ===");
+        println!("{} blocks awaiting reprocessing", skipped_blocks.len());
+        println!();
+
+        for skipped in &skipped_blocks {
+            println!("Block {}", skipped.block_number);
+            println!("  Error:       {}", skipped.error);
+            println!("  Skip Count:  {}", skipped.skip_count);
+            println!("  Reprocess:   {}", if skipped.reprocess_requested { "queued" } else { "not requested" });
+            println!("  Skipped At:  {}", format_timestamp(skipped.skipped_at));
+        }
+
+        Ok(())
+    }
+
+    /// Handle verify command by comparing stored block hashes against the chain
+    pub async fn handle_verify(&self, from: u64, to: u64) -> Result<(), CliError> {
+        if from > to {
+            return Err(CliError::InvalidArgument(format!(
+                "--from ({}) must not be greater than --to ({})",
+                from, to
+            )));
+        }
+        let rpc_client = self.rpc_client.as_ref().ok_or_else(|| {
+            CliError::Operation("Verification requires an RPC endpoint".to_string())
+        })?;
+
+        let checked = self.database.get_block_hashes_in_range(from, to)?.len();
+        let transactions = self.database.count_transactions_in_range(from, to)?;
+        let mismatched = self.database.verify_against_chain(rpc_client, from, to).await?;
+
+        println!("=== Block Hash Verification ===");
+        println!("Range:      {} - {}", from, to);
+        println!("Checked:    {} blocks with stored hashes", checked);
+        println!("Stored:     {} transactions in range", transactions);
+
+        if mismatched.is_empty() {
+            println!("Result:     All stored block hashes match the chain");
+            return Ok(());
+        }
+
+        println!("Mismatched: {} blocks", mismatched.len());
+        for block_number in &mismatched {
+            println!("  Block {}", block_number);
+        }
+
+        Err(CliError::Operation(format!(
+            "{} blocks no longer match the chain; reprocess them to repair stored data",
+            mismatched.len()
+        )))
+    }
+
+    /// Handle audit command, failing when the stored net flow drifted from the transactions
+    pub async fn handle_audit(&self) -> Result<(), CliError> {
+        let audit = self.database.audit_net_flow()?;
+
+        println!("=== Net-Flow Audit ===");
+        println!("Stored Inflow:      {}", audit.stored_inflow);
+        println!("Recomputed Inflow:  {}", audit.recomputed_inflow);
+        println!("Stored Outflow:     {}", audit.stored_outflow);
+        println!("Recomputed Outflow: {}", audit.recomputed_outflow);
+
+        if audit.is_consistent() {
+            println!("Result:             Stored totals match the transactions");
+            return Ok(());
+        }
+
+        println!("Inflow Drift:       {}", audit.inflow_drift);
+        println!("Outflow Drift:      {}", audit.outflow_drift);
+        println!("Net Flow Drift:     {}", audit.net_flow_drift);
+
+        Err(CliError::Operation(format!(
+            "Stored net flow differs from the transactions by {}",
+            audit.net_flow_drift
+        )))
+    }
+
+    /// Handle export command by streaming every transaction as CSV to a file or stdout
+    pub async fn handle_export(&self, output: Option<&str>, jobs: usize) -> Result<(), CliError> {
+        if jobs == 0 {
+            return Err(CliError::InvalidArgument("Jobs must be at least 1".to_string()));
+        }
+        match output {
+            Some(path) => {
+                let file = File::create(path)
+                    .map_err(|e| CliError::Operation(format!("Failed to create '{}': {}", path, e)))?;
+                let exported = self.write_transactions_csv_parallel(BufWriter::new(file), jobs)?;
+                println!("Exported {} transactions to {}", exported, path);
+            }
+            None => {
+                self.write_transactions_csv_parallel(BufWriter::new(io::stdout().lock()), jobs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle export-flows command by writing the bucketed flow history to a file or stdout
+    pub async fn handle_export_flows(
+        &self,
+        interval: FlowInterval,
+        format: FlowExportFormat,
+        from: u64,
+        to: u64,
+        output: Option<&str>,
+    ) -> Result<(), CliError> {
+        if from > to {
+            return Err(CliError::InvalidArgument(format!(
+                "'from' ({}) must not be greater than 'to' ({})",
+                from, to
+            )));
+        }
+        let create = |path: &str| {
+            File::create(path).map_err(|e| CliError::Operation(format!("Failed to create '{}': {}", path, e)))
+        };
+        match (format, output) {
+            (FlowExportFormat::Csv, Some(path)) => {
+                let exported = self.write_flows_csv(BufWriter::new(create(path)?), interval, from, to)?;
+                println!("Exported {} buckets to {}", exported, path);
+            }
+            (FlowExportFormat::Csv, None) => {
+                self.write_flows_csv(BufWriter::new(io::stdout().lock()), interval, from, to)?;
+            }
+            (FlowExportFormat::Parquet, None) => {
+                return Err(CliError::InvalidArgument("Parquet export requires --output".to_string()));
+            }
+            #[cfg(feature = "parquet")]
+            (FlowExportFormat::Parquet, Some(path)) => {
+                let exported = self.write_flows_parquet(create(path)?, interval, from, to)?;
+                println!("Exported {} buckets to {}", exported, path);
+            }
+            #[cfg(not(feature = "parquet"))]
+            (FlowExportFormat::Parquet, Some(_)) => {
+                return Err(CliError::InvalidArgument(
+                    "Parquet export is not available; rebuild with --features parquet".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a CSV header and one line per bucket of `from..=to`, returning the bucket count
+    ///
+    /// Buckets are read [`MAX_FLOW_SERIES_BUCKETS`] at a time and written as they
+    /// arrive, so any range can be exported.
+    pub fn write_flows_csv<W: Write>(
+        &self,
+        mut writer: W,
+        interval: FlowInterval,
+        from: u64,
+        to: u64,
+    ) -> Result<u64, CliError> {
+        let write_error = |e: io::Error| CliError::Operation(format!("Failed to write export: {}", e));
+
+        writeln!(writer, "{}", FLOW_CSV_HEADER).map_err(write_error)?;
+        let exported = self.for_each_flow_window(interval, from, to, |points| {
+            for point in points {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{}",
+                    point.bucket,
+                    point.bucket + interval.seconds(),
+                    point.inflow,
+                    point.outflow,
+                    bucket_net_flow(point)?,
+                    point.cumulative_inflow,
+                    point.cumulative_outflow
+                )
+                .map_err(write_error)?;
+            }
+            Ok(())
+        })?;
+        writer.flush().map_err(write_error)?;
+
+        Ok(exported)
+    }
+
+    /// Like [`write_flows_csv`](Self::write_flows_csv), writing one Parquet row group per window of buckets
+    ///
+    /// Bucket bounds are INT64 columns; amounts are UTF8 decimal strings, since
+    /// they do not fit a fixed-width integer.
+    #[cfg(feature = "parquet")]
+    pub fn write_flows_parquet(&self, file: File, interval: FlowInterval, from: u64, to: u64) -> Result<u64, CliError> {
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let parquet_error = |e: parquet::errors::ParquetError| CliError::Operation(format!("Failed to write export: {}", e));
+        let schema = parse_message_type(
+            "message flows {
+                REQUIRED INT64 bucket_start;
+                REQUIRED INT64 bucket_end;
+                REQUIRED BYTE_ARRAY inflow (UTF8);
+                REQUIRED BYTE_ARRAY outflow (UTF8);
+                REQUIRED BYTE_ARRAY net_flow (UTF8);
+                REQUIRED BYTE_ARRAY cumulative_inflow (UTF8);
+                REQUIRED BYTE_ARRAY cumulative_outflow (UTF8);
+            }",
+        )
+        .map_err(parquet_error)?;
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(WriterProperties::builder().build()))
+            .map_err(parquet_error)?;
+
+        let exported = self.for_each_flow_window(interval, from, to, |points| {
+            let starts: Vec<i64> = points.iter().map(|point| point.bucket as i64).collect();
+            let ends: Vec<i64> = starts.iter().map(|start| start + interval.seconds() as i64).collect();
+            let net_flows = points.iter().map(bucket_net_flow).collect::<Result<Vec<_>, _>>()?;
+            let strings = |values: Vec<&str>| values.into_iter().map(ByteArray::from).collect::<Vec<_>>();
+            let amount_columns = [
+                strings(points.iter().map(|point| point.inflow.as_str()).collect()),
+                strings(points.iter().map(|point| point.outflow.as_str()).collect()),
+                strings(net_flows.iter().map(String::as_str).collect()),
+                strings(points.iter().map(|point| point.cumulative_inflow.as_str()).collect()),
+                strings(points.iter().map(|point| point.cumulative_outflow.as_str()).collect()),
+            ];
+
+            let mut row_group = writer.next_row_group().map_err(parquet_error)?;
+            for values in [&starts, &ends] {
+                let mut column = row_group.next_column().map_err(parquet_error)?.expect("schema has this column");
+                column.typed::<Int64Type>().write_batch(values, None, None).map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+            }
+            for values in &amount_columns {
+                let mut column = row_group.next_column().map_err(parquet_error)?.expect("schema has this column");
+                column.typed::<ByteArrayType>().write_batch(values, None, None).map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+            }
+            row_group.close().map_err(parquet_error)?;
+            Ok(())
+        })?;
+        writer.close().map_err(parquet_error)?;
+
+        Ok(exported)
+    }
+
+    /// Read the flow series of `from..=to` in windows of at most [`MAX_FLOW_SERIES_BUCKETS`] buckets
+    ///
+    /// Returns the number of buckets handed to `write`.
+    fn for_each_flow_window<F>(&self, interval: FlowInterval, from: u64, to: u64, mut write: F) -> Result<u64, CliError>
+    where
+        F: FnMut(&[FlowSeriesPoint]) -> Result<(), CliError>,
+    {
+        let interval_seconds = interval.seconds();
+        let window_seconds = interval_seconds * MAX_FLOW_SERIES_BUCKETS;
+        let mut window_start = from - from % interval_seconds;
+        let mut exported = 0u64;
+        while window_start <= to {
+            let window_end = to.min(window_start.saturating_add(window_seconds - 1));
+            let points = self.database.flow_series(interval_seconds, window_start, window_end)?;
+            write(&points)?;
+            exported += points.len() as u64;
+            match window_start.checked_add(window_seconds) {
+                Some(next) => window_start = next,
+                None => break,
+            }
+        }
+        Ok(exported)
+    }
+
+    /// Write a CSV header and one line per transaction in id order, returning the row count
+    ///
+    /// Rows are streamed from the database, so the export never holds the whole
+    /// table in memory.
+    pub fn write_transactions_csv<W: Write>(&self, mut writer: W) -> Result<u64, CliError> {
+        let write_error = |e: io::Error| CliError::Operation(format!("Failed to write export: {}", e));
+
+        writeln!(writer, "{}", TRANSACTION_CSV_HEADER).map_err(write_error)?;
+
+        let mut exported = 0u64;
+        let mut failure = None;
+        self.database.for_each_transaction(|tx| {
+            if failure.is_some() {
+                return;
+            }
+            match write_csv_row(&mut writer, &tx) {
+                Ok(()) => exported += 1,
+                Err(e) => failure = Some(e),
+            }
+        })?;
+
+        if let Some(e) = failure {
+            return Err(write_error(e));
+        }
+        writer.flush().map_err(write_error)?;
+
+        Ok(exported)
+    }
+
+    /// Like [`write_transactions_csv`](Self::write_transactions_csv), serializing rows on `jobs` worker threads
+    ///
+    /// The database scan hands out chunks of [`EXPORT_CHUNK_ROWS`] rows through a
+    /// bounded queue, so only a few chunks per worker are held in memory.
+    /// Serialized chunks are written back in scan order, which makes the output
+    /// byte-for-byte identical to the single-threaded export.
+    pub fn write_transactions_csv_parallel<W: Write>(&self, mut writer: W, jobs: usize) -> Result<u64, CliError> {
+        if jobs <= 1 {
+            return self.write_transactions_csv(writer);
+        }
+        let write_error = |e: io::Error| CliError::Operation(format!("Failed to write export: {}", e));
+
+        writeln!(writer, "{}", TRANSACTION_CSV_HEADER).map_err(write_error)?;
+
+        let (chunk_sender, chunk_receiver) = mpsc::sync_channel::<(u64, Vec<TransactionRow>)>(jobs);
+        let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+        let (line_sender, line_receiver) = mpsc::sync_channel::<(u64, u64, Vec<u8>)>(jobs);
+        let database = &self.database;
+
+        std::thread::scope(|scope| {
+            let scan = scope.spawn(move || {
+                let mut chunk = Vec::with_capacity(EXPORT_CHUNK_ROWS);
+                let mut sequence = 0u64;
+                let mut accepted = true;
+                let result = database.for_each_transaction(|tx| {
+                    if !accepted {
+                        return;
+                    }
+                    chunk.push(tx);
+                    if chunk.len() == EXPORT_CHUNK_ROWS {
+                        let full = std::mem::replace(&mut chunk, Vec::with_capacity(EXPORT_CHUNK_ROWS));
+                        accepted = chunk_sender.send((sequence, full)).is_ok();
+                        sequence += 1;
+                    }
+                });
+                if accepted && !chunk.is_empty() {
+                    let _ = chunk_sender.send((sequence, chunk));
+                }
+                result
+            });
+
+            for _ in 0..jobs {
+                let chunk_receiver = Arc::clone(&chunk_receiver);
+                let line_sender = line_sender.clone();
+                scope.spawn(move || loop {
+                    let next = match chunk_receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    let Ok((sequence, rows)) = next else { return };
+                    let mut bytes = Vec::new();
+                    for tx in &rows {
+                        write_csv_row(&mut bytes, tx).expect("writing to a Vec cannot fail");
+                    }
+                    if line_sender.send((sequence, rows.len() as u64, bytes)).is_err() {
+                        return;
+                    }
+                });
+            }
+            // The workers now hold the only other handles, so both queues close once they finish
+            drop(chunk_receiver);
+            drop(line_sender);
+
+            let mut exported = 0u64;
+            let mut pending = BTreeMap::new();
+            let mut next_sequence = 0u64;
+            let mut failure = None;
+            for (sequence, rows, bytes) in line_receiver.iter() {
+                pending.insert(sequence, (rows, bytes));
+                while let Some((rows, bytes)) = pending.remove(&next_sequence) {
+                    if let Err(e) = writer.write_all(&bytes) {
+                        failure = Some(e);
+                        break;
+                    }
+                    exported += rows;
+                    next_sequence += 1;
+                }
+                if failure.is_some() {
+                    break;
+                }
+            }
+            // Dropping the receiver stops the workers, which in turn stops the scan
+            drop(line_receiver);
+
+            let scanned = scan.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            if let Some(e) = failure {
+                return Err(write_error(e));
+            }
+            scanned?;
+            writer.flush().map_err(write_error)?;
+            Ok(exported)
+        })
+    }
+
+    /// Handle version command
+    pub fn handle_version() {
+        let build_info = crate::build_info::BuildInfo::current();
+        println!("Version:    {}", build_info.version);
+        println!("Git Commit: {}", build_info.git_commit);
+    }
+
+    /// Handle list-addresses command
+    pub fn handle_list_addresses() {
+        let addresses = crate::blockchain::TransferDetector::new().watched_addresses();
+        println!("=== Watched Addresses ({}) ===", addresses.len());
+        for watched in addresses {
+            println!("{}  {}", watched.address, watched.label);
+        }
+    }
+
+    /// Handle errors command by fetching `/diagnostics/errors` from the API
+    pub async fn handle_errors(url: &str) -> Result<(), CliError> {
+        let endpoint = format!("{}/diagnostics/errors", url.trim_end_matches('/'));
+        let response = reqwest::get(&endpoint)
+            .await
+            .map_err(|e| CliError::Operation(format!("Request to {} failed: {}", endpoint, e)))?;
+        if !response.status().is_success() {
+            return Err(CliError::Operation(format!(
+                "Request to {} returned HTTP {}",
+                endpoint,
+                response.status()
+            )));
+        }
+        let statistics: ErrorStatisticsResponse = response
+            .json()
+            .await
+            .map_err(|e| CliError::Operation(format!("Invalid response from {}: {}", endpoint, e)))?;
+
+        if statistics.errors.is_empty() {
+            println!("No errors recorded.");
+            return Ok(());
+        }
+
+        println!("=== Recorded Errors ===");
+        for error in &statistics.errors {
+            println!("{}", error.error_type);
+            println!("  Count:       {}", error.count);
+            println!("  First Seen:  {}", format_timestamp(error.first_seen));
+            println!("  Last Seen:   {}", format_timestamp(error.last_seen));
+            println!("  Frequency:   {:.3}/s", error.frequency);
+        }
+
+        Ok(())
+    }
+
+    /// Handle tail command by polling the API for new transfers until Ctrl-C
+    pub async fn handle_tail(
+        url: &str,
+        interval_ms: u64,
+        since_id: Option<i64>,
+        color: bool,
+        amount_storage: AmountStorage,
+    ) -> Result<(), CliError> {
+        if interval_ms == 0 {
+            return Err(CliError::InvalidArgument("--interval-ms must be greater than 0".to_string()));
+        }
+
+        let mut tail = TransferTail::new(url, amount_storage, since_id.unwrap_or(0)).with_color(color);
+        let start = match since_id {
+            Some(since_id) => since_id,
+            None => tail.skip_to_latest().await?,
+        };
+
+        eprintln!("Tailing new transfers from {} after id {} (Ctrl-C to stop)", url, start);
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        tail.run(&mut out, Duration::from_millis(interval_ms)).await
+    }
+
+    /// Handle init-config command by writing the sample configuration to disk
+    pub fn handle_init_config(path: &str, force: bool) -> Result<(), CliError> {
+        if Path::new(path).exists() && !force {
+            return Err(CliError::InvalidArgument(format!(
+                "Configuration file '{}' already exists (use --force to overwrite)",
+                path
+            )));
+        }
+
+        AppConfig::default()
+            .save_to_file(path)
+            .map_err(|e| CliError::Operation(format!("Failed to write configuration: {}", e)))?;
+
+        println!("Sample configuration written to {}", path);
+        Ok(())
+    }
+
+    /// The configuration as the indexer would load it, redacted and rendered in `format`
+    pub fn render_effective_config(format: ConfigFormat) -> Result<String, CliError> {
+        let config = AppConfig::load()
+            .map_err(|e| CliError::Operation(format!("Invalid configuration: {}", e)))?
+            .redacted();
+
+        match format {
+            ConfigFormat::Toml => toml::to_string_pretty(&config).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(&config).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| CliError::Operation(format!("Failed to render configuration: {}", e)))
+    }
+
+    /// Handle `config show` by printing the effective configuration
+    pub fn handle_config_show(format: ConfigFormat) -> Result<(), CliError> {
+        println!("{}", Self::render_effective_config(format)?);
+        Ok(())
+    }
+
     /// Execute CLI command based on parsed arguments
     pub async fn execute_command(&self, command: &Commands) -> Result<(), CliError> {
         match command {
@@ -140,10 +1035,67 @@ impl CliHandler {
             Commands::Transactions { limit, offset } => {
                 self.handle_recent_transactions(*limit, *offset).await
             }
+            Commands::ListSkipped => self.handle_list_skipped().await,
+            Commands::Verify { from, to } => self.handle_verify(*from, *to).await,
+            Commands::Audit => self.handle_audit().await,
+            Commands::Export { output, jobs } => self.handle_export(output.as_deref(), *jobs).await,
+            Commands::ExportFlows { interval, format, from, to, output } => {
+                self.handle_export_flows(*interval, *format, *from, *to, output.as_deref()).await
+            }
+            Commands::Tail { url, interval_ms, since_id, no_color } => {
+                Self::handle_tail(url, *interval_ms, *since_id, !no_color, self.amount_storage).await
+            }
+            Commands::Errors { url } => Self::handle_errors(url).await,
+            Commands::Version => {
+                Self::handle_version();
+                Ok(())
+            }
+            Commands::Rebuild => self.handle_rebuild().await,
+            Commands::Maintain { vacuum } => self.handle_maintain(*vacuum).await,
+            Commands::Backfill { resume: true, timeout_seconds, .. } => {
+                self.handle_resume_backfill(*timeout_seconds).await
+            }
+            Commands::Backfill { from: Some(from), to: Some(to), timeout_seconds, .. } => {
+                self.handle_backfill(*from, *to, *timeout_seconds).await
+            }
+            Commands::Backfill { .. } => Err(CliError::InvalidArgument(
+                "Backfill needs --from and --to, or --resume".to_string(),
+            )),
+            Commands::ListAddresses => {
+                Self::handle_list_addresses();
+                Ok(())
+            }
+            Commands::ProcessBlock { block, dry_run } => self.handle_process_block(*block, *dry_run).await,
+            Commands::InitConfig { path, force } => Self::handle_init_config(path, *force),
+            Commands::Config { command: ConfigCommand::Show { output } } => Self::handle_config_show(*output),
         }
     }
 }
 
+/// Inflow minus outflow within one flow-series bucket
+fn bucket_net_flow(point: &FlowSeriesPoint) -> Result<String, CliError> {
+    crate::models::NetFlowCalculator::calculate_net(&point.inflow, &point.outflow)
+        .map_err(|e| CliError::Operation(format!("Failed to compute net flow: {}", e)))
+}
+
+/// Write one transaction as a CSV line
+fn write_csv_row<W: Write>(writer: &mut W, tx: &TransactionRow) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{},{},{},{},{},{},{},{},{},{}",
+        tx.id,
+        tx.block_number,
+        tx.transaction_hash,
+        tx.log_index,
+        tx.from_address,
+        tx.to_address,
+        tx.amount,
+        tx.timestamp,
+        tx.direction,
+        tx.created_at
+    )
+}
+
 /// Format Unix timestamp to human-readable string
 fn format_timestamp(timestamp: u64) -> String {
     use std::time::{UNIX_EPOCH, Duration};
@@ -176,9 +1128,10 @@ mod tests {
                 block_number: 100,
                 transaction_hash: "0x1234567890abcdef".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0xsender1".to_string(),
                 to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance
-                amount: "1000.5".to_string(),
+                amount: "100050".to_string(),
                 timestamp: 1640995200, // 2022-01-01 00:00:00 UTC
                 direction: TransferDirection::ToBinance,
             },
@@ -186,9 +1139,10 @@ mod tests {
                 block_number: 101,
                 transaction_hash: "0xfedcba0987654321".to_string(),
                 log_index: 1,
+                transaction_index: 0,
                 from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(), // Binance
                 to_address: "0xreceiver1".to_string(),
-                amount: "500.25".to_string(),
+                amount: "50025".to_string(),
                 timestamp: 1640995260, // 2022-01-01 00:01:00 UTC
                 direction: TransferDirection::FromBinance,
             },
@@ -196,9 +1150,10 @@ mod tests {
                 block_number: 102,
                 transaction_hash: "0xabcdef1234567890".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0xsender2".to_string(),
                 to_address: "0x505e71695e9bc45943c58adec1650577bca68fd9".to_string(), // Binance
-                amount: "2500.0".to_string(),
+                amount: "250000".to_string(),
                 timestamp: 1640995320, // 2022-01-01 00:02:00 UTC
                 direction: TransferDirection::ToBinance,
             },
@@ -340,6 +1295,41 @@ mod tests {
         assert!(result.is_ok(), "Execute transactions command should succeed");
     }
 
+    #[tokio::test]
+    async fn test_execute_command_list_skipped() {
+        let db = setup_test_database().await;
+        let cli_handler = CliHandler::new(Arc::clone(&db));
+
+        assert!(cli_handler.execute_command(&Commands::ListSkipped).await.is_ok());
+
+        db.record_skipped_block(12345, "RPC error: header not found").unwrap();
+        let result = cli_handler.execute_command(&Commands::ListSkipped).await;
+        assert!(result.is_ok(), "Execute list-skipped command should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_maintain() {
+        let db = setup_test_database().await;
+        populate_test_data(&db).await;
+        let cli_handler = CliHandler::new(Arc::clone(&db));
+
+        assert!(cli_handler.execute_command(&Commands::Maintain { vacuum: false }).await.is_ok());
+        assert!(cli_handler.execute_command(&Commands::Maintain { vacuum: true }).await.is_ok());
+        assert_eq!(db.get_transaction_count().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_verify_requires_rpc_client_and_valid_range() {
+        let db = setup_test_database().await;
+        let cli_handler = CliHandler::new(db);
+
+        let result = cli_handler.execute_command(&Commands::Verify { from: 10, to: 5 }).await;
+        assert!(matches!(result, Err(CliError::InvalidArgument(_))));
+
+        let result = cli_handler.execute_command(&Commands::Verify { from: 1, to: 5 }).await;
+        assert!(matches!(result, Err(CliError::Operation(_))));
+    }
+
     #[tokio::test]
     async fn test_cli_handler_with_database_error() {
         // Create a database and then close it to simulate connection issues
@@ -356,6 +1346,229 @@ mod tests {
         assert!(result.is_ok(), "Should work with Arc even after dropping reference");
     }
 
+    #[tokio::test]
+    async fn test_export_writes_csv_in_id_order() {
+        let db = setup_test_database().await;
+        populate_test_data(&db).await;
+        let cli_handler = CliHandler::new(db);
+
+        let mut output = Vec::new();
+        let exported = cli_handler.write_transactions_csv(&mut output).expect("export should succeed");
+        assert_eq!(exported, 3);
+
+        let csv = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], TRANSACTION_CSV_HEADER);
+        assert!(lines[1].starts_with("1,100,0x1234567890abcdef,0,0xsender1,"));
+        assert!(lines[2].contains(",outflow,"));
+        assert!(lines[3].starts_with("3,102,"));
+
+        // Export to a file through the command
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("transactions.csv");
+        let command = Commands::Export { output: Some(path.to_str().unwrap().to_string()), jobs: 1 };
+        cli_handler.execute_command(&command).await.expect("export command should succeed");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+    }
+
+    /// Transfers over three days, the middle one empty, with one before the range
+    fn store_daily_flows(db: &Database) {
+        let day = 86_400u64;
+        let store = |log_index: u32, timestamp: u64, amount: &str, direction: &str| {
+            db.store_transaction(1, "0xflows", log_index, "0xfrom", "0xto", amount, timestamp, direction)
+                .expect("Failed to store transaction");
+        };
+        store(0, 19_000 * day - 1, "7", "inflow");
+        store(1, 19_000 * day, "100", "inflow");
+        store(2, 19_000 * day + 3_600, "40", "outflow");
+        store(3, 19_001 * day - 1, "25", "inflow");
+        store(4, 19_002 * day + 60, "300", "outflow");
+    }
+
+    #[tokio::test]
+    async fn test_export_flows_writes_day_buckets_as_csv() {
+        let db = setup_test_database().await;
+        store_daily_flows(&db);
+        let cli_handler = CliHandler::new(db);
+        let day = 86_400u64;
+
+        let mut output = Vec::new();
+        let exported = cli_handler
+            .write_flows_csv(&mut output, FlowInterval::Day, 19_000 * day + 500, 19_002 * day + 500)
+            .expect("export should succeed");
+        assert_eq!(exported, 3);
+
+        let csv = String::from_utf8(output).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(FLOW_CSV_HEADER));
+        let rows: Vec<Vec<String>> = lines.map(|line| line.split(',').map(str::to_string).collect()).collect();
+        let row = |start: u64, end: u64, flows: [&str; 5]| {
+            [start.to_string(), end.to_string()].into_iter().chain(flows.iter().map(|s| s.to_string())).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            rows,
+            vec![
+                row(19_000 * day, 19_001 * day, ["125", "40", "85", "132", "40"]),
+                row(19_001 * day, 19_002 * day, ["0", "0", "0", "132", "40"]),
+                row(19_002 * day, 19_003 * day, ["0", "300", "-300", "132", "340"]),
+            ]
+        );
+
+        // Buckets are contiguous and their sums add up to the cumulative totals
+        let mut cumulative_inflow: i64 = 7;
+        for pair in rows.windows(2) {
+            assert_eq!(pair[0][1], pair[1][0]);
+        }
+        for row in &rows {
+            cumulative_inflow += row[2].parse::<i64>().unwrap();
+            assert_eq!(row[5].parse::<i64>().unwrap(), cumulative_inflow);
+        }
+
+        // Export to a file through the command
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("flows.csv");
+        let command = Commands::ExportFlows {
+            interval: FlowInterval::Day,
+            format: FlowExportFormat::Csv,
+            from: 19_000 * day + 500,
+            to: 19_002 * day + 500,
+            output: Some(path.to_str().unwrap().to_string()),
+        };
+        cli_handler.execute_command(&command).await.expect("export command should succeed");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+
+        // Ranges longer than one flow-series window are exported in pieces
+        let mut output = Vec::new();
+        let exported = cli_handler
+            .write_flows_csv(&mut output, FlowInterval::Hour, 0, MAX_FLOW_SERIES_BUCKETS * 3_600 + 1)
+            .expect("export should succeed");
+        assert_eq!(exported, MAX_FLOW_SERIES_BUCKETS + 1);
+        assert_eq!(String::from_utf8(output).unwrap().lines().count() as u64, exported + 1);
+
+        assert!(cli_handler
+            .handle_export_flows(FlowInterval::Day, FlowExportFormat::Csv, 10, 0, None)
+            .await
+            .is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[tokio::test]
+    async fn test_export_flows_writes_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let db = setup_test_database().await;
+        store_daily_flows(&db);
+        let cli_handler = CliHandler::new(db);
+        let day = 86_400u64;
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("flows.parquet");
+        let exported = cli_handler
+            .write_flows_parquet(File::create(&path).unwrap(), FlowInterval::Day, 19_000 * day, 19_002 * day)
+            .expect("export should succeed");
+        assert_eq!(exported, 3);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[2].contains(&format!("bucket_start: {}", 19_002 * day)));
+        assert!(rows[2].contains("net_flow: \"-300\""));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_export_matches_single_threaded_export() {
+        let db = setup_test_database().await;
+        // Enough rows for several chunks plus a partial one
+        let transfers: Vec<ProcessedTransfer> = (0..(EXPORT_CHUNK_ROWS * 3 + 17) as u64)
+            .map(|i| ProcessedTransfer {
+                block_number: 1_000 + i / 10,
+                transaction_hash: format!("0x{:064x}", i),
+                log_index: (i % 10) as u32,
+                transaction_index: 0,
+                from_address: "0xsender".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: (i + 1).to_string(),
+                timestamp: 1640995200 + i,
+                direction: TransferDirection::ToBinance,
+            })
+            .collect();
+        db.store_transfers_and_update_net_flow(&transfers).expect("Failed to store test transfers");
+        let cli_handler = CliHandler::new(db);
+
+        let mut sequential = Vec::new();
+        let sequential_count = cli_handler.write_transactions_csv_parallel(&mut sequential, 1).unwrap();
+        let mut parallel = Vec::new();
+        let parallel_count = cli_handler.write_transactions_csv_parallel(&mut parallel, 4).unwrap();
+
+        assert_eq!(sequential_count, transfers.len() as u64);
+        assert_eq!(parallel_count, sequential_count);
+        assert_eq!(parallel, sequential);
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("transactions.csv");
+        let command = Commands::Export { output: Some(path.to_str().unwrap().to_string()), jobs: 4 };
+        cli_handler.execute_command(&command).await.expect("export command should succeed");
+        assert_eq!(std::fs::read(&path).unwrap(), sequential);
+
+        let command = Commands::Export { output: None, jobs: 0 };
+        assert!(matches!(cli_handler.execute_command(&command).await, Err(CliError::InvalidArgument(_))));
+    }
+
+    #[test]
+    #[serial_test::serial(config_file)]
+    fn test_handle_init_config() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("config.toml");
+        let path_str = path.to_str().unwrap();
+
+        CliHandler::handle_init_config(path_str, false).expect("init-config should succeed");
+        assert!(path.exists());
+
+        // The generated file must re-parse as a valid configuration
+        std::env::set_var("CONFIG_FILE", path_str);
+        let loaded = AppConfig::load_from_file();
+        std::env::remove_var("CONFIG_FILE");
+        let loaded = loaded.expect("Generated config should parse");
+        assert_eq!(loaded.api.port, AppConfig::default().api.port);
+        loaded.validate().expect("Generated config should pass validation");
+
+        // A second run without --force must not overwrite the file
+        let result = CliHandler::handle_init_config(path_str, false);
+        assert!(matches!(result, Err(CliError::InvalidArgument(_))));
+
+        // With --force the file is rewritten
+        assert!(CliHandler::handle_init_config(path_str, true).is_ok());
+    }
+
+    #[test]
+    #[serial_test::serial(config_file)]
+    fn test_config_show_prefers_env_over_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut file_config = AppConfig::default();
+        file_config.processing.batch_size = 50;
+        file_config.save_to_file(file.path().to_str().unwrap()).expect("Failed to write config");
+
+        std::env::set_var("CONFIG_FILE", file.path());
+        let from_file = CliHandler::render_effective_config(ConfigFormat::Toml);
+        std::env::set_var("PROCESSING_BATCH_SIZE", "77");
+        let toml_output = CliHandler::render_effective_config(ConfigFormat::Toml);
+        let json_output = CliHandler::render_effective_config(ConfigFormat::Json);
+        std::env::remove_var("PROCESSING_BATCH_SIZE");
+        std::env::remove_var("CONFIG_FILE");
+
+        assert!(from_file.unwrap().contains("batch_size = 50"));
+        let toml_output = toml_output.unwrap();
+        assert!(toml_output.contains("batch_size = 77"), "{}", toml_output);
+        assert!(!toml_output.contains("batch_size = 50"));
+        let json: serde_json::Value = serde_json::from_str(&json_output.unwrap()).unwrap();
+        assert_eq!(json["processing"]["batch_size"], 77);
+    }
+
     #[test]
     fn test_format_timestamp() {
         // Test with a known timestamp
@@ -1,19 +1,31 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    body::Body,
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Json, Response},
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use tokio::net::TcpListener;
+use tokio::sync::Notify;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 
-use crate::database::{Database, DbError};
+use crate::blockchain::{
+    is_unconfirmed, normalize_address, BlockProcessor, EndpointHealth, EndpointPool, PipelineOccupancy, TransferDetector, WatchedAddress,
+};
+use crate::build_info::BuildInfo;
+use crate::database::{Database, DbError, MAX_FLOW_SERIES_BUCKETS};
+use crate::error_recovery::{ErrorRecoveryManager, ErrorStatistic};
+use crate::models::{AmountStorage, CalculationError, ProcessedTransfer};
 
 #[derive(Error, Debug)]
 pub enum ApiError {
@@ -28,21 +40,149 @@ pub enum ApiError {
 impl From<ApiError> for StatusCode {
     fn from(error: ApiError) -> Self {
         match error {
-            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Database(e) => database_error_status(&e),
             ApiError::InvalidParameter(_) => StatusCode::BAD_REQUEST,
             ApiError::Server(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
+/// Status for a failed database call
+///
+/// An exhausted connection pool is transient, so it is reported as 503 for
+/// clients to retry; anything else is a 500.
+fn database_error_status(error: &DbError) -> StatusCode {
+    match error {
+        DbError::PoolExhausted { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Amount representation returned by the net-flow and transactions endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmountUnits {
+    /// Raw integer wei strings in the plain amount fields
+    #[default]
+    Wei,
+    /// Decimal POL strings in the `_pol` amount fields
+    Pol,
+    /// Both the wei and the `_pol` fields
+    Both,
+}
+
+impl AmountUnits {
+    /// Convert a stored amount into the requested `(wei, pol)` strings
+    ///
+    /// The representation that was not requested is `None` and left out of the response.
+    pub fn render(self, storage: AmountStorage, stored: &str) -> Result<(Option<String>, Option<String>), CalculationError> {
+        let wei = match self {
+            AmountUnits::Pol => None,
+            AmountUnits::Wei | AmountUnits::Both => Some(storage.stored_to_wei(stored)?),
+        };
+        let pol = match self {
+            AmountUnits::Wei => None,
+            AmountUnits::Pol | AmountUnits::Both => Some(storage.stored_to_pol(stored)?),
+        };
+        Ok((wei, pol))
+    }
+}
+
+/// Query parameter selecting the amount representation
+#[derive(Debug, Deserialize)]
+pub struct UnitsQuery {
+    #[serde(default)]
+    pub units: AmountUnits,
+}
+
 /// Response structure for net-flow endpoint
 #[derive(Debug, Serialize)]
 pub struct NetFlowResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_inflow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_outflow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_flow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_inflow_pol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_outflow_pol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_flow_pol: Option<String>,
+    /// `inflow`, `outflow`, or `neutral` depending on the sign of `net_flow`
+    pub direction: &'static str,
+    pub last_processed_block: u64,
+    pub last_updated: u64,
+    pub age_seconds: u64,
+}
+
+/// Response structure for ranged net-flow endpoint
+#[derive(Debug, Serialize)]
+pub struct NetFlowRangeResponse {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub total_inflow: String,
+    pub total_outflow: String,
+    pub net_flow: String,
+}
+
+/// Query parameters for ranged net-flow endpoint
+#[derive(Debug, Deserialize)]
+pub struct NetFlowRangeQuery {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// Response structure for rolling-window net-flow endpoint
+#[derive(Debug, Serialize)]
+pub struct NetFlowRollingResponse {
+    pub window_seconds: u64,
+    pub from_timestamp: u64,
+    pub to_timestamp: u64,
     pub total_inflow: String,
     pub total_outflow: String,
     pub net_flow: String,
     pub last_processed_block: u64,
-    pub last_updated: u64,
+}
+
+/// Query parameters for rolling-window net-flow endpoint
+#[derive(Debug, Deserialize)]
+pub struct NetFlowRollingQuery {
+    #[serde(default = "default_rolling_window")]
+    pub window: u64,
+}
+
+fn default_rolling_window() -> u64 {
+    86_400
+}
+
+/// Query parameters for the flow-series endpoint
+#[derive(Debug, Deserialize)]
+pub struct FlowSeriesQuery {
+    #[serde(default = "default_series_interval")]
+    pub interval: u64,
+    pub from: u64,
+    pub to: u64,
+}
+
+fn default_series_interval() -> u64 {
+    3_600
+}
+
+/// Response structure for the flow-series endpoint
+#[derive(Debug, Serialize)]
+pub struct FlowSeriesResponse {
+    pub interval_seconds: u64,
+    pub points: Vec<FlowSeriesPointResponse>,
+}
+
+/// Cumulative totals at the end of one flow-series bucket
+#[derive(Debug, Serialize)]
+pub struct FlowSeriesPointResponse {
+    pub bucket: u64,
+    pub cumulative_inflow: String,
+    pub cumulative_outflow: String,
 }
 
 /// Response structure for status endpoint
@@ -52,11 +192,133 @@ pub struct StatusResponse {
     pub last_processed_block: u64,
     pub total_transactions: u64,
     pub last_updated: u64,
+    pub seconds_since_last_block: u64,
     pub database_status: String,
+    /// Lowest block with a stored transaction, null when none are stored
+    pub indexed_from: Option<u64>,
+    /// Highest block with a stored transaction, null when none are stored
+    pub indexed_to: Option<u64>,
+    /// Fetcher-to-writer buffer, absent when no block monitor runs in this process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<PipelineStatus>,
+    /// Live-polling RPC endpoints, primary first; absent when this process makes no RPC calls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_endpoints: Option<Vec<EndpointHealth>>,
 }
 
-/// Response structure for individual transaction
+/// Occupancy of the block pipeline buffer reported by `/status`
+#[derive(Debug, Serialize)]
+pub struct PipelineStatus {
+    /// Fetched blocks waiting for the database writer
+    pub buffered_blocks: usize,
+    pub capacity: usize,
+}
+
+/// Response structure for readiness endpoint
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub last_processed_block: u64,
+    pub seconds_since_last_block: u64,
+    pub max_staleness_seconds: u64,
+}
+
+/// Query parameters for the reprocess admin endpoint
+#[derive(Debug, Deserialize)]
+pub struct ReprocessQuery {
+    pub block: Option<u64>,
+}
+
+/// Response structure for the reprocess admin endpoint
+#[derive(Debug, Serialize)]
+pub struct ReprocessResponse {
+    pub queued: usize,
+    pub block: Option<u64>,
+}
+
+/// Query parameters for the process-block admin endpoint
+#[derive(Debug, Deserialize)]
+pub struct ProcessBlockQuery {
+    /// Return the detected transfers without storing them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Response structure for the process-block admin endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessBlockResponse {
+    pub block_number: u64,
+    pub block_hash: String,
+    pub dry_run: bool,
+    /// Every tracked transfer detected in the block, amounts in wei
+    pub transfers: Vec<ProcessedTransfer>,
+    /// Transfers newly stored; ones already indexed are left alone
+    pub stored: usize,
+}
+
+/// One error type seen by the running indexer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorStatisticResponse {
+    pub error_type: String,
+    pub count: u32,
+    /// Unix time of the first occurrence
+    pub first_seen: u64,
+    /// Unix time of the latest occurrence
+    pub last_seen: u64,
+    /// Occurrences per second between the first and latest one
+    pub frequency: f64,
+}
+
+/// Response structure for the error diagnostics endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorStatisticsResponse {
+    /// Most frequent first
+    pub errors: Vec<ErrorStatisticResponse>,
+}
+
+/// Response structure for the net-flow audit endpoint, amounts in wei
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetFlowAuditResponse {
+    /// Whether the stored totals match the transactions exactly
+    pub consistent: bool,
+    pub stored_inflow: String,
+    pub stored_outflow: String,
+    pub recomputed_inflow: String,
+    pub recomputed_outflow: String,
+    /// Stored minus recomputed inflow
+    pub inflow_drift: String,
+    /// Stored minus recomputed outflow
+    pub outflow_drift: String,
+    /// Stored minus recomputed net flow
+    pub net_flow_drift: String,
+}
+
+/// Query parameters for the counterparties endpoint
+#[derive(Debug, Deserialize)]
+pub struct CounterpartiesQuery {
+    /// "inflow" for senders into the address, "outflow" for recipients of it
+    pub direction: String,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+/// A counterparty and the total amount exchanged with the watched address
+#[derive(Debug, Serialize)]
+pub struct CounterpartyResponse {
+    pub address: String,
+    pub total_amount: String,
+}
+
+/// Response structure for the counterparties endpoint
 #[derive(Debug, Serialize)]
+pub struct CounterpartiesResponse {
+    pub address: String,
+    pub direction: String,
+    pub counterparties: Vec<CounterpartyResponse>,
+}
+
+/// Response structure for individual transaction
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionResponse {
     pub id: i64,
     pub block_number: u64,
@@ -64,20 +326,40 @@ pub struct TransactionResponse {
     pub log_index: u32,
     pub from_address: String,
     pub to_address: String,
-    pub amount: String,
+    /// Amount in wei; absent with `units=pol`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    /// Amount in POL; present with `units=pol` or `units=both`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_pol: Option<String>,
     pub timestamp: u64,
     pub direction: String,
     pub created_at: u64,
+    /// Labels of watched addresses on either side; omitted when address labels are disabled
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<AddressLabels>,
+}
+
+/// Exchange labels of a transaction's sender and recipient
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AddressLabels {
+    /// Label of `from_address`, null when it is not a watched address
+    pub from_label: Option<String>,
+    /// Label of `to_address`, null when it is not a watched address
+    pub to_label: Option<String>,
 }
 
 /// Response structure for transactions endpoint
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionsResponse {
     pub transactions: Vec<TransactionResponse>,
     pub total_count: u64,
     pub limit: u32,
     pub offset: u32,
     pub has_more: bool,
+    /// Pass as `before_id` to get the next page of a newest-first listing; null on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_before_id: Option<i64>,
 }
 
 /// Query parameters for transactions endpoint
@@ -87,12 +369,27 @@ pub struct TransactionsQuery {
     pub limit: u32,
     #[serde(default)]
     pub offset: u32,
+    /// Return transactions with a greater id, oldest first, instead of the most recent page
+    pub since_id: Option<i64>,
+    /// Return the most recent transactions with a smaller id; the keyset cursor from `next_before_id`
+    pub before_id: Option<i64>,
+    /// Only return transactions with this direction: `inflow`, `outflow` or `internal`
+    pub direction: Option<String>,
+    #[serde(default)]
+    pub units: AmountUnits,
 }
 
 fn default_limit() -> u32 {
     100
 }
 
+/// Response structure for the watched-addresses endpoint
+#[derive(Debug, Serialize)]
+pub struct WatchedAddressesResponse {
+    pub count: usize,
+    pub addresses: Vec<WatchedAddress>,
+}
+
 /// Error response structure
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
@@ -100,74 +397,503 @@ pub struct ErrorResponse {
     pub message: String,
 }
 
+/// Wall-clock source returning the current unix time in seconds
+pub type Clock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+/// Default staleness threshold for the readiness check
+pub const DEFAULT_MAX_STALENESS_SECONDS: u64 = 120;
+
+/// Current unix time in seconds from the system clock
+pub fn system_clock() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub database: Arc<Database>,
+    pub clock: Clock,
+    pub max_staleness_seconds: u64,
+    pub pipeline_occupancy: Option<Arc<PipelineOccupancy>>,
+    pub watched_addresses: Arc<Vec<WatchedAddress>>,
+    /// Processor for `/admin/process-block`, `None` when the server has no RPC access
+    pub block_processor: Option<Arc<BlockProcessor>>,
+    /// Endpoint health reported by `/status`
+    pub endpoint_pool: Option<Arc<EndpointPool>>,
+    /// Indent JSON responses unless a request passes `?pretty=false`
+    pub pretty_json: bool,
+    /// Error patterns reported by `/diagnostics/errors`, `None` without a block monitor
+    pub error_recovery_manager: Option<Arc<ErrorRecoveryManager>>,
+    /// Reject the `/admin` endpoints that write to the database with 403
+    pub read_only: bool,
+    /// Label transaction addresses that match a watched address
+    pub address_labels: bool,
+    /// Blocks needed on top of one processed on demand before its transfers count in net flow
+    pub min_confirmations: u64,
+}
+
+impl AppState {
+    /// Create application state using the system clock and default thresholds
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            clock: Arc::new(system_clock),
+            max_staleness_seconds: DEFAULT_MAX_STALENESS_SECONDS,
+            pipeline_occupancy: None,
+            watched_addresses: Arc::new(TransferDetector::new().watched_addresses()),
+            block_processor: None,
+            endpoint_pool: None,
+            pretty_json: false,
+            error_recovery_manager: None,
+            read_only: false,
+            address_labels: true,
+            min_confirmations: 0,
+        }
+    }
+
+    /// Override the clock used for staleness calculations
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Override the readiness staleness threshold
+    pub fn with_max_staleness_seconds(mut self, seconds: u64) -> Self {
+        self.max_staleness_seconds = seconds;
+        self
+    }
+
+    /// Report the block monitor's pipeline buffer in `/status`
+    pub fn with_pipeline_occupancy(mut self, occupancy: Arc<PipelineOccupancy>) -> Self {
+        self.pipeline_occupancy = Some(occupancy);
+        self
+    }
+
+    /// Process blocks on demand through `block_processor` at `/admin/process-block`
+    pub fn with_block_processor(mut self, block_processor: Arc<BlockProcessor>) -> Self {
+        self.block_processor = Some(block_processor);
+        self
+    }
+
+    /// Report the health of `pool`'s RPC endpoints in `/status`
+    pub fn with_endpoint_pool(mut self, pool: Arc<EndpointPool>) -> Self {
+        self.endpoint_pool = Some(pool);
+        self
+    }
+
+    /// Indent JSON responses by default instead of only on `?pretty=true`
+    pub fn with_pretty_json(mut self, enabled: bool) -> Self {
+        self.pretty_json = enabled;
+        self
+    }
+
+    /// Report the error patterns recorded by `manager` at `/diagnostics/errors`
+    pub fn with_error_recovery_manager(mut self, manager: Arc<ErrorRecoveryManager>) -> Self {
+        self.error_recovery_manager = Some(manager);
+        self
+    }
+
+    /// Forbid the `/admin` endpoints that write to the database
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Store transfers of blocks processed on demand as pending until `min_confirmations` blocks follow
+    pub fn with_min_confirmations(mut self, min_confirmations: u64) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
+    /// Reject a write with 403 when the server is read-only
+    fn ensure_writable(&self) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+        if !self.read_only {
+            return Ok(());
+        }
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "forbidden".to_string(),
+                message: "This server is read-only".to_string(),
+            }),
+        ))
+    }
+
+    /// Override the watched addresses reported by `/config/watched-addresses` and used for labels
+    pub fn with_watched_addresses(mut self, addresses: Vec<WatchedAddress>) -> Self {
+        self.watched_addresses = Arc::new(addresses);
+        self
+    }
+
+    /// Choose whether transactions carry `from_label`/`to_label` (on by default)
+    pub fn with_address_labels(mut self, enabled: bool) -> Self {
+        self.address_labels = enabled;
+        self
+    }
+
+    /// Labels of the watched addresses among `from_address` and `to_address`, `None` when disabled
+    fn labels_for(&self, from_address: &str, to_address: &str) -> Option<AddressLabels> {
+        if !self.address_labels {
+            return None;
+        }
+        let label = |address: &str| {
+            self.watched_addresses
+                .iter()
+                .find(|watched| normalize_address(&watched.address) == normalize_address(address))
+                .map(|watched| watched.label.clone())
+        };
+        Some(AddressLabels {
+            from_label: label(from_address),
+            to_label: label(to_address),
+        })
+    }
+
+    /// Seconds elapsed since the given unix timestamp according to the state clock
+    fn seconds_since(&self, timestamp: u64) -> u64 {
+        (self.clock)().saturating_sub(timestamp)
+    }
 }
 
+/// Status dashboard served at `/`; it renders the JSON endpoints client-side
+const ADMIN_UI_HTML: &str = include_str!("admin_ui.html");
+
+/// Default time allowed for in-flight requests to finish after shutdown is requested
+pub const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// HTTP API server
 pub struct ApiServer {
     database: Arc<Database>,
     pub port: u16,
+    pub max_staleness_seconds: u64,
+    pub shutdown_drain_timeout: Duration,
+    pub admin_ui_enabled: bool,
+    pub pretty_json: bool,
+    pub read_only: bool,
+    pub address_labels: bool,
+    pub min_confirmations: u64,
+    pipeline_occupancy: Option<Arc<PipelineOccupancy>>,
+    block_processor: Option<Arc<BlockProcessor>>,
+    endpoint_pool: Option<Arc<EndpointPool>>,
+    error_recovery_manager: Option<Arc<ErrorRecoveryManager>>,
 }
 
 impl ApiServer {
     /// Create a new API server instance
     pub fn new(database: Arc<Database>, port: u16) -> Self {
-        Self { database, port }
+        Self {
+            database,
+            port,
+            max_staleness_seconds: DEFAULT_MAX_STALENESS_SECONDS,
+            shutdown_drain_timeout: DEFAULT_SHUTDOWN_DRAIN_TIMEOUT,
+            admin_ui_enabled: true,
+            pretty_json: false,
+            read_only: false,
+            address_labels: true,
+            min_confirmations: 0,
+            pipeline_occupancy: None,
+            block_processor: None,
+            endpoint_pool: None,
+            error_recovery_manager: None,
+        }
+    }
+
+    /// Set the staleness threshold used by the readiness endpoint
+    pub fn with_max_staleness_seconds(mut self, seconds: u64) -> Self {
+        self.max_staleness_seconds = seconds;
+        self
+    }
+
+    /// Set how long in-flight requests may run after shutdown is requested
+    pub fn with_shutdown_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_drain_timeout = timeout;
+        self
+    }
+
+    /// Enable or disable the status dashboard at `/`
+    pub fn with_admin_ui(mut self, enabled: bool) -> Self {
+        self.admin_ui_enabled = enabled;
+        self
+    }
+
+    /// Indent JSON responses by default; requests can still pass `?pretty=false`
+    pub fn with_pretty_json(mut self, enabled: bool) -> Self {
+        self.pretty_json = enabled;
+        self
+    }
+
+    /// Answer the `/admin` endpoints that write to the database with 403, e.g. on a read replica
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Hold transfers of blocks processed on demand as pending until `min_confirmations` blocks follow
+    pub fn with_min_confirmations(mut self, min_confirmations: u64) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
+    /// Label transaction addresses that match a watched address with `from_label`/`to_label`
+    pub fn with_address_labels(mut self, enabled: bool) -> Self {
+        self.address_labels = enabled;
+        self
+    }
+
+    /// Report the block monitor's pipeline buffer in `/status`
+    pub fn with_pipeline_occupancy(mut self, occupancy: Arc<PipelineOccupancy>) -> Self {
+        self.pipeline_occupancy = Some(occupancy);
+        self
+    }
+
+    /// Serve `/admin/process-block` with `block_processor`
+    pub fn with_block_processor(mut self, block_processor: BlockProcessor) -> Self {
+        self.block_processor = Some(Arc::new(block_processor));
+        self
+    }
+
+    /// Report the health of `pool`'s RPC endpoints in `/status`
+    pub fn with_endpoint_pool(mut self, pool: Arc<EndpointPool>) -> Self {
+        self.endpoint_pool = Some(pool);
+        self
+    }
+
+    /// Serve `/diagnostics/errors` from the error patterns recorded by `manager`
+    pub fn with_error_recovery_manager(mut self, manager: Arc<ErrorRecoveryManager>) -> Self {
+        self.error_recovery_manager = Some(manager);
+        self
     }
 
-    /// Start the HTTP server
+    /// Start the HTTP server, shutting down gracefully on Ctrl-C
     pub async fn start(&self) -> Result<(), ApiError> {
-        let app_state = AppState {
-            database: self.database.clone(),
+        self.serve(async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                log::error!("Unable to listen for shutdown signal: {}", e);
+                std::future::pending::<()>().await;
+            }
+        })
+        .await
+    }
+
+    /// Start the HTTP server, shutting down gracefully once `shutdown_signal` is set
+    ///
+    /// This is the flag the block monitor uses, so both stop together.
+    pub async fn start_with_shutdown_signal(&self, shutdown_signal: Arc<AtomicBool>) -> Result<(), ApiError> {
+        self.serve(wait_for_shutdown_signal(shutdown_signal)).await
+    }
+
+    async fn serve<F>(&self, shutdown: F) -> Result<(), ApiError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let addr = format!("0.0.0.0:{}", self.port);
+        let listener = TcpListener::bind(&addr)
+            .await
+            .map_err(|e| ApiError::Server(format!("Failed to bind to {}: {}", addr, e)))?;
+
+        log::info!("HTTP API server starting on {}", addr);
+
+        serve_with_graceful_shutdown(listener, self.router(), shutdown, self.shutdown_drain_timeout).await
+    }
+
+    /// Build the router serving every API endpoint
+    fn router(&self) -> Router {
+        let mut app_state = AppState::new(self.database.clone())
+            .with_max_staleness_seconds(self.max_staleness_seconds)
+            .with_pretty_json(self.pretty_json)
+            .with_read_only(self.read_only)
+            .with_address_labels(self.address_labels)
+            .with_min_confirmations(self.min_confirmations);
+        if let Some(occupancy) = &self.pipeline_occupancy {
+            app_state = app_state.with_pipeline_occupancy(Arc::clone(occupancy));
+        }
+        if let Some(block_processor) = &self.block_processor {
+            app_state = app_state.with_block_processor(Arc::clone(block_processor));
+        }
+        if let Some(pool) = &self.endpoint_pool {
+            app_state = app_state.with_endpoint_pool(Arc::clone(pool));
+        }
+        if let Some(manager) = &self.error_recovery_manager {
+            app_state = app_state.with_error_recovery_manager(Arc::clone(manager));
+        }
+
+        let router = if self.admin_ui_enabled {
+            Router::new().route("/", get(get_admin_ui))
+        } else {
+            Router::new()
         };
 
-        let app = Router::new()
+        router
             .route("/net-flow", get(get_net_flow))
+            .route("/net-flow/range", get(get_net_flow_range))
+            .route("/net-flow/rolling", get(get_net_flow_rolling))
+            .route("/flow/series", get(get_flow_series))
             .route("/status", get(get_status))
+            .route("/ready", get(get_ready))
+            .route("/version", get(get_version))
+            .route("/config/watched-addresses", get(get_watched_addresses))
+            .route("/diagnostics/errors", get(get_error_statistics))
+            .route("/diagnostics/net-flow-audit", get(get_net_flow_audit))
             .route("/transactions", get(get_transactions))
+            .route("/address/:address/counterparties", get(get_address_counterparties))
+            .route("/admin/reprocess", post(post_admin_reprocess))
+            .route("/admin/process-block/:block_number", post(post_admin_process_block))
             .layer(
                 ServiceBuilder::new()
                     .layer(CorsLayer::permissive())
+                    .layer(middleware::from_fn_with_state(app_state.clone(), pretty_json))
             )
-            .with_state(app_state);
+            .with_state(app_state)
+    }
+}
 
-        let addr = format!("0.0.0.0:{}", self.port);
-        let listener = TcpListener::bind(&addr)
-            .await
-            .map_err(|e| ApiError::Server(format!("Failed to bind to {}: {}", addr, e)))?;
+/// Serve `app` on `listener` until `shutdown` resolves, then drain in-flight requests
+///
+/// Once `shutdown` resolves the listener is closed, so new connections are refused,
+/// while requests already being handled may finish. If they are still running after
+/// `drain_timeout` the server returns anyway and their connections are dropped.
+pub async fn serve_with_graceful_shutdown<F>(
+    listener: TcpListener,
+    app: Router,
+    shutdown: F,
+    drain_timeout: Duration,
+) -> Result<(), ApiError>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let shutdown_started = Arc::new(Notify::new());
+    let signal = {
+        let shutdown_started = Arc::clone(&shutdown_started);
+        async move {
+            shutdown.await;
+            log::info!("HTTP API server shutting down, draining in-flight requests");
+            shutdown_started.notify_one();
+        }
+    };
 
-        log::info!("HTTP API server starting on {}", addr);
+    let server = axum::serve(listener, app).with_graceful_shutdown(signal);
 
-        axum::serve(listener, app)
-            .await
-            .map_err(|e| ApiError::Server(format!("Server error: {}", e)))?;
+    tokio::select! {
+        result = server => result.map_err(|e| ApiError::Server(format!("Server error: {}", e))),
+        _ = async {
+            shutdown_started.notified().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            log::warn!("In-flight requests still running after {:?}, stopping HTTP API server", drain_timeout);
+            Ok(())
+        }
+    }
+}
+
+/// Resolve once `shutdown_signal` is set
+async fn wait_for_shutdown_signal(shutdown_signal: Arc<AtomicBool>) {
+    while !shutdown_signal.load(Ordering::Relaxed) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Query parameter accepted by every endpoint
+#[derive(Debug, Deserialize)]
+pub struct PrettyQuery {
+    pub pretty: Option<bool>,
+}
 
-        Ok(())
+/// Re-serialize JSON responses with indentation when `?pretty=true` or `pretty_json` is set
+///
+/// Handlers always produce compact JSON; only requests asking for indentation pay
+/// for parsing and re-serializing the body.
+pub async fn pretty_json(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let pretty = match Query::<PrettyQuery>::try_from_uri(request.uri()) {
+        Ok(Query(query)) => query.pretty.unwrap_or(state.pretty_json),
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "invalid_parameter".to_string(),
+                    message: format!("pretty must be true or false: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    if !pretty || !is_json {
+        return response;
     }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Failed to read response body for pretty-printing: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(prettify_body(&bytes)))
+}
+
+/// Pretty-print a JSON body, keeping field order
+///
+/// serde_json is built with `preserve_order`, so objects keep the order the
+/// handler serialized their fields in. A body that is not valid JSON is returned
+/// unchanged.
+fn prettify_body(compact: &[u8]) -> Vec<u8> {
+    serde_json::from_slice::<serde_json::Value>(compact)
+        .and_then(|value| serde_json::to_vec_pretty(&value))
+        .unwrap_or_else(|_| compact.to_vec())
+}
+
+fn conversion_error(error: CalculationError) -> (StatusCode, Json<ErrorResponse>) {
+    log::error!("Failed to convert stored amount: {}", error);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: "conversion_error".to_string(),
+            message: format!("Failed to convert stored amount: {}", error),
+        }),
+    )
 }
 
 /// GET /net-flow - Get current cumulative net-flow data
 pub async fn get_net_flow(
+    Query(params): Query<UnitsQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<NetFlowResponse>, (StatusCode, Json<ErrorResponse>)> {
     match state.database.get_net_flow_data() {
         Ok(net_flow_data) => {
+            let storage = state.database.amount_storage();
+            let (total_inflow, total_inflow_pol) =
+                params.units.render(storage, &net_flow_data.total_inflow).map_err(conversion_error)?;
+            let (total_outflow, total_outflow_pol) =
+                params.units.render(storage, &net_flow_data.total_outflow).map_err(conversion_error)?;
+            let (net_flow, net_flow_pol) =
+                params.units.render(storage, &net_flow_data.net_flow).map_err(conversion_error)?;
             let response = NetFlowResponse {
-                total_inflow: net_flow_data.total_inflow,
-                total_outflow: net_flow_data.total_outflow,
-                net_flow: net_flow_data.net_flow,
+                direction: net_flow_data.flow_direction(),
+                total_inflow,
+                total_outflow,
+                net_flow,
+                total_inflow_pol,
+                total_outflow_pol,
+                net_flow_pol,
                 last_processed_block: net_flow_data.last_processed_block,
                 last_updated: net_flow_data.last_updated,
+                age_seconds: state.seconds_since(net_flow_data.last_updated),
             };
             Ok(Json(response))
         }
         Err(e) => {
             log::error!("Failed to get net-flow data: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                database_error_status(&e),
                 Json(ErrorResponse {
                     error: "database_error".to_string(),
                     message: format!("Failed to retrieve net-flow data: {}", e),
@@ -177,28 +903,164 @@ pub async fn get_net_flow(
     }
 }
 
+/// GET /net-flow/range - Get net-flow for an inclusive block range
+pub async fn get_net_flow_range(
+    Query(params): Query<NetFlowRangeQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<NetFlowRangeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if params.from > params.to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "INVALID_PARAM".to_string(),
+                message: format!("'from' ({}) must not be greater than 'to' ({})", params.from, params.to),
+            }),
+        ));
+    }
+
+    match state.database.net_flow_between(params.from, params.to) {
+        Ok(net_flow_data) => Ok(Json(NetFlowRangeResponse {
+            from_block: params.from,
+            to_block: params.to,
+            total_inflow: net_flow_data.total_inflow,
+            total_outflow: net_flow_data.total_outflow,
+            net_flow: net_flow_data.net_flow,
+        })),
+        Err(e) => {
+            log::error!("Failed to get ranged net-flow data: {}", e);
+            Err((
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to retrieve net-flow data: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// GET /net-flow/rolling - Get net-flow over a trailing window of block time
+pub async fn get_net_flow_rolling(
+    Query(params): Query<NetFlowRollingQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<NetFlowRollingResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if params.window == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message: "Window must be greater than 0 seconds".to_string(),
+            }),
+        ));
+    }
+
+    let now = (state.clock)();
+    match state.database.net_flow_last(params.window, now) {
+        Ok(net_flow_data) => Ok(Json(NetFlowRollingResponse {
+            window_seconds: params.window,
+            from_timestamp: now.saturating_sub(params.window),
+            to_timestamp: now,
+            total_inflow: net_flow_data.total_inflow,
+            total_outflow: net_flow_data.total_outflow,
+            net_flow: net_flow_data.net_flow,
+            last_processed_block: net_flow_data.last_processed_block,
+        })),
+        Err(e) => {
+            log::error!("Failed to get rolling net-flow data: {}", e);
+            Err((
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to retrieve net-flow data: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// GET /flow/series - Get cumulative inflow and outflow per bucket of block time
+pub async fn get_flow_series(
+    Query(params): Query<FlowSeriesQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<FlowSeriesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let invalid = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message,
+            }),
+        )
+    };
+    if params.interval == 0 {
+        return Err(invalid("Interval must be greater than 0 seconds".to_string()));
+    }
+    if params.from > params.to {
+        return Err(invalid(format!(
+            "'from' ({}) must not be greater than 'to' ({})",
+            params.from, params.to
+        )));
+    }
+    let buckets = params.to / params.interval - params.from / params.interval + 1;
+    if buckets > MAX_FLOW_SERIES_BUCKETS {
+        return Err(invalid(format!(
+            "Range spans {} buckets; at most {} are allowed",
+            buckets, MAX_FLOW_SERIES_BUCKETS
+        )));
+    }
+
+    match state.database.flow_series(params.interval, params.from, params.to) {
+        Ok(series) => Ok(Json(FlowSeriesResponse {
+            interval_seconds: params.interval,
+            points: series
+                .into_iter()
+                .map(|point| FlowSeriesPointResponse {
+                    bucket: point.bucket,
+                    cumulative_inflow: point.cumulative_inflow,
+                    cumulative_outflow: point.cumulative_outflow,
+                })
+                .collect(),
+        })),
+        Err(e) => {
+            log::error!("Failed to get flow series: {}", e);
+            Err((
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to retrieve flow series: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
 /// GET /status - Get system status and health information
 pub async fn get_status(
     State(state): State<AppState>,
 ) -> Result<Json<StatusResponse>, (StatusCode, Json<ErrorResponse>)> {
-    match (
-        state.database.get_net_flow_data(),
-        state.database.get_transaction_count(),
-    ) {
-        (Ok(net_flow_data), Ok(transaction_count)) => {
+    match state.database.get_stats() {
+        Ok(stats) => {
             let response = StatusResponse {
                 status: "healthy".to_string(),
-                last_processed_block: net_flow_data.last_processed_block,
-                total_transactions: transaction_count,
-                last_updated: net_flow_data.last_updated,
+                last_processed_block: stats.net_flow.last_processed_block,
+                total_transactions: stats.transaction_count,
+                last_updated: stats.net_flow.last_updated,
+                seconds_since_last_block: state.seconds_since(stats.net_flow.last_updated),
                 database_status: "connected".to_string(),
+                indexed_from: stats.block_range.map(|(from, _)| from),
+                indexed_to: stats.block_range.map(|(_, to)| to),
+                pipeline: state.pipeline_occupancy.as_ref().map(|occupancy| PipelineStatus {
+                    buffered_blocks: occupancy.buffered(),
+                    capacity: occupancy.capacity(),
+                }),
+                rpc_endpoints: state.endpoint_pool.as_ref().map(|pool| pool.health()),
             };
             Ok(Json(response))
         }
-        (Err(e), _) | (_, Err(e)) => {
+        Err(e) => {
             log::error!("Failed to get status data: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                database_error_status(&e),
                 Json(ErrorResponse {
                     error: "database_error".to_string(),
                     message: format!("Failed to retrieve status data: {}", e),
@@ -208,6 +1070,251 @@ pub async fn get_status(
     }
 }
 
+/// GET / - Status dashboard polling the JSON endpoints from the browser
+pub async fn get_admin_ui() -> Html<&'static str> {
+    Html(ADMIN_UI_HTML)
+}
+
+/// GET /version - Report the crate version and git commit of the running build
+pub async fn get_version() -> Json<BuildInfo> {
+    Json(BuildInfo::current())
+}
+
+/// GET /diagnostics/errors - Errors recorded by the running indexer, most frequent first
+pub async fn get_error_statistics(
+    State(state): State<AppState>,
+) -> Result<Json<ErrorStatisticsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(manager) = &state.error_recovery_manager else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "unavailable".to_string(),
+                message: "Error statistics are only recorded by a server running the block monitor".to_string(),
+            }),
+        ));
+    };
+
+    let statistics = manager.get_error_statistics().map_err(|e| {
+        log::error!("Failed to read error statistics: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "internal_error".to_string(),
+                message: format!("Failed to read error statistics: {}", e),
+            }),
+        )
+    })?;
+
+    let now = (state.clock)();
+    let seen_at = |at: std::time::Instant| now.saturating_sub(at.elapsed().as_secs());
+    Ok(Json(ErrorStatisticsResponse {
+        errors: statistics
+            .into_iter()
+            .map(|statistic: ErrorStatistic| ErrorStatisticResponse {
+                first_seen: seen_at(statistic.first_occurrence),
+                last_seen: seen_at(statistic.last_occurrence),
+                error_type: statistic.error_type,
+                count: statistic.count,
+                frequency: statistic.frequency,
+            })
+            .collect(),
+    }))
+}
+
+/// GET /diagnostics/net-flow-audit - Compare the stored net-flow totals against the transactions
+pub async fn get_net_flow_audit(
+    State(state): State<AppState>,
+) -> Result<Json<NetFlowAuditResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let audit = state.database.audit_net_flow().map_err(|e| {
+        log::error!("Failed to audit net flow: {}", e);
+        (
+            database_error_status(&e),
+            Json(ErrorResponse {
+                error: "database_error".to_string(),
+                message: format!("Failed to audit net flow: {}", e),
+            }),
+        )
+    })?;
+
+    let storage = state.database.amount_storage();
+    let wei = |stored: &str| storage.stored_to_wei(stored).map_err(conversion_error);
+    Ok(Json(NetFlowAuditResponse {
+        consistent: audit.is_consistent(),
+        stored_inflow: wei(&audit.stored_inflow)?,
+        stored_outflow: wei(&audit.stored_outflow)?,
+        recomputed_inflow: wei(&audit.recomputed_inflow)?,
+        recomputed_outflow: wei(&audit.recomputed_outflow)?,
+        inflow_drift: wei(&audit.inflow_drift)?,
+        outflow_drift: wei(&audit.outflow_drift)?,
+        net_flow_drift: wei(&audit.net_flow_drift)?,
+    }))
+}
+
+/// GET /config/watched-addresses - List the watched exchange addresses and their labels
+pub async fn get_watched_addresses(State(state): State<AppState>) -> Json<WatchedAddressesResponse> {
+    let addresses = state.watched_addresses.as_ref().clone();
+    Json(WatchedAddressesResponse {
+        count: addresses.len(),
+        addresses,
+    })
+}
+
+/// GET /ready - Report readiness, failing when block processing has gone stale
+pub async fn get_ready(
+    State(state): State<AppState>,
+) -> Result<Json<ReadinessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    match state.database.get_net_flow_data() {
+        Ok(net_flow_data) => {
+            let seconds_since_last_block = state.seconds_since(net_flow_data.last_updated);
+
+            if seconds_since_last_block > state.max_staleness_seconds {
+                log::warn!(
+                    "Readiness check failed: no block processed for {}s (threshold {}s)",
+                    seconds_since_last_block, state.max_staleness_seconds
+                );
+                return Err((
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ErrorResponse {
+                        error: "stale".to_string(),
+                        message: format!(
+                            "No block processed for {} seconds (threshold {} seconds)",
+                            seconds_since_last_block, state.max_staleness_seconds
+                        ),
+                    }),
+                ));
+            }
+
+            Ok(Json(ReadinessResponse {
+                ready: true,
+                last_processed_block: net_flow_data.last_processed_block,
+                seconds_since_last_block,
+                max_staleness_seconds: state.max_staleness_seconds,
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to get readiness data: {}", e);
+            Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to retrieve readiness data: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// POST /admin/reprocess - Queue skipped blocks for the monitor to retry
+pub async fn post_admin_reprocess(
+    Query(params): Query<ReprocessQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ReprocessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state.ensure_writable()?;
+    match state.database.request_skipped_block_reprocess(params.block) {
+        Ok(0) if params.block.is_some() => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "not_found".to_string(),
+                message: format!("Block {} is not in the skipped blocks list", params.block.unwrap_or_default()),
+            }),
+        )),
+        Ok(queued) => {
+            log::info!("Queued {} skipped blocks for reprocessing", queued);
+            Ok(Json(ReprocessResponse {
+                queued,
+                block: params.block,
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to queue skipped blocks for reprocessing: {}", e);
+            Err((
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to queue skipped blocks: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// POST /admin/process-block/{n} - Process one block now and return the transfers detected in it
+///
+/// With `?dry_run=true` nothing is stored. Otherwise transfers not indexed yet
+/// are stored and counted in net flow; the last processed block is unchanged.
+pub async fn post_admin_process_block(
+    Path(block_number): Path<u64>,
+    Query(params): Query<ProcessBlockQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ProcessBlockResponse>, (StatusCode, Json<ErrorResponse>)> {
+    // A dry run writes nothing, so a read-only server still answers it
+    if !params.dry_run {
+        state.ensure_writable()?;
+    }
+    let Some(block_processor) = &state.block_processor else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "unavailable".to_string(),
+                message: "This server has no RPC endpoint to process blocks with".to_string(),
+            }),
+        ));
+    };
+
+    let (block_hash, transfers) = block_processor.process_block_with_hash(block_number).await.map_err(|e| {
+        log::warn!("On-demand processing of block {} failed: {}", block_number, e);
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(ErrorResponse {
+                error: "processing_error".to_string(),
+                message: format!("Failed to process block {}: {}", block_number, e),
+            }),
+        )
+    })?;
+
+    let stored = if params.dry_run {
+        0
+    } else {
+        let pending = state.min_confirmations > 0 && {
+            let chain_tip = block_processor.latest_block_number().await.map_err(|e| {
+                log::warn!("Failed to fetch the latest block for block {}: {}", block_number, e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    Json(ErrorResponse {
+                        error: "processing_error".to_string(),
+                        message: format!("Failed to fetch the latest block: {}", e),
+                    }),
+                )
+            })?;
+            is_unconfirmed(block_number, state.min_confirmations, chain_tip)
+        };
+        state.database.store_processed_block(block_number, &block_hash, &transfers, pending).map_err(|e| {
+            log::error!("Failed to store transfers of block {}: {}", block_number, e);
+            (
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to store transfers: {}", e),
+                }),
+            )
+        })?
+    };
+    log::info!(
+        "Processed block {} on demand: {} transfers detected, {} stored",
+        block_number,
+        transfers.len(),
+        stored
+    );
+
+    Ok(Json(ProcessBlockResponse {
+        block_number,
+        block_hash,
+        dry_run: params.dry_run,
+        transfers,
+        stored,
+    }))
+}
+
 /// GET /transactions - Get recent transactions with pagination
 pub async fn get_transactions(
     Query(params): Query<TransactionsQuery>,
@@ -234,28 +1341,77 @@ pub async fn get_transactions(
         ));
     }
 
-    match (
-        state.database.get_recent_transactions(params.limit, params.offset),
-        state.database.get_transaction_count(),
-    ) {
-        (Ok(transactions), Ok(total_count)) => {
-            let transaction_responses: Vec<TransactionResponse> = transactions
+    if let Some(direction) = params.direction.as_deref().filter(|d| !["inflow", "outflow", "internal"].contains(d)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message: format!("Direction must be 'inflow', 'outflow' or 'internal', got '{}'", direction),
+            }),
+        ));
+    }
+
+    let keyset = params.before_id.is_some() || params.direction.is_some();
+    if keyset && (params.offset > 0 || params.since_id.is_some()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message: "before_id and direction cannot be combined with offset or since_id".to_string(),
+            }),
+        ));
+    }
+
+    // Offsets are only used when asked for; the first page and later ones use the
+    // id keyset, which does not slow down with depth. Cursor queries fetch one
+    // extra row to learn whether more remain
+    let page = match (params.since_id, params.offset) {
+        (Some(since_id), _) => state.database.get_transactions_since(since_id, params.limit + 1),
+        (None, 0) => state.database.recent_transactions_before_id(
+            params.before_id.unwrap_or(i64::MAX),
+            params.direction.as_deref(),
+            params.limit + 1,
+        ),
+        (None, offset) => state.database.get_recent_transactions(params.limit, offset),
+    };
+
+    match (page, state.database.get_transaction_count()) {
+        (Ok(mut transactions), Ok(total_count)) => {
+            let has_more = if params.since_id.is_some() || params.offset == 0 {
+                let has_more = transactions.len() > params.limit as usize;
+                transactions.truncate(params.limit as usize);
+                has_more
+            } else {
+                (params.offset + params.limit) < total_count as u32
+            };
+            let next_before_id = match (params.since_id, transactions.last()) {
+                (None, Some(last)) if has_more => Some(last.id),
+                _ => None,
+            };
+
+            let storage = state.database.amount_storage();
+            let transaction_responses = transactions
                 .into_iter()
-                .map(|tx| TransactionResponse {
-                    id: tx.id,
-                    block_number: tx.block_number,
-                    transaction_hash: tx.transaction_hash,
-                    log_index: tx.log_index,
-                    from_address: tx.from_address,
-                    to_address: tx.to_address,
-                    amount: tx.amount,
-                    timestamp: tx.timestamp,
-                    direction: tx.direction,
-                    created_at: tx.created_at,
+                .map(|tx| {
+                    let (amount, amount_pol) = params.units.render(storage, &tx.amount)?;
+                    let labels = state.labels_for(&tx.from_address, &tx.to_address);
+                    Ok(TransactionResponse {
+                        id: tx.id,
+                        block_number: tx.block_number,
+                        transaction_hash: tx.transaction_hash,
+                        log_index: tx.log_index,
+                        from_address: tx.from_address,
+                        to_address: tx.to_address,
+                        amount,
+                        amount_pol,
+                        timestamp: tx.timestamp,
+                        direction: tx.direction,
+                        created_at: tx.created_at,
+                        labels,
+                    })
                 })
-                .collect();
-
-            let has_more = (params.offset + params.limit) < total_count as u32;
+                .collect::<Result<Vec<_>, CalculationError>>()
+                .map_err(conversion_error)?;
 
             let response = TransactionsResponse {
                 transactions: transaction_responses,
@@ -263,6 +1419,7 @@ pub async fn get_transactions(
                 limit: params.limit,
                 offset: params.offset,
                 has_more,
+                next_before_id,
             };
 
             Ok(Json(response))
@@ -270,7 +1427,7 @@ pub async fn get_transactions(
         (Err(e), _) | (_, Err(e)) => {
             log::error!("Failed to get transactions: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                database_error_status(&e),
                 Json(ErrorResponse {
                     error: "database_error".to_string(),
                     message: format!("Failed to retrieve transactions: {}", e),
@@ -278,4 +1435,175 @@ pub async fn get_transactions(
             ))
         }
     }
-}
\ No newline at end of file
+}
+
+/// GET /address/:address/counterparties - Get distinct counterparties of an address with summed amounts
+pub async fn get_address_counterparties(
+    Path(address): Path<String>,
+    Query(params): Query<CounterpartiesQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<CounterpartiesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if params.direction != "inflow" && params.direction != "outflow" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message: format!("Direction must be 'inflow' or 'outflow', got '{}'", params.direction),
+            }),
+        ));
+    }
+
+    if params.limit == 0 || params.limit > 1000 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid_parameter".to_string(),
+                message: "Limit must be between 1 and 1000".to_string(),
+            }),
+        ));
+    }
+
+    match state.database.counterparties(&address, &params.direction, params.limit) {
+        Ok(counterparties) => Ok(Json(CounterpartiesResponse {
+            address,
+            direction: params.direction,
+            counterparties: counterparties
+                .into_iter()
+                .map(|(address, total_amount)| CounterpartyResponse { address, total_amount })
+                .collect(),
+        })),
+        Err(e) => {
+            log::error!("Failed to get counterparties: {}", e);
+            Err((
+                database_error_status(&e),
+                Json(ErrorResponse {
+                    error: "database_error".to_string(),
+                    message: format!("Failed to retrieve counterparties: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TransferDirection;
+    use crate::testing::MockPolygonNode;
+
+    #[test]
+    fn test_prettify_body_keeps_field_order() {
+        let compact = br#"{"zeta":1,"alpha":{"b":[true,null],"a":{}},"tricky":"quote \" brace { comma ,"}"#;
+        let pretty = String::from_utf8(prettify_body(compact)).unwrap();
+        assert_eq!(
+            pretty,
+            "{\n  \"zeta\": 1,\n  \"alpha\": {\n    \"b\": [\n      true,\n      null\n    ],\n    \"a\": {}\n  },\n  \"tricky\": \"quote \\\" brace { comma ,\"\n}"
+        );
+        assert_eq!(prettify_body(b"not json"), b"not json");
+    }
+
+    #[tokio::test]
+    async fn test_process_block_returns_transfers_and_stores_unless_dry_run() {
+        let inflow = ProcessedTransfer {
+            block_number: 500,
+            transaction_hash: format!("0x{:064x}", 0x500),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "3000000000000000000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::ToBinance,
+        };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(500)
+            .with_block(500, vec![inflow.clone()])
+            .start()
+            .await;
+        let database = Arc::new(Database::new_in_memory().expect("Failed to create test database"));
+        let state = AppState::new(Arc::clone(&database))
+            .with_block_processor(Arc::new(BlockProcessor::new(rpc_client)));
+        let process = |dry_run: bool| {
+            post_admin_process_block(Path(500), Query(ProcessBlockQuery { dry_run }), State(state.clone()))
+        };
+
+        // A dry run reports the transfer without storing it
+        let Json(response) = process(true).await.unwrap();
+        assert_eq!(response.block_hash, MockPolygonNode::block_hash(500));
+        assert!(response.dry_run);
+        assert_eq!(response.stored, 0);
+        assert_eq!(response.transfers.len(), 1);
+        assert_eq!(response.transfers[0].transaction_hash, inflow.transaction_hash);
+        assert_eq!(response.transfers[0].amount, inflow.amount);
+        assert_eq!(response.transfers[0].direction, TransferDirection::ToBinance);
+        assert_eq!(database.get_transaction_count().unwrap(), 0);
+        assert_eq!(database.get_net_flow_data().unwrap().total_inflow, "0");
+
+        // Otherwise it is stored once, however often the block is processed
+        for expected_stored in [1, 0] {
+            let Json(response) = process(false).await.unwrap();
+            assert_eq!(response.stored, expected_stored);
+            assert_eq!(response.transfers.len(), 1);
+        }
+        assert_eq!(database.get_transaction_count().unwrap(), 1);
+        assert_eq!(database.get_net_flow_data().unwrap().total_inflow, "3000000000000000000");
+        assert_eq!(database.get_last_processed_block().unwrap(), 0);
+        assert_eq!(database.get_block_hash(500).unwrap(), Some(MockPolygonNode::block_hash(500)));
+    }
+
+    #[tokio::test]
+    async fn test_process_block_inside_confirmation_window_stores_pending_transfers() {
+        let inflow = ProcessedTransfer {
+            block_number: 500,
+            transaction_hash: format!("0x{:064x}", 0x500),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "3000000000000000000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::ToBinance,
+        };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(501)
+            .with_block(500, vec![inflow])
+            .start()
+            .await;
+        let database = Arc::new(Database::new_in_memory().expect("Failed to create test database"));
+        let state = AppState::new(Arc::clone(&database))
+            .with_block_processor(Arc::new(BlockProcessor::new(rpc_client)))
+            .with_min_confirmations(3);
+
+        let Json(response) = post_admin_process_block(Path(500), Query(ProcessBlockQuery { dry_run: false }), State(state))
+            .await
+            .unwrap();
+        assert_eq!(response.stored, 1);
+        assert_eq!(database.get_transaction_count().unwrap(), 1);
+        assert_eq!(database.get_net_flow_data().unwrap().total_inflow, "0");
+        assert_eq!(database.get_block_hash(500).unwrap(), Some(MockPolygonNode::block_hash(500)));
+
+        assert_eq!(database.finalize_pending_transfers(500).unwrap(), 1);
+        assert_eq!(database.get_net_flow_data().unwrap().total_inflow, "3000000000000000000");
+    }
+    #[tokio::test]
+    async fn test_read_only_server_answers_dry_run_process_block() {
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(500)
+            .with_block(500, vec![])
+            .start()
+            .await;
+        let database = Arc::new(Database::new_in_memory().expect("Failed to create test database"));
+        let state = AppState::new(Arc::clone(&database))
+            .with_block_processor(Arc::new(BlockProcessor::new(rpc_client)))
+            .with_read_only(true);
+        let process = |dry_run: bool| {
+            post_admin_process_block(Path(500), Query(ProcessBlockQuery { dry_run }), State(state.clone()))
+        };
+
+        let Json(response) = process(true).await.unwrap();
+        assert!(response.dry_run);
+        assert_eq!(response.block_hash, MockPolygonNode::block_hash(500));
+        let (status, _) = process(false).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
@@ -1,8 +1,11 @@
 pub mod cli;
 pub mod http;
+pub mod tail;
 
-pub use cli::{CliHandler, Cli, Commands, CliError};
+pub use cli::{CliHandler, Cli, Commands, CliError, ConfigCommand, ConfigFormat, FlowExportFormat, FlowInterval};
+pub use tail::TransferTail;
 pub use http::{
-    ApiServer, ApiError, AppState, NetFlowResponse, StatusResponse, 
-    TransactionResponse, TransactionsResponse, get_net_flow, get_status, get_transactions
+    ApiServer, ApiError, AppState, AmountUnits, Clock, NetFlowResponse, NetFlowRangeResponse, NetFlowRollingResponse, StatusResponse, ReadinessResponse,
+    ReprocessResponse, ErrorStatisticsResponse, ErrorStatisticResponse, NetFlowAuditResponse, TransactionResponse, TransactionsResponse, CounterpartiesResponse, CounterpartyResponse, get_net_flow, get_net_flow_range, get_net_flow_rolling, get_status, get_ready,
+    get_transactions, get_address_counterparties, get_net_flow_audit, get_version, get_admin_ui, post_admin_reprocess
 };
\ No newline at end of file
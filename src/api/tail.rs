@@ -0,0 +1,249 @@
+use std::io::Write;
+use std::time::Duration;
+
+use crate::api::cli::CliError;
+use crate::api::http::{TransactionResponse, TransactionsResponse};
+use crate::models::AmountStorage;
+
+/// Number of transactions requested per poll
+const TAIL_PAGE_SIZE: u32 = 100;
+
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Follows the HTTP API and prints each newly indexed transfer once
+pub struct TransferTail {
+    client: reqwest::Client,
+    base_url: String,
+    amount_storage: AmountStorage,
+    color: bool,
+    cursor: i64,
+}
+
+impl TransferTail {
+    /// Create a tail printing transfers with an id greater than `since_id`
+    pub fn new(base_url: impl Into<String>, amount_storage: AmountStorage, since_id: i64) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            amount_storage,
+            color: true,
+            cursor: since_id,
+        }
+    }
+
+    /// Enable or disable ANSI colors for inflow/outflow
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Id of the last transfer printed
+    pub fn cursor(&self) -> i64 {
+        self.cursor
+    }
+
+    /// Move the cursor to the most recently stored transfer so only later ones are printed
+    pub async fn skip_to_latest(&mut self) -> Result<i64, CliError> {
+        let url = format!("{}/transactions?limit=1", self.base_url);
+        let page = self.fetch(&url).await?;
+        if let Some(latest) = page.transactions.first() {
+            self.cursor = self.cursor.max(latest.id);
+        }
+        Ok(self.cursor)
+    }
+
+    /// Fetch and print every transfer newer than the cursor, returning how many were printed
+    pub async fn poll<W: Write>(&mut self, out: &mut W) -> Result<usize, CliError> {
+        let mut printed = 0;
+
+        loop {
+            let url = format!(
+                "{}/transactions?since_id={}&limit={}&units=pol",
+                self.base_url, self.cursor, TAIL_PAGE_SIZE
+            );
+            let page = self.fetch(&url).await?;
+
+            for transaction in &page.transactions {
+                // Guard against servers that ignore since_id
+                if transaction.id <= self.cursor {
+                    continue;
+                }
+                writeln!(out, "{}", self.format_line(transaction))
+                    .map_err(|e| CliError::Operation(format!("Failed to write transfer: {}", e)))?;
+                self.cursor = transaction.id;
+                printed += 1;
+            }
+
+            if !page.has_more || page.transactions.is_empty() {
+                break;
+            }
+        }
+
+        out.flush()
+            .map_err(|e| CliError::Operation(format!("Failed to write transfer: {}", e)))?;
+        Ok(printed)
+    }
+
+    /// Poll every `interval` until Ctrl-C is pressed
+    pub async fn run<W: Write>(&mut self, out: &mut W, interval: Duration) -> Result<(), CliError> {
+        loop {
+            if let Err(e) = self.poll(out).await {
+                log::warn!("Failed to poll for new transfers: {}", e);
+            }
+
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    log::info!("Stopping tail at transaction id {}", self.cursor);
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<TransactionsResponse, CliError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| CliError::Operation(format!("Request to {} failed: {}", url, e)))?;
+
+        if !response.status().is_success() {
+            return Err(CliError::Operation(format!(
+                "Request to {} returned HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| CliError::Operation(format!("Invalid response from {}: {}", url, e)))
+    }
+
+    fn format_line(&self, transaction: &TransactionResponse) -> String {
+        // Servers without the `units` parameter only send the stored amount
+        let amount = match (&transaction.amount_pol, &transaction.amount) {
+            (Some(pol), _) => pol.clone(),
+            (None, Some(stored)) => self.amount_storage.stored_to_pol(stored).unwrap_or_else(|_| stored.clone()),
+            (None, None) => "?".to_string(),
+        };
+
+        let color = match transaction.direction.as_str() {
+            "inflow" if self.color => ANSI_GREEN,
+            "outflow" if self.color => ANSI_RED,
+            _ => "",
+        };
+        let reset = if color.is_empty() { "" } else { ANSI_RESET };
+
+        format!(
+            "#{} block {} {}{:<7}{} {} POL {} -> {} {}",
+            transaction.id,
+            transaction.block_number,
+            color,
+            transaction.direction,
+            reset,
+            amount,
+            transaction.from_address,
+            transaction.to_address,
+            transaction.transaction_hash
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn transaction(id: i64, direction: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "block_number": 1000 + id,
+            "transaction_hash": format!("0xtx{}", id),
+            "log_index": 0,
+            "from_address": "0xfrom",
+            "to_address": "0xto",
+            "amount": "1500000000000000000",
+            "timestamp": 1_700_000_000u64,
+            "direction": direction,
+            "created_at": 1_700_000_000u64
+        })
+    }
+
+    async fn mount_page(server: &MockServer, since_id: &str, rows: Vec<serde_json::Value>, has_more: bool) {
+        Mock::given(method("GET"))
+            .and(path("/transactions"))
+            .and(query_param("since_id", since_id))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "transactions": rows,
+                "total_count": 3,
+                "limit": TAIL_PAGE_SIZE,
+                "offset": 0,
+                "has_more": has_more
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_tail_prints_each_new_transfer_once() {
+        let server = MockServer::start().await;
+        mount_page(&server, "0", vec![transaction(1, "inflow")], false).await;
+        mount_page(&server, "1", vec![transaction(2, "outflow")], true).await;
+        mount_page(&server, "2", vec![transaction(3, "inflow")], false).await;
+        mount_page(&server, "3", vec![], false).await;
+
+        let mut tail = TransferTail::new(server.uri(), AmountStorage::Wei, 0).with_color(false);
+        let mut out = Vec::new();
+
+        assert_eq!(tail.poll(&mut out).await.unwrap(), 1);
+        // A page with has_more set is drained within the same poll
+        assert_eq!(tail.poll(&mut out).await.unwrap(), 2);
+        assert_eq!(tail.poll(&mut out).await.unwrap(), 0);
+        assert_eq!(tail.cursor(), 3);
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for id in 1..=3 {
+            assert_eq!(output.matches(&format!("0xtx{}", id)).count(), 1);
+        }
+        assert!(lines[0].starts_with("#1 block 1001 inflow "));
+        assert!(lines[0].contains(" 1.5 POL "));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[tokio::test]
+    async fn test_tail_colors_directions() {
+        let server = MockServer::start().await;
+        mount_page(&server, "0", vec![transaction(1, "inflow"), transaction(2, "outflow")], false).await;
+
+        let mut tail = TransferTail::new(server.uri(), AmountStorage::Wei, 0);
+        let mut out = Vec::new();
+        tail.poll(&mut out).await.unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains(&format!("{}inflow {}", ANSI_GREEN, ANSI_RESET)));
+        assert!(lines[1].contains(&format!("{}outflow{}", ANSI_RED, ANSI_RESET)));
+    }
+
+    #[tokio::test]
+    async fn test_tail_reports_http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let mut tail = TransferTail::new(server.uri(), AmountStorage::Wei, 0);
+        assert!(tail.poll(&mut Vec::new()).await.is_err());
+        assert_eq!(tail.cursor(), 0);
+    }
+}
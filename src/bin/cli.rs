@@ -1,5 +1,6 @@
 use clap::Parser;
-use polygon_pol_indexer::api::{CliHandler, Cli};
+use polygon_pol_indexer::api::{CliHandler, Cli, Commands, ConfigCommand};
+use polygon_pol_indexer::blockchain::{BlockMonitor, BlockMonitorConfig, BlockProcessor, RpcClient, TransferDetector};
 use polygon_pol_indexer::database::Database;
 use polygon_pol_indexer::config::AppConfig;
 use std::sync::Arc;
@@ -15,12 +16,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let cli = Cli::parse();
     
+    // Commands that don't need a database connection
+    if let Commands::Version = &cli.command {
+        CliHandler::handle_version();
+        return Ok(());
+    }
+    if let Commands::ListAddresses = &cli.command {
+        CliHandler::handle_list_addresses();
+        return Ok(());
+    }
+    if let Commands::Errors { url } = &cli.command {
+        if let Err(e) = CliHandler::handle_errors(url).await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Commands::Tail { url, interval_ms, since_id, no_color } = &cli.command {
+        let amount_storage = AppConfig::load().unwrap_or_default().processing.amount_storage;
+        if let Err(e) = CliHandler::handle_tail(url, *interval_ms, *since_id, !no_color, amount_storage).await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Commands::Config { command: ConfigCommand::Show { output } } = &cli.command {
+        if let Err(e) = CliHandler::handle_config_show(*output) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Commands::InitConfig { path, force } = &cli.command {
+        if let Err(e) = CliHandler::handle_init_config(path, *force) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Load configuration and get database path
     let config = AppConfig::load().unwrap_or_default();
     let db_path = if cli.database != "./blockchain.db" {
         cli.database.clone()
     } else {
-        config.database.path
+        config.database.path.clone()
     };
     
     // Initialize database connection
@@ -33,8 +73,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
     
-    // Create CLI handler
-    let cli_handler = CliHandler::new(database);
+    // Create CLI handler; verification reads historical blocks, so prefer the archive endpoint
+    let rpc_endpoint = config.rpc.archive_endpoint()
+        .unwrap_or_else(|| config.rpc.primary_endpoint())
+        .to_string();
+    let mut cli_handler = CliHandler::new(database)
+        .with_rpc_client(
+            RpcClient::new_with_config(rpc_endpoint, config.rpc.timeout_seconds)
+                .with_compression(config.rpc.enable_compression)
+                .with_max_response_bytes(config.rpc.max_response_bytes),
+        )
+        .with_amount_storage(config.processing.amount_storage)
+        .with_min_confirmations(config.processing.min_confirmations)
+        .with_track_zero_value_transfers(config.processing.track_zero_value_transfers)
+        .with_transfer_detector(
+            TransferDetector::new()
+                .with_drop_self_transfers(config.processing.drop_self_transfers)
+                .with_classify_internal_transfers(config.processing.classify_internal_transfers)
+                .with_strict_topic_padding(config.processing.strict_topic_padding)
+                .with_classification_trace(config.processing.trace_classification),
+        );
+    if let Commands::ProcessBlock { .. } = &cli.command {
+        cli_handler = cli_handler.with_block_processor(build_block_processor(
+            &config,
+            RpcClient::new_with_config(config.rpc.primary_endpoint().to_string(), config.rpc.timeout_seconds)
+                .with_compression(config.rpc.enable_compression)
+                .with_max_response_bytes(config.rpc.max_response_bytes),
+        ));
+    }
+    if let Commands::Backfill { .. } = &cli.command {
+        match build_block_monitor(&config, &db_path) {
+            Ok(block_monitor) => cli_handler = cli_handler.with_block_monitor(block_monitor),
+            Err(e) => {
+                eprintln!("Failed to set up backfill: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     
     // Execute the command
     if let Err(e) = cli_handler.execute_command(&cli.command).await {
@@ -45,6 +120,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Block monitor for backfill, writing through its own connection to `db_path`
+fn build_block_monitor(config: &AppConfig, db_path: &str) -> Result<BlockMonitor, Box<dyn std::error::Error>> {
+    let build_rpc_client = |endpoint: &str| {
+        RpcClient::new_with_config(endpoint.to_string(), config.rpc.timeout_seconds)
+            .with_compression(config.rpc.enable_compression)
+            .with_max_response_bytes(config.rpc.max_response_bytes)
+    };
+    let build_block_processor = |client: RpcClient| build_block_processor(config, client);
+
+    let rpc_client = build_rpc_client(config.rpc.primary_endpoint());
+    let database = Database::new_with_amount_storage(db_path, config.processing.amount_storage)?;
+    let monitor_config = BlockMonitorConfig {
+        max_retries: config.rpc.max_retries,
+        retry_delay_seconds: config.rpc.retry_delay_seconds,
+        max_retry_delay_seconds: config.rpc.max_retry_delay_seconds,
+        fail_fast: config.processing.fail_fast,
+        ordered_commits: config.processing.ordered_commits,
+        pipeline_buffer_size: config.processing.pipeline_buffer_size,
+        min_confirmations: config.processing.min_confirmations,
+        ..BlockMonitorConfig::default()
+    };
+
+    let mut block_monitor = BlockMonitor::new(
+        rpc_client.clone(),
+        build_block_processor(rpc_client),
+        database,
+        Some(monitor_config),
+    )
+    .with_require_archive_for_backfill(config.rpc.require_archive_for_backfill)
+    .with_raw_log_journal(config.processing.journal_raw_logs);
+    if let Some(archive_endpoint) = config.rpc.archive_endpoint() {
+        block_monitor = block_monitor
+            .with_archive_block_processor(build_block_processor(build_rpc_client(archive_endpoint)));
+    }
+
+    Ok(block_monitor)
+}
+
+/// Block processor following the indexer's `processing` settings
+fn build_block_processor(config: &AppConfig, client: RpcClient) -> BlockProcessor {
+    BlockProcessor::new(client)
+        .with_min_block(config.processing.min_block)
+        .with_track_zero_value_transfers(config.processing.track_zero_value_transfers)
+        .with_max_transfers_per_block(config.processing.max_transfers_per_block)
+        .with_on_amount_overflow(config.processing.on_amount_overflow)
+        .with_max_timestamp_skew(config.processing.max_timestamp_skew_seconds)
+        .with_transfer_detector(
+            TransferDetector::new()
+                .with_drop_self_transfers(config.processing.drop_self_transfers)
+                .with_classify_internal_transfers(config.processing.classify_internal_transfers)
+                .with_strict_topic_padding(config.processing.strict_topic_padding)
+                .with_classification_trace(config.processing.trace_classification),
+        )
+}
+
 fn print_banner() {
     println!("╔══════════════════════════════════════════════════════════════╗");
     println!("║              🔗 Polygon POL Token Indexer 🔗                ║");
@@ -3,11 +3,12 @@ use polygon_pol_indexer::api::ApiServer;
 use polygon_pol_indexer::database::Database;
 use polygon_pol_indexer::config::AppConfig;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "polygon-pol-indexer-server")]
 #[command(about = "HTTP API server for POL token net-flow data\nCreated by Agnivesh Kumar for Alfred Capital assignment")]
-#[command(version = "0.1.0")]
+#[command(version = polygon_pol_indexer::build_info::LONG_VERSION)]
 struct Args {
     /// Database path
     #[arg(long, default_value = "./blockchain.db")]
@@ -47,10 +48,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize database
     let database = Database::new(&db_path)
         .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    database.set_synchronous(config.database.synchronous)
+        .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    let database = database.with_read_pool(
+        config.database.connection_pool_size as usize,
+        Duration::from_millis(config.database.busy_timeout_ms as u64),
+    );
     let database = Arc::new(database);
 
     // Create and start API server
-    let server = ApiServer::new(database, port);
+    let server = ApiServer::new(database, port)
+        .with_max_staleness_seconds(config.api.readiness_max_staleness_seconds)
+        .with_shutdown_drain_timeout(Duration::from_secs(config.api.shutdown_drain_timeout_seconds))
+        .with_admin_ui(config.api.admin_ui_enabled)
+        .with_pretty_json(config.api.pretty_json)
+        .with_address_labels(config.api.include_address_labels);
     
     log::info!("Starting HTTP API server on port {}", args.port);
     
@@ -1,15 +1,20 @@
+use std::ops::RangeInclusive;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
-use tokio::time::{sleep, interval};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use tokio::time::{interval, Instant};
 use tokio::signal;
 use thiserror::Error;
 use log::{info, warn, error, debug};
+use tracing::Instrument;
 
 use crate::blockchain::{RpcClient, BlockProcessor};
+use crate::blockchain::pipeline::{self, FetchedBlock, OrderedCommitBuffer, PipelineItem, PipelineOccupancy, DEFAULT_PIPELINE_CAPACITY};
 use crate::database::Database;
+use crate::models::{CalculationError, NetFlowCalculator, ProcessedTransfer, TransferDirection};
 use crate::error::IndexerError;
-use crate::error_recovery::{ErrorRecoveryManager, EnhancedRetryManager};
+use crate::error_recovery::{AlertWebhook, ErrorRecoveryManager, EnhancedRetryManager};
 use crate::logging::{LogContext, PerformanceMonitor, ErrorLogger, MetricsLogger};
 use crate::retry::{CircuitBreaker, RetryConfig};
 
@@ -21,6 +26,20 @@ pub enum MonitorError {
     Config(String),
     #[error("Shutdown requested")]
     Shutdown,
+    #[error("Processing paused after {skips} consecutive skipped blocks; block {block_number} left unprocessed")]
+    SkipLimitReached { block_number: u64, skips: u32 },
+}
+
+impl MonitorError {
+    /// Whether the monitor can keep running after this error
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            MonitorError::Indexer(e) => e.is_recoverable(),
+            // The next poll retries from the block that hit the limit
+            MonitorError::SkipLimitReached { .. } => true,
+            MonitorError::Config(_) | MonitorError::Shutdown => false,
+        }
+    }
 }
 
 impl From<crate::blockchain::rpc_client::RpcError> for MonitorError {
@@ -46,6 +65,23 @@ pub struct BlockMonitorConfig {
     pub max_retries: u32,
     pub retry_delay_seconds: u64,
     pub max_retry_delay_seconds: u64,
+    /// Stop the monitor on the first non-recoverable error instead of skipping and continuing
+    pub fail_fast: bool,
+    /// Hold blocks that arrive early until every lower block is written
+    pub ordered_commits: bool,
+    /// Fetched blocks buffered between the fetcher and the writer
+    pub pipeline_buffer_size: usize,
+    /// On a fresh start, blocks behind the tip to process instead of starting at the tip
+    pub initial_lookback_blocks: u64,
+    /// Blocks built on top of a block before its transfers count toward net flow;
+    /// until then they are stored as pending. 0 counts them immediately
+    pub min_confirmations: u64,
+    /// A live block this many blocks past the latest net-flow snapshot gets a new one;
+    /// the last processed block always gets one on shutdown. 1 snapshots every block
+    pub snapshot_every_n_blocks: u64,
+    /// Live blocks that may be skipped in a row before processing pauses and an
+    /// alert fires; 0 never pauses
+    pub max_consecutive_skips: u32,
 }
 
 impl Default for BlockMonitorConfig {
@@ -55,19 +91,99 @@ impl Default for BlockMonitorConfig {
             max_retries: 5,
             retry_delay_seconds: 1,
             max_retry_delay_seconds: 60,
+            fail_fast: false,
+            ordered_commits: true,
+            pipeline_buffer_size: DEFAULT_PIPELINE_CAPACITY,
+            initial_lookback_blocks: 0,
+            min_confirmations: 0,
+            snapshot_every_n_blocks: 1,
+            max_consecutive_skips: 0,
         }
     }
 }
 
+/// Debounced alert on the cumulative net flow crossing a threshold
+///
+/// A threshold at or below zero alerts when the net flow falls below it (a
+/// possible exchange drain); a positive threshold alerts when it rises above.
+/// Only the crossing alerts: the net flow has to recover past the threshold
+/// before it can alert again.
+#[derive(Debug, Clone)]
+pub struct NetFlowAlert {
+    threshold: i128,
+    breached: bool,
+}
+
+impl NetFlowAlert {
+    pub fn new(threshold: i128) -> Self {
+        Self { threshold, breached: false }
+    }
+
+    /// Record the latest net flow, returning an alert message if it just crossed the threshold
+    pub fn observe(&mut self, net_flow: i128) -> Option<String> {
+        self.observe_amount(&net_flow.to_string())
+            .expect("an integer is a valid amount")
+    }
+
+    /// Like [`observe`](Self::observe), for a net flow in wei as a decimal string of any size
+    pub fn observe_amount(&mut self, net_flow: &str) -> Result<Option<String>, CalculationError> {
+        let ordering = NetFlowCalculator::compare_amounts(net_flow, &self.threshold.to_string())?;
+        let breached = if self.threshold <= 0 {
+            ordering == std::cmp::Ordering::Less
+        } else {
+            ordering == std::cmp::Ordering::Greater
+        };
+        let crossed = breached && !self.breached;
+        self.breached = breached;
+
+        Ok(crossed.then(|| {
+            format!(
+                "Net flow {} wei crossed {} the alert threshold of {} wei",
+                net_flow,
+                if self.threshold <= 0 { "below" } else { "above" },
+                self.threshold
+            )
+        }))
+    }
+}
+
 pub struct BlockMonitor {
     rpc_client: Arc<RpcClient>,
     block_processor: Arc<BlockProcessor>,
+    /// Processor backed by an archive endpoint, used for backfill instead of the primary
+    archive_block_processor: Option<Arc<BlockProcessor>>,
     database: Arc<Database>,
     pub config: BlockMonitorConfig,
     pub shutdown_signal: Arc<AtomicBool>,
     rpc_circuit_breaker: Arc<CircuitBreaker>,
     database_circuit_breaker: Arc<CircuitBreaker>,
     error_recovery_manager: Arc<ErrorRecoveryManager>,
+    /// Unix time (seconds) of the last successfully processed block, 0 if none yet
+    last_block_processed_at: Arc<AtomicU64>,
+    net_flow_alert: Option<std::sync::Mutex<NetFlowAlert>>,
+    /// Refuse to backfill through the primary when no archive processor is configured
+    require_archive_for_backfill: bool,
+    pipeline_occupancy: Arc<PipelineOccupancy>,
+    /// Journal each block's decoded POL logs alongside its transfers
+    journal_raw_logs: bool,
+    /// Latest block number seen from the node, 0 until first polled
+    chain_tip: Arc<AtomicU64>,
+    /// How often the monitoring loop runs database maintenance, `None` to never
+    maintenance_interval: Option<Duration>,
+    /// Include `VACUUM` in scheduled maintenance
+    maintenance_vacuum: bool,
+    /// Log only every Nth repeat of an error; see [`ErrorLogger::log_sampled_error`]
+    error_sample_rate: u32,
+    /// Write blocks with [`Database::store_missing_transfers`] so rewriting one is harmless
+    skip_duplicate_transfers: bool,
+    /// Block of the latest net-flow snapshot written by this monitor
+    last_snapshot_block: AtomicU64,
+    /// Live blocks skipped since the last one written successfully
+    consecutive_skips: AtomicU32,
+    /// How long [`get_status`](Self::get_status) reuses a fetched latest block
+    status_cache_ttl: Duration,
+    /// Latest block fetched for status and when; held while fetching so concurrent callers share one call
+    status_latest_block: tokio::sync::Mutex<Option<(u64, Instant)>>,
 }
 
 impl BlockMonitor {
@@ -83,13 +199,128 @@ impl BlockMonitor {
         Self {
             rpc_client: Arc::new(rpc_client),
             block_processor: Arc::new(block_processor),
+            archive_block_processor: None,
             database: Arc::new(database),
             config: config.unwrap_or_default(),
             shutdown_signal: Arc::new(AtomicBool::new(false)),
             rpc_circuit_breaker: Arc::new(CircuitBreaker::new(5, 60)), // 5 failures, 60s recovery
             database_circuit_breaker: Arc::new(CircuitBreaker::new(3, 30)), // 3 failures, 30s recovery
             error_recovery_manager: Arc::new(ErrorRecoveryManager::new()),
+            last_block_processed_at: Arc::new(AtomicU64::new(0)),
+            net_flow_alert: None,
+            require_archive_for_backfill: false,
+            pipeline_occupancy: Arc::new(PipelineOccupancy::default()),
+            journal_raw_logs: false,
+            chain_tip: Arc::new(AtomicU64::new(0)),
+            maintenance_interval: None,
+            maintenance_vacuum: false,
+            error_sample_rate: 1,
+            skip_duplicate_transfers: true,
+            last_snapshot_block: AtomicU64::new(0),
+            consecutive_skips: AtomicU32::new(0),
+            status_cache_ttl: Duration::from_secs(2),
+            status_latest_block: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Alert when the cumulative net flow crosses `threshold` wei, see [`NetFlowAlert`]
+    pub fn with_net_flow_alert(mut self, threshold: i128) -> Self {
+        self.net_flow_alert = Some(std::sync::Mutex::new(NetFlowAlert::new(threshold)));
+        self
+    }
+
+    /// Also post alerts to `webhook`; call before sharing [`Self::error_recovery_manager`]
+    pub fn with_alert_webhook(mut self, webhook: AlertWebhook) -> Self {
+        self.error_recovery_manager = Arc::new(ErrorRecoveryManager::new().with_alert_webhook(webhook));
+        self
+    }
+
+    /// Use `block_processor` (backed by an archive endpoint) for backfill and deep history
+    ///
+    /// Live polling at the chain tip keeps using the primary processor.
+    pub fn with_archive_block_processor(mut self, block_processor: BlockProcessor) -> Self {
+        self.archive_block_processor = Some(Arc::new(block_processor));
+        self
+    }
+
+    /// Make backfill fail when no archive processor is configured
+    ///
+    /// A primary endpoint is often a pruned full node, which cannot serve old
+    /// blocks reliably.
+    pub fn with_require_archive_for_backfill(mut self, require_archive_for_backfill: bool) -> Self {
+        self.require_archive_for_backfill = require_archive_for_backfill;
+        self
+    }
+
+    /// Journal the decoded POL logs of every written block (off by default)
+    ///
+    /// The journal lets [`Database::rebuild_from_raw_logs`] rebuild the stored
+    /// transfers and net flow without going back to the chain.
+    pub fn with_raw_log_journal(mut self, journal_raw_logs: bool) -> Self {
+        self.journal_raw_logs = journal_raw_logs;
+        self
+    }
+
+    /// Run [`Database::maintenance`] from the monitoring loop every `interval`
+    ///
+    /// Maintenance runs between polls, so no blocks are processed while it holds
+    /// the database.
+    pub fn with_scheduled_maintenance(mut self, interval: Duration, vacuum: bool) -> Self {
+        self.maintenance_interval = Some(interval);
+        self.maintenance_vacuum = vacuum;
+        self
+    }
+
+    /// Log the first of a run of identical errors, then only every `sample_rate`th
+    ///
+    /// Keeps a long RPC outage, which fails every poll, from flooding the log. A run
+    /// ends once the error has not recurred for
+    /// [`ERROR_RUN_RESET_AFTER`](crate::error_recovery::ERROR_RUN_RESET_AFTER), so the
+    /// first error of the next outage is logged again.
+    pub fn with_error_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.error_sample_rate = sample_rate;
+        self
+    }
+
+    /// Reuse the latest block fetched by [`get_status`](Self::get_status) for `ttl` (2 seconds by default)
+    ///
+    /// A frequently polled status then costs at most one RPC call per `ttl`;
+    /// a zero `ttl` fetches on every call.
+    pub fn with_status_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.status_cache_ttl = ttl;
+        self
+    }
+
+    /// Choose whether transfers already stored are skipped when a block is written (the default)
+    ///
+    /// A block retried after a partial write, or reprocessed from the skipped
+    /// list, then stores only its missing transfers and counts only those in the
+    /// net flow. When off, a duplicate fails the write and the block is skipped.
+    pub fn with_skip_duplicate_transfers(mut self, skip_duplicate_transfers: bool) -> Self {
+        self.skip_duplicate_transfers = skip_duplicate_transfers;
+        self
+    }
+
+    /// Processor used for backfill: the archive processor if configured, otherwise the primary
+    ///
+    /// Falling back to the primary is logged as a warning, or refused when an
+    /// archive endpoint is required.
+    fn backfill_block_processor(&self) -> Result<&Arc<BlockProcessor>, MonitorError> {
+        if let Some(archive_block_processor) = &self.archive_block_processor {
+            return Ok(archive_block_processor);
+        }
+        if self.require_archive_for_backfill {
+            return Err(MonitorError::Config(
+                "Backfill requires an archive endpoint (rpc.require_archive_for_backfill is set), \
+                 but no endpoint with role \"archive\" is configured"
+                    .to_string(),
+            ));
         }
+        LogContext::new("block_monitor", "backfill").warn(
+            "No archive endpoint configured; backfilling through the primary endpoint, \
+             which may be a pruned node without the requested history",
+        );
+        Ok(&self.block_processor)
     }
 
     /// Start the block monitoring loop
@@ -99,9 +330,12 @@ impl BlockMonitor {
         // Get the starting block number
         let mut last_processed_block = self.get_starting_block_number().await?;
         info!("Starting from block number: {}", last_processed_block);
+        // Blocks up to here were snapshotted by the previous run's shutdown
+        self.last_snapshot_block.store(last_processed_block, Ordering::Relaxed);
 
         // Set up polling interval
         let mut interval = interval(Duration::from_secs(self.config.poll_interval_seconds));
+        let mut last_maintenance = Instant::now();
 
         // Set up graceful shutdown handling
         let shutdown_signal = Arc::clone(&self.shutdown_signal);
@@ -129,6 +363,9 @@ impl BlockMonitor {
             // Wait for next polling interval
             interval.tick().await;
 
+            // Retry any skipped blocks an operator has queued for reprocessing
+            self.reprocess_requested_blocks().await;
+
             // Process new blocks with retry logic
             match self.process_new_blocks(&mut last_processed_block).await {
                 Ok(blocks_processed) => {
@@ -136,11 +373,39 @@ impl BlockMonitor {
                         debug!("Processed {} new blocks, current block: {}", blocks_processed, last_processed_block);
                     }
                 }
+                Err(e) if self.should_fail_fast(&e) => {
+                    error!("Stopping block monitor on non-recoverable error: {}", e);
+                    self.persist_state(last_processed_block).await?;
+                    return Err(e);
+                }
+                Err(MonitorError::Indexer(e)) => {
+                    // Already logged, sampled, where it occurred
+                    debug!("Poll failed, retrying on the next interval: {}", e);
+                }
                 Err(e) => {
                     warn!("Error processing blocks: {}", e);
                     // Continue the loop - errors are handled with retries in process_new_blocks
                 }
             }
+
+            if self.maintenance_interval.is_some_and(|every| last_maintenance.elapsed() >= every) {
+                self.run_maintenance();
+                last_maintenance = Instant::now();
+            }
+        }
+    }
+
+    /// Run scheduled database maintenance, logging rather than failing on errors
+    fn run_maintenance(&self) {
+        let context = LogContext::new("block_monitor", "maintenance");
+        let started = Instant::now();
+        match self.database.maintenance(self.maintenance_vacuum) {
+            Ok(()) => context.info(&format!(
+                "Database maintenance ({}) finished in {:?}",
+                if self.maintenance_vacuum { "ANALYZE, VACUUM" } else { "ANALYZE" },
+                started.elapsed()
+            )),
+            Err(e) => context.warn(&format!("Database maintenance failed: {}", e)),
         }
     }
 
@@ -153,73 +418,524 @@ impl BlockMonitor {
             return Ok(0);
         }
 
-        let mut blocks_processed = 0;
-        let mut current_block = *last_processed_block + 1;
+        let range = (*last_processed_block + 1)..=latest_block;
+        let summary = self
+            .run_pipeline(
+                Arc::clone(&self.block_processor),
+                range,
+                Some(last_processed_block),
+                &BackfillControl::default(),
+            )
+            .await?;
 
-        // Process each new block sequentially
-        while current_block <= latest_block {
-            // Check for shutdown signal during processing
-            if self.shutdown_signal.load(Ordering::Relaxed) {
-                info!("Shutdown signal received during block processing");
+        self.finalize_confirmed_transfers().await;
+
+        Ok(summary.blocks_processed)
+    }
+
+    /// Whether transfers in `block_number` are still inside the confirmation window
+    fn is_unconfirmed(&self, block_number: u64) -> bool {
+        is_unconfirmed(block_number, self.config.min_confirmations, self.chain_tip.load(Ordering::Relaxed))
+    }
+
+    /// Count pending transfers whose block now has `min_confirmations` blocks on top of it
+    ///
+    /// Every block written since the last finalization is checked against the chain
+    /// first, including blocks that had no transfers. A block whose hash changed was
+    /// reorganized away: its transfers are discarded, taking back any already
+    /// counted, and the canonical block is processed in their place. Returns how
+    /// many transfers were counted.
+    pub async fn finalize_confirmed_transfers(&self) -> u64 {
+        if self.config.min_confirmations == 0 {
+            return 0;
+        }
+        let tip = self.chain_tip.load(Ordering::Relaxed);
+        let Some(confirmed_through) = tip.checked_sub(self.config.min_confirmations) else {
+            return 0;
+        };
+
+        let unfinalized_blocks = match self.database.get_unfinalized_blocks(confirmed_through) {
+            Ok(unfinalized_blocks) => unfinalized_blocks,
+            Err(e) => {
+                error!("Failed to load blocks awaiting confirmation: {}", e);
+                return 0;
+            }
+        };
+
+        for (block_number, stored_hash) in unfinalized_blocks {
+            let canonical_hash = match self.rpc_client.get_block_with_retry(block_number).await {
+                Ok(block) => block.hash,
+                Err(e) => {
+                    // Leave everything pending rather than count a block that could not be checked
+                    warn!("Could not check block {} before counting its transfers: {}", block_number, e);
+                    return 0;
+                }
+            };
+            if stored_hash.as_deref().is_some_and(|stored| stored.eq_ignore_ascii_case(&canonical_hash)) {
+                continue;
+            }
+
+            warn!(
+                "Block {} was reorganized ({} -> {}); replacing its transfers",
+                block_number,
+                stored_hash.as_deref().unwrap_or("unknown"),
+                canonical_hash
+            );
+            if let Err(e) = self.database.discard_block(block_number) {
+                error!("Failed to discard reorganized block {}: {}", block_number, e);
+                return 0;
+            }
+            if let Err(e) = self.process_single_block(block_number).await {
+                warn!("Failed to process block {} after reorg: {}", block_number, e);
+                self.record_skipped_block(block_number, &e);
+            }
+        }
+
+        match self.database.finalize_pending_transfers(confirmed_through) {
+            Ok(promoted) => {
+                if promoted > 0 {
+                    info!("Counted {} confirmed transfers through block {}", promoted, confirmed_through);
+                    self.check_net_flow_alert();
+                }
+                promoted
+            }
+            Err(e) => {
+                error!("Failed to count confirmed transfers: {}", e);
+                0
+            }
+        }
+    }
+
+    /// Backfill `from_block..=to_block` through the archive endpoint, if one is configured
+    ///
+    /// Backfilled blocks are written like live blocks and failures are recorded as
+    /// skipped, but the last processed block is left untouched so backfilling an
+    /// older range never moves the live cursor backwards.
+    ///
+    /// If `control` cancels the run or its deadline passes, the block being
+    /// written is finished and a partial summary is returned; see
+    /// [`BackfillSummary::resume_from`]. The range and the progress through it
+    /// are also kept in the backfill_state table, so
+    /// [`resume_backfill`](Self::resume_backfill) can continue after a restart.
+    pub async fn backfill(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        control: BackfillControl,
+    ) -> Result<BackfillSummary, MonitorError> {
+        if from_block > to_block {
+            return Err(MonitorError::Config(format!(
+                "Invalid backfill range: {} > {}",
+                from_block, to_block
+            )));
+        }
+        let block_processor = Arc::clone(self.backfill_block_processor()?);
+        self.database.start_backfill(from_block, to_block)?;
+
+        self.run_backfill(block_processor, from_block, to_block, control).await
+    }
+
+    /// Continue the latest backfill after the last block it wrote or skipped
+    ///
+    /// Blocks already done are not processed again. Returns a completed summary
+    /// without fetching anything when the backfill had already finished, and a
+    /// configuration error when none was ever started.
+    pub async fn resume_backfill(&self, control: BackfillControl) -> Result<BackfillSummary, MonitorError> {
+        let state = self
+            .database
+            .get_backfill_state()?
+            .ok_or_else(|| MonitorError::Config("No backfill to resume".to_string()))?;
+        if state.is_complete() {
+            info!("Backfill of blocks {} to {} already complete", state.from_block, state.to_block);
+            return Ok(BackfillSummary {
+                from_block: state.from_block,
+                to_block: state.to_block,
+                completed: true,
+                last_block: state.last_completed_block,
+                ..BackfillSummary::default()
+            });
+        }
+        let block_processor = Arc::clone(self.backfill_block_processor()?);
+
+        self.run_backfill(block_processor, state.resume_from(), state.to_block, control).await
+    }
+
+    /// Write `from_block..=to_block` with `block_processor`, recording progress in backfill_state
+    async fn run_backfill(
+        &self,
+        block_processor: Arc<BlockProcessor>,
+        from_block: u64,
+        to_block: u64,
+        control: BackfillControl,
+    ) -> Result<BackfillSummary, MonitorError> {
+        let context = LogContext::new("block_monitor", "backfill")
+            .with_metadata("from_block", serde_json::json!(from_block))
+            .with_metadata("to_block", serde_json::json!(to_block))
+            .with_metadata("archive", serde_json::json!(self.archive_block_processor.is_some()));
+        context.info(&format!("Backfilling blocks {} to {}", from_block, to_block));
+        if self.config.min_confirmations > 0 {
+            // Blocks near the tip are stored as pending, so the tip has to be known
+            self.get_latest_block_with_retry().await?;
+        }
+
+        let summary = self
+            .run_pipeline(block_processor, from_block..=to_block, None, &control)
+            .await?;
+        if !summary.completed {
+            context.warn(&format!(
+                "Backfill stopped before block {}; resume from block {}",
+                to_block,
+                summary.resume_from()
+            ));
+        }
+
+        Ok(BackfillSummary {
+            from_block,
+            to_block,
+            ..summary
+        })
+    }
+
+    /// Fetch `blocks` with `block_processor` and write them in order
+    ///
+    /// A fetcher task pulls blocks onto a bounded channel while this task writes
+    /// them, so network latency and database writes overlap. When `cursor` is given,
+    /// each written block advances it and the persisted last processed block.
+    ///
+    /// Failed blocks are recorded as skipped. With `fail_fast` set, a non-recoverable
+    /// failure also stops the pipeline and is returned. Cancelling `control` stops
+    /// it after the block being written, leaving the summary incomplete.
+    ///
+    /// Only the live pipeline reports its buffer through
+    /// [`pipeline_occupancy`](Self::pipeline_occupancy); a backfill running alongside
+    /// it counts its own.
+    async fn run_pipeline(
+        &self,
+        block_processor: Arc<BlockProcessor>,
+        blocks: RangeInclusive<u64>,
+        cursor: Option<&mut u64>,
+        control: &BackfillControl,
+    ) -> Result<BackfillSummary, MonitorError> {
+        let mut summary = BackfillSummary {
+            from_block: *blocks.start(),
+            to_block: *blocks.end(),
+            ..BackfillSummary::default()
+        };
+
+        let occupancy = match cursor {
+            Some(_) => Arc::clone(&self.pipeline_occupancy),
+            None => Arc::new(PipelineOccupancy::default()),
+        };
+        let (mut receiver, fetcher) = pipeline::spawn_fetcher(
+            block_processor,
+            Arc::clone(&self.rpc_circuit_breaker),
+            blocks.clone(),
+            self.config.pipeline_buffer_size,
+            Duration::from_secs(self.config.retry_delay_seconds),
+            Arc::clone(&self.shutdown_signal),
+            Arc::clone(&occupancy),
+        );
+
+        let written = self
+            .write_pipeline(&mut receiver, &occupancy, *blocks.start(), &mut summary, cursor, control)
+            .await;
+        if let Err(e) = written {
+            fetcher.abort();
+            return Err(e);
+        }
+
+        drop(receiver);
+        summary.completed = summary.last_block == Some(*blocks.end());
+        if !summary.completed {
+            // Stopped early; don't wait on blocks nobody will write
+            fetcher.abort();
+        } else if let Err(e) = fetcher.await {
+            error!("Block fetcher task failed: {}", e);
+        }
+
+        Ok(summary)
+    }
+
+    /// Write every item received from the fetcher, starting at `first_block`
+    ///
+    /// With `ordered_commits` set, items are committed in ascending block order
+    /// even if they arrive out of order; blocks still waiting on a lower block
+    /// when the channel closes are dropped so they are fetched again later.
+    async fn write_pipeline(
+        &self,
+        receiver: &mut tokio::sync::mpsc::Receiver<PipelineItem>,
+        occupancy: &PipelineOccupancy,
+        first_block: u64,
+        summary: &mut BackfillSummary,
+        mut cursor: Option<&mut u64>,
+        control: &BackfillControl,
+    ) -> Result<(), MonitorError> {
+        let mut ordered = self.config.ordered_commits.then(|| OrderedCommitBuffer::new(first_block));
+        let mut control = control.clone();
+        let deadline = control.deadline.map(|deadline| Instant::now() + deadline);
+
+        loop {
+            let item = tokio::select! {
+                biased;
+                reason = control.stopped(deadline) => {
+                    warn!("Stopping block pipeline: {}", reason);
+                    break;
+                }
+                item = receiver.recv() => item,
+            };
+            let Some(item) = item else {
                 break;
+            };
+            occupancy.taken();
+            let ready = match ordered.as_mut() {
+                Some(buffer) => buffer.push(item),
+                None => vec![item],
+            };
+            for item in ready {
+                self.write_pipeline_item(item, summary, cursor.as_deref_mut()).await?;
+                if let (None, Some(block_number)) = (cursor.as_ref(), summary.last_block) {
+                    self.record_backfill_progress(block_number);
+                }
+            }
+        }
+
+        if let Some(buffer) = ordered.filter(|buffer| buffer.pending_len() > 0) {
+            warn!(
+                "Dropping {} fetched blocks waiting on a missing lower block: {:?}",
+                buffer.pending_len(),
+                buffer.pending_blocks()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Write one pipeline item, recording it as skipped if it failed
+    ///
+    /// Live blocks also get a net-flow snapshot; backfilled blocks do not, since
+    /// the running totals do not correspond to an older block. A live block failing
+    /// after `max_consecutive_skips` skipped ones pauses processing instead.
+    async fn write_pipeline_item(
+        &self,
+        item: PipelineItem,
+        summary: &mut BackfillSummary,
+        cursor: Option<&mut u64>,
+    ) -> Result<(), MonitorError> {
+        let live = cursor.is_some();
+        match item {
+            PipelineItem::Fetched(fetched) => {
+                let block_number = fetched.block_number;
+                match self.write_block(fetched).await {
+                    Ok(transfer_count) => {
+                        info!("Processed block {} with {} POL transfers", block_number, transfer_count);
+
+                        if let Some(last_processed_block) = cursor {
+                            // Update last processed block in database
+                            if let Err(e) = self.database.set_last_processed_block(block_number) {
+                                error!("Failed to update last processed block in database: {}", e);
+                                // Don't return error here, just log it and continue
+                            }
+                            // Without ordered commits a block can land below the latest snapshot
+                            let since_snapshot = block_number.abs_diff(self.last_snapshot_block.load(Ordering::Relaxed));
+                            if since_snapshot >= self.config.snapshot_every_n_blocks.max(1) {
+                                self.record_net_flow_snapshot(block_number);
+                            }
+
+                            *last_processed_block = block_number;
+                            self.record_block_processed_at(SystemTime::now());
+                            self.consecutive_skips.store(0, Ordering::Relaxed);
+                        }
+                        summary.blocks_processed += 1;
+                        summary.transfers_found += transfer_count as u64;
+                        summary.last_block = Some(block_number);
+                    }
+                    Err(e) => {
+                        error!("Failed to write block {}: {}", block_number, e);
+                        if live {
+                            self.check_skip_limit(block_number)?;
+                        }
+                        warn!("Skipping block {} due to processing error", block_number);
+                        self.record_skipped_block(block_number, &e);
+                        summary.skipped_blocks += 1;
+                        summary.last_block = Some(block_number);
+                        if self.should_fail_fast(&e) {
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+            PipelineItem::Failed { block_number, error } => {
+                // Skip this block to avoid an infinite loop; it is recorded in the
+                // skipped_blocks table so it can be listed and reprocessed later
+                if live {
+                    self.check_skip_limit(block_number)?;
+                }
+                warn!("Skipping block {} due to processing error", block_number);
+                let error = MonitorError::Indexer(error);
+                self.record_skipped_block(block_number, &error);
+                summary.skipped_blocks += 1;
+                summary.last_block = Some(block_number);
+                if self.should_fail_fast(&error) {
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Persist that a backfill is done through `block_number`, logging rather than failing on errors
+    ///
+    /// A lost update only makes a resumed backfill redo that block, which stores
+    /// nothing twice.
+    fn record_backfill_progress(&self, block_number: u64) {
+        if let Err(e) = self.database.set_backfill_progress(block_number) {
+            error!("Failed to record backfill progress at block {}: {}", block_number, e);
+        }
+    }
+
+    /// Snapshot the net-flow totals after `block_number`, logging rather than failing on errors
+    fn record_net_flow_snapshot(&self, block_number: u64) {
+        match self.database.record_net_flow_snapshot(block_number) {
+            Ok(()) => self.last_snapshot_block.store(block_number, Ordering::Relaxed),
+            Err(e) => error!("Failed to record net-flow snapshot for block {}: {}", block_number, e),
+        }
+    }
+
+    /// Count a live block about to be skipped, refusing once `max_consecutive_skips` are skipped in a row
+    ///
+    /// The refused block is neither skipped nor written. Skipped blocks do not move
+    /// the cursor either, so while paused each poll retries from the first block of
+    /// the run of skips and is refused there until that block succeeds. The alert
+    /// fires once per run of skips; a block written successfully starts a new run.
+    fn check_skip_limit(&self, block_number: u64) -> Result<(), MonitorError> {
+        let limit = self.config.max_consecutive_skips;
+        if limit == 0 {
+            return Ok(());
+        }
+        let skips = self.consecutive_skips.load(Ordering::Relaxed);
+        if skips < limit {
+            self.consecutive_skips.store(skips + 1, Ordering::Relaxed);
+            return Ok(());
+        }
+        if skips == limit {
+            self.error_recovery_manager.send_alert(&format!(
+                "Paused block processing at block {} after {} consecutive skipped blocks",
+                block_number, limit
+            ));
+            self.consecutive_skips.store(limit + 1, Ordering::Relaxed);
+        }
+        Err(MonitorError::SkipLimitReached { block_number, skips: limit })
+    }
+
+    /// Whether `error` should stop the monitor rather than be logged and skipped
+    fn should_fail_fast(&self, error: &MonitorError) -> bool {
+        self.config.fail_fast && !error.is_recoverable()
+    }
+
+    /// Record a block that failed processing and emit the skipped-block metric
+    fn record_skipped_block(&self, block_number: u64, error: &MonitorError) {
+        if let MonitorError::Indexer(indexer_error) = error {
+            self.error_recovery_manager.record_occurrence(indexer_error);
+        }
+        if let Err(e) = self.database.record_skipped_block(block_number, &error.to_string()) {
+            error!("Failed to record skipped block {}: {}", block_number, e);
+            return;
+        }
+
+        let total_skipped = self.database.get_skipped_block_count().unwrap_or(0);
+        MetricsLogger::log_block_skipped(block_number, &error.to_string(), total_skipped);
+    }
+
+    /// Retry skipped blocks queued for reprocessing, returning how many succeeded
+    ///
+    /// Blocks that succeed are removed from the skipped_blocks table; blocks that
+    /// fail again are re-recorded with the new error and an incremented skip count.
+    pub async fn reprocess_requested_blocks(&self) -> u32 {
+        let requested = match self.database.get_reprocess_requested_blocks() {
+            Ok(requested) => requested,
+            Err(e) => {
+                error!("Failed to load blocks queued for reprocessing: {}", e);
+                return 0;
             }
+        };
 
-            match self.process_single_block(current_block).await {
+        let mut reprocessed = 0;
+        for skipped in requested {
+            match self.process_single_block(skipped.block_number).await {
                 Ok(transfer_count) => {
-                    info!("Processed block {} with {} POL transfers", current_block, transfer_count);
-                    
-                    // Update last processed block in database
-                    if let Err(e) = self.database.set_last_processed_block(current_block) {
-                        error!("Failed to update last processed block in database: {}", e);
-                        // Don't return error here, just log it and continue
+                    info!("Reprocessed skipped block {} with {} POL transfers", skipped.block_number, transfer_count);
+                    if let Err(e) = self.database.remove_skipped_block(skipped.block_number) {
+                        error!("Failed to clear skipped block {}: {}", skipped.block_number, e);
                     }
-                    
-                    *last_processed_block = current_block;
-                    blocks_processed += 1;
-                    current_block += 1;
+                    reprocessed += 1;
                 }
                 Err(e) => {
-                    error!("Failed to process block {}: {}", current_block, e);
-                    // For block processing errors, we'll retry the same block
-                    // after a delay to avoid getting stuck
-                    sleep(Duration::from_secs(self.config.retry_delay_seconds)).await;
-                    
-                    // Skip this block after max retries to avoid infinite loop
-                    // In production, you might want to implement more sophisticated error handling
-                    warn!("Skipping block {} due to processing error", current_block);
-                    current_block += 1;
+                    warn!("Reprocessing skipped block {} failed: {}", skipped.block_number, e);
+                    self.record_skipped_block(skipped.block_number, &e);
                 }
             }
         }
 
-        Ok(blocks_processed)
+        reprocessed
     }
 
     /// Process a single block and return the number of transfers found
+    ///
+    /// Traced as a `process_single_block` span around the fetch and write spans.
     async fn process_single_block(&self, block_number: u64) -> Result<u32, MonitorError> {
-        let monitor = PerformanceMonitor::new("process_single_block")
+        let span = tracing::info_span!("process_single_block", block_number, transfer_count = tracing::field::Empty);
+        async {
+            let context = LogContext::new("block_monitor", "process_single_block")
+                .with_block_number(block_number);
+            context.debug(&format!("Processing block {}", block_number));
+
+            // Fetch block with circuit breaker protection
+            let fetched = pipeline::fetch_block(&self.block_processor, &self.rpc_circuit_breaker, block_number).await?;
+
+            let transfer_count = self.write_block(fetched).await?;
+            tracing::Span::current().record("transfer_count", transfer_count);
+            Ok(transfer_count)
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Write a fetched block's transfers and hash to the database
+    async fn write_block(&self, fetched: FetchedBlock) -> Result<u32, MonitorError> {
+        let FetchedBlock { block_number, block_hash, transfers, block_timestamp, logs } = fetched;
+        let monitor = PerformanceMonitor::new("write_block")
             .with_metadata("block_number", serde_json::json!(block_number));
-        
-        let context = LogContext::new("block_monitor", "process_single_block")
-            .with_block_number(block_number);
-        context.debug(&format!("Processing block {}", block_number));
-        
-        // Process block with circuit breaker protection
-        let transfers = {
-            let rpc_circuit_breaker = Arc::clone(&self.rpc_circuit_breaker);
-            rpc_circuit_breaker.execute(|| async {
-                self.block_processor.process_block(block_number).await
-                    .map_err(|e| IndexerError::from(e))
-            }).await?
-        };
-        
+
         let transfer_count = transfers.len() as u32;
 
         // Store transfers with database circuit breaker protection
         let database_circuit_breaker = Arc::clone(&self.database_circuit_breaker);
         database_circuit_breaker.execute(|| async {
-            for transfer in &transfers {
-                self.database.store_transfer_and_update_net_flow(transfer)
+            let _writing = tracing::info_span!("db_write", block_number, transfer_count).entered();
+            if self.journal_raw_logs {
+                self.database.store_raw_logs(block_timestamp, &logs)
+                    .map_err(IndexerError::from)?;
+            }
+            match (self.is_unconfirmed(block_number), self.skip_duplicate_transfers) {
+                (true, true) => {
+                    let stored = self.database.store_missing_pending_transfers(&transfers)
+                        .map_err(IndexerError::from)?;
+                    log_duplicates_skipped(block_number, &transfers, stored);
+                }
+                (true, false) => self.database.store_pending_transfers(&transfers)
+                    .map_err(IndexerError::from)?,
+                (false, true) => {
+                    let stored = self.database.store_missing_transfers(&transfers)
+                        .map_err(IndexerError::from)?;
+                    log_duplicates_skipped(block_number, &transfers, stored);
+                }
+                (false, false) => self.database.store_transfers_and_update_net_flow(&transfers)
+                    .map_err(|e| IndexerError::from(e))?,
+            }
+            // Skipped early blocks have no hash to verify against later
+            if !block_hash.is_empty() {
+                self.database.record_block_hash(block_number, &block_hash)
                     .map_err(|e| IndexerError::from(e))?;
             }
             Ok::<(), IndexerError>(())
@@ -227,8 +943,9 @@ impl BlockMonitor {
 
         let duration = monitor.finish();
         MetricsLogger::log_block_processed(block_number, transfer_count, duration);
+        self.check_net_flow_alert();
 
-        let context = LogContext::new("block_monitor", "process_single_block")
+        let context = LogContext::new("block_monitor", "write_block")
             .with_block_number(block_number)
             .with_metadata("transfer_count", serde_json::json!(transfer_count))
             .with_duration_ms(duration);
@@ -237,6 +954,39 @@ impl BlockMonitor {
         Ok(transfer_count)
     }
 
+    /// Raise an alert if the stored net flow just crossed the configured threshold
+    fn check_net_flow_alert(&self) {
+        let Some(alert) = &self.net_flow_alert else {
+            return;
+        };
+
+        let net_flow = self.database
+            .get_net_flow_data()
+            .map_err(|e| e.to_string())
+            .and_then(|row| self.database.amount_storage().stored_to_wei(&row.net_flow).map_err(|e| e.to_string()));
+        let net_flow = match net_flow {
+            Ok(net_flow) => net_flow,
+            Err(e) => {
+                warn!("Failed to read net flow for alerting: {}", e);
+                return;
+            }
+        };
+
+        let message = match alert.lock() {
+            Ok(mut alert) => match alert.observe_amount(&net_flow) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Failed to compare net flow for alerting: {}", e);
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+        if let Some(message) = message {
+            self.error_recovery_manager.send_alert(&message);
+        }
+    }
+
     /// Get the latest block number with retry logic and circuit breaker
     pub async fn get_latest_block_with_retry(&self) -> Result<u64, MonitorError> {
         let circuit_breaker = Arc::clone(&self.rpc_circuit_breaker);
@@ -246,18 +996,25 @@ impl BlockMonitor {
         }).await;
 
         match result {
-            Ok(block_number) => Ok(block_number),
+            Ok(block_number) => {
+                self.chain_tip.store(block_number, Ordering::Relaxed);
+                Ok(block_number)
+            }
             Err(e) => {
                 let context = LogContext::new("block_monitor", "get_latest_block")
                     .with_metadata("error_severity", serde_json::json!(format!("{:?}", e.severity())));
                 
-                ErrorLogger::log_error(&e, Some(context));
+                let occurrence = self.error_recovery_manager.record_occurrence(&e);
+                ErrorLogger::log_sampled_error(&e, Some(context), occurrence, self.error_sample_rate);
                 Err(MonitorError::Indexer(e))
             }
         }
     }
 
     /// Get the starting block number (either from database or current latest)
+    ///
+    /// On a fresh start this is `initial_lookback_blocks` behind the latest block,
+    /// so the most recent `initial_lookback_blocks` blocks are processed first.
     async fn get_starting_block_number(&self) -> Result<u64, MonitorError> {
         // Try to get last processed block from database
         match self.database.get_last_processed_block() {
@@ -272,25 +1029,73 @@ impl BlockMonitor {
             }
         }
 
-        // If no last processed block, start from current latest block
-        info!("No previous state found, starting from current latest block");
+        // If no last processed block, start from the current latest block minus the lookback
         let latest_block = self.get_latest_block_with_retry().await?;
+        let starting_block = latest_block.saturating_sub(self.config.initial_lookback_blocks);
+        if starting_block < latest_block {
+            info!(
+                "No previous state found, catching up {} blocks behind latest block {}",
+                latest_block - starting_block, latest_block
+            );
+        } else {
+            info!("No previous state found, starting from current latest block");
+        }
         
         // Initialize the database with the starting block
-        if let Err(e) = self.database.set_last_processed_block(latest_block) {
+        if let Err(e) = self.database.set_last_processed_block(starting_block) {
             warn!("Failed to initialize last processed block in database: {}", e);
         }
 
-        Ok(latest_block)
+        Ok(starting_block)
     }
 
     /// Persist the current state to database
     pub async fn persist_state(&self, last_processed_block: u64) -> Result<(), MonitorError> {
         info!("Persisting state: last processed block = {}", last_processed_block);
         self.database.set_last_processed_block(last_processed_block)?;
+        // Snapshot the final totals unless the last block already has a snapshot
+        if last_processed_block > self.last_snapshot_block.load(Ordering::Relaxed) {
+            self.record_net_flow_snapshot(last_processed_block);
+        }
         Ok(())
     }
 
+    /// Record the wall-clock time at which a block was successfully processed
+    pub fn record_block_processed_at(&self, at: SystemTime) {
+        let seconds = at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.last_block_processed_at.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Seconds elapsed between the last processed block and `now`, if any block has been processed
+    pub fn seconds_since_last_block_at(&self, now: SystemTime) -> Option<u64> {
+        let last = self.last_block_processed_at.load(Ordering::Relaxed);
+        if last == 0 {
+            return None;
+        }
+        let now = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Some(now.saturating_sub(last))
+    }
+
+    /// Seconds elapsed since the last successfully processed block
+    pub fn seconds_since_last_block(&self) -> Option<u64> {
+        self.seconds_since_last_block_at(SystemTime::now())
+    }
+
+    /// Database the monitor writes to, for sharing with the API server
+    pub fn database(&self) -> Arc<Database> {
+        Arc::clone(&self.database)
+    }
+
+    /// Occupancy of the live fetcher-to-writer buffer, for reporting in `/status`
+    pub fn pipeline_occupancy(&self) -> Arc<PipelineOccupancy> {
+        Arc::clone(&self.pipeline_occupancy)
+    }
+
+    /// Error patterns recorded while monitoring, for reporting in `/diagnostics/errors`
+    pub fn error_recovery_manager(&self) -> Arc<ErrorRecoveryManager> {
+        Arc::clone(&self.error_recovery_manager)
+    }
+
     /// Request graceful shutdown
     pub fn shutdown(&self) {
         info!("Requesting graceful shutdown");
@@ -299,10 +1104,9 @@ impl BlockMonitor {
 
     /// Get current monitoring status
     pub async fn get_status(&self) -> Result<MonitorStatus, MonitorError> {
-        let latest_block = self.get_latest_block_with_retry().await?;
-        let last_processed_block = self.database.get_last_processed_block().unwrap_or(0);
-        let net_flow_data = self.database.get_net_flow_data()?;
-        let transaction_count = self.database.get_transaction_count()?;
+        let latest_block = self.status_latest_block().await?;
+        let stats = self.database.get_stats()?;
+        let last_processed_block = stats.net_flow.last_processed_block;
 
         Ok(MonitorStatus {
             latest_block,
@@ -312,12 +1116,27 @@ impl BlockMonitor {
             } else {
                 0
             },
-            total_transactions: transaction_count,
-            current_net_flow: net_flow_data.net_flow,
+            total_transactions: stats.transaction_count,
+            current_net_flow: stats.net_flow.net_flow,
             is_running: !self.shutdown_signal.load(Ordering::Relaxed),
+            seconds_since_last_block: self.seconds_since_last_block(),
+            skipped_blocks: stats.skipped_blocks,
         })
     }
 
+    /// Latest block for status, fetched at most once per status cache TTL
+    async fn status_latest_block(&self) -> Result<u64, MonitorError> {
+        let mut cached = self.status_latest_block.lock().await;
+        if let Some((latest_block, fetched_at)) = *cached {
+            if fetched_at.elapsed() < self.status_cache_ttl {
+                return Ok(latest_block);
+            }
+        }
+        let latest_block = self.get_latest_block_with_retry().await?;
+        *cached = Some((latest_block, Instant::now()));
+        Ok(latest_block)
+    }
+
     /// Get error statistics from the error recovery manager
     pub fn get_error_statistics(&self) -> Result<Vec<crate::error_recovery::ErrorStatistic>, MonitorError> {
         self.error_recovery_manager.get_error_statistics()
@@ -338,6 +1157,104 @@ pub struct MonitorStatus {
     pub total_transactions: u64,
     pub current_net_flow: String,
     pub is_running: bool,
+    pub seconds_since_last_block: Option<u64>,
+    /// Number of blocks skipped after processing failures and not yet reprocessed
+    pub skipped_blocks: u64,
+}
+
+/// Outcome of a backfill run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillSummary {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Blocks fetched and written successfully
+    pub blocks_processed: u32,
+    /// POL transfers found in the written blocks
+    pub transfers_found: u64,
+    /// Blocks that failed and were recorded in the skipped_blocks table
+    pub skipped_blocks: u32,
+    /// Whether every block in the range was written or skipped
+    pub completed: bool,
+    /// Highest block written or skipped, `None` if the run stopped before the first one
+    pub last_block: Option<u64>,
+}
+
+impl BackfillSummary {
+    /// First block a follow-up backfill should start from
+    pub fn resume_from(&self) -> u64 {
+        self.last_block.map_or(self.from_block, |block| block + 1)
+    }
+}
+
+/// Cancellation and deadline for a backfill run
+///
+/// The default never cancels and has no deadline.
+#[derive(Debug, Clone, Default)]
+pub struct BackfillControl {
+    cancel: Option<watch::Receiver<bool>>,
+    deadline: Option<Duration>,
+}
+
+impl BackfillControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop once `true` is sent on the channel
+    pub fn with_cancellation(mut self, cancel: watch::Receiver<bool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Stop once `deadline` has passed since the run started
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Resolve with the reason once the run is cancelled or `deadline` passes
+    async fn stopped(&mut self, deadline: Option<Instant>) -> &'static str {
+        let cancelled = async {
+            if let Some(cancel) = self.cancel.as_mut() {
+                if cancel.wait_for(|cancelled| *cancelled).await.is_ok() {
+                    return;
+                }
+            }
+            // Without a channel, or once its sender is dropped, the run can't be cancelled
+            std::future::pending::<()>().await
+        };
+        let timed_out = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = cancelled => "cancelled",
+            _ = timed_out => "deadline reached",
+        }
+    }
+}
+
+/// Whether transfers in `block_number` still need `min_confirmations` blocks on top of it at `chain_tip`
+pub fn is_unconfirmed(block_number: u64, min_confirmations: u64, chain_tip: u64) -> bool {
+    min_confirmations > 0 && block_number.saturating_add(min_confirmations) > chain_tip
+}
+
+/// Warn when writing a block found some of its transfers already stored
+fn log_duplicates_skipped(block_number: u64, transfers: &[ProcessedTransfer], stored: usize) {
+    let relevant = transfers
+        .iter()
+        .filter(|transfer| transfer.direction != TransferDirection::NotRelevant)
+        .count();
+    if stored < relevant {
+        warn!(
+            "Block {}: {} of {} transfers were already stored and are not counted again",
+            block_number,
+            relevant - stored,
+            relevant
+        );
+    }
 }
 
 #[cfg(test)]
@@ -353,6 +1270,7 @@ mod tests {
         assert_eq!(config.max_retries, 5);
         assert_eq!(config.retry_delay_seconds, 1);
         assert_eq!(config.max_retry_delay_seconds, 60);
+        assert!(!config.fail_fast);
     }
 
     #[test]
@@ -378,6 +1296,13 @@ mod tests {
             max_retries: 3,
             retry_delay_seconds: 2,
             max_retry_delay_seconds: 30,
+            fail_fast: false,
+            ordered_commits: true,
+            pipeline_buffer_size: DEFAULT_PIPELINE_CAPACITY,
+            initial_lookback_blocks: 0,
+            min_confirmations: 0,
+            snapshot_every_n_blocks: 1,
+            max_consecutive_skips: 0,
         };
         
         let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
@@ -412,6 +1337,8 @@ mod tests {
             total_transactions: 42,
             current_net_flow: "1500.5".to_string(),
             is_running: true,
+            seconds_since_last_block: Some(3),
+            skipped_blocks: 0,
         };
 
         assert_eq!(status.latest_block, 1000);
@@ -433,6 +1360,27 @@ mod tests {
         assert!(result.is_err()); // Expected to fail due to no network connection
     }
 
+    #[tokio::test]
+    async fn test_fresh_start_catches_up_from_initial_lookback() {
+        use crate::testing::MockPolygonNode;
+
+        let (_server, rpc_client) = MockPolygonNode::new().with_latest_block(1_000).start().await;
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let config = BlockMonitorConfig { initial_lookback_blocks: 10, ..BlockMonitorConfig::default() };
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
+
+        let mut last_processed_block = monitor.get_starting_block_number().await.unwrap();
+        assert_eq!(last_processed_block, 990);
+        assert_eq!(monitor.database.get_last_processed_block().unwrap(), 990);
+
+        let processed = monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(processed, 10);
+        assert_eq!(last_processed_block, 1_000);
+        assert_eq!(monitor.database.get_block_hash(990).unwrap(), None);
+        assert!(monitor.database.get_block_hash(991).unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn test_persist_state() {
         let rpc_client = RpcClient::new("http://test".to_string());
@@ -454,11 +1402,1186 @@ mod tests {
     }
 
     #[test]
-    fn test_monitor_error_display() {
-        let config_error = MonitorError::Config("Test config error".to_string());
-        assert_eq!(format!("{}", config_error), "Monitor configuration error: Test config error");
+    fn test_seconds_since_last_block() {
+        let rpc_client = RpcClient::new("http://test".to_string());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
 
-        let shutdown_error = MonitorError::Shutdown;
-        assert_eq!(format!("{}", shutdown_error), "Shutdown requested");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None);
+        assert_eq!(monitor.seconds_since_last_block(), None);
+
+        let processed_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        monitor.record_block_processed_at(processed_at);
+
+        assert_eq!(monitor.seconds_since_last_block_at(processed_at), Some(0));
+        assert_eq!(monitor.seconds_since_last_block_at(processed_at + Duration::from_secs(45)), Some(45));
+    }
+
+    #[tokio::test]
+    async fn test_block_over_transfer_cap_is_recorded_as_skipped() {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let transfers = |block_number: u64, count: u32| -> Vec<ProcessedTransfer> {
+            (0..count)
+                .map(|log_index| ProcessedTransfer {
+                    block_number,
+                    transaction_hash: format!("0xflood{}", block_number),
+                    log_index,
+                    transaction_index: 0,
+                    from_address: "0x1234567890123456789012345678901234567890".to_string(),
+                    to_address: BINANCE_ADDRESSES[0].to_string(),
+                    amount: "1000".to_string(),
+                    timestamp: 0,
+                    direction: TransferDirection::NotRelevant,
+                })
+                .collect()
+        };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(102)
+            .with_block(101, transfers(101, 3))
+            .with_block(102, transfers(102, 2))
+            .start()
+            .await;
+
+        let block_processor = BlockProcessor::new(rpc_client.clone()).with_max_transfers_per_block(2);
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let config = BlockMonitorConfig {
+            retry_delay_seconds: 0,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
+
+        let mut last_processed_block = 100;
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 1);
+
+        let skipped = monitor.database.get_skipped_blocks().unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].block_number, 101);
+        assert!(skipped[0].error.contains("more than 2 POL transfers"), "{}", skipped[0].error);
+        // Nothing from the rejected block reaches the database; a block at the cap does
+        assert!(monitor.database.get_transactions_by_block(101).unwrap().is_empty());
+        assert_eq!(monitor.database.get_transactions_by_block(102).unwrap().len(), 2);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_reprocessing_a_partially_stored_block_does_not_double_count() {
+        use crate::testing::MockPolygonNode;
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+
+        let transfer = |log_index: u32, amount: &str| ProcessedTransfer {
+            block_number: 101,
+            transaction_hash: format!("0xretry{}", log_index),
+            log_index,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: amount.to_string(),
+            timestamp: 0,
+            direction: TransferDirection::NotRelevant,
+        };
+        let block = vec![transfer(0, "1000"), transfer(1, "250"), transfer(2, "5")];
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(101)
+            .with_block(101, block.clone())
+            .start()
+            .await;
+
+        // An earlier attempt stored the first transfer before failing
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        database
+            .store_transfers_and_update_net_flow(&[ProcessedTransfer { direction: TransferDirection::ToBinance, ..block[0].clone() }])
+            .unwrap();
+
+        let config = BlockMonitorConfig { retry_delay_seconds: 0, ..BlockMonitorConfig::default() };
+        let monitor = BlockMonitor::new(rpc_client.clone(), BlockProcessor::new(rpc_client), database, Some(config));
+        let mut last_processed_block = 100;
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 1);
+
+        assert!(monitor.database.get_skipped_blocks().unwrap().is_empty());
+        assert_eq!(monitor.database.get_transactions_by_block(101).unwrap().len(), 3);
+        assert_eq!(monitor.database.get_net_flow_data().unwrap().total_inflow, "1255");
+    }
+
+    /// Span exporter keeping every exported span in memory
+    #[cfg(feature = "otel")]
+    #[derive(Debug, Clone, Default)]
+    struct CapturingExporter(Arc<std::sync::Mutex<Vec<opentelemetry_sdk::export::trace::SpanData>>>);
+
+    #[cfg(feature = "otel")]
+    impl opentelemetry_sdk::export::trace::SpanExporter for CapturingExporter {
+        fn export(
+            &mut self,
+            batch: Vec<opentelemetry_sdk::export::trace::SpanData>,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = opentelemetry_sdk::export::trace::ExportResult> + Send>> {
+            self.0.lock().unwrap().extend(batch);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_process_single_block_emits_trace_spans() {
+        use crate::testing::MockPolygonNode;
+
+        let exporter = CapturingExporter::default();
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let _subscriber = tracing::subscriber::set_default(crate::telemetry::subscriber(&provider));
+
+        let (_server, rpc_client) = MockPolygonNode::new().with_latest_block(101).start().await;
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None);
+        assert_eq!(monitor.process_single_block(101).await.unwrap(), 0);
+
+        let spans = exporter.0.lock().unwrap().clone();
+        let block_span = spans
+            .iter()
+            .find(|span| span.name == "process_single_block")
+            .expect("no process_single_block span exported");
+        // Unsigned fields are exported as strings
+        let attribute = |key: &str| {
+            block_span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string())
+        };
+        assert_eq!(attribute("block_number").as_deref(), Some("101"));
+        assert_eq!(attribute("transfer_count").as_deref(), Some("0"));
+
+        // Each phase is a child of the block span
+        for phase in ["rpc_fetch", "decode", "db_write"] {
+            assert!(
+                spans.iter().any(|span| span.name == phase
+                    && span.parent_span_id == block_span.span_context.span_id()),
+                "no {} span under process_single_block: {:?}",
+                phase,
+                spans.iter().map(|span| span.name.clone()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_block_is_recorded_as_skipped() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x65"
+            })))
+            .mount(&server)
+            .await;
+        // Block 101 always fails to load
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "header not found"}
+            })))
+            .mount(&server)
+            .await;
+
+        let rpc_client = RpcClient::new(server.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let config = BlockMonitorConfig {
+            retry_delay_seconds: 0,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
+
+        let mut last_processed_block = 100;
+        let processed = monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(processed, 0);
+        assert_eq!(last_processed_block, 100);
+
+        let skipped = monitor.database.get_skipped_blocks().unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].block_number, 101);
+        assert!(skipped[0].error.contains("header not found"));
+        assert_eq!(monitor.get_status().await.unwrap().skipped_blocks, 1);
+
+        // A queued retry that fails again keeps the block skipped and bumps its count
+        assert_eq!(monitor.database.request_skipped_block_reprocess(Some(101)).unwrap(), 1);
+        assert_eq!(monitor.reprocess_requested_blocks().await, 0);
+
+        let skipped = monitor.database.get_skipped_blocks().unwrap();
+        assert_eq!(skipped[0].skip_count, 2);
+        assert!(!skipped[0].reprocess_requested);
+        assert_eq!(monitor.get_status().await.unwrap().skipped_blocks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_status_reuses_latest_block_within_ttl() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x65"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let rpc_client = RpcClient::new(server.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None)
+            .with_status_cache_ttl(Duration::from_secs(60));
+
+        for _ in 0..20 {
+            assert_eq!(monitor.get_status().await.unwrap().latest_block, 101);
+        }
+        // Concurrent callers share the cached value too
+        let (first, second, third) = tokio::join!(monitor.get_status(), monitor.get_status(), monitor.get_status());
+        for status in [first, second, third] {
+            assert_eq!(status.unwrap().latest_block, 101);
+        }
+
+        server.verify().await;
+    }
+
+    /// Monitor resuming at block 100 whose chain tip is 101, a block that always fails to load
+    async fn monitor_with_unloadable_block(server: &wiremock::MockServer, fail_fast: bool) -> BlockMonitor {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x65"
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "invalid argument"}
+            })))
+            .mount(server)
+            .await;
+
+        let rpc_client = RpcClient::new(server.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        database.set_last_processed_block(100).unwrap();
+        let config = BlockMonitorConfig {
+            poll_interval_seconds: 1,
+            retry_delay_seconds: 0,
+            fail_fast,
+            ..BlockMonitorConfig::default()
+        };
+        BlockMonitor::new(rpc_client, block_processor, database, Some(config))
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_stops_monitor_on_non_recoverable_error() {
+        let server = wiremock::MockServer::start().await;
+        let monitor = monitor_with_unloadable_block(&server, true).await;
+
+        let result = tokio::time::timeout(Duration::from_secs(5), monitor.start())
+            .await
+            .expect("fail_fast monitor should stop on its own");
+        match result {
+            Err(error @ MonitorError::Indexer(_)) => assert!(!error.is_recoverable()),
+            other => panic!("expected a non-recoverable indexer error, got {:?}", other),
+        }
+
+        // The failed block is still recorded for later reprocessing
+        assert_eq!(monitor.database.get_skipped_blocks().unwrap()[0].block_number, 101);
+        assert_eq!(monitor.database.get_last_processed_block().unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_without_fail_fast_monitor_keeps_looping() {
+        let server = wiremock::MockServer::start().await;
+        let monitor = monitor_with_unloadable_block(&server, false).await;
+
+        // Still running after several polls that each skip the failing block
+        let result = tokio::time::timeout(Duration::from_millis(2500), monitor.start()).await;
+        assert!(result.is_err(), "monitor should keep looping, got {:?}", result);
+        assert_eq!(monitor.database.get_skipped_blocks().unwrap()[0].block_number, 101);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pipelined_processing_preserves_order_and_net_flow() {
+        use crate::blockchain::transfer_detector::{BINANCE_ADDRESSES, POL_TOKEN_ADDRESS, TRANSFER_EVENT_SIGNATURE};
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        fn topic(address: &str) -> String {
+            format!("0x{:0>64}", address.trim_start_matches("0x"))
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": "0x67"
+            })))
+            .mount(&server)
+            .await;
+
+        // Blocks 101..=103 alternate inflows and outflows of 1, 2 and 3 POL
+        let external = "0x1111111111111111111111111111111111111111";
+        for (block_number, pol) in [(101u64, 1u64), (102, 2), (103, 3)] {
+            let block_hex = format!("0x{:x}", block_number);
+            let (from, to) = if block_number % 2 == 1 {
+                (external, BINANCE_ADDRESSES[0])
+            } else {
+                (BINANCE_ADDRESSES[0], external)
+            };
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({
+                    "method": "eth_getBlockByNumber", "params": [block_hex, true]
+                })))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": 1,
+                            "result": {
+                                "number": block_hex,
+                                "hash": format!("0xhash{}", block_number),
+                                "timestamp": "0x61cf9980",
+                                "transactions": []
+                            }
+                        }))
+                        .set_delay(Duration::from_millis(10 * (4 - (block_number - 100)))),
+                )
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({
+                    "method": "eth_getLogs", "params": [{"fromBlock": block_hex}]
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [{
+                        "address": POL_TOKEN_ADDRESS,
+                        "topics": [TRANSFER_EVENT_SIGNATURE, topic(from), topic(to)],
+                        "data": format!("0x{:064x}", pol as u128 * 1_000_000_000_000_000_000),
+                        "blockNumber": block_hex,
+                        "transactionHash": format!("0xtx{}", block_number),
+                        "logIndex": "0x0"
+                    }]
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let rpc_client = RpcClient::new(server.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None);
+
+        let mut last_processed_block = 100;
+        let processed = monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(processed, 3);
+        assert_eq!(last_processed_block, 103);
+        assert_eq!(monitor.database.get_last_processed_block().unwrap(), 103);
+
+        // Transactions are written in block order (ids ascend with block numbers)
+        let mut stored = monitor.database.get_recent_transactions(10, 0).unwrap();
+        stored.sort_by_key(|tx| tx.id);
+        let blocks: Vec<u64> = stored.iter().map(|tx| tx.block_number).collect();
+        assert_eq!(blocks, vec![101, 102, 103]);
+        assert_eq!(monitor.database.get_block_hash(102).unwrap().as_deref(), Some("0xhash102"));
+
+        let net_flow = monitor.database.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "4000000000000000000");
+        assert_eq!(net_flow.total_outflow, "2000000000000000000");
+        assert_eq!(net_flow.net_flow, "2000000000000000000");
+    }
+
+    #[test]
+    fn test_monitor_error_display() {
+        let config_error = MonitorError::Config("Test config error".to_string());
+        assert_eq!(format!("{}", config_error), "Monitor configuration error: Test config error");
+
+        let shutdown_error = MonitorError::Shutdown;
+        assert_eq!(format!("{}", shutdown_error), "Shutdown requested");
+    }
+
+    /// Mount a chain at `server` whose tip is `latest_block` and whose blocks have no transfers
+    async fn mount_empty_chain(server: &wiremock::MockServer, latest_block: u64) {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", latest_block)
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"number": "0x1", "hash": "0xabc", "timestamp": "0x61cf9980", "transactions": []}
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": []
+            })))
+            .mount(server)
+            .await;
+    }
+
+    async fn monitor_with_archive(primary: &wiremock::MockServer, archive: &wiremock::MockServer) -> BlockMonitor {
+        let rpc_client = RpcClient::new(primary.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+
+        BlockMonitor::new(rpc_client, block_processor, database, None)
+            .with_archive_block_processor(BlockProcessor::new(RpcClient::new(archive.uri())))
+    }
+
+    #[tokio::test]
+    async fn test_backfill_uses_archive_endpoint() {
+        let primary = wiremock::MockServer::start().await;
+        let archive = wiremock::MockServer::start().await;
+        mount_empty_chain(&primary, 1_000).await;
+        mount_empty_chain(&archive, 1_000).await;
+
+        let monitor = monitor_with_archive(&primary, &archive).await;
+        monitor.database.set_last_processed_block(900).unwrap();
+
+        let summary = monitor.backfill(10, 12, BackfillControl::default()).await.unwrap();
+        assert_eq!(
+            summary,
+            BackfillSummary {
+                from_block: 10,
+                to_block: 12,
+                blocks_processed: 3,
+                transfers_found: 0,
+                skipped_blocks: 0,
+                completed: true,
+                last_block: Some(12),
+            }
+        );
+
+        assert!(primary.received_requests().await.unwrap().is_empty());
+        assert!(!archive.received_requests().await.unwrap().is_empty());
+        assert_eq!(monitor.database.get_block_hash(11).unwrap(), Some("0xabc".to_string()));
+        // Backfilling an older range does not move the live cursor
+        assert_eq!(monitor.database.get_last_processed_block().unwrap(), 900);
+
+        assert!(matches!(monitor.backfill(12, 10, BackfillControl::default()).await, Err(MonitorError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_backfill_returns_partial_summary() {
+        let archive = wiremock::MockServer::start().await;
+        mount_empty_chain(&archive, 1_000_000).await;
+        let monitor = monitor_with_archive(&archive, &archive).await;
+
+        // Cancel once a few blocks have been written
+        let (cancel, cancelled) = watch::channel(false);
+        let database = Arc::clone(&monitor.database);
+        tokio::spawn(async move {
+            while database.get_block_hashes_in_range(1, 100_000).unwrap().len() < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            cancel.send(true).unwrap();
+        });
+
+        let control = BackfillControl::new().with_cancellation(cancelled);
+        let summary = monitor.backfill(1, 100_000, control).await.unwrap();
+
+        assert!(!summary.completed);
+        let last_block = summary.last_block.expect("blocks were written before cancelling");
+        assert!((3..100_000).contains(&last_block));
+        assert_eq!(summary.blocks_processed as u64, last_block);
+        assert_eq!(summary.resume_from(), last_block + 1);
+        // Everything up to the resume point was written, nothing past it
+        let written = monitor.database.get_block_hashes_in_range(1, 100_000).unwrap();
+        assert_eq!(written.len() as u64, last_block);
+
+        let resumed = monitor
+            .backfill(summary.resume_from(), summary.resume_from() + 1, BackfillControl::default())
+            .await
+            .unwrap();
+        assert!(resumed.completed);
+        assert_eq!(resumed.last_block, Some(last_block + 2));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_backfill_resumes_from_persisted_state() {
+        let archive = wiremock::MockServer::start().await;
+        mount_empty_chain(&archive, 1_000_000).await;
+        let monitor = monitor_with_archive(&archive, &archive).await;
+        monitor.database.set_last_processed_block(500_000).unwrap();
+        assert!(matches!(
+            monitor.resume_backfill(BackfillControl::default()).await,
+            Err(MonitorError::Config(_))
+        ));
+
+        // Interrupt once a few blocks have been written
+        let (cancel, cancelled) = watch::channel(false);
+        let database = Arc::clone(&monitor.database);
+        tokio::spawn(async move {
+            while database.get_block_hashes_in_range(1, 300).unwrap().len() < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            cancel.send(true).unwrap();
+        });
+        let control = BackfillControl::new().with_cancellation(cancelled);
+        let interrupted = monitor.backfill(1, 300, control).await.unwrap();
+        assert!(!interrupted.completed);
+
+        let state = monitor.database.get_backfill_state().unwrap().expect("backfill state is persisted");
+        assert_eq!((state.from_block, state.to_block), (1, 300));
+        assert_eq!(state.last_completed_block, interrupted.last_block);
+
+        let resumed = monitor.resume_backfill(BackfillControl::default()).await.unwrap();
+        assert!(resumed.completed);
+        assert_eq!(resumed.from_block, interrupted.resume_from());
+        // Every block written exactly once across both runs
+        assert_eq!(interrupted.blocks_processed + resumed.blocks_processed, 300);
+        assert_eq!(monitor.database.get_block_hashes_in_range(1, 300).unwrap().len(), 300);
+        assert!(monitor.database.get_backfill_state().unwrap().unwrap().is_complete());
+        // The live cursor is independent of the backfill
+        assert_eq!(monitor.database.get_last_processed_block().unwrap(), 500_000);
+
+        // Resuming a finished backfill does nothing
+        let again = monitor.resume_backfill(BackfillControl::default()).await.unwrap();
+        assert!(again.completed);
+        assert_eq!(again.blocks_processed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stops_at_deadline() {
+        let archive = wiremock::MockServer::start().await;
+        mount_empty_chain(&archive, 1_000_000).await;
+        let monitor = monitor_with_archive(&archive, &archive).await;
+
+        let control = BackfillControl::new().with_deadline(Duration::from_millis(100));
+        let summary = monitor.backfill(1, 100_000, control).await.unwrap();
+
+        assert!(!summary.completed);
+        assert_eq!(summary.resume_from(), summary.last_block.map_or(1, |block| block + 1));
+        assert!(summary.resume_from() < 100_000);
+    }
+
+    #[tokio::test]
+    async fn test_live_polling_uses_primary_endpoint() {
+        let primary = wiremock::MockServer::start().await;
+        let archive = wiremock::MockServer::start().await;
+        mount_empty_chain(&primary, 102).await;
+        mount_empty_chain(&archive, 102).await;
+
+        let monitor = monitor_with_archive(&primary, &archive).await;
+
+        let mut last_processed_block = 100;
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 2);
+        assert_eq!(last_processed_block, 102);
+
+        assert!(!primary.received_requests().await.unwrap().is_empty());
+        assert!(archive.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_falls_back_to_primary_without_archive() {
+        let primary = wiremock::MockServer::start().await;
+        mount_empty_chain(&primary, 1_000).await;
+
+        let rpc_client = RpcClient::new(primary.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None);
+
+        let summary = monitor.backfill(5, 5, BackfillControl::default()).await.unwrap();
+        assert_eq!(summary.blocks_processed, 1);
+        assert!(!primary.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_refuses_without_archive_when_required() {
+        let primary = wiremock::MockServer::start().await;
+        mount_empty_chain(&primary, 1_000).await;
+
+        let rpc_client = RpcClient::new(primary.uri());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, None)
+            .with_require_archive_for_backfill(true);
+
+        match monitor.backfill(5, 5, BackfillControl::default()).await {
+            Err(MonitorError::Config(message)) => {
+                assert!(message.contains("requires an archive endpoint"), "{}", message)
+            }
+            other => panic!("expected a configuration error, got {:?}", other.map(|summary| summary.blocks_processed)),
+        }
+        assert!(primary.received_requests().await.unwrap().is_empty());
+    }
+
+    /// JSON-RPC node that answers slowly and records the peak number of requests in flight
+    async fn start_concurrency_tracking_node(latest_block: u64) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::AtomicUsize;
+
+        #[derive(Clone, Default)]
+        struct Tracker {
+            in_flight: Arc<AtomicUsize>,
+            peak: Arc<AtomicUsize>,
+        }
+
+        async fn handle(
+            axum::extract::State((tracker, latest_block)): axum::extract::State<(Tracker, u64)>,
+            axum::Json(request): axum::Json<serde_json::Value>,
+        ) -> axum::Json<serde_json::Value> {
+            let in_flight = tracker.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            tracker.peak.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tracker.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let result = match request["method"].as_str() {
+                Some("eth_blockNumber") => serde_json::json!(format!("0x{:x}", latest_block)),
+                Some("eth_getLogs") => serde_json::json!([]),
+                _ => serde_json::json!({
+                    "number": request["params"][0], "hash": "0xabc", "timestamp": "0x61cf9980", "transactions": []
+                }),
+            };
+            axum::Json(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result}))
+        }
+
+        let tracker = Tracker::default();
+        let peak = Arc::clone(&tracker.peak);
+        let app = axum::Router::new()
+            .route("/", axum::routing::post(handle))
+            .with_state((tracker, latest_block));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (url, peak)
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_are_written_every_n_blocks_and_on_shutdown() {
+        let (url, _) = start_concurrency_tracking_node(23).await;
+        let rpc_client = RpcClient::new(url);
+        let config = BlockMonitorConfig {
+            snapshot_every_n_blocks: 5,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(
+            rpc_client.clone(),
+            BlockProcessor::new(rpc_client),
+            Database::new_in_memory().expect("Failed to create test database"),
+            Some(config),
+        );
+
+        // Twenty-one blocks, 3..=23, resuming from a run that snapshotted block 2 on shutdown;
+        // snapshots are counted from there rather than aligned to multiples of 5
+        let mut last_processed_block = 2;
+        monitor.last_snapshot_block.store(last_processed_block, Ordering::Relaxed);
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 21);
+        monitor.persist_state(last_processed_block).await.unwrap();
+
+        let mut blocks: Vec<u64> = monitor.database.get_net_flow_snapshots(100).unwrap()
+            .iter()
+            .map(|snapshot| snapshot.block_number)
+            .collect();
+        blocks.sort_unstable();
+        assert_eq!(blocks, vec![7, 12, 17, 22, 23]);
+
+        // Shutting down again without new blocks adds nothing
+        monitor.persist_state(last_processed_block).await.unwrap();
+        assert_eq!(monitor.database.get_net_flow_snapshots(100).unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_and_live_polling_share_request_limit() {
+        let (url, peak) = start_concurrency_tracking_node(120).await;
+        let limiter = Arc::new(tokio::sync::Semaphore::new(1));
+
+        let rpc_client = RpcClient::new(url.clone()).with_request_limiter(Arc::clone(&limiter));
+        let archive_client = RpcClient::new(url).with_request_limiter(Arc::clone(&limiter));
+        let monitor = BlockMonitor::new(
+            rpc_client.clone(),
+            BlockProcessor::new(rpc_client),
+            Database::new_in_memory().expect("Failed to create test database"),
+            None,
+        )
+        .with_archive_block_processor(BlockProcessor::new(archive_client));
+
+        let mut last_processed_block = 100;
+        let (backfill, live) = tokio::join!(
+            monitor.backfill(1, 20, BackfillControl::default()),
+            monitor.process_new_blocks(&mut last_processed_block)
+        );
+
+        assert_eq!(backfill.unwrap().blocks_processed, 20);
+        assert_eq!(live.unwrap(), 20);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_does_not_report_its_buffer_as_live_occupancy() {
+        let (url, _) = start_concurrency_tracking_node(120).await;
+        let rpc_client = RpcClient::new(url);
+        let config = BlockMonitorConfig {
+            pipeline_buffer_size: 4,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(
+            rpc_client.clone(),
+            BlockProcessor::new(rpc_client),
+            Database::new_in_memory().expect("Failed to create test database"),
+            Some(config),
+        );
+        let occupancy = monitor.pipeline_occupancy();
+
+        assert_eq!(monitor.backfill(1, 20, BackfillControl::default()).await.unwrap().blocks_processed, 20);
+        assert_eq!(occupancy.capacity(), 0);
+
+        let mut last_processed_block = 100;
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 20);
+        assert_eq!(occupancy.capacity(), 4);
+        assert_eq!(occupancy.buffered(), 0);
+    }
+
+    /// Feed blocks 201..=205 to the writer in a scrambled order and return the
+    /// block numbers of the net-flow snapshots in the order they were stored
+    async fn snapshot_order_for_scrambled_blocks(ordered_commits: bool) -> Vec<u64> {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+
+        let rpc_client = RpcClient::new("http://test".to_string());
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let config = BlockMonitorConfig {
+            ordered_commits,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
+
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(8);
+        for block_number in [203u64, 201, 205, 202, 204] {
+            let transfer = ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0xtx{}", block_number),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: BINANCE_ADDRESSES[0].to_string(),
+                amount: block_number.to_string(),
+                timestamp: 0,
+                direction: TransferDirection::ToBinance,
+            };
+            sender
+                .send(PipelineItem::Fetched(FetchedBlock {
+                    block_number,
+                    block_hash: format!("0xhash{}", block_number),
+                    transfers: vec![transfer],
+                    block_timestamp: 0,
+                    logs: Vec::new(),
+                }))
+                .await
+                .unwrap();
+        }
+        drop(sender);
+
+        let mut summary = BackfillSummary::default();
+        let mut last_processed_block = 200;
+        monitor
+            .write_pipeline(
+                &mut receiver,
+                &monitor.pipeline_occupancy,
+                201,
+                &mut summary,
+                Some(&mut last_processed_block),
+                &BackfillControl::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.blocks_processed, 5);
+
+        let snapshots = monitor.database.get_net_flow_snapshots(100).unwrap();
+        // Every block's transfer is in the final snapshot whatever the order
+        assert_eq!(snapshots.last().unwrap().total_inflow, (201..=205u64).sum::<u64>().to_string());
+        snapshots.iter().map(|snapshot| snapshot.block_number).collect()
+    }
+
+    #[tokio::test]
+    async fn test_monitor_pauses_after_max_consecutive_skips() {
+        use crate::testing::MockPolygonNode;
+        use std::sync::atomic::AtomicBool;
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let (server, rpc_client) = MockPolygonNode::new().with_latest_block(110).start().await;
+        // Every block fails while the node is down, as during an RPC outage
+        let node_down = Arc::new(AtomicBool::new(true));
+        let responder_node_down = Arc::clone(&node_down);
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(move |_: &wiremock::Request| {
+                let body = if responder_node_down.load(Ordering::SeqCst) {
+                    serde_json::json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "header not found"}})
+                } else {
+                    serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": []})
+                };
+                ResponseTemplate::new(200).set_body_json(body)
+            })
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let config = BlockMonitorConfig {
+            max_consecutive_skips: 3,
+            retry_delay_seconds: 0,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(
+            rpc_client.clone(),
+            BlockProcessor::new(rpc_client),
+            Database::new_in_memory().expect("Failed to create test database"),
+            Some(config),
+        );
+
+        // Blocks 101..=103 are skipped, then 104 pauses processing
+        let mut last_processed_block = 100;
+        let result = monitor.process_new_blocks(&mut last_processed_block).await;
+        assert!(
+            matches!(result, Err(MonitorError::SkipLimitReached { block_number: 104, skips: 3 })),
+            "unexpected result: {:?}",
+            result
+        );
+        assert_eq!(last_processed_block, 100);
+        assert_eq!(monitor.database.get_skipped_block_count().unwrap(), 3);
+
+        // The cursor never moved past the skipped blocks, so the next poll retries
+        // from the first of them and is refused there; nothing more is skipped
+        let result = monitor.process_new_blocks(&mut last_processed_block).await;
+        assert!(
+            matches!(result, Err(MonitorError::SkipLimitReached { block_number: 101, .. })),
+            "unexpected result: {:?}",
+            result
+        );
+        assert_eq!(last_processed_block, 100);
+        assert_eq!(monitor.database.get_skipped_block_count().unwrap(), 3);
+
+        // Once the node recovers, processing resumes from block 101
+        node_down.store(false, Ordering::SeqCst);
+        assert_eq!(monitor.process_new_blocks(&mut last_processed_block).await.unwrap(), 10);
+        assert_eq!(last_processed_block, 110);
+    }
+
+    #[tokio::test]
+    async fn test_ordered_commits_store_snapshots_in_block_order() {
+        assert_eq!(snapshot_order_for_scrambled_blocks(true).await, vec![201, 202, 203, 204, 205]);
+        // Without the buffer the snapshots follow arrival order
+        assert_eq!(snapshot_order_for_scrambled_blocks(false).await, vec![203, 201, 205, 202, 204]);
+    }
+
+    #[test]
+    fn test_net_flow_alert_fires_once_per_crossing() {
+        let mut alert = NetFlowAlert::new(-1_000);
+
+        assert_eq!(alert.observe(-500), None);
+        assert!(alert.observe(-1_001).unwrap().contains("below the alert threshold of -1000 wei"));
+        // Further outflows past the threshold do not repeat the alert
+        assert_eq!(alert.observe(-5_000), None);
+        assert_eq!(alert.observe(-1_200), None);
+        // Recovering re-arms it
+        assert_eq!(alert.observe(-1_000), None);
+        assert!(alert.observe(-2_000).is_some());
+
+        let mut alert = NetFlowAlert::new(1_000);
+        assert_eq!(alert.observe(1_000), None);
+        assert!(alert.observe(1_001).unwrap().contains("above"));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_alerts_when_net_flow_crosses_threshold() {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{AmountStorage, ProcessedTransfer, TransferDirection};
+        use crate::testing::{capture_logs, captured_logs};
+
+        capture_logs();
+        let external = "0x1111111111111111111111111111111111111111";
+        let block = |block_number: u64, amount: u64, direction: TransferDirection| {
+            let (from, to) = match direction {
+                TransferDirection::ToBinance => (external, BINANCE_ADDRESSES[0]),
+                _ => (BINANCE_ADDRESSES[0], external),
+            };
+            FetchedBlock {
+                block_number,
+                block_hash: format!("0xhash{}", block_number),
+                transfers: vec![ProcessedTransfer {
+                    block_number,
+                    transaction_hash: format!("0xalert{}", block_number),
+                    log_index: 0,
+                    transaction_index: 0,
+                    from_address: from.to_string(),
+                    to_address: to.to_string(),
+                    amount: amount.to_string(),
+                    timestamp: 0,
+                    direction,
+                }],
+                block_timestamp: 0,
+                logs: Vec::new(),
+            }
+        };
+        let alerts = || captured_logs(log::Level::Warn, "alert threshold of -7531 wei").len();
+
+        // Net flow: -5000, -8000 (crosses), -9000, -4000 (recovers), -10000 (crosses again)
+        let steps = [
+            (5_000, TransferDirection::FromBinance, 0),
+            (3_000, TransferDirection::FromBinance, 1),
+            (1_000, TransferDirection::FromBinance, 1),
+            (5_000, TransferDirection::ToBinance, 1),
+            (6_000, TransferDirection::FromBinance, 2),
+        ];
+
+        // The threshold is in wei, so the stored net flow is converted before comparing
+        for (round, storage) in [AmountStorage::Wei, AmountStorage::Decimal].into_iter().enumerate() {
+            let rpc_client = RpcClient::new("http://test".to_string());
+            let block_processor = BlockProcessor::new(rpc_client.clone());
+            let database = Database::new_in_memory_with_amount_storage(storage).expect("Failed to create test database");
+            let monitor = BlockMonitor::new(rpc_client, block_processor, database, None).with_net_flow_alert(-7_531);
+
+            for (offset, (amount, direction, expected_alerts)) in steps.clone().into_iter().enumerate() {
+                monitor.write_block(block(300 + offset as u64, amount, direction)).await.unwrap();
+                assert_eq!(alerts(), expected_alerts + 2 * round, "{:?} storage, after block {}", storage, 300 + offset);
+            }
+        }
+    }
+
+    /// Monitor requiring three confirmations, with a 1 POL inflow stored as pending in block 100 at the tip
+    async fn monitor_with_pending_inflow() -> (wiremock::MockServer, BlockMonitor) {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let inflow = ProcessedTransfer {
+            block_number: 100,
+            transaction_hash: "0xpending".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: "1000000000000000000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::ToBinance,
+        };
+        let (server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(100)
+            .with_block(100, vec![inflow])
+            .start()
+            .await;
+        let block_processor = BlockProcessor::new(rpc_client.clone());
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        let config = BlockMonitorConfig {
+            retry_delay_seconds: 0,
+            min_confirmations: 3,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
+
+        let mut last_processed_block = 99;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(monitor.database.get_transactions_by_block(100).unwrap().len(), 1);
+        assert_eq!(monitor.database.get_pending_transfer_count().unwrap(), 1);
+        assert_eq!(monitor.database.get_net_flow_data().unwrap().total_inflow, "0");
+
+        (server, monitor)
+    }
+
+    /// Report `latest_block` as the tip; a lower `priority` overrides earlier tips
+    async fn advance_tip(server: &wiremock::MockServer, latest_block: u64, priority: u8) {
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::body_partial_json(serde_json::json!({"method": "eth_blockNumber"})))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", latest_block)
+            })))
+            .with_priority(priority)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_pending_transfer_is_counted_once_confirmed() {
+        let (server, monitor) = monitor_with_pending_inflow().await;
+
+        // Two blocks on top are not enough
+        advance_tip(&server, 102, 2).await;
+        let mut last_processed_block = 100;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(monitor.database.get_pending_transfer_count().unwrap(), 1);
+        assert_eq!(monitor.database.get_net_flow_data().unwrap().total_inflow, "0");
+
+        advance_tip(&server, 103, 1).await;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert_eq!(monitor.database.get_pending_transfer_count().unwrap(), 0);
+        let net_flow = monitor.database.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "1000000000000000000");
+        assert_eq!(net_flow.net_flow, "1000000000000000000");
+        assert_eq!(monitor.database.recompute_net_flow().unwrap().total_inflow, "1000000000000000000");
+    }
+
+    #[tokio::test]
+    async fn test_reorged_pending_transfer_is_replaced_not_counted() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let (server, monitor) = monitor_with_pending_inflow().await;
+
+        // Block 100 is replaced by one with a different hash and no transfers
+        let reorged_hash = format!("0x{:064x}", 0xbeef);
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber", "params": ["0x64", true]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": {"number": "0x64", "hash": reorged_hash, "timestamp": "0x6553f164", "transactions": []}
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs", "params": [{"fromBlock": "0x64", "toBlock": "0x64"}]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": []})))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        advance_tip(&server, 103, 1).await;
+
+        let mut last_processed_block = 100;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+
+        assert!(monitor.database.get_transactions_by_block(100).unwrap().is_empty());
+        assert_eq!(monitor.database.get_pending_transfer_count().unwrap(), 0);
+        assert_eq!(monitor.database.get_net_flow_data().unwrap().total_inflow, "0");
+        assert_eq!(monitor.database.get_block_hash(100).unwrap(), Some(reorged_hash));
+    }
+
+    #[tokio::test]
+    async fn test_reorged_block_counted_outside_the_window_is_taken_back() {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let (_server, rpc_client) = MockPolygonNode::new().with_latest_block(103).start().await;
+        let database = Database::new_in_memory().expect("Failed to create test database");
+        database.finalize_pending_transfers(99).unwrap();
+
+        // Block 100 already had three blocks on top when written, so it was counted at once
+        let orphaned = ProcessedTransfer {
+            block_number: 100,
+            transaction_hash: "0xorphaned".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: "1000000000000000000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::ToBinance,
+        };
+        database.store_processed_block(100, "0xorphanedhash", &[orphaned], false).unwrap();
+        assert_eq!(database.get_net_flow_data().unwrap().total_inflow, "1000000000000000000");
+
+        let config = BlockMonitorConfig {
+            retry_delay_seconds: 0,
+            min_confirmations: 3,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(rpc_client.clone(), BlockProcessor::new(rpc_client), database, Some(config));
+
+        // The canonical block 100 has another hash and no transfers
+        let mut last_processed_block = 102;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+
+        assert!(monitor.database.get_transactions_by_block(100).unwrap().is_empty());
+        let net_flow = monitor.database.get_net_flow_data().unwrap();
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.net_flow.as_str()), ("0", "0"));
+        assert_eq!(monitor.database.recompute_net_flow().unwrap().total_inflow, "0");
+        assert_eq!(monitor.database.get_block_hash(100).unwrap(), Some(MockPolygonNode::block_hash(100)));
+    }
+
+    #[tokio::test]
+    async fn test_reorg_of_a_block_without_transfers_stores_the_canonical_transfers() {
+        use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let (server, rpc_client) = MockPolygonNode::new().with_latest_block(100).start().await;
+        let config = BlockMonitorConfig {
+            retry_delay_seconds: 0,
+            min_confirmations: 3,
+            ..BlockMonitorConfig::default()
+        };
+        let monitor = BlockMonitor::new(
+            rpc_client.clone(),
+            BlockProcessor::new(rpc_client),
+            Database::new_in_memory().expect("Failed to create test database"),
+            Some(config),
+        );
+        let mut last_processed_block = 99;
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+        assert!(monitor.database.get_transactions_by_block(100).unwrap().is_empty());
+
+        // Block 100 was empty; the block that replaces it holds an inflow
+        let reorged_hash = format!("0x{:064x}", 0xbeef);
+        let inflow = MockPolygonNode::transfer_log(&ProcessedTransfer {
+            block_number: 100,
+            transaction_hash: "0xreorged".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: "1000000000000000000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::ToBinance,
+        });
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber", "params": ["0x64", true]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": {"number": "0x64", "hash": reorged_hash, "timestamp": "0x6553f164", "transactions": []}
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs", "params": [{"fromBlock": "0x64", "toBlock": "0x64"}]})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": [{
+                    "address": inflow.address,
+                    "topics": inflow.topics,
+                    "data": inflow.data,
+                    "blockNumber": "0x64",
+                    "transactionHash": inflow.transaction_hash,
+                    "logIndex": "0x0",
+                    "transactionIndex": "0x0",
+                    "removed": false
+                }]
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        advance_tip(&server, 103, 1).await;
+
+        monitor.process_new_blocks(&mut last_processed_block).await.unwrap();
+
+        assert_eq!(monitor.database.get_transactions_by_block(100).unwrap().len(), 1);
+        assert_eq!(monitor.database.get_pending_transfer_count().unwrap(), 0);
+        assert_eq!(monitor.database.get_net_flow_data().unwrap().total_inflow, "1000000000000000000");
+        assert_eq!(monitor.database.get_block_hash(100).unwrap(), Some(reorged_hash));
+    }
+}
@@ -1,7 +1,13 @@
 
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use thiserror::Error;
-use crate::blockchain::{RpcClient, Block, LogFilter};
-use crate::blockchain::transfer_detector::{TransferDetector, TRANSFER_EVENT_SIGNATURE, POL_TOKEN_ADDRESS};
+use tracing::Instrument;
+use crate::blockchain::{RpcClient, LogFilter};
+use crate::blockchain::transfer_detector::{TransferDetector, POL_TOKEN_ADDRESS};
 use crate::models::{ProcessedTransfer, RawLog, TransferDirection};
 
 #[derive(Error, Debug)]
@@ -12,11 +18,90 @@ pub enum ProcessError {
     Rpc(#[from] crate::blockchain::rpc_client::RpcError),
     #[error("Transfer detection error: {0}")]
     TransferDetection(#[from] crate::blockchain::transfer_detector::TransferDetectionError),
+    #[error("Block {block_number} has more than {max} POL transfers")]
+    TooManyTransfers { block_number: u64, max: usize },
+    #[error("Transfer {transaction_hash}:{log_index} in block {block_number} has amount {amount}, above u128::MAX")]
+    AmountOverflow { block_number: u64, transaction_hash: String, log_index: u32, amount: String },
+}
+
+/// Decoded POL transfers accepted from a single block before it is rejected
+pub const DEFAULT_MAX_TRANSFERS_PER_BLOCK: usize = 100_000;
+
+/// Seconds a block timestamp may run ahead of local time before it is replaced
+pub const DEFAULT_MAX_TIMESTAMP_SKEW_SECONDS: u64 = 900;
+
+/// What happens to a tracked transfer whose amount does not fit in `u128`
+///
+/// Amounts are decoded as full uint256 values and stored as decimal strings, so
+/// nothing is lost by default. The other modes are for deployments whose
+/// downstream consumers read amounts as `u128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmountOverflow {
+    /// Store the exact amount
+    #[default]
+    Keep,
+    /// Drop the transfer with a warning
+    Skip,
+    /// Store `u128::MAX` instead, with a warning
+    Clamp,
+    /// Fail the block, which is then recorded as skipped
+    Error,
+}
+
+impl AmountOverflow {
+    /// Name accepted in configuration
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AmountOverflow::Keep => "keep",
+            AmountOverflow::Skip => "skip",
+            AmountOverflow::Clamp => "clamp",
+            AmountOverflow::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for AmountOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AmountOverflow {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "keep" => Ok(AmountOverflow::Keep),
+            "skip" => Ok(AmountOverflow::Skip),
+            "clamp" => Ok(AmountOverflow::Clamp),
+            "error" => Ok(AmountOverflow::Error),
+            _ => Err(format!("Unknown amount overflow mode '{}' (expected keep, skip, clamp or error)", value)),
+        }
+    }
+}
+
+/// A processed block with the decoded POL logs its transfers came from
+#[derive(Debug, Clone, Default)]
+pub struct ProcessedBlock {
+    /// Empty for blocks that were skipped without being fetched
+    pub block_hash: String,
+    pub timestamp: u64,
+    pub transfers: Vec<ProcessedTransfer>,
+    /// Every decoded POL log of the block, tracked or not
+    pub logs: Vec<RawLog>,
 }
 
 pub struct BlockProcessor {
     rpc_client: RpcClient,
     transfer_detector: TransferDetector,
+    min_block: u64,
+    /// Keep transfers of zero tokens instead of dropping them
+    track_zero_value_transfers: bool,
+    max_transfers_per_block: usize,
+    on_amount_overflow: AmountOverflow,
+    /// Seconds a block timestamp may be ahead of local time, 0 to trust every timestamp
+    max_timestamp_skew_seconds: u64,
 }
 
 impl BlockProcessor {
@@ -24,30 +109,214 @@ impl BlockProcessor {
         Self {
             rpc_client,
             transfer_detector: TransferDetector::new(),
+            min_block: 0,
+            track_zero_value_transfers: false,
+            max_transfers_per_block: DEFAULT_MAX_TRANSFERS_PER_BLOCK,
+            on_amount_overflow: AmountOverflow::default(),
+            max_timestamp_skew_seconds: DEFAULT_MAX_TIMESTAMP_SKEW_SECONDS,
         }
     }
 
-    /// Process a block and extract POL token transfers involving Binance addresses
-    pub async fn process_block(&self, block_number: u64) -> Result<Vec<ProcessedTransfer>, ProcessError> {
-        // Get block data to extract timestamp
-        let block = self.rpc_client.get_block(block_number).await?;
-        let timestamp = parse_hex_timestamp(&block.timestamp)?;
+    /// Skip blocks below `min_block` instead of fetching and decoding them
+    pub fn with_min_block(mut self, min_block: u64) -> Self {
+        self.min_block = min_block;
+        self
+    }
+
+    /// Choose whether zero-value transfers are returned for storage (off by default)
+    ///
+    /// They are valid ERC-20 events, used by some airdrops and approval trackers,
+    /// but never change the net flow.
+    pub fn with_track_zero_value_transfers(mut self, track_zero_value_transfers: bool) -> Self {
+        self.track_zero_value_transfers = track_zero_value_transfers;
+        self
+    }
+
+    /// Reject blocks that decode to more than `max_transfers_per_block` POL transfers
+    ///
+    /// Guards memory and the database against a node returning a block stuffed
+    /// with fabricated logs; the block fails and is recorded as skipped.
+    pub fn with_max_transfers_per_block(mut self, max_transfers_per_block: usize) -> Self {
+        self.max_transfers_per_block = max_transfers_per_block;
+        self
+    }
+
+    /// Choose what happens to tracked transfers whose amount exceeds `u128::MAX`
+    pub fn with_on_amount_overflow(mut self, on_amount_overflow: AmountOverflow) -> Self {
+        self.on_amount_overflow = on_amount_overflow;
+        self
+    }
+
+    /// Replace block timestamps more than `max_timestamp_skew_seconds` ahead of local time
+    ///
+    /// A node with a skewed clock can report blocks from the future, which would
+    /// hide their transfers from rolling-window queries until local time caught
+    /// up. Such timestamps are logged as suspect and the local time is used
+    /// instead. 0 keeps every timestamp as reported.
+    pub fn with_max_timestamp_skew(mut self, max_timestamp_skew_seconds: u64) -> Self {
+        self.max_timestamp_skew_seconds = max_timestamp_skew_seconds;
+        self
+    }
+
+    /// Use `transfer_detector`, e.g. one with additional event decoders registered
+    pub fn with_transfer_detector(mut self, transfer_detector: TransferDetector) -> Self {
+        self.transfer_detector = transfer_detector;
+        self
+    }
+
+    /// Log filter for POL token events in a single block
+    ///
+    /// A `topics` entry matches a single value, so the signature is only filtered
+    /// on the node when exactly one event decoder is registered; otherwise logs are
+    /// matched against the registered signatures locally.
+    fn log_filter_for_block(&self, block_number: u64) -> LogFilter {
+        let signatures = self.transfer_detector.event_signatures();
+        let topics = match signatures.as_slice() {
+            [signature] => Some(vec![Some(signature.clone().into())]),
+            _ => None,
+        };
 
-        // Create log filter for POL token Transfer events
-        let log_filter = LogFilter {
+        LogFilter {
             from_block: format!("0x{:x}", block_number),
             to_block: format!("0x{:x}", block_number),
             address: Some(POL_TOKEN_ADDRESS.to_string()),
-            topics: Some(vec![Some(TRANSFER_EVENT_SIGNATURE.to_string())]),
+            topics,
+        }
+    }
+
+    /// Lowest block number that is fetched and decoded
+    pub fn min_block(&self) -> u64 {
+        self.min_block
+    }
+
+    /// Parse a block's timestamp, falling back to local time when it is too far ahead of it
+    fn block_timestamp(&self, block_number: u64, hex_timestamp: &str) -> Result<u64, ProcessError> {
+        let timestamp = parse_hex_timestamp(hex_timestamp)?;
+        if self.max_timestamp_skew_seconds == 0 {
+            return Ok(timestamp);
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        if timestamp <= now.saturating_add(self.max_timestamp_skew_seconds) {
+            return Ok(timestamp);
+        }
+
+        log::warn!(
+            "Block {} timestamp {} is {}s ahead of local time, more than the allowed {}s; using local time {}",
+            block_number, timestamp, timestamp - now, self.max_timestamp_skew_seconds, now
+        );
+        Ok(now)
+    }
+
+    /// Whether a decoded transfer should be returned for storage
+    fn is_tracked(&self, transfer: &ProcessedTransfer) -> bool {
+        transfer.direction != TransferDirection::NotRelevant
+            && (self.track_zero_value_transfers || !is_zero_amount(&transfer.amount))
+    }
+
+    /// Apply `on_amount_overflow` to a tracked transfer, returning whether it is kept
+    fn check_amount_overflow(&self, transfer: &mut ProcessedTransfer) -> Result<bool, ProcessError> {
+        if !exceeds_u128(&transfer.amount) {
+            return Ok(true);
+        }
+
+        match self.on_amount_overflow {
+            AmountOverflow::Keep => Ok(true),
+            AmountOverflow::Skip => {
+                log::warn!(
+                    "Skipping transfer {}:{} in block {}: amount {} exceeds u128::MAX",
+                    transfer.transaction_hash, transfer.log_index, transfer.block_number, transfer.amount
+                );
+                Ok(false)
+            }
+            AmountOverflow::Clamp => {
+                log::warn!(
+                    "Clamping amount {} of transfer {}:{} in block {} to u128::MAX",
+                    transfer.amount, transfer.transaction_hash, transfer.log_index, transfer.block_number
+                );
+                transfer.amount = u128::MAX.to_string();
+                Ok(true)
+            }
+            AmountOverflow::Error => Err(ProcessError::AmountOverflow {
+                block_number: transfer.block_number,
+                transaction_hash: transfer.transaction_hash.clone(),
+                log_index: transfer.log_index,
+                amount: transfer.amount.clone(),
+            }),
+        }
+    }
+
+    /// Process a block and extract POL token transfers involving Binance addresses
+    pub async fn process_block(&self, block_number: u64) -> Result<Vec<ProcessedTransfer>, ProcessError> {
+        let (_, transfers) = self.process_block_with_hash(block_number).await?;
+        Ok(transfers)
+    }
+
+    /// Process a block, returning its hash alongside the extracted transfers
+    ///
+    /// Blocks below `min_block`, and a genesis block the node reports as null,
+    /// are skipped with an empty hash and no transfers.
+    pub async fn process_block_with_hash(&self, block_number: u64) -> Result<(String, Vec<ProcessedTransfer>), ProcessError> {
+        let processed = self.process_block_with_logs(block_number).await?;
+        Ok((processed.block_hash, processed.transfers))
+    }
+
+    /// Process a block, also returning its timestamp and every decoded POL log
+    ///
+    /// The logs include transfers that were not tracked, so they can be
+    /// journaled and classified again later.
+    pub async fn process_block_with_logs(&self, block_number: u64) -> Result<ProcessedBlock, ProcessError> {
+        if block_number < self.min_block {
+            log::info!("Skipping block {} below configured min_block {}", block_number, self.min_block);
+            return Ok(ProcessedBlock::default());
+        }
+
+        // Get block data to extract timestamp and hash
+        let block = self
+            .rpc_client
+            .get_block_if_exists(block_number)
+            .instrument(tracing::info_span!("rpc_fetch", block_number, rpc.method = "eth_getBlockByNumber"))
+            .await?;
+        let block = match block {
+            Some(block) => block,
+            None if block_number == 0 => {
+                log::info!("Skipping genesis block reported as null by the node");
+                return Ok(ProcessedBlock::default());
+            }
+            None => return Err(ProcessError::Processing(format!("Block {} not found", block_number))),
         };
+        let timestamp = self.block_timestamp(block_number, &block.timestamp)?;
+
+        // Create log filter for POL token events with a registered decoder
+        let log_filter = self.log_filter_for_block(block_number);
 
         // Get logs from the block
-        let raw_logs = self.rpc_client.get_logs(log_filter).await?;
+        let raw_logs = self
+            .rpc_client
+            .get_logs(log_filter)
+            .instrument(tracing::info_span!("rpc_fetch", block_number, rpc.method = "eth_getLogs"))
+            .await?;
+        let raw_logs = drop_duplicate_logs(raw_logs);
 
         // Process each log and filter for Binance-related transfers
         let mut processed_transfers = Vec::new();
+        let mut decoded_logs = Vec::new();
+        let decode_span = tracing::info_span!("decode", block_number, transfer_count = tracing::field::Empty);
+        let _decoding = decode_span.enter();
         
         for raw_log in raw_logs {
+            // Logs removed by a reorg are no longer part of the canonical chain
+            if raw_log.removed {
+                log::warn!(
+                    "Skipping removed log {}:{} in block {}",
+                    raw_log.transaction_hash, raw_log.log_index, block_number
+                );
+                continue;
+            }
+
             // Only process POL token transfers
             if self.transfer_detector.is_pol_transfer(&raw_log) {
                 match self.transfer_detector.decode_transfer_log(&raw_log) {
@@ -56,9 +325,20 @@ impl BlockProcessor {
                         transfer.timestamp = timestamp;
                         
                         // Only include transfers involving Binance addresses
-                        if transfer.direction != TransferDirection::NotRelevant {
+                        if self.is_tracked(&transfer) && self.check_amount_overflow(&mut transfer)? {
                             processed_transfers.push(transfer);
                         }
+                        decoded_logs.push(raw_log);
+                        if decoded_logs.len() > self.max_transfers_per_block {
+                            log::error!(
+                                "Rejecting block {}: more than {} POL transfers",
+                                block_number, self.max_transfers_per_block
+                            );
+                            return Err(ProcessError::TooManyTransfers {
+                                block_number,
+                                max: self.max_transfers_per_block,
+                            });
+                        }
                     }
                     Err(e) => {
                         // Log the error but continue processing other transfers
@@ -68,29 +348,92 @@ impl BlockProcessor {
             }
         }
 
-        Ok(processed_transfers)
+        decode_span.record("transfer_count", processed_transfers.len());
+
+        Ok(ProcessedBlock {
+            block_hash: block.hash,
+            timestamp,
+            transfers: processed_transfers,
+            logs: decoded_logs,
+        })
     }
 
     /// Extract and filter POL token transfers from a block
     pub async fn extract_pol_transfers(&self, block_number: u64) -> Result<Vec<RawLog>, ProcessError> {
-        let log_filter = LogFilter {
-            from_block: format!("0x{:x}", block_number),
-            to_block: format!("0x{:x}", block_number),
-            address: Some(POL_TOKEN_ADDRESS.to_string()),
-            topics: Some(vec![Some(TRANSFER_EVENT_SIGNATURE.to_string())]),
-        };
+        let log_filter = self.log_filter_for_block(block_number);
 
-        let raw_logs = self.rpc_client.get_logs(log_filter).await?;
+        let raw_logs = drop_duplicate_logs(self.rpc_client.get_logs(log_filter).await?);
         
-        // Filter for POL token transfers only
+        // Filter for POL token transfers only, dropping logs removed by a reorg
         let pol_transfers: Vec<RawLog> = raw_logs
             .into_iter()
-            .filter(|log| self.transfer_detector.is_pol_transfer(log))
+            .filter(|log| !log.removed && self.transfer_detector.is_pol_transfer(log))
             .collect();
 
         Ok(pol_transfers)
     }
 
+    /// Fetch transfers into or out of the watched exchange addresses over `blocks`
+    ///
+    /// Instead of every POL log, the node is asked for two topic-filtered sets,
+    /// inflows (topic 2 is a watched address) and outflows (topic 1 is), which
+    /// are merged by `(transaction_hash, log_index)` since a transfer between two
+    /// watched addresses matches both. Only blocks with a matching transfer are
+    /// fetched for their timestamp. Transfers are returned in chain order.
+    pub async fn process_exchange_transfers(&self, blocks: RangeInclusive<u64>) -> Result<Vec<ProcessedTransfer>, ProcessError> {
+        let blocks = (*blocks.start()).max(self.min_block)..=*blocks.end();
+        let watched = self.transfer_detector.binance_addresses();
+        if blocks.is_empty() || watched.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let inflows = self.rpc_client
+            .get_logs(LogFilter::exchange_inflows(POL_TOKEN_ADDRESS, &watched, blocks.clone()))
+            .await?;
+        let outflows = self.rpc_client
+            .get_logs(LogFilter::exchange_outflows(POL_TOKEN_ADDRESS, &watched, blocks))
+            .await?;
+
+        let mut seen = HashSet::new();
+        let mut raw_logs: Vec<RawLog> = drop_duplicate_logs(inflows)
+            .into_iter()
+            .chain(drop_duplicate_logs(outflows))
+            .filter(|log| !log.removed && seen.insert((log.transaction_hash.to_lowercase(), log.log_index)))
+            .collect();
+        raw_logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut timestamps = HashMap::new();
+        let mut transfers = Vec::new();
+        for raw_log in raw_logs {
+            if !self.transfer_detector.is_pol_transfer(&raw_log) {
+                continue;
+            }
+            let mut transfer = match self.transfer_detector.decode_transfer_log(&raw_log) {
+                Ok(transfer) => transfer,
+                Err(e) => {
+                    log::warn!("Failed to decode transfer log: {}", e);
+                    continue;
+                }
+            };
+            if !self.is_tracked(&transfer) || !self.check_amount_overflow(&mut transfer)? {
+                continue;
+            }
+
+            transfer.timestamp = match timestamps.get(&raw_log.block_number) {
+                Some(timestamp) => *timestamp,
+                None => {
+                    let block = self.rpc_client.get_block(raw_log.block_number).await?;
+                    let timestamp = self.block_timestamp(raw_log.block_number, &block.timestamp)?;
+                    timestamps.insert(raw_log.block_number, timestamp);
+                    timestamp
+                }
+            };
+            transfers.push(transfer);
+        }
+
+        Ok(transfers)
+    }
+
     /// Identify Binance-related transfers from a list of processed transfers
     pub fn identify_binance_transfers(&self, transfers: Vec<ProcessedTransfer>) -> Vec<ProcessedTransfer> {
         transfers
@@ -99,12 +442,50 @@ impl BlockProcessor {
             .collect()
     }
 
+    /// Latest block number reported by the node
+    pub async fn latest_block_number(&self) -> Result<u64, ProcessError> {
+        Ok(self.rpc_client.get_latest_block_number().await?)
+    }
+
     /// Get the transfer detector for external use
     pub fn transfer_detector(&self) -> &TransferDetector {
         &self.transfer_detector
     }
 }
 
+/// Keep only the first log for each `(transaction_hash, log_index)` pair
+///
+/// Some RPC providers occasionally repeat a log within a single response; storing
+/// both would trip the database's unique constraint and abort the whole batch.
+fn drop_duplicate_logs(raw_logs: Vec<RawLog>) -> Vec<RawLog> {
+    let mut seen = HashSet::new();
+    raw_logs
+        .into_iter()
+        .filter(|log| {
+            let first = seen.insert((log.transaction_hash.to_lowercase(), log.log_index));
+            if !first {
+                log::warn!(
+                    "Duplicate log {}:{} in block {} returned by RPC, dropping",
+                    log.transaction_hash,
+                    log.log_index,
+                    log.block_number
+                );
+            }
+            first
+        })
+        .collect()
+}
+
+/// Whether a decoded wei amount is zero
+pub(crate) fn is_zero_amount(amount: &str) -> bool {
+    !amount.is_empty() && amount.bytes().all(|b| b == b'0')
+}
+
+/// Whether a decimal integer amount is larger than `u128::MAX`
+fn exceeds_u128(amount: &str) -> bool {
+    amount.bytes().all(|b| b.is_ascii_digit()) && !amount.is_empty() && amount.parse::<u128>().is_err()
+}
+
 fn parse_hex_timestamp(hex_timestamp: &str) -> Result<u64, ProcessError> {
     let hex_without_prefix = hex_timestamp.strip_prefix("0x").unwrap_or(hex_timestamp);
     u64::from_str_radix(hex_without_prefix, 16)
@@ -114,8 +495,8 @@ fn parse_hex_timestamp(hex_timestamp: &str) -> Result<u64, ProcessError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::blockchain::rpc_client::Transaction;
-    use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+    use crate::blockchain::rpc_client::{Block, BlockTransaction, Transaction};
+    use crate::blockchain::transfer_detector::{BINANCE_ADDRESSES, TRANSFER_EVENT_SIGNATURE};
 
     // Mock RPC client for testing
     struct MockRpcClient {
@@ -155,12 +536,12 @@ mod tests {
             hash: format!("0xblock{:x}", block_number),
             timestamp: format!("0x{:x}", timestamp),
             transactions: vec![
-                Transaction {
+                BlockTransaction::Full(Transaction {
                     hash: "0xtx1".to_string(),
                     from: "0xfrom1".to_string(),
                     to: Some("0xto1".to_string()),
                     block_number: format!("0x{:x}", block_number),
-                }
+                })
             ],
         }
     }
@@ -183,6 +564,8 @@ mod tests {
             block_number,
             transaction_hash: format!("0xtx{}", log_index),
             log_index,
+            transaction_index: 0,
+            removed: false,
         }
     }
 
@@ -203,6 +586,434 @@ mod tests {
         assert!(processor.transfer_detector().is_binance_address(BINANCE_ADDRESSES[0]));
     }
 
+    #[test]
+    fn test_log_filter_follows_registered_event_signatures() {
+        let processor = BlockProcessor::new(RpcClient::new("http://test".to_string()));
+        let filter = processor.log_filter_for_block(255);
+        assert_eq!(filter.from_block, "0xff");
+        assert_eq!(filter.topics, Some(vec![Some(TRANSFER_EVENT_SIGNATURE.to_string().into())]));
+
+        // Several signatures cannot share a single topic slot, so they are matched locally
+        let detector = TransferDetector::new().with_event_decoder(
+            "0x5548c837ab068cf56a2c2479df0882a4922fd203edb7517321831d95078c5f62",
+            crate::blockchain::Erc20TransferDecoder,
+        );
+        let processor = processor.with_transfer_detector(detector);
+        assert_eq!(processor.log_filter_for_block(255).topics, None);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_below_min_block_are_skipped() {
+        // The endpoint is unreachable, so any RPC call would fail
+        let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let processor = BlockProcessor::new(rpc_client).with_min_block(2);
+        assert_eq!(processor.min_block(), 2);
+
+        for block_number in [0, 1] {
+            let (block_hash, transfers) = processor.process_block_with_hash(block_number).await
+                .expect("early block should be skipped without error");
+            assert!(block_hash.is_empty());
+            assert!(transfers.is_empty());
+        }
+
+        assert!(processor.process_block(2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_null_genesis_block_is_skipped() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": null
+            })))
+            .mount(&server)
+            .await;
+
+        let processor = BlockProcessor::new(RpcClient::new(server.uri()));
+        assert!(processor.process_block(0).await.unwrap().is_empty());
+        assert!(matches!(processor.process_block(5).await, Err(ProcessError::Processing(_))));
+    }
+
+    #[tokio::test]
+    async fn test_removed_logs_are_skipped() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"number": "0x3039", "hash": "0xabc", "timestamp": "0x61cf9980", "transactions": []}
+            })))
+            .mount(&server)
+            .await;
+
+        let topic = |address: &str| format!("0x{:0>64}", address.trim_start_matches("0x"));
+        let log = |tx_hash: &str, log_index: &str, removed: Option<bool>| {
+            let mut log = serde_json::json!({
+                "address": POL_TOKEN_ADDRESS,
+                "topics": [
+                    TRANSFER_EVENT_SIGNATURE,
+                    topic("0x1234567890123456789012345678901234567890"),
+                    topic(BINANCE_ADDRESSES[0])
+                ],
+                "data": "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+                "blockNumber": "0x3039",
+                "transactionHash": tx_hash,
+                "logIndex": log_index
+            });
+            if let Some(removed) = removed {
+                log["removed"] = serde_json::json!(removed);
+            }
+            log
+        };
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [log("0xkept", "0x0", Some(false)), log("0xreorged", "0x1", Some(true)), log("0xplain", "0x2", None)]
+            })))
+            .mount(&server)
+            .await;
+
+        let processor = BlockProcessor::new(RpcClient::new(server.uri()));
+
+        let transfers = processor.process_block(12345).await.unwrap();
+        let hashes: Vec<&str> = transfers.iter().map(|t| t.transaction_hash.as_str()).collect();
+        assert_eq!(hashes, vec!["0xkept", "0xplain"]);
+
+        let raw_logs = processor.extract_pol_transfers(12345).await.unwrap();
+        assert_eq!(raw_logs.len(), 2);
+        assert!(raw_logs.iter().all(|log| !log.removed));
+    }
+
+    #[tokio::test]
+    async fn test_future_block_timestamp_falls_back_to_local_time() {
+        use crate::testing::{capture_logs, captured_logs};
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // Year 4147, far beyond any tolerated clock skew
+        const FUTURE_TIMESTAMP: u64 = 68_719_476_735;
+
+        capture_logs();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"number": "0x3a98", "hash": "0xabc", "timestamp": format!("0x{:x}", FUTURE_TIMESTAMP), "transactions": []}
+            })))
+            .mount(&server)
+            .await;
+        let topic = |address: &str| format!("0x{:0>64}", address.trim_start_matches("0x"));
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [{
+                    "address": POL_TOKEN_ADDRESS,
+                    "topics": [
+                        TRANSFER_EVENT_SIGNATURE,
+                        topic("0x1234567890123456789012345678901234567890"),
+                        topic(BINANCE_ADDRESSES[0])
+                    ],
+                    "data": "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+                    "blockNumber": "0x3a98",
+                    "transactionHash": "0xskewed",
+                    "logIndex": "0x0"
+                }]
+            })))
+            .mount(&server)
+            .await;
+        let now = || std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+
+        let before = now();
+        let processed = BlockProcessor::new(RpcClient::new(server.uri()))
+            .process_block_with_logs(15000)
+            .await
+            .unwrap();
+        assert!((before..=now()).contains(&processed.timestamp));
+        assert_eq!(processed.transfers[0].timestamp, processed.timestamp);
+        assert_eq!(captured_logs(log::Level::Warn, "Block 15000 timestamp 68719476735 is").len(), 1);
+
+        // A skew of 0 trusts the node
+        let processed = BlockProcessor::new(RpcClient::new(server.uri()))
+            .with_max_timestamp_skew(0)
+            .process_block_with_logs(15000)
+            .await
+            .unwrap();
+        assert_eq!(processed.timestamp, FUTURE_TIMESTAMP);
+        assert_eq!(processed.transfers[0].timestamp, FUTURE_TIMESTAMP);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_index_is_decoded_and_stored() {
+        use crate::database::Database;
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"number": "0x3039", "hash": "0xabc", "timestamp": "0x61cf9980", "transactions": []}
+            })))
+            .mount(&server)
+            .await;
+
+        let topic = |address: &str| format!("0x{:0>64}", address.trim_start_matches("0x"));
+        let log = |tx_hash: &str, log_index: &str, transaction_index: Option<&str>| {
+            let mut log = serde_json::json!({
+                "address": POL_TOKEN_ADDRESS,
+                "topics": [
+                    TRANSFER_EVENT_SIGNATURE,
+                    topic("0x1234567890123456789012345678901234567890"),
+                    topic(BINANCE_ADDRESSES[0])
+                ],
+                "data": "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+                "blockNumber": "0x3039",
+                "transactionHash": tx_hash,
+                "logIndex": log_index
+            });
+            if let Some(transaction_index) = transaction_index {
+                log["transactionIndex"] = serde_json::json!(transaction_index);
+            }
+            log
+        };
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [log("0xindexed", "0x0", Some("0x5")), log("0xunindexed", "0x1", None)]
+            })))
+            .mount(&server)
+            .await;
+
+        let processor = BlockProcessor::new(RpcClient::new(server.uri()));
+        let transfers = processor.process_block(12345).await.unwrap();
+        let indexes: Vec<(&str, u32)> =
+            transfers.iter().map(|t| (t.transaction_hash.as_str(), t.transaction_index)).collect();
+        assert_eq!(indexes, vec![("0xindexed", 5), ("0xunindexed", 0)]);
+
+        let db = Database::new_in_memory().unwrap();
+        db.store_transfers_and_update_net_flow(&transfers).unwrap();
+        let stored: Vec<(String, u32)> = db
+            .get_transactions_by_block(12345)
+            .unwrap()
+            .into_iter()
+            .map(|tx| (tx.transaction_hash, tx.transaction_index))
+            .collect();
+        assert_eq!(stored, vec![("0xindexed".to_string(), 5), ("0xunindexed".to_string(), 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_logs_are_dropped_with_warning() {
+        use crate::testing::{capture_logs, captured_logs, MockPolygonNode};
+
+        capture_logs();
+        let block_number = 4242;
+        let first = ProcessedTransfer {
+            block_number,
+            transaction_hash: "0xdupe4242".to_string(),
+            log_index: 7,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: "1000".to_string(),
+            timestamp: 0,
+            direction: TransferDirection::NotRelevant,
+        };
+        // Same (tx_hash, log_index) but a different payload, so the test can tell which one survived
+        let repeated = ProcessedTransfer { amount: "2000".to_string(), ..first.clone() };
+        let other = ProcessedTransfer { log_index: 8, ..first.clone() };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_block(block_number, vec![first, repeated, other])
+            .start()
+            .await;
+
+        let processor = BlockProcessor::new(rpc_client);
+        let transfers = processor.process_block(block_number).await.unwrap();
+        let kept: Vec<(u32, &str)> = transfers.iter().map(|t| (t.log_index, t.amount.as_str())).collect();
+        assert_eq!(kept, vec![(7, "1000"), (8, "1000")]);
+
+        let raw_logs = processor.extract_pol_transfers(block_number).await.unwrap();
+        assert_eq!(raw_logs.len(), 2);
+
+        let warnings = captured_logs(log::Level::Warn, "Duplicate log 0xdupe4242:7");
+        assert_eq!(warnings.len(), 2, "one warning per fetch: {:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn test_zero_value_transfers_are_stored_only_when_tracked() {
+        use crate::database::Database;
+        use crate::testing::MockPolygonNode;
+
+        let block_number = 5150;
+        let transfer = |log_index: u32, amount: &str| ProcessedTransfer {
+            block_number,
+            transaction_hash: format!("0xzero{}", log_index),
+            log_index,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: amount.to_string(),
+            timestamp: 0,
+            direction: TransferDirection::NotRelevant,
+        };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_block(block_number, vec![transfer(0, "0"), transfer(1, "1000")])
+            .start()
+            .await;
+
+        for (track, expected) in [(false, vec!["0xzero1"]), (true, vec!["0xzero0", "0xzero1"])] {
+            let processor = BlockProcessor::new(rpc_client.clone()).with_track_zero_value_transfers(track);
+            let transfers = processor.process_block(block_number).await.unwrap();
+
+            let db = Database::new_in_memory().unwrap();
+            db.store_transfers_and_update_net_flow(&transfers).unwrap();
+            let stored: Vec<String> = db
+                .get_transactions_by_block(block_number)
+                .unwrap()
+                .into_iter()
+                .map(|tx| tx.transaction_hash)
+                .collect();
+            assert_eq!(stored, expected, "track_zero_value_transfers = {}", track);
+
+            // Either way, only the non-zero transfer moves the totals
+            let net_flow = db.get_net_flow_data().unwrap();
+            assert_eq!(net_flow.total_inflow, "1000");
+            assert_eq!(net_flow.total_outflow, "0");
+            assert_eq!(net_flow.net_flow, "1000");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_amount_overflow_modes_for_values_above_u128_max() {
+        use crate::testing::MockPolygonNode;
+
+        let block_number = 6160;
+        // u128::MAX + 1
+        let oversized = "340282366920938463463374607431768211456";
+        let transfer = |log_index: u32, amount: &str| ProcessedTransfer {
+            block_number,
+            transaction_hash: format!("0xoverflow{}", log_index),
+            log_index,
+            transaction_index: 0,
+            from_address: "0x1234567890123456789012345678901234567890".to_string(),
+            to_address: BINANCE_ADDRESSES[0].to_string(),
+            amount: amount.to_string(),
+            timestamp: 0,
+            direction: TransferDirection::NotRelevant,
+        };
+        let (_server, rpc_client) = MockPolygonNode::new()
+            .with_block(block_number, vec![transfer(0, "1000"), transfer(1, oversized)])
+            .start()
+            .await;
+        let processor = |mode: AmountOverflow| BlockProcessor::new(rpc_client.clone()).with_on_amount_overflow(mode);
+        let amounts = |transfers: Vec<ProcessedTransfer>| -> Vec<String> {
+            transfers.into_iter().map(|t| t.amount).collect()
+        };
+
+        let kept = processor(AmountOverflow::Keep).process_block(block_number).await.unwrap();
+        assert_eq!(amounts(kept), vec!["1000", oversized]);
+
+        let skipped = processor(AmountOverflow::Skip).process_block(block_number).await.unwrap();
+        assert_eq!(amounts(skipped), vec!["1000"]);
+
+        let clamped = processor(AmountOverflow::Clamp).process_block(block_number).await.unwrap();
+        assert_eq!(amounts(clamped), vec!["1000".to_string(), u128::MAX.to_string()]);
+
+        match processor(AmountOverflow::Error).process_block(block_number).await {
+            Err(ProcessError::AmountOverflow { block_number: failed, log_index, amount, .. }) => {
+                assert_eq!((failed, log_index, amount.as_str()), (block_number, 1, oversized));
+            }
+            other => panic!("expected an amount overflow error, got {:?}", other.map(amounts)),
+        }
+
+        assert_eq!("Clamp".parse::<AmountOverflow>().unwrap(), AmountOverflow::Clamp);
+        assert!("wrap".parse::<AmountOverflow>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exchange_transfers_merge_inflow_and_outflow_filters() {
+        use crate::testing::{MockPolygonNode, GENESIS_TIMESTAMP};
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let external = "0x1234567890123456789012345678901234567890";
+        let transfer = |block_number: u64, log_index: u32, from: &str, to: &str| {
+            MockPolygonNode::transfer_log(&ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0xexchange{}", log_index),
+                log_index,
+                transaction_index: 0,
+                from_address: from.to_string(),
+                to_address: to.to_string(),
+                amount: "1000".to_string(),
+                timestamp: 0,
+                direction: TransferDirection::NotRelevant,
+            })
+        };
+        let inflow = transfer(301, 0, external, BINANCE_ADDRESSES[0]);
+        let outflow = transfer(300, 1, BINANCE_ADDRESSES[1], external);
+
+        let (server, rpc_client) = MockPolygonNode::new().with_latest_block(310).start().await;
+        let processor = BlockProcessor::new(rpc_client);
+        let watched = processor.transfer_detector().binance_addresses();
+        // The inflow is also returned for the outflow filter, as a provider may do
+        for (filter, logs) in [
+            (LogFilter::exchange_inflows(POL_TOKEN_ADDRESS, &watched, 300..=305), vec![&inflow]),
+            (LogFilter::exchange_outflows(POL_TOKEN_ADDRESS, &watched, 300..=305), vec![&outflow, &inflow]),
+        ] {
+            let logs: Vec<serde_json::Value> = logs
+                .into_iter()
+                .map(|log| serde_json::json!({
+                    "address": log.address,
+                    "topics": log.topics,
+                    "data": log.data,
+                    "blockNumber": format!("0x{:x}", log.block_number),
+                    "transactionHash": log.transaction_hash,
+                    "logIndex": format!("0x{:x}", log.log_index)
+                }))
+                .collect();
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({"method": "eth_getLogs", "params": [filter]})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "result": logs
+                })))
+                .with_priority(1)
+                .mount(&server)
+                .await;
+        }
+
+        let transfers = processor.process_exchange_transfers(300..=305).await.unwrap();
+        let found: Vec<(u64, u32, TransferDirection)> = transfers
+            .iter()
+            .map(|transfer| (transfer.block_number, transfer.log_index, transfer.direction.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(300, 1, TransferDirection::FromBinance), (301, 0, TransferDirection::ToBinance)]
+        );
+        assert_eq!(transfers[0].timestamp, GENESIS_TIMESTAMP + 300);
+        assert_eq!(transfers[1].timestamp, GENESIS_TIMESTAMP + 301);
+
+        // Two filtered log queries plus one block fetch per block with a transfer
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
     #[test]
     fn test_identify_binance_transfers() {
         let rpc_client = RpcClient::new("http://test".to_string());
@@ -213,6 +1024,7 @@ mod tests {
                 block_number: 1,
                 transaction_hash: "0x1".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "other".to_string(),
                 to_address: "binance".to_string(),
                 amount: "100".to_string(),
@@ -223,6 +1035,7 @@ mod tests {
                 block_number: 1,
                 transaction_hash: "0x2".to_string(),
                 log_index: 1,
+                transaction_index: 0,
                 from_address: "other1".to_string(),
                 to_address: "other2".to_string(),
                 amount: "200".to_string(),
@@ -233,6 +1046,7 @@ mod tests {
                 block_number: 1,
                 transaction_hash: "0x3".to_string(),
                 log_index: 2,
+                transaction_index: 0,
                 from_address: "binance".to_string(),
                 to_address: "other".to_string(),
                 amount: "300".to_string(),
@@ -0,0 +1,176 @@
+//! RPC endpoints in order of preference, with the health of each
+//!
+//! An [`RpcClient`](crate::blockchain::RpcClient) sends every request to the
+//! first endpoint of its [`EndpointPool`]. A request that fails to reach it
+//! demotes the endpoint behind the healthy ones, and periodic `eth_blockNumber`
+//! probes restore it once it answers again: healthy endpoints are ordered by
+//! probe latency, so the fastest healthy one is primary.
+
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Health of one RPC endpoint as reported by `/status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointHealth {
+    /// Endpoint name from the configuration; URLs are left out since they may carry API keys
+    pub name: String,
+    pub healthy: bool,
+    /// Round trip of the last successful probe, null until one succeeds
+    pub latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Unix time of the last probe or failed request, null if none yet
+    pub last_checked: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Arc<str>,
+    health: EndpointHealth,
+}
+
+/// RPC endpoints in order of preference, shared by every clone of a client
+#[derive(Debug)]
+pub struct EndpointPool {
+    endpoints: RwLock<Vec<Endpoint>>,
+}
+
+impl EndpointPool {
+    /// Pool holding a single endpoint
+    pub fn new(name: &str, url: &str) -> Self {
+        let pool = Self { endpoints: RwLock::new(Vec::new()) };
+        pool.push(name, url);
+        pool
+    }
+
+    /// Add an endpoint after the existing ones
+    pub fn push(&self, name: &str, url: &str) {
+        let mut endpoints = self.endpoints.write().unwrap_or_else(|e| e.into_inner());
+        endpoints.push(Endpoint {
+            url: url.into(),
+            health: EndpointHealth {
+                name: name.to_string(),
+                healthy: true,
+                latency_ms: None,
+                consecutive_failures: 0,
+                last_error: None,
+                last_checked: None,
+            },
+        });
+    }
+
+    /// Rename the endpoint at the front of the pool
+    pub fn rename_primary(&self, name: &str) {
+        let mut endpoints = self.endpoints.write().unwrap_or_else(|e| e.into_inner());
+        endpoints[0].health.name = name.to_string();
+    }
+
+    /// URL requests are sent to
+    pub fn primary_url(&self) -> Arc<str> {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(&endpoints[0].url)
+    }
+
+    /// Name of the endpoint requests are sent to
+    pub fn primary_name(&self) -> String {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        endpoints[0].health.name.clone()
+    }
+
+    /// Number of endpoints in the pool
+    pub fn len(&self) -> usize {
+        self.endpoints.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether the pool has no endpoints; never true for a pool built with [`new`](Self::new)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every endpoint URL, in the current order of preference
+    pub fn urls(&self) -> Vec<Arc<str>> {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        endpoints.iter().map(|endpoint| Arc::clone(&endpoint.url)).collect()
+    }
+
+    /// Health of every endpoint, primary first
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        endpoints.iter().map(|endpoint| endpoint.health.clone()).collect()
+    }
+
+    /// Record a probe of `url`: its latency on success, or the error on failure
+    pub fn record_probe(&self, url: &str, result: Result<Duration, String>) {
+        self.update(url, |health| match result {
+            Ok(latency) => {
+                health.healthy = true;
+                health.latency_ms = Some(latency.as_millis() as u64);
+                health.consecutive_failures = 0;
+                health.last_error = None;
+            }
+            Err(error) => Self::mark_failed(health, error),
+        });
+    }
+
+    /// Mark `url` unhealthy after a request to it failed, moving it behind the healthy endpoints
+    pub fn demote(&self, url: &str, error: String) {
+        self.update(url, |health| Self::mark_failed(health, error));
+    }
+
+    fn mark_failed(health: &mut EndpointHealth, error: String) {
+        health.healthy = false;
+        health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+        health.last_error = Some(error);
+    }
+
+    /// Apply `change` to the health of `url`, then restore the order of preference
+    fn update(&self, url: &str, change: impl FnOnce(&mut EndpointHealth)) {
+        let mut endpoints = self.endpoints.write().unwrap_or_else(|e| e.into_inner());
+        let Some(endpoint) = endpoints.iter_mut().find(|endpoint| &*endpoint.url == url) else {
+            return;
+        };
+        change(&mut endpoint.health);
+        endpoint.health.last_checked = SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|now| now.as_secs());
+
+        let previous_primary = endpoints[0].health.name.clone();
+        // Stable, so endpoints without a measured latency keep their configured order
+        endpoints.sort_by_key(|endpoint| (!endpoint.health.healthy, endpoint.health.latency_ms.unwrap_or(u64::MAX)));
+        let primary = &endpoints[0].health;
+        if primary.name != previous_primary {
+            log::warn!(
+                "RPC primary switched from endpoint '{}' to '{}'{}",
+                previous_primary,
+                primary.name,
+                if primary.healthy { "" } else { " (no endpoint is healthy)" }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fastest_healthy_endpoint_is_primary() {
+        let pool = EndpointPool::new("primary", "http://primary");
+        pool.push("fallback", "http://fallback");
+        assert_eq!(&*pool.primary_url(), "http://primary");
+
+        pool.demote("http://primary", "connection refused".to_string());
+        assert_eq!(pool.primary_name(), "fallback");
+        assert_eq!(pool.health()[1].consecutive_failures, 1);
+
+        // A recovered endpoint only wins back the lead by being faster
+        pool.record_probe("http://fallback", Ok(Duration::from_millis(80)));
+        pool.record_probe("http://primary", Ok(Duration::from_millis(120)));
+        assert_eq!(pool.primary_name(), "fallback");
+        pool.record_probe("http://primary", Ok(Duration::from_millis(20)));
+        assert_eq!(pool.primary_name(), "primary");
+
+        let health = pool.health();
+        assert!(health.iter().all(|endpoint| endpoint.healthy && endpoint.last_error.is_none()));
+        assert_eq!(health[0].latency_ms, Some(20));
+    }
+}
@@ -2,8 +2,12 @@ pub mod rpc_client;
 pub mod block_processor;
 pub mod transfer_detector;
 pub mod block_monitor;
+pub mod pipeline;
+pub mod endpoint_health;
 
-pub use rpc_client::{RpcClient, Block, LogFilter};
-pub use block_processor::{BlockProcessor, ProcessError};
-pub use transfer_detector::{TransferDetector, TransferDetectionError, normalize_address, validate_address};
-pub use block_monitor::{BlockMonitor, BlockMonitorConfig, MonitorError, MonitorStatus};
\ No newline at end of file
+pub use rpc_client::{RpcClient, Block, BlockTransaction, LogFilter, TopicFilter};
+pub use block_processor::{AmountOverflow, BlockProcessor, ProcessError, ProcessedBlock, DEFAULT_MAX_TIMESTAMP_SKEW_SECONDS, DEFAULT_MAX_TRANSFERS_PER_BLOCK};
+pub use transfer_detector::{EventDecoder, Erc20TransferDecoder, TransferDetector, TransferDetectionError, WatchedAddress, normalize_address, validate_address};
+pub use block_monitor::{is_unconfirmed, BackfillControl, BackfillSummary, BlockMonitor, BlockMonitorConfig, MonitorError, MonitorStatus};
+pub use endpoint_health::{EndpointHealth, EndpointPool};
+pub use pipeline::{FetchedBlock, OrderedCommitBuffer, PipelineItem, PipelineOccupancy, DEFAULT_PIPELINE_CAPACITY};
\ No newline at end of file
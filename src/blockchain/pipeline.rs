@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::blockchain::BlockProcessor;
+use crate::error::IndexerError;
+use crate::models::{ProcessedTransfer, RawLog};
+use crate::retry::CircuitBreaker;
+
+/// Default number of fetched blocks buffered between the fetcher and the writer
+pub const DEFAULT_PIPELINE_CAPACITY: usize = 16;
+
+/// Number of fetched blocks waiting in the pipeline channel, shared with `/status`
+///
+/// A buffer that stays at capacity means the writer, not the node, is the
+/// bottleneck.
+#[derive(Debug, Default)]
+pub struct PipelineOccupancy {
+    buffered: AtomicUsize,
+    capacity: AtomicUsize,
+}
+
+impl PipelineOccupancy {
+    /// Blocks fetched but not yet taken by the writer
+    pub fn buffered(&self) -> usize {
+        self.buffered.load(Ordering::Relaxed)
+    }
+
+    /// Capacity of the running pipeline's channel, 0 before the first one starts
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Record that the writer took a block off the channel
+    pub fn taken(&self) {
+        let _ = self.buffered.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |buffered| {
+            Some(buffered.saturating_sub(1))
+        });
+    }
+}
+
+/// A block fetched from the chain, ready to be written to the database
+#[derive(Debug, Clone)]
+pub struct FetchedBlock {
+    pub block_number: u64,
+    pub block_hash: String,
+    pub transfers: Vec<ProcessedTransfer>,
+    pub block_timestamp: u64,
+    /// Decoded POL logs of the block, journaled when the raw-log journal is enabled
+    pub logs: Vec<RawLog>,
+}
+
+/// Item passed from the fetcher task to the writer
+#[derive(Debug)]
+pub enum PipelineItem {
+    /// The block was fetched and its transfers extracted
+    Fetched(FetchedBlock),
+    /// The block could not be fetched and should be skipped
+    Failed { block_number: u64, error: IndexerError },
+}
+
+impl PipelineItem {
+    /// Number of the block this item is for
+    pub fn block_number(&self) -> u64 {
+        match self {
+            PipelineItem::Fetched(fetched) => fetched.block_number,
+            PipelineItem::Failed { block_number, .. } => *block_number,
+        }
+    }
+}
+
+/// Reorders pipeline items so the writer commits blocks in ascending order
+///
+/// Items that arrive ahead of the next expected block are held until every
+/// lower block has arrived, so net-flow totals and snapshots only ever move
+/// forward through the chain even when blocks are fetched out of order.
+/// [`spawn_fetcher`] sends blocks in ascending order, so for now the buffer
+/// only guards against a fetcher that fetches blocks concurrently.
+#[derive(Debug)]
+pub struct OrderedCommitBuffer {
+    next_block: u64,
+    pending: BTreeMap<u64, PipelineItem>,
+}
+
+impl OrderedCommitBuffer {
+    /// Create a buffer expecting `first_block` to be committed first
+    pub fn new(first_block: u64) -> Self {
+        Self {
+            next_block: first_block,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Add an item, returning the items that are now ready to commit in order
+    ///
+    /// Items for blocks already released are passed straight through.
+    pub fn push(&mut self, item: PipelineItem) -> Vec<PipelineItem> {
+        let block_number = item.block_number();
+        if block_number < self.next_block {
+            log::warn!("Block {} arrived after it was already due, committing it out of order", block_number);
+            return vec![item];
+        }
+        self.pending.insert(block_number, item);
+
+        let mut ready = Vec::new();
+        while let Some(item) = self.pending.remove(&self.next_block) {
+            ready.push(item);
+            self.next_block += 1;
+        }
+        ready
+    }
+
+    /// Number of items waiting for a lower block
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Block numbers still waiting for a lower block, in ascending order
+    pub fn pending_blocks(&self) -> Vec<u64> {
+        self.pending.keys().copied().collect()
+    }
+}
+
+/// Fetch a single block and extract its transfers through the RPC circuit breaker
+pub async fn fetch_block(
+    block_processor: &BlockProcessor,
+    rpc_circuit_breaker: &CircuitBreaker,
+    block_number: u64,
+) -> Result<FetchedBlock, IndexerError> {
+    let processed = rpc_circuit_breaker
+        .execute(|| async {
+            block_processor
+                .process_block_with_logs(block_number)
+                .await
+                .map_err(IndexerError::from)
+        })
+        .await?;
+
+    Ok(FetchedBlock {
+        block_number,
+        block_hash: processed.block_hash,
+        transfers: processed.transfers,
+        block_timestamp: processed.timestamp,
+        logs: processed.logs,
+    })
+}
+
+/// Spawn a fetcher task that fetches `blocks` in ascending order and pushes them
+/// onto a bounded channel of `capacity` items
+///
+/// The fetcher waits whenever the channel is full, so a slow writer applies
+/// backpressure instead of letting fetched blocks accumulate in memory. Failed
+/// blocks are sent as [`PipelineItem::Failed`] after `failure_delay`. The fetcher
+/// stops when the range is exhausted, the shutdown signal is set, or the
+/// receiver is dropped.
+///
+/// Each sent block is counted in `occupancy`; the writer reports taking it with
+/// [`PipelineOccupancy::taken`].
+pub fn spawn_fetcher(
+    block_processor: Arc<BlockProcessor>,
+    rpc_circuit_breaker: Arc<CircuitBreaker>,
+    blocks: RangeInclusive<u64>,
+    capacity: usize,
+    failure_delay: Duration,
+    shutdown_signal: Arc<AtomicBool>,
+    occupancy: Arc<PipelineOccupancy>,
+) -> (mpsc::Receiver<PipelineItem>, JoinHandle<()>) {
+    let fetch = move |block_number| {
+        let block_processor = Arc::clone(&block_processor);
+        let rpc_circuit_breaker = Arc::clone(&rpc_circuit_breaker);
+        async move { fetch_block(&block_processor, &rpc_circuit_breaker, block_number).await }
+    };
+    spawn_fetch_loop(fetch, blocks, capacity, failure_delay, shutdown_signal, occupancy)
+}
+
+/// Run the fetcher loop of [`spawn_fetcher`] with `fetch` producing each block
+fn spawn_fetch_loop<F, Fut>(
+    fetch: F,
+    blocks: RangeInclusive<u64>,
+    capacity: usize,
+    failure_delay: Duration,
+    shutdown_signal: Arc<AtomicBool>,
+    occupancy: Arc<PipelineOccupancy>,
+) -> (mpsc::Receiver<PipelineItem>, JoinHandle<()>)
+where
+    F: Fn(u64) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<FetchedBlock, IndexerError>> + Send,
+{
+    let capacity = capacity.max(1);
+    let (sender, receiver) = mpsc::channel(capacity);
+    occupancy.capacity.store(capacity, Ordering::Relaxed);
+    occupancy.buffered.store(0, Ordering::Relaxed);
+
+    let handle = tokio::spawn(async move {
+        for block_number in blocks {
+            if shutdown_signal.load(Ordering::Relaxed) {
+                log::info!("Shutdown signal received, stopping block fetcher at block {}", block_number);
+                break;
+            }
+
+            let item = match fetch(block_number).await {
+                Ok(fetched) => PipelineItem::Fetched(fetched),
+                Err(error) => {
+                    log::error!("Failed to fetch block {}: {}", block_number, error);
+                    sleep(failure_delay).await;
+                    PipelineItem::Failed { block_number, error }
+                }
+            };
+
+            // Waits here while the channel is full
+            let Ok(permit) = sender.reserve().await else {
+                log::debug!("Block writer stopped, stopping block fetcher");
+                break;
+            };
+            occupancy.buffered.fetch_add(1, Ordering::Relaxed);
+            permit.send(item);
+        }
+    });
+
+    (receiver, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::RpcClient;
+    use tokio::time::Instant;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const FETCH_DELAY: Duration = Duration::from_millis(40);
+    const WRITE_DELAY: Duration = Duration::from_millis(40);
+
+    async fn mock_slow_chain(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "result": {"number": "0x1", "hash": "0xabc", "timestamp": "0x61cf9980", "transactions": []}
+                    }))
+                    .set_delay(FETCH_DELAY),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_getLogs"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "result": []
+            })))
+            .mount(server)
+            .await;
+    }
+
+    /// Spawn a fetcher whose blocks each take `FETCH_DELAY` of tokio time, counting fetches
+    fn spawn_slow_fetcher(
+        blocks: RangeInclusive<u64>,
+        capacity: usize,
+        occupancy: Arc<PipelineOccupancy>,
+        fetches: Arc<AtomicUsize>,
+    ) -> (mpsc::Receiver<PipelineItem>, JoinHandle<()>) {
+        let fetch = move |block_number| {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            async move {
+                sleep(FETCH_DELAY).await;
+                match fetched(block_number) {
+                    PipelineItem::Fetched(block) => Ok(block),
+                    PipelineItem::Failed { .. } => unreachable!(),
+                }
+            }
+        };
+        spawn_fetch_loop(fetch, blocks, capacity, Duration::ZERO, Arc::new(AtomicBool::new(false)), occupancy)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pipeline_overlaps_fetching_and_writing() {
+        let blocks = 1..=8u64;
+
+        // Sequential path: fetch a block, write it, fetch the next
+        let started = Instant::now();
+        let mut sequential_order = Vec::new();
+        for block_number in blocks.clone() {
+            sleep(FETCH_DELAY).await;
+            sleep(WRITE_DELAY).await;
+            sequential_order.push(block_number);
+        }
+        assert_eq!(started.elapsed(), (FETCH_DELAY + WRITE_DELAY) * 8);
+
+        // Pipelined path: the writer drains while the fetcher keeps fetching
+        let started = Instant::now();
+        let (mut receiver, fetcher) =
+            spawn_slow_fetcher(blocks, DEFAULT_PIPELINE_CAPACITY, Arc::default(), Arc::default());
+        let mut pipelined_order = Vec::new();
+        while let Some(item) = receiver.recv().await {
+            match item {
+                PipelineItem::Fetched(fetched) => {
+                    sleep(WRITE_DELAY).await;
+                    pipelined_order.push(fetched.block_number);
+                }
+                PipelineItem::Failed { block_number, error } => panic!("block {} failed: {}", block_number, error),
+            }
+        }
+        fetcher.await.unwrap();
+
+        assert_eq!(pipelined_order, sequential_order);
+        // Only the first fetch is waited for; every later one overlaps a write
+        assert_eq!(started.elapsed(), FETCH_DELAY + WRITE_DELAY * 8);
+    }
+
+    fn fetched(block_number: u64) -> PipelineItem {
+        PipelineItem::Fetched(FetchedBlock {
+            block_number,
+            block_hash: String::new(),
+            transfers: Vec::new(),
+            block_timestamp: 0,
+            logs: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_ordered_commit_buffer_releases_blocks_in_order() {
+        let mut buffer = OrderedCommitBuffer::new(10);
+        let released = |items: Vec<PipelineItem>| items.iter().map(PipelineItem::block_number).collect::<Vec<_>>();
+
+        assert!(buffer.push(fetched(12)).is_empty());
+        assert!(buffer.push(fetched(11)).is_empty());
+        assert_eq!(buffer.pending_blocks(), vec![11, 12]);
+
+        // Failed blocks still unblock the ones after them
+        let failed = PipelineItem::Failed {
+            block_number: 10,
+            error: IndexerError::Rpc(crate::error::RpcError::BlockNotFound { block_number: 10 }),
+        };
+        assert_eq!(released(buffer.push(failed)), vec![10, 11, 12]);
+        assert_eq!(released(buffer.push(fetched(13))), vec![13]);
+        assert_eq!(released(buffer.push(fetched(9))), vec![9]);
+        assert_eq!(buffer.pending_len(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetcher_blocks_once_buffer_is_full() {
+        let capacity = 3;
+        let occupancy = Arc::new(PipelineOccupancy::default());
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let (mut receiver, fetcher) = spawn_slow_fetcher(1..=50, capacity, Arc::clone(&occupancy), Arc::clone(&fetches));
+
+        // A stalled writer: long enough to fetch many more blocks than fit in the buffer
+        for _ in 0..15 {
+            tokio::time::advance(FETCH_DELAY).await;
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(occupancy.capacity(), capacity);
+        assert_eq!(occupancy.buffered(), capacity);
+        // Only one block beyond the buffer is fetched while waiting
+        assert_eq!(fetches.load(Ordering::SeqCst), capacity + 1);
+
+        // Draining the buffer lets the fetcher continue
+        let mut received = Vec::new();
+        while let Some(item) = receiver.recv().await {
+            occupancy.taken();
+            received.push(item.block_number());
+        }
+        fetcher.await.unwrap();
+        assert_eq!(received, (1..=50).collect::<Vec<_>>());
+        assert_eq!(occupancy.buffered(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetcher_stops_when_writer_drops_receiver() {
+        let server = MockServer::start().await;
+        mock_slow_chain(&server).await;
+
+        let processor = Arc::new(BlockProcessor::new(RpcClient::new(server.uri())));
+        let (mut receiver, fetcher) = spawn_fetcher(
+            processor,
+            Arc::new(CircuitBreaker::new(5, 60)),
+            1..=1_000,
+            1,
+            Duration::ZERO,
+            Arc::new(AtomicBool::new(false)),
+            Arc::default(),
+        );
+
+        assert!(matches!(receiver.recv().await, Some(PipelineItem::Fetched(_))));
+        drop(receiver);
+
+        tokio::time::timeout(Duration::from_secs(5), fetcher)
+            .await
+            .expect("fetcher should stop once the writer is gone")
+            .unwrap();
+    }
+}
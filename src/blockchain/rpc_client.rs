@@ -1,11 +1,15 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
+use tokio::sync::{Semaphore, SemaphorePermit};
 use crate::models::RawLog;
 use crate::error::{IndexerError, RpcError as NewRpcError};
 use crate::logging::{LogContext, PerformanceMonitor, MetricsLogger};
 use crate::retry::RetryUtils;
+use super::endpoint_health::EndpointPool;
 
 #[derive(Error, Debug)]
 pub enum RpcError {
@@ -28,11 +32,20 @@ struct JsonRpcRequest {
 #[derive(Debug, Deserialize)]
 struct JsonRpcResponse {
     jsonrpc: String,
+    /// `None` when the field is absent; an explicit `null` result is kept as `Value::Null`
+    #[serde(default, deserialize_with = "deserialize_present")]
     result: Option<Value>,
     error: Option<JsonRpcError>,
     id: u64,
 }
 
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonRpcError {
     code: i32,
@@ -40,23 +53,77 @@ struct JsonRpcError {
     data: Option<Value>,
 }
 
+impl JsonRpcError {
+    /// Provider-specific error detail (revert reasons, limits) rendered as a string
+    fn data_string(&self) -> Option<String> {
+        match &self.data {
+            None | Some(Value::Null) => None,
+            Some(Value::String(data)) => Some(data.clone()),
+            Some(data) => Some(data.to_string()),
+        }
+    }
+}
+
+/// Fields a block response must carry for [`Block`] to deserialize
+const REQUIRED_BLOCK_FIELDS: [&str; 4] = ["number", "hash", "timestamp", "transactions"];
+
 #[derive(Debug, Deserialize)]
 pub struct Block {
     pub number: String,
     pub hash: String,
     pub timestamp: String,
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<BlockTransaction>,
+}
+
+/// Entry in a block's `transactions` list
+///
+/// Blocks are requested with full transaction objects, but some providers ignore
+/// the flag and return only hashes. Transfers are read from logs, so nothing needs
+/// more than the hash and hash-only blocks are used without refetching.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BlockTransaction {
+    Full(Transaction),
+    Hash(String),
+}
+
+impl BlockTransaction {
+    pub fn hash(&self) -> &str {
+        match self {
+            BlockTransaction::Full(transaction) => &transaction.hash,
+            BlockTransaction::Hash(hash) => hash,
+        }
+    }
+
+    /// Recipient of the transaction; `None` for contract creations and hash-only entries
+    pub fn to_address(&self) -> Option<&str> {
+        match self {
+            BlockTransaction::Full(transaction) => transaction.to_address(),
+            BlockTransaction::Hash(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Transaction {
     pub hash: String,
     pub from: String,
+    /// Null for contract creations; read it through [`Transaction::to_address`]
     pub to: Option<String>,
     #[serde(rename = "blockNumber")]
     pub block_number: String,
 }
 
+impl Transaction {
+    /// Recipient of the transaction, `None` for a contract creation
+    ///
+    /// Nodes report a contract creation's `to` as null; some send an empty
+    /// string or a bare `0x` instead, which count as no recipient too.
+    pub fn to_address(&self) -> Option<&str> {
+        self.to.as_deref().filter(|to| !to.is_empty() && *to != "0x")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogFilter {
     #[serde(rename = "fromBlock")]
@@ -64,7 +131,68 @@ pub struct LogFilter {
     #[serde(rename = "toBlock")]
     pub to_block: String,
     pub address: Option<String>,
-    pub topics: Option<Vec<Option<String>>>,
+    pub topics: Option<Vec<Option<TopicFilter>>>,
+}
+
+/// Value of one `topics` position in an `eth_getLogs` filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TopicFilter {
+    /// The topic must equal this value
+    Single(String),
+    /// The topic must equal any of these values
+    AnyOf(Vec<String>),
+}
+
+impl From<String> for TopicFilter {
+    fn from(topic: String) -> Self {
+        TopicFilter::Single(topic)
+    }
+}
+
+impl LogFilter {
+    /// Transfer events of `token` sent to any of `addresses` within `blocks`
+    ///
+    /// Matches on topic 2, the indexed recipient, so the node only returns
+    /// inflows to the watched addresses.
+    pub fn exchange_inflows(token: &str, addresses: &[String], blocks: RangeInclusive<u64>) -> Self {
+        Self::transfers_matching(token, blocks, None, Some(address_topics(addresses)))
+    }
+
+    /// Transfer events of `token` sent from any of `addresses` within `blocks`
+    ///
+    /// Matches on topic 1, the indexed sender.
+    pub fn exchange_outflows(token: &str, addresses: &[String], blocks: RangeInclusive<u64>) -> Self {
+        Self::transfers_matching(token, blocks, Some(address_topics(addresses)), None)
+    }
+
+    fn transfers_matching(
+        token: &str,
+        blocks: RangeInclusive<u64>,
+        from: Option<TopicFilter>,
+        to: Option<TopicFilter>,
+    ) -> Self {
+        Self {
+            from_block: format!("0x{:x}", blocks.start()),
+            to_block: format!("0x{:x}", blocks.end()),
+            address: Some(token.to_string()),
+            topics: Some(vec![
+                Some(TopicFilter::Single(crate::blockchain::transfer_detector::TRANSFER_EVENT_SIGNATURE.to_string())),
+                from,
+                to,
+            ]),
+        }
+    }
+}
+
+/// Addresses left-padded to 32-byte topics, as indexed event arguments are encoded
+fn address_topics(addresses: &[String]) -> TopicFilter {
+    TopicFilter::AnyOf(
+        addresses
+            .iter()
+            .map(|address| format!("0x{:0>64}", crate::blockchain::normalize_address(address)))
+            .collect(),
+    )
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,45 +206,216 @@ pub struct EthLog {
     pub transaction_hash: String,
     #[serde(rename = "logIndex")]
     pub log_index: String,
+    /// Absent on some providers, in which case the log is treated as index 0
+    #[serde(rename = "transactionIndex", default)]
+    pub transaction_index: Option<String>,
+    /// True when the log was removed by a reorg; absent on most providers
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// Default cap on a single RPC response body
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Settings the HTTP client is built from, kept so it can be rebuilt by builders
+#[derive(Debug, Clone, Copy)]
+struct HttpSettings {
+    timeout_seconds: u64,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout_seconds: Option<u64>,
+    compression: bool,
 }
 
+impl HttpSettings {
+    fn build_client(&self) -> Client {
+        let mut builder = Client::builder()
+            .timeout(std::time::Duration::from_secs(self.timeout_seconds))
+            // Sends `Accept-Encoding: gzip` and decodes gzip-encoded responses
+            .gzip(self.compression);
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(idle_timeout) = self.pool_idle_timeout_seconds {
+            builder = builder.pool_idle_timeout(std::time::Duration::from_secs(idle_timeout));
+        }
+        builder.build().expect("Failed to create HTTP client")
+    }
+}
+
+/// JSON-RPC client sending requests to the preferred endpoint of its [`EndpointPool`]
+///
+/// The HTTP client and endpoints are shared behind `Arc`s, so clones are cheap,
+/// reuse the same connection pool and see the same endpoint health.
 #[derive(Clone)]
 pub struct RpcClient {
-    client: Client,
-    endpoint: String,
+    client: Arc<Client>,
+    http_settings: HttpSettings,
+    endpoints: Arc<EndpointPool>,
+    max_response_bytes: u64,
+    /// Permits bounding in-flight requests, possibly shared with other clients
+    request_limiter: Option<Arc<Semaphore>>,
 }
 
 impl RpcClient {
     pub fn new(endpoint: String) -> Self {
         let context = LogContext::new("rpc_client", "initialization")
             .with_metadata("endpoint", serde_json::json!(endpoint));
-        context.info("Initializing RPC client");
+        context.debug("Initializing RPC client");
         
+        let http_settings = HttpSettings {
+            timeout_seconds: 30,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_seconds: None,
+            compression: true,
+        };
         Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
-            endpoint,
+            client: Arc::new(http_settings.build_client()),
+            http_settings,
+            endpoints: Arc::new(EndpointPool::new("primary", &endpoint)),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            request_limiter: None,
         }
     }
 
     /// Enhanced RPC client with timeout and connection pooling
     pub fn new_with_config(endpoint: String, timeout_seconds: u64) -> Self {
+        Self::new_with_pool_config(endpoint, timeout_seconds, 10, 30)
+    }
+
+    /// Enhanced RPC client with timeout and custom connection pool sizing
+    pub fn new_with_pool_config(
+        endpoint: String,
+        timeout_seconds: u64,
+        pool_max_idle_per_host: usize,
+        pool_idle_timeout_seconds: u64,
+    ) -> Self {
         let context = LogContext::new("rpc_client", "initialization")
             .with_metadata("endpoint", serde_json::json!(endpoint))
-            .with_metadata("timeout_seconds", serde_json::json!(timeout_seconds));
-        context.info("Initializing RPC client with custom configuration");
+            .with_metadata("timeout_seconds", serde_json::json!(timeout_seconds))
+            .with_metadata("pool_max_idle_per_host", serde_json::json!(pool_max_idle_per_host))
+            .with_metadata("pool_idle_timeout_seconds", serde_json::json!(pool_idle_timeout_seconds));
+        context.debug("Initializing RPC client with custom configuration");
         
+        let http_settings = HttpSettings {
+            timeout_seconds,
+            pool_max_idle_per_host: Some(pool_max_idle_per_host),
+            pool_idle_timeout_seconds: Some(pool_idle_timeout_seconds),
+            compression: true,
+        };
         Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(timeout_seconds))
-                .pool_max_idle_per_host(10)
-                .pool_idle_timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
-            endpoint,
+            client: Arc::new(http_settings.build_client()),
+            http_settings,
+            endpoints: Arc::new(EndpointPool::new("primary", &endpoint)),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            request_limiter: None,
+        }
+    }
+
+    /// Abort responses whose body is larger than `max_response_bytes`
+    ///
+    /// Bounds the memory a single `eth_getLogs` over a busy range can take.
+    pub fn with_max_response_bytes(mut self, max_response_bytes: u64) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Request gzip-compressed responses and decode them; enabled by default
+    ///
+    /// Rebuilds the HTTP client, so call it before cloning the client.
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        if self.http_settings.compression != enabled {
+            self.http_settings.compression = enabled;
+            self.client = Arc::new(self.http_settings.build_client());
+        }
+        self
+    }
+
+    /// Name the endpoint this client was created with, as reported by `/status`
+    pub fn with_endpoint_name(self, name: &str) -> Self {
+        self.endpoints.rename_primary(name);
+        self
+    }
+
+    /// Fail over to `url` when the endpoints before it are unhealthy
+    ///
+    /// The endpoint is shared with every clone of this client.
+    pub fn with_fallback_endpoint(self, name: &str, url: &str) -> Self {
+        self.endpoints.push(name, url);
+        self
+    }
+
+    /// Endpoints of this client with their health, shared with every clone
+    pub fn endpoint_pool(&self) -> Arc<EndpointPool> {
+        Arc::clone(&self.endpoints)
+    }
+
+    /// Probe every endpoint with `eth_blockNumber`, reordering them by health and latency
+    ///
+    /// Each probe is a single request bounded by `timeout`, without retries.
+    pub async fn check_endpoint_health(&self, timeout: std::time::Duration) {
+        for url in self.endpoints.urls() {
+            let started = std::time::Instant::now();
+            let result = match tokio::time::timeout(timeout, self.request_endpoint(&url, "eth_blockNumber", vec![])).await {
+                Ok(Ok(_)) => Ok(started.elapsed()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("No response within {:?}", timeout)),
+            };
+            self.endpoints.record_probe(&url, result);
+        }
+    }
+
+    /// Probe the endpoints every `interval` in the background; see [`check_endpoint_health`](Self::check_endpoint_health)
+    ///
+    /// Must be called from within a Tokio runtime. Aborting the returned handle stops the probes.
+    pub fn spawn_health_checker(
+        &self,
+        interval: std::time::Duration,
+        probe_timeout: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                client.check_endpoint_health(probe_timeout).await;
+            }
+        })
+    }
+
+    /// Hold a permit from `limiter` for every request
+    ///
+    /// Clients sharing one semaphore, such as the primary and archive clients used
+    /// by live monitoring and backfill, are bounded together.
+    pub fn with_request_limiter(mut self, limiter: Arc<Semaphore>) -> Self {
+        self.request_limiter = Some(limiter);
+        self
+    }
+
+    /// Wait for a request permit when a limiter is configured
+    async fn acquire_request_permit(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.request_limiter {
+            // The semaphore is never closed, so acquiring only fails if that changes
+            Some(limiter) => limiter.acquire().await.ok(),
+            None => None,
+        }
+    }
+
+    /// Read the response body chunk by chunk, giving up as soon as it passes the size limit
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<Vec<u8>, NewRpcError> {
+        let too_large = NewRpcError::ResponseTooLarge { limit_bytes: self.max_response_bytes };
+        if response.content_length().is_some_and(|length| length > self.max_response_bytes) {
+            return Err(too_large);
         }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (body.len() + chunk.len()) as u64 > self.max_response_bytes {
+                return Err(too_large);
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
     }
 
     async fn make_request(&self, method: &str, params: Vec<Value>) -> Result<Value, RpcError> {
@@ -127,20 +426,26 @@ impl RpcClient {
             id: 1,
         };
 
+        let _permit = self.acquire_request_permit().await;
         let response = self
             .client
-            .post(&self.endpoint)
+            .post(&*self.endpoints.primary_url())
             .json(&request)
             .send()
             .await?;
 
-        let rpc_response: JsonRpcResponse = response.json().await?;
+        let body = self.read_body(response).await.map_err(|e| match e {
+            NewRpcError::Http(e) => RpcError::Http(e),
+            other => RpcError::Rpc(other.to_string()),
+        })?;
+        let rpc_response: JsonRpcResponse = serde_json::from_slice(&body)?;
 
         if let Some(error) = rpc_response.error {
-            return Err(RpcError::Rpc(format!(
-                "Code: {}, Message: {}",
-                error.code, error.message
-            )));
+            let data = error.data_string();
+            return Err(RpcError::Rpc(match data {
+                Some(data) => format!("Code: {}, Message: {}, Data: {}", error.code, error.message, data),
+                None => format!("Code: {}, Message: {}", error.code, error.message),
+            }));
         }
 
         rpc_response
@@ -149,10 +454,28 @@ impl RpcClient {
     }
 
     /// Enhanced make_request with better error handling and logging
+    ///
+    /// An endpoint that cannot be reached, times out or answers with a server
+    /// error is demoted, so the next request goes to the next endpoint.
     async fn make_request_enhanced(&self, method: &str, params: Vec<Value>) -> Result<Value, IndexerError> {
+        let endpoint = self.endpoints.primary_url();
+        let result = self.request_endpoint(&endpoint, method, params).await;
+        if let Err(IndexerError::Rpc(
+            error @ (NewRpcError::Connection(_) | NewRpcError::Timeout { .. } | NewRpcError::ServerError { .. }),
+        )) = &result
+        {
+            if self.endpoints.len() > 1 {
+                self.endpoints.demote(&endpoint, error.to_string());
+            }
+        }
+        result
+    }
+
+    /// Send one request to `endpoint`
+    async fn request_endpoint(&self, endpoint: &str, method: &str, params: Vec<Value>) -> Result<Value, IndexerError> {
         let context = LogContext::new("rpc_client", "make_request")
             .with_metadata("method", serde_json::json!(method))
-            .with_metadata("endpoint", serde_json::json!(self.endpoint));
+            .with_metadata("endpoint", serde_json::json!(endpoint));
 
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
@@ -163,9 +486,11 @@ impl RpcClient {
 
         context.trace(&format!("Sending RPC request: {}", method));
 
+        // Held until the body has been read
+        let _permit = self.acquire_request_permit().await;
         let response = self
             .client
-            .post(&self.endpoint)
+            .post(endpoint)
             .json(&request)
             .send()
             .await
@@ -184,28 +509,34 @@ impl RpcClient {
 
         let status = response.status();
         if !status.is_success() {
-            let error_msg = format!("HTTP error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown"));
-            return Err(IndexerError::Rpc(NewRpcError::Connection(error_msg)));
+            return Err(IndexerError::Rpc(classify_http_status(status)));
         }
 
-        let rpc_response: JsonRpcResponse = response.json().await
-            .map_err(|e| IndexerError::Rpc(NewRpcError::Http(e)))?;
+        let body = self.read_body(response).await.map_err(IndexerError::Rpc)?;
+        let rpc_response: JsonRpcResponse = serde_json::from_slice(&body)
+            .map_err(|e| IndexerError::Rpc(NewRpcError::Json(e)))?;
 
         if let Some(error) = rpc_response.error {
+            let data = error.data_string();
             let rpc_error = match error.code {
                 -32700 => NewRpcError::InvalidResponse("Parse error".to_string()),
                 -32600 => NewRpcError::InvalidResponse("Invalid request".to_string()),
-                -32601 => NewRpcError::Method { code: error.code, message: error.message },
-                -32602 => NewRpcError::InvalidResponse("Invalid params".to_string()),
-                -32603 => NewRpcError::Method { code: error.code, message: error.message },
-                _ => NewRpcError::Method { code: error.code, message: error.message },
+                -32602 => NewRpcError::InvalidResponse(match data {
+                    Some(data) => format!("Invalid params: {} (data: {})", error.message, data),
+                    None => format!("Invalid params: {}", error.message),
+                }),
+                _ => NewRpcError::Method { code: error.code, message: error.message, data },
             };
             return Err(IndexerError::Rpc(rpc_error));
         }
 
-        rpc_response
-            .result
-            .ok_or_else(|| IndexerError::Rpc(NewRpcError::InvalidResponse("No result in response".to_string())))
+        rpc_response.result.ok_or_else(|| {
+            context.debug(&format!(
+                "RPC response has neither a result nor an error: {}",
+                String::from_utf8_lossy(&body)
+            ));
+            IndexerError::Rpc(NewRpcError::EmptyResponse)
+        })
     }
 
     pub async fn get_latest_block_number(&self) -> Result<u64, RpcError> {
@@ -221,6 +552,17 @@ impl RpcClient {
             .map_err(|e| RpcError::Rpc(format!("Failed to parse block number: {}", e)))
     }
 
+    /// Fetch the latest block number, giving up once `timeout` has passed
+    ///
+    /// Meant for the startup connectivity check: retries still apply, but a dead
+    /// or misconfigured endpoint fails within `timeout` instead of after the full
+    /// request timeout and retry schedule.
+    pub async fn probe_latest_block_number(&self, timeout: std::time::Duration) -> Result<u64, IndexerError> {
+        tokio::time::timeout(timeout, self.get_latest_block_number_with_retry())
+            .await
+            .unwrap_or_else(|_| Err(IndexerError::Rpc(NewRpcError::Timeout { seconds: timeout.as_secs() })))
+    }
+
     /// Enhanced version with retry logic and better error handling
     pub async fn get_latest_block_number_with_retry(&self) -> Result<u64, IndexerError> {
         RetryUtils::retry_rpc("get_latest_block_number", || async {
@@ -259,6 +601,13 @@ impl RpcClient {
     }
 
     pub async fn get_block(&self, block_number: u64) -> Result<Block, RpcError> {
+        self.get_block_if_exists(block_number)
+            .await?
+            .ok_or_else(|| RpcError::Rpc(format!("Block {} not found", block_number)))
+    }
+
+    /// Get a block, returning `None` when the node responds with a null block
+    pub async fn get_block_if_exists(&self, block_number: u64) -> Result<Option<Block>, RpcError> {
         let block_hex = format!("0x{:x}", block_number);
         let params = vec![
             serde_json::Value::String(block_hex),
@@ -268,10 +617,15 @@ impl RpcClient {
         let result = self.make_request("eth_getBlockByNumber", params).await?;
         
         if result.is_null() {
-            return Err(RpcError::Rpc(format!("Block {} not found", block_number)));
+            return Ok(None);
+        }
+
+        if let Some(field) = missing_block_field(&result) {
+            return Err(RpcError::Rpc(missing_block_field_message(block_number, field)));
         }
         
         serde_json::from_value(result)
+            .map(Some)
             .map_err(|e| RpcError::Json(e))
     }
 
@@ -297,6 +651,12 @@ impl RpcClient {
                     if value.is_null() {
                         return Err(IndexerError::Rpc(NewRpcError::BlockNotFound { block_number }));
                     }
+
+                    if let Some(field) = missing_block_field(&value) {
+                        return Err(IndexerError::Rpc(NewRpcError::InvalidResponse(
+                            missing_block_field_message(block_number, field)
+                        )));
+                    }
                     
                     let block: Block = serde_json::from_value(value)
                         .map_err(|e| IndexerError::Processing(
@@ -328,6 +688,11 @@ impl RpcClient {
         let raw_logs = eth_logs.into_iter().map(|eth_log| {
             let block_number = parse_hex_to_u64(&eth_log.block_number).unwrap_or(0);
             let log_index = parse_hex_to_u32(&eth_log.log_index).unwrap_or(0);
+            let transaction_index = eth_log
+                .transaction_index
+                .as_deref()
+                .and_then(|index| parse_hex_to_u32(index).ok())
+                .unwrap_or(0);
             
             RawLog {
                 address: eth_log.address,
@@ -336,6 +701,8 @@ impl RpcClient {
                 block_number,
                 transaction_hash: eth_log.transaction_hash,
                 log_index,
+                transaction_index,
+                removed: eth_log.removed,
             }
         }).collect();
         
@@ -371,6 +738,10 @@ impl RpcClient {
                     for eth_log in eth_logs {
                         let block_number = parse_hex_to_u64_enhanced(&eth_log.block_number)?;
                         let log_index = parse_hex_to_u32_enhanced(&eth_log.log_index)?;
+                        let transaction_index = match eth_log.transaction_index.as_deref() {
+                            Some(index) => parse_hex_to_u32_enhanced(index)?,
+                            None => 0,
+                        };
                         
                         raw_logs.push(RawLog {
                             address: eth_log.address,
@@ -379,6 +750,8 @@ impl RpcClient {
                             block_number,
                             transaction_hash: eth_log.transaction_hash,
                             log_index,
+                            transaction_index,
+                            removed: eth_log.removed,
                         });
                     }
 
@@ -394,6 +767,90 @@ impl RpcClient {
             }
         }).await
     }
+
+    /// Fetch logs over a wide block range by splitting it into chunks of at most
+    /// `max_block_span` blocks, issuing sequential `eth_getLogs` calls.
+    /// Chunks rejected by the provider as too large are halved and retried.
+    pub async fn get_logs_paginated(&self, filter: LogFilter, max_block_span: u64) -> Result<Vec<RawLog>, IndexerError> {
+        let (from_block, to_block) = match (
+            parse_hex_to_u64_enhanced(&filter.from_block),
+            parse_hex_to_u64_enhanced(&filter.to_block),
+        ) {
+            (Ok(from), Ok(to)) => (from, to),
+            // Block tags such as "latest" cannot be split into chunks
+            _ => return self.get_logs_with_retry(filter).await,
+        };
+
+        if from_block > to_block {
+            return Ok(Vec::new());
+        }
+
+        let mut span = max_block_span.max(1);
+        let mut chunk_start = from_block;
+        let mut all_logs = Vec::new();
+
+        while chunk_start <= to_block {
+            let chunk_end = chunk_start.saturating_add(span - 1).min(to_block);
+            let chunk_filter = LogFilter {
+                from_block: format!("0x{:x}", chunk_start),
+                to_block: format!("0x{:x}", chunk_end),
+                address: filter.address.clone(),
+                topics: filter.topics.clone(),
+            };
+
+            match self.get_logs_with_retry(chunk_filter).await {
+                Ok(logs) => {
+                    all_logs.extend(logs);
+                    chunk_start = chunk_end + 1;
+                }
+                Err(e) if is_range_too_large(&e) && chunk_end > chunk_start => {
+                    span = (chunk_end - chunk_start).div_ceil(2);
+                    let context = LogContext::new("rpc_client", "get_logs_paginated")
+                        .with_metadata("from_block", serde_json::json!(chunk_start))
+                        .with_metadata("to_block", serde_json::json!(chunk_end))
+                        .with_metadata("new_span", serde_json::json!(span));
+                    context.warn(&format!("Provider rejected block range, reducing span to {} blocks", span));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        all_logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let context = LogContext::new("rpc_client", "get_logs_paginated")
+            .with_metadata("from_block", serde_json::json!(from_block))
+            .with_metadata("to_block", serde_json::json!(to_block))
+            .with_metadata("log_count", serde_json::json!(all_logs.len()));
+        context.debug(&format!("Retrieved {} logs across blocks {}-{}", all_logs.len(), from_block, to_block));
+
+        Ok(all_logs)
+    }
+}
+
+/// Map a non-success HTTP status to an RPC error; whether it is retried is up to `RetryConfig`
+fn classify_http_status(status: reqwest::StatusCode) -> NewRpcError {
+    match status.as_u16() {
+        429 => NewRpcError::RateLimit { seconds: 60 },
+        401 | 403 => NewRpcError::Authentication,
+        status @ 500..=599 => NewRpcError::ServerError { status },
+        status => NewRpcError::ClientError { status },
+    }
+}
+
+/// Check whether an RPC error indicates the requested log range exceeds provider limits
+fn is_range_too_large(error: &IndexerError) -> bool {
+    let message = match error {
+        IndexerError::Rpc(NewRpcError::Method { code: -32005, .. }) => return true,
+        IndexerError::Rpc(NewRpcError::ResponseTooLarge { .. }) => return true,
+        IndexerError::Rpc(NewRpcError::Method { message, .. }) => message,
+        IndexerError::Rpc(NewRpcError::InvalidResponse(message)) => message,
+        _ => return false,
+    };
+
+    let message = message.to_lowercase();
+    ["block range", "range too large", "range is too large", "more than", "response size", "limit exceeded", "too many"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
 }
 
 fn parse_hex_to_u64(hex_str: &str) -> Result<u64, RpcError> {
@@ -408,6 +865,20 @@ fn parse_hex_to_u32(hex_str: &str) -> Result<u32, RpcError> {
         .map_err(|e| RpcError::Rpc(format!("Failed to parse hex to u32: {}", e)))
 }
 
+/// First required block field that is absent or null in `block`
+///
+/// Checked before deserializing so the error names the field instead of
+/// surfacing serde's message about the struct.
+fn missing_block_field(block: &serde_json::Value) -> Option<&'static str> {
+    REQUIRED_BLOCK_FIELDS
+        .into_iter()
+        .find(|field| block.get(field).is_none_or(|value| value.is_null()))
+}
+
+fn missing_block_field_message(block_number: u64, field: &str) -> String {
+    format!("Block {} response is missing required field `{}`", block_number, field)
+}
+
 /// Enhanced hex parsing with better error handling
 fn parse_hex_to_u64_enhanced(hex_str: &str) -> Result<u64, IndexerError> {
     let hex_without_prefix = hex_str.strip_prefix("0x").unwrap_or(hex_str);
@@ -438,7 +909,23 @@ mod tests {
     async fn test_rpc_client_creation() {
         let endpoint = "https://polygon-rpc.com/".to_string();
         let client = RpcClient::new(endpoint.clone());
-        assert_eq!(client.endpoint, endpoint);
+        assert_eq!(&*client.endpoints.primary_url(), endpoint);
+    }
+
+    #[test]
+    fn test_rpc_client_clones_are_cheap_and_quiet() {
+        crate::testing::capture_logs();
+
+        let client = RpcClient::new_with_config("https://polygon-rpc.com/".to_string(), 30);
+
+        let started = std::time::Instant::now();
+        let clones: Vec<RpcClient> = (0..1000).map(|_| client.clone()).collect();
+        let elapsed = started.elapsed();
+
+        assert!(elapsed < std::time::Duration::from_millis(100), "1000 clones took {:?}", elapsed);
+        assert!(clones.iter().all(|clone| Arc::ptr_eq(&clone.client, &client.client)));
+        assert!(clones.iter().all(|clone| Arc::ptr_eq(&clone.endpoints, &client.endpoints)));
+        assert!(crate::testing::captured_logs(log::Level::Info, "Initializing RPC client").is_empty());
     }
 
     #[tokio::test]
@@ -521,7 +1008,7 @@ mod tests {
             from_block: "0x1234".to_string(),
             to_block: "0x1235".to_string(),
             address: Some("0xabc123".to_string()),
-            topics: Some(vec![Some("0xdef456".to_string())]),
+            topics: Some(vec![Some("0xdef456".to_string().into())]),
         };
 
         let json = serde_json::to_string(&filter).unwrap();
@@ -530,6 +1017,36 @@ mod tests {
         assert!(json.contains("\"address\":\"0xabc123\""));
     }
 
+    #[test]
+    fn test_exchange_log_filters_serialize_topic_sets() {
+        let watched = vec![
+            "0xF977814e90dA44bFA03b6295A0616a897441aceC".to_string(),
+            "0x5a52e96bacdabb82fd05763e25335261b270efcb".to_string(),
+        ];
+        let padded = |address: &str| format!("0x000000000000000000000000{}", address);
+        let watched_topics = serde_json::json!([
+            padded("f977814e90da44bfa03b6295a0616a897441acec"),
+            padded("5a52e96bacdabb82fd05763e25335261b270efcb")
+        ]);
+
+        let inflows = LogFilter::exchange_inflows("0xtoken", &watched, 10..=20);
+        assert_eq!(
+            serde_json::to_value(&inflows).unwrap(),
+            json!({
+                "fromBlock": "0xa",
+                "toBlock": "0x14",
+                "address": "0xtoken",
+                "topics": [crate::blockchain::transfer_detector::TRANSFER_EVENT_SIGNATURE, null, watched_topics]
+            })
+        );
+
+        let outflows = LogFilter::exchange_outflows("0xtoken", &watched, 10..=20);
+        assert_eq!(
+            serde_json::to_value(&outflows).unwrap()["topics"],
+            json!([crate::blockchain::transfer_detector::TRANSFER_EVENT_SIGNATURE, watched_topics, null])
+        );
+    }
+
     // Mock server test would require additional dependencies like wiremock
     // For now, we'll test the parsing logic and structure
     #[test]
@@ -537,4 +1054,476 @@ mod tests {
         let rpc_error = RpcError::Rpc("Custom error".to_string());
         assert_eq!(format!("{}", rpc_error), "RPC error: Custom error");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_json_rpc_error_data_is_surfaced() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {
+                    "code": 3,
+                    "message": "execution reverted",
+                    "data": {"reason": "ERC20: transfer amount exceeds balance"}
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+
+        let error = client.make_request_enhanced("eth_call", vec![]).await.unwrap_err();
+        match &error {
+            IndexerError::Rpc(NewRpcError::Method { code, data, .. }) => {
+                assert_eq!(*code, 3);
+                assert_eq!(data.as_deref(), Some(r#"{"reason":"ERC20: transfer amount exceeds balance"}"#));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(error.to_string().contains("ERC20: transfer amount exceeds balance"));
+
+        // The legacy request path keeps the data through the conversion to the new error type
+        let legacy = client.make_request("eth_call", vec![]).await.unwrap_err();
+        let converted = IndexerError::from(legacy);
+        assert!(converted.to_string().contains("message=execution reverted, data="));
+        assert!(converted.to_string().contains("exceeds balance"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_pool_config_handles_concurrent_requests() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": "0x10",
+            })))
+            .expect(64)
+            .mount(&server)
+            .await;
+
+        // A small pool must still serve many more concurrent requests than it keeps idle
+        let client = RpcClient::new_with_pool_config(server.uri(), 10, 2, 5);
+        let mut requests = tokio::task::JoinSet::new();
+        for _ in 0..64 {
+            let client = client.clone();
+            requests.spawn(async move { client.get_latest_block_number_with_retry().await });
+        }
+
+        while let Some(result) = requests.join_next().await {
+            assert_eq!(result.unwrap().unwrap(), 0x10);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gzip_encoded_response_is_decoded() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(br#"{"jsonrpc":"2.0","id":1,"result":"0x2a"}"#).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("accept-encoding", "gzip"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .insert_header("content-type", "application/json")
+                    .set_body_bytes(compressed),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+        assert_eq!(client.get_latest_block_number_with_retry().await.unwrap(), 42);
+
+        // Without compression the request does not advertise gzip
+        let uncompressed = RpcClient::new(server.uri()).with_compression(false);
+        assert!(uncompressed.get_latest_block_number().await.is_err());
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.last().unwrap().headers.keys().all(|name| name.as_str() != "accept-encoding"));
+    }
+
+    fn mock_log(block_number: u64, log_index: u32) -> serde_json::Value {
+        json!({
+            "address": "0x455e53847f9f0f0b0fcf0b0b0b0b0b0b0b0b0b0b",
+            "topics": [],
+            "data": "0x",
+            "blockNumber": format!("0x{:x}", block_number),
+            "transactionHash": format!("0xtx{}_{}", block_number, log_index),
+            "logIndex": format!("0x{:x}", log_index),
+        })
+    }
+
+    async fn mount_logs_response(server: &wiremock::MockServer, from: u64, to: u64, result: serde_json::Value) {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "eth_getLogs",
+                "params": [{ "fromBlock": format!("0x{:x}", from), "toBlock": format!("0x{:x}", to) }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": result,
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    fn range_filter(from: u64, to: u64) -> LogFilter {
+        LogFilter {
+            from_block: format!("0x{:x}", from),
+            to_block: format!("0x{:x}", to),
+            address: None,
+            topics: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_paginated_merges_chunks_in_order() {
+        let server = wiremock::MockServer::start().await;
+
+        // Blocks 100..=109 with a span of 4 require three chunks
+        mount_logs_response(&server, 100, 103, json!([mock_log(103, 0), mock_log(100, 2), mock_log(100, 1)])).await;
+        mount_logs_response(&server, 104, 107, json!([mock_log(105, 0)])).await;
+        mount_logs_response(&server, 108, 109, json!([mock_log(109, 1), mock_log(109, 0)])).await;
+
+        let client = RpcClient::new(server.uri());
+        let logs = client.get_logs_paginated(range_filter(100, 109), 4).await.unwrap();
+
+        let positions: Vec<(u64, u32)> = logs.iter().map(|log| (log.block_number, log.log_index)).collect();
+        assert_eq!(positions, vec![(100, 1), (100, 2), (103, 0), (105, 0), (109, 0), (109, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_paginated_shrinks_span_on_range_error() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "eth_getLogs",
+                "params": [{ "fromBlock": "0xc8", "toBlock": "0xcb" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32005, "message": "query returned more than 10000 results" },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_logs_response(&server, 200, 201, json!([mock_log(201, 0)])).await;
+        mount_logs_response(&server, 202, 203, json!([mock_log(202, 0)])).await;
+
+        let client = RpcClient::new(server.uri());
+        let logs = client.get_logs_paginated(range_filter(200, 203), 4).await.unwrap();
+
+        let blocks: Vec<u64> = logs.iter().map(|log| log.block_number).collect();
+        assert_eq!(blocks, vec![201, 202]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_is_rejected() {
+        let server = wiremock::MockServer::start().await;
+        let logs: Vec<serde_json::Value> = (0..50).map(|log_index| mock_log(300, log_index)).collect();
+        mount_logs_response(&server, 300, 300, json!(logs)).await;
+
+        let client = RpcClient::new(server.uri()).with_max_response_bytes(4 * 1024);
+        let error = client.get_logs_with_retry(range_filter(300, 300)).await.unwrap_err();
+
+        assert!(
+            matches!(error, IndexerError::Rpc(NewRpcError::ResponseTooLarge { limit_bytes: 4096 })),
+            "unexpected error: {:?}",
+            error
+        );
+        assert!(!error.is_recoverable());
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_paginated_shrinks_span_on_oversized_response() {
+        let server = wiremock::MockServer::start().await;
+        let logs = |block_number: u64| -> Vec<serde_json::Value> {
+            (0..15).map(|log_index| mock_log(block_number, log_index)).collect()
+        };
+        let both: Vec<serde_json::Value> = logs(400).into_iter().chain(logs(401)).collect();
+        mount_logs_response(&server, 400, 401, json!(both)).await;
+        mount_logs_response(&server, 400, 400, json!(logs(400))).await;
+        mount_logs_response(&server, 401, 401, json!(logs(401))).await;
+
+        // One block's logs fit under the limit, two do not
+        let client = RpcClient::new(server.uri()).with_max_response_bytes(4 * 1024);
+        let fetched = client.get_logs_paginated(range_filter(400, 401), 2).await.unwrap();
+
+        assert_eq!(fetched.len(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_service_unavailable_is_retried() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": "0x2a"})))
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+        assert_eq!(client.get_latest_block_number_with_retry().await.unwrap(), 42);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_empty_response_is_typed_and_retried() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1})))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": "0x2a"})))
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+        let error = client.make_request_enhanced("eth_blockNumber", vec![]).await.unwrap_err();
+        assert!(matches!(error, IndexerError::Rpc(NewRpcError::EmptyResponse)), "unexpected error: {:?}", error);
+        assert!(error.is_recoverable());
+
+        // The retrying call gets past a second empty answer
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1})))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        assert_eq!(client.get_latest_block_number_with_retry().await.unwrap(), 42);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_fails_without_retry() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+        let error = client.get_latest_block_number_with_retry().await.unwrap_err();
+
+        assert!(
+            matches!(error, IndexerError::Rpc(NewRpcError::ClientError { status: 400 })),
+            "unexpected error: {:?}",
+            error
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_http_status_classification() {
+        use reqwest::StatusCode;
+
+        for status in [500, 502, 503, 504] {
+            let error = IndexerError::Rpc(classify_http_status(StatusCode::from_u16(status).unwrap()));
+            assert!(error.is_recoverable(), "{} should be recoverable", status);
+        }
+        for status in [400, 401, 403, 404] {
+            let error = IndexerError::Rpc(classify_http_status(StatusCode::from_u16(status).unwrap()));
+            assert!(!error.is_recoverable(), "{} should not be recoverable", status);
+        }
+        assert!(matches!(classify_http_status(StatusCode::TOO_MANY_REQUESTS), NewRpcError::RateLimit { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_startup_probe_fails_within_probe_timeout() {
+        // Accepts connections but never answers, like a dead endpoint behind a load balancer
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = RpcClient::new(format!("http://{}", listener.local_addr().unwrap()));
+
+        let started = std::time::Instant::now();
+        let result = client.probe_latest_block_number(std::time::Duration::from_secs(1)).await;
+
+        assert!(matches!(result, Err(IndexerError::Rpc(NewRpcError::Timeout { seconds: 1 }))), "{:?}", result);
+        // Well short of the client's 30 second request timeout
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "took {:?}", started.elapsed());
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_block_missing_field_is_named_in_error() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = wiremock::MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"number": "0x64", "hash": "0xabc", "transactions": []}
+            })))
+            .mount(&server)
+            .await;
+
+        let client = RpcClient::new(server.uri());
+        let error = client.get_block_with_retry(100).await.unwrap_err();
+        match &error {
+            IndexerError::Rpc(NewRpcError::InvalidResponse(message)) => assert!(message.contains("`timestamp`"), "{}", message),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let legacy = client.get_block(100).await.unwrap_err();
+        assert!(legacy.to_string().contains("`timestamp`"), "{}", legacy);
+    }
+
+    #[test]
+    fn test_block_deserializes_full_transaction_objects() {
+        let block: Block = serde_json::from_value(json!({
+            "number": "0x64",
+            "hash": "0xabc",
+            "timestamp": "0x61cf9980",
+            "transactions": [
+                {"hash": "0xtx1", "from": "0xfrom", "to": "0xto", "blockNumber": "0x64", "value": "0x0"},
+                {"hash": "0xtx2", "from": "0xfrom", "to": null, "blockNumber": "0x64"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(block.transactions.len(), 2);
+        match &block.transactions[1] {
+            BlockTransaction::Full(transaction) => assert_eq!(transaction.to, None),
+            other => panic!("expected a full transaction, got {:?}", other),
+        }
+        assert_eq!(block.transactions[0].hash(), "0xtx1");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_fails_over_and_re_promotes_recovered_primary() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let block_number = |n: u64| ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", n)}));
+        let primary = MockServer::start().await;
+        let fallback = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .with_priority(2)
+            .mount(&primary)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_blockNumber"})))
+            .respond_with(block_number(200).set_delay(std::time::Duration::from_millis(100)))
+            .mount(&fallback)
+            .await;
+
+        let client = RpcClient::new(primary.uri())
+            .with_endpoint_name("tip")
+            .with_fallback_endpoint("standby", &fallback.uri());
+        let timeout = std::time::Duration::from_secs(5);
+
+        client.check_endpoint_health(timeout).await;
+        let health = client.endpoint_pool().health();
+        assert_eq!(health[0].name, "standby");
+        assert!(health[0].healthy);
+        assert_eq!(health[1].name, "tip");
+        assert!(!health[1].healthy);
+        assert!(health[1].last_error.as_deref().unwrap_or_default().contains("503"));
+        assert_eq!(client.get_latest_block_number_with_retry().await.unwrap(), 200);
+
+        // The primary recovers and, being faster, is promoted again
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_blockNumber"})))
+            .respond_with(block_number(201))
+            .with_priority(1)
+            .mount(&primary)
+            .await;
+        client.check_endpoint_health(timeout).await;
+        let health = client.endpoint_pool().health();
+        assert_eq!(health[0].name, "tip");
+        assert!(health.iter().all(|endpoint| endpoint.healthy));
+        assert!(health[0].latency_ms < health[1].latency_ms);
+        assert_eq!(client.get_latest_block_number_with_retry().await.unwrap(), 201);
+    }
+
+    #[test]
+    fn test_contract_creation_transaction_has_no_recipient() {
+        let block: Block = serde_json::from_value(json!({
+            "number": "0x64",
+            "hash": "0xabc",
+            "timestamp": "0x61cf9980",
+            "transactions": [
+                {
+                    "hash": "0xcreate",
+                    "from": "0xdeployer",
+                    "to": null,
+                    "blockNumber": "0x64",
+                    "input": "0x6080604052",
+                    "creates": "0xnewcontract"
+                },
+                {"hash": "0xcreate2", "from": "0xdeployer", "blockNumber": "0x64"},
+                {"hash": "0xcall", "from": "0xfrom", "to": "0xto", "blockNumber": "0x64"}
+            ]
+        }))
+        .unwrap();
+
+        let recipients: Vec<Option<&str>> = block.transactions.iter().map(BlockTransaction::to_address).collect();
+        assert_eq!(recipients, vec![None, None, Some("0xto")]);
+        match &block.transactions[0] {
+            BlockTransaction::Full(transaction) => assert_eq!(transaction.to_address(), None),
+            other => panic!("expected a full transaction, got {:?}", other),
+        }
+
+        let empty = Transaction { hash: "0x1".into(), from: "0x2".into(), to: Some("0x".into()), block_number: "0x64".into() };
+        assert_eq!(empty.to_address(), None);
+        assert_eq!(BlockTransaction::Hash("0xcall".to_string()).to_address(), None);
+    }
+
+    #[test]
+    fn test_block_deserializes_transaction_hashes() {
+        let block: Block = serde_json::from_value(json!({
+            "number": "0x64",
+            "hash": "0xabc",
+            "timestamp": "0x61cf9980",
+            "transactions": ["0xtx1", "0xtx2"]
+        }))
+        .unwrap();
+
+        assert!(matches!(block.transactions[0], BlockTransaction::Hash(_)));
+        let hashes: Vec<&str> = block.transactions.iter().map(BlockTransaction::hash).collect();
+        assert_eq!(hashes, ["0xtx1", "0xtx2"]);
+    }
+}
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 use crate::models::{RawLog, ProcessedTransfer, TransferDirection};
 
@@ -34,49 +35,33 @@ pub const BINANCE_ADDRESSES: &[&str] = &[
     "0x082489a616ab4d46d1947ee3f912e080815b08da",
 ];
 
-pub struct TransferDetector {
-    pol_token_address: String,
-    binance_addresses: HashSet<String>,
-}
-
-impl TransferDetector {
-    pub fn new() -> Self {
-        let binance_addresses: HashSet<String> = BINANCE_ADDRESSES
-            .iter()
-            .map(|addr| normalize_address(addr))
-            .collect();
-
-        Self {
-            pol_token_address: normalize_address(POL_TOKEN_ADDRESS),
-            binance_addresses,
-        }
-    }
+/// Label reported for the addresses in [`BINANCE_ADDRESSES`]
+pub const BINANCE_LABEL: &str = "Binance";
 
-    /// Check if a log represents a POL token transfer event
-    pub fn is_pol_transfer(&self, log: &RawLog) -> bool {
-        // Check if the log is from the POL token contract
-        let normalized_log_address = normalize_address(&log.address);
-        if normalized_log_address != self.pol_token_address {
-            return false;
-        }
+/// A watched exchange address and the exchange it belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchedAddress {
+    /// 0x-prefixed, lowercase
+    pub address: String,
+    pub label: String,
+}
 
-        // Check if it's a Transfer event by verifying the event signature
-        if log.topics.is_empty() {
-            return false;
-        }
+/// Decodes a POL token event log into a transfer
+///
+/// Decoders are registered with a [`TransferDetector`] under an event signature
+/// (topic 0), which lets events other than the ERC-20 `Transfer`, such as bridge
+/// deposits and withdrawals, be mapped to inflows and outflows.
+pub trait EventDecoder: Send + Sync {
+    /// Decode `log`, using `detector` to classify the transfer direction
+    fn decode(&self, log: &RawLog, detector: &TransferDetector) -> Result<ProcessedTransfer, TransferDetectionError>;
+}
 
-        let event_signature = normalize_address(&log.topics[0]);
-        event_signature == normalize_address(TRANSFER_EVENT_SIGNATURE)
-    }
-
-    /// Decode a POL transfer event log into a ProcessedTransfer
-    pub fn decode_transfer_log(&self, log: &RawLog) -> Result<ProcessedTransfer, TransferDetectionError> {
-        if !self.is_pol_transfer(log) {
-            return Err(TransferDetectionError::InvalidLog(
-                "Log is not a POL transfer event".to_string()
-            ));
-        }
+/// Decoder for the standard ERC-20 `Transfer(address indexed from, address indexed to, uint256 value)` event
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Erc20TransferDecoder;
 
+impl EventDecoder for Erc20TransferDecoder {
+    fn decode(&self, log: &RawLog, detector: &TransferDetector) -> Result<ProcessedTransfer, TransferDetectionError> {
         // ERC-20 Transfer event has 3 topics: [signature, from, to]
         if log.topics.len() != 3 {
             return Err(TransferDetectionError::InvalidLog(
@@ -85,19 +70,20 @@ impl TransferDetector {
         }
 
         // Extract from and to addresses from topics (remove 0x prefix and leading zeros)
-        let from_address = extract_address_from_topic(&log.topics[1])?;
-        let to_address = extract_address_from_topic(&log.topics[2])?;
+        let from_address = detector.address_from_topic(&log.topics[1])?;
+        let to_address = detector.address_from_topic(&log.topics[2])?;
 
         // Extract amount from data field
         let amount = extract_amount_from_data(&log.data)?;
 
         // Determine transfer direction
-        let direction = self.classify_transfer(&from_address, &to_address);
+        let direction = detector.classify_transfer(&from_address, &to_address);
 
         Ok(ProcessedTransfer {
             block_number: log.block_number,
             transaction_hash: log.transaction_hash.clone(),
             log_index: log.log_index,
+            transaction_index: log.transaction_index,
             from_address,
             to_address,
             amount,
@@ -105,20 +91,202 @@ impl TransferDetector {
             direction,
         })
     }
+}
+
+pub struct TransferDetector {
+    pol_token_address: String,
+    binance_addresses: HashSet<String>,
+    /// Event decoders keyed by normalized event signature
+    decoders: HashMap<String, Box<dyn EventDecoder>>,
+    /// Classify transfers from an address to itself as not relevant
+    drop_self_transfers: bool,
+    /// Classify transfers between two different Binance addresses as internal
+    classify_internal_transfers: bool,
+    /// Reject address topics whose 12 bytes of left padding are not all zero
+    strict_topic_padding: bool,
+    /// Log why each transfer got its direction at debug level
+    trace_classification: bool,
+}
+
+impl TransferDetector {
+    pub fn new() -> Self {
+        let binance_addresses: HashSet<String> = BINANCE_ADDRESSES
+            .iter()
+            .map(|addr| normalize_address(addr))
+            .collect();
+
+        let detector = Self {
+            pol_token_address: normalize_address(POL_TOKEN_ADDRESS),
+            binance_addresses,
+            decoders: HashMap::new(),
+            drop_self_transfers: true,
+            classify_internal_transfers: false,
+            strict_topic_padding: false,
+            trace_classification: false,
+        };
+        detector.with_event_decoder(TRANSFER_EVENT_SIGNATURE, Erc20TransferDecoder)
+    }
+
+    /// Register `decoder` for POL token logs whose topic 0 is `signature`
+    ///
+    /// Registering a signature again replaces its decoder, including the default
+    /// ERC-20 Transfer decoder.
+    pub fn with_event_decoder(mut self, signature: &str, decoder: impl EventDecoder + 'static) -> Self {
+        self.decoders.insert(normalize_address(signature), Box::new(decoder));
+        self
+    }
+
+    /// Choose whether transfers from an address to itself are dropped (the default)
+    ///
+    /// A self-transfer moves no funds, so a kept self-transfer of a Binance address
+    /// is classified as [`TransferDirection::Internal`] when internal transfers are
+    /// classified and as not relevant otherwise; it never changes the net flow.
+    pub fn with_drop_self_transfers(mut self, drop_self_transfers: bool) -> Self {
+        self.drop_self_transfers = drop_self_transfers;
+        self
+    }
+
+    /// Choose whether transfers between two different Binance addresses are
+    /// classified as [`TransferDirection::Internal`] rather than not relevant
+    ///
+    /// Internal transfers are stored but never change the net-flow totals.
+    pub fn with_classify_internal_transfers(mut self, classify_internal_transfers: bool) -> Self {
+        self.classify_internal_transfers = classify_internal_transfers;
+        self
+    }
+
+    /// Choose whether address topics must carry 12 zero bytes of left padding
+    ///
+    /// Nonzero padding means the log is malformed. In strict mode such a topic is
+    /// rejected with [`TransferDetectionError::InvalidAddress`]; by default the
+    /// padding is ignored and the low 20 bytes are used.
+    pub fn with_strict_topic_padding(mut self, strict_topic_padding: bool) -> Self {
+        self.strict_topic_padding = strict_topic_padding;
+        self
+    }
+
+    /// Choose whether every classification is logged at debug level (off by default)
+    ///
+    /// Each line has the normalized sender and recipient, whether each is a
+    /// watched address, the resulting direction and the rule that chose it, to
+    /// explain transfers classified unexpectedly, e.g. as not relevant.
+    pub fn with_classification_trace(mut self, trace_classification: bool) -> Self {
+        self.trace_classification = trace_classification;
+        self
+    }
+
+    /// Extract the address from an indexed 32-byte topic, checking its padding in strict mode
+    ///
+    /// Event decoders should use this rather than slicing the topic themselves.
+    pub fn address_from_topic(&self, topic: &str) -> Result<String, TransferDetectionError> {
+        let address = extract_address_from_topic(topic)?;
+        if self.strict_topic_padding {
+            let padding = &normalize_address(topic)[..24];
+            if padding.chars().any(|c| c != '0') {
+                return Err(TransferDetectionError::InvalidAddress(
+                    format!("Address topic has nonzero padding 0x{}", padding)
+                ));
+            }
+        }
+        Ok(address)
+    }
+
+    /// Registered event signatures (0x-prefixed, sorted)
+    pub fn event_signatures(&self) -> Vec<String> {
+        let mut signatures: Vec<String> = self.decoders.keys()
+            .map(|signature| format!("0x{}", signature))
+            .collect();
+        signatures.sort();
+        signatures
+    }
+
+    /// Check if a log is a POL token event with a registered decoder
+    pub fn is_pol_transfer(&self, log: &RawLog) -> bool {
+        self.decoder_for(log).is_some()
+    }
+
+    /// Decode a POL token event log into a ProcessedTransfer using its registered decoder
+    pub fn decode_transfer_log(&self, log: &RawLog) -> Result<ProcessedTransfer, TransferDetectionError> {
+        match self.decoder_for(log) {
+            Some(decoder) => decoder.decode(log, self),
+            None => Err(TransferDetectionError::InvalidLog(
+                "Log is not a POL transfer event".to_string()
+            )),
+        }
+    }
+
+    /// Find the decoder for a log from the POL token contract, if its event is registered
+    fn decoder_for(&self, log: &RawLog) -> Option<&dyn EventDecoder> {
+        // Check if the log is from the POL token contract
+        let normalized_log_address = normalize_address(&log.address);
+        if normalized_log_address != self.pol_token_address {
+            return None;
+        }
+
+        // Check the event signature against the registered decoders
+        let event_signature = normalize_address(log.topics.first()?);
+        self.decoders.get(&event_signature).map(|decoder| decoder.as_ref())
+    }
 
     /// Classify a transfer based on from/to addresses
     pub fn classify_transfer(&self, from_address: &str, to_address: &str) -> TransferDirection {
         let normalized_from = normalize_address(from_address);
         let normalized_to = normalize_address(to_address);
-
         let from_is_binance = self.binance_addresses.contains(&normalized_from);
         let to_is_binance = self.binance_addresses.contains(&normalized_to);
 
-        match (from_is_binance, to_is_binance) {
-            (false, true) => TransferDirection::ToBinance,   // Inflow to Binance
-            (true, false) => TransferDirection::FromBinance, // Outflow from Binance
-            _ => TransferDirection::NotRelevant,             // Both or neither are Binance
+        let (direction, reason) = if normalized_from == normalized_to {
+            // A self-transfer moves no funds in or out of the address
+            match (self.drop_self_transfers, to_is_binance) {
+                (true, _) => (TransferDirection::NotRelevant, "self-transfers are dropped"),
+                (false, true) if self.classify_internal_transfers => {
+                    (TransferDirection::Internal, "kept self-transfer of a watched address")
+                }
+                (false, true) => (
+                    TransferDirection::NotRelevant,
+                    "kept self-transfer of a watched address and internal transfers are not classified",
+                ),
+                (false, false) => (TransferDirection::NotRelevant, "self-transfer between unwatched addresses"),
+            }
+        } else {
+            match (from_is_binance, to_is_binance) {
+                (false, true) => (TransferDirection::ToBinance, "only the recipient is watched"),
+                (true, false) => (TransferDirection::FromBinance, "only the sender is watched"),
+                (true, true) if self.classify_internal_transfers => {
+                    (TransferDirection::Internal, "both addresses are watched")
+                }
+                (true, true) => (
+                    TransferDirection::NotRelevant,
+                    "both addresses are watched and internal transfers are not classified",
+                ),
+                (false, false) => (TransferDirection::NotRelevant, "neither address is watched"),
+            }
+        };
+
+        if self.trace_classification {
+            log::debug!(
+                "Classified transfer from 0x{} (watched: {}) to 0x{} (watched: {}) as {:?}: {}",
+                normalized_from, from_is_binance, normalized_to, to_is_binance, direction, reason
+            );
         }
+        direction
+    }
+
+    /// Watched Binance addresses (0x-prefixed, lowercase, sorted)
+    pub fn binance_addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self.binance_addresses.iter()
+            .map(|address| format!("0x{}", address))
+            .collect();
+        addresses.sort();
+        addresses
+    }
+
+    /// Watched addresses with their exchange labels, sorted by address
+    pub fn watched_addresses(&self) -> Vec<WatchedAddress> {
+        self.binance_addresses()
+            .into_iter()
+            .map(|address| WatchedAddress { address, label: BINANCE_LABEL.to_string() })
+            .collect()
     }
 
     /// Check if an address is a Binance address
@@ -184,16 +352,35 @@ fn extract_amount_from_data(data: &str) -> Result<String, TransferDetectionError
         ));
     }
 
-    // Convert hex to decimal string
-    let amount_hex = &normalized_data;
-    
-    // Parse as u128 to handle large token amounts
-    match u128::from_str_radix(amount_hex, 16) {
-        Ok(amount) => Ok(amount.to_string()),
-        Err(e) => Err(TransferDetectionError::HexDecoding(
-            format!("Failed to parse amount: {}", e)
-        )),
+    // Convert the full uint256 to a decimal string; u128 would overflow above ~3.4e38
+    hex_to_decimal(&normalized_data).ok_or_else(|| TransferDetectionError::HexDecoding(
+        format!("Failed to parse amount: invalid hex 0x{}", normalized_data)
+    ))
+}
+
+/// Convert an unsigned big-endian hex string of any length to a decimal string
+fn hex_to_decimal(hex: &str) -> Option<String> {
+    // Decimal digits, least significant first
+    let mut digits: Vec<u8> = vec![0];
+
+    for c in hex.chars() {
+        let mut carry = c.to_digit(16)?;
+        for digit in digits.iter_mut() {
+            let value = u32::from(*digit) * 16 + carry;
+            *digit = (value % 10) as u8;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
     }
+
+    Some(digits.iter().rev().map(|d| char::from(b'0' + d)).collect())
 }
 
 #[cfg(test)]
@@ -229,6 +416,37 @@ mod tests {
         assert_eq!(result, "f977814e90da44bfa03b6295a0616a897441acec");
     }
 
+    #[test]
+    fn test_topic_padding_is_checked_only_in_strict_mode() {
+        let padded = "0x000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec";
+        let dirty = "0x0000000000000000000000ffF977814e90da44bfa03b6295a0616a897441acec";
+
+        let lenient = TransferDetector::new();
+        assert_eq!(lenient.address_from_topic(padded).unwrap(), "f977814e90da44bfa03b6295a0616a897441acec");
+        assert_eq!(lenient.address_from_topic(dirty).unwrap(), "f977814e90da44bfa03b6295a0616a897441acec");
+
+        let strict = TransferDetector::new().with_strict_topic_padding(true);
+        assert_eq!(strict.address_from_topic(padded).unwrap(), "f977814e90da44bfa03b6295a0616a897441acec");
+        assert!(matches!(
+            strict.address_from_topic(dirty),
+            Err(TransferDetectionError::InvalidAddress(message)) if message.contains("0x0000000000000000000000ff")
+        ));
+
+        // The Transfer decoder goes through the same check
+        let log = RawLog {
+            address: POL_TOKEN_ADDRESS.to_string(),
+            topics: vec![TRANSFER_EVENT_SIGNATURE.to_string(), dirty.to_string(), padded.to_string()],
+            data: "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000".to_string(),
+            block_number: 12345,
+            transaction_hash: "0xdirty".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            removed: false,
+        };
+        assert!(lenient.decode_transfer_log(&log).is_ok());
+        assert!(matches!(strict.decode_transfer_log(&log), Err(TransferDetectionError::InvalidAddress(_))));
+    }
+
     #[test]
     fn test_extract_amount_from_data() {
         // 1 POL (1 * 10^18 wei) = 0xde0b6b3a7640000
@@ -248,6 +466,55 @@ mod tests {
         assert_eq!(result, "0");
     }
 
+    #[test]
+    fn test_extract_amount_preserves_full_uint256() {
+        // Max uint256 overflows u128 but must be preserved exactly
+        let data = format!("0x{}", "f".repeat(64));
+        let result = extract_amount_from_data(&data).unwrap();
+        assert_eq!(result, "115792089237316195423570985008687907853269984665640564039457584007913129639935");
+
+        // Just above u128::MAX
+        let data = "0x0000000000000000000000000000000100000000000000000000000000000000";
+        assert_eq!(extract_amount_from_data(data).unwrap(), "340282366920938463463374607431768211456");
+
+        let data = format!("0x{}g", "0".repeat(63));
+        assert!(matches!(extract_amount_from_data(&data), Err(TransferDetectionError::HexDecoding(_))));
+    }
+
+    #[test]
+    fn test_decode_max_uint256_transfer_into_net_flow() {
+        let detector = TransferDetector::new();
+        let log = RawLog {
+            address: POL_TOKEN_ADDRESS.to_string(),
+            topics: vec![
+                TRANSFER_EVENT_SIGNATURE.to_string(),
+                "0x0000000000000000000000001234567890123456789012345678901234567890".to_string(),
+                "0x000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            ],
+            data: format!("0x{}", "f".repeat(64)),
+            block_number: 12345,
+            transaction_hash: "0xabc123".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            removed: false,
+        };
+
+        let transfer = detector.decode_transfer_log(&log).unwrap();
+        assert_eq!(transfer.direction, TransferDirection::ToBinance);
+
+        // Net-flow totals keep every digit, including across additions
+        let database = crate::database::Database::new_in_memory().unwrap();
+        database.store_transfer_and_update_net_flow(&transfer).unwrap();
+        database.store_transfer_and_update_net_flow(&ProcessedTransfer { log_index: 1, ..transfer.clone() }).unwrap();
+
+        let net_flow = database.get_net_flow_data().unwrap();
+        assert_eq!(
+            net_flow.total_inflow,
+            "231584178474632390847141970017375815706539969331281128078915168015826259279870"
+        );
+        assert_eq!(net_flow.net_flow, net_flow.total_inflow);
+    }
+
     #[test]
     fn test_transfer_detector_creation() {
         let detector = TransferDetector::new();
@@ -299,6 +566,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_internal_transfer_classification() {
+        let binance_a = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+        let binance_b = "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245";
+
+        let detector = TransferDetector::new().with_classify_internal_transfers(true);
+        assert_eq!(detector.classify_transfer(binance_a, binance_b), TransferDirection::Internal);
+        assert_eq!(detector.classify_transfer(binance_b, binance_a), TransferDirection::Internal);
+        assert_eq!(
+            detector.classify_transfer(binance_a, &binance_a.to_lowercase()),
+            TransferDirection::NotRelevant
+        );
+    }
+
+    #[test]
+    fn test_self_transfer_classification() {
+        let binance_addr = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+        let other_addr = "0x1234567890123456789012345678901234567890";
+
+        // Dropped by default, regardless of address casing
+        let detector = TransferDetector::new();
+        assert_eq!(
+            detector.classify_transfer(binance_addr, &binance_addr.to_lowercase()),
+            TransferDirection::NotRelevant
+        );
+
+        // Kept when the flag is off, but never as a flow
+        let detector = TransferDetector::new().with_drop_self_transfers(false);
+        assert_eq!(
+            detector.classify_transfer(binance_addr, &binance_addr.to_lowercase()),
+            TransferDirection::NotRelevant
+        );
+        assert_eq!(detector.classify_transfer(other_addr, other_addr), TransferDirection::NotRelevant);
+        assert_eq!(detector.classify_transfer(binance_addr, other_addr), TransferDirection::FromBinance);
+
+        // Stored as internal when internal transfers are classified
+        let detector = TransferDetector::new()
+            .with_drop_self_transfers(false)
+            .with_classify_internal_transfers(true);
+        assert_eq!(
+            detector.classify_transfer(binance_addr, &binance_addr.to_lowercase()),
+            TransferDirection::Internal
+        );
+        assert_eq!(detector.classify_transfer(other_addr, other_addr), TransferDirection::NotRelevant);
+    }
+
+    #[test]
+    fn test_kept_self_transfer_leaves_net_flow_unchanged() {
+        let detector = TransferDetector::new()
+            .with_drop_self_transfers(false)
+            .with_classify_internal_transfers(true);
+        let watched_topic = "0x000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec";
+        let log = RawLog {
+            address: POL_TOKEN_ADDRESS.to_string(),
+            topics: vec![TRANSFER_EVENT_SIGNATURE.to_string(), watched_topic.to_string(), watched_topic.to_string()],
+            data: "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000".to_string(),
+            block_number: 12345,
+            transaction_hash: "0xabc123".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            removed: false,
+        };
+
+        let transfer = detector.decode_transfer_log(&log).unwrap();
+        assert_eq!(transfer.direction, TransferDirection::Internal);
+
+        let database = crate::database::Database::new_in_memory().unwrap();
+        database.store_transfer_and_update_net_flow(&transfer).unwrap();
+
+        let net_flow = database.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "0");
+        assert_eq!(net_flow.total_outflow, "0");
+        assert_eq!(net_flow.net_flow, "0");
+    }
+
+    #[test]
+    fn test_classification_trace_explains_not_relevant_transfers() {
+        crate::testing::capture_logs();
+        let sender = "0x7a11ce0000000000000000000000000000000197";
+        let recipient = "0xB0B0000000000000000000000000000000000197";
+
+        let quiet = TransferDetector::new();
+        assert_eq!(quiet.classify_transfer(sender, "0xc0ffee0000000000000000000000000000000197"), TransferDirection::NotRelevant);
+        assert!(crate::testing::captured_logs(log::Level::Debug, "0xc0ffee0000000000000000000000000000000197").is_empty());
+
+        let detector = TransferDetector::new().with_classification_trace(true);
+        assert_eq!(detector.classify_transfer(sender, recipient), TransferDirection::NotRelevant);
+
+        let lines = crate::testing::captured_logs(log::Level::Debug, "0x7a11ce0000000000000000000000000000000197");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(
+            lines[0],
+            "Classified transfer from 0x7a11ce0000000000000000000000000000000197 (watched: false) \
+             to 0xb0b0000000000000000000000000000000000197 (watched: false) as NotRelevant: neither address is watched"
+        );
+    }
+
     #[test]
     fn test_is_pol_transfer() {
         let detector = TransferDetector::new();
@@ -315,6 +679,8 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         };
         
         assert!(detector.is_pol_transfer(&pol_log));
@@ -327,6 +693,8 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         };
         
         assert!(!detector.is_pol_transfer(&other_log));
@@ -339,6 +707,8 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         };
         
         assert!(!detector.is_pol_transfer(&wrong_event_log));
@@ -359,6 +729,8 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123def456".to_string(),
             log_index: 2,
+            transaction_index: 0,
+            removed: false,
         };
         
         let result = detector.decode_transfer_log(&log).unwrap();
@@ -384,6 +756,8 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         };
         
         assert!(detector.decode_transfer_log(&invalid_log).is_err());
@@ -400,8 +774,82 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         };
         
         assert!(detector.decode_transfer_log(&wrong_contract_log).is_err());
     }
-}
\ No newline at end of file
+
+    /// Test decoder for a bridge `Deposit(address indexed user, uint256 amount)` event,
+    /// treating the deposit as a transfer from the bridge to the user
+    struct BridgeDepositDecoder;
+
+    const BRIDGE_ADDRESS: &str = "0x0000000000000000000000000000000000001001";
+    const DEPOSIT_EVENT_SIGNATURE: &str = "0x5548c837ab068cf56a2c2479df0882a4922fd203edb7517321831d95078c5f62";
+
+    impl EventDecoder for BridgeDepositDecoder {
+        fn decode(&self, log: &RawLog, detector: &TransferDetector) -> Result<ProcessedTransfer, TransferDetectionError> {
+            let user = detector.address_from_topic(log.topics.get(1).ok_or_else(|| {
+                TransferDetectionError::InvalidLog("Deposit event is missing the user topic".to_string())
+            })?)?;
+            let from_address = normalize_address(BRIDGE_ADDRESS);
+
+            Ok(ProcessedTransfer {
+                block_number: log.block_number,
+                transaction_hash: log.transaction_hash.clone(),
+                log_index: log.log_index,
+                transaction_index: log.transaction_index,
+                direction: detector.classify_transfer(&from_address, &user),
+                from_address,
+                to_address: user,
+                amount: extract_amount_from_data(&log.data)?,
+                timestamp: 0,
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_event_decoder() {
+        let detector = TransferDetector::new().with_event_decoder(DEPOSIT_EVENT_SIGNATURE, BridgeDepositDecoder);
+        assert_eq!(
+            detector.event_signatures(),
+            vec![DEPOSIT_EVENT_SIGNATURE.to_string(), TRANSFER_EVENT_SIGNATURE.to_string()]
+        );
+
+        let deposit_log = RawLog {
+            address: POL_TOKEN_ADDRESS.to_string(),
+            topics: vec![
+                DEPOSIT_EVENT_SIGNATURE.to_uppercase().replace("0X", "0x"),
+                "0x000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            ],
+            data: "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000".to_string(),
+            block_number: 12345,
+            transaction_hash: "0xdeposit".to_string(),
+            log_index: 1,
+            transaction_index: 0,
+            removed: false,
+        };
+
+        assert!(detector.is_pol_transfer(&deposit_log));
+        let transfer = detector.decode_transfer_log(&deposit_log).unwrap();
+        assert_eq!(transfer.from_address, "0000000000000000000000000000000000001001");
+        assert_eq!(transfer.to_address, "f977814e90da44bfa03b6295a0616a897441acec");
+        assert_eq!(transfer.amount, "1000000000000000000");
+        assert_eq!(transfer.direction, TransferDirection::ToBinance);
+
+        // The default ERC-20 Transfer decoder is still registered
+        let transfer_log = RawLog {
+            topics: vec![
+                TRANSFER_EVENT_SIGNATURE.to_string(),
+                "0x000000000000000000000000f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                "0x0000000000000000000000001234567890123456789012345678901234567890".to_string(),
+            ],
+            ..deposit_log.clone()
+        };
+        assert_eq!(detector.decode_transfer_log(&transfer_log).unwrap().direction, TransferDirection::FromBinance);
+
+        // Without registration the deposit event is ignored
+        assert!(!TransferDetector::new().is_pol_transfer(&deposit_log));
+    }
+}
@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+/// Crate version from Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit the binary was built from, or "unknown"
+pub const GIT_COMMIT: &str = env!("GIT_COMMIT");
+
+/// Version string shown by `--version`, e.g. "0.1.0 (1a2b3c4d5e6f)"
+pub const LONG_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("GIT_COMMIT"), ")");
+
+/// Build information identifying the running binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+}
+
+impl BuildInfo {
+    /// Build information of the current binary
+    pub fn current() -> Self {
+        Self {
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+use rusqlite::{Connection, Result};
+
+use crate::models::DecimalAmount;
+
+/// Name of the exact decimal sum aggregate registered on every connection
+pub const DECIMAL_SUM: &str = "decimal_sum";
+
+/// Register the custom SQL functions used by queries on `conn`
+pub fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_aggregate_function(
+        DECIMAL_SUM,
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        DecimalSum,
+    )
+}
+
+/// `decimal_sum(amount)`: exact sum of decimal TEXT amounts, returned as TEXT
+///
+/// SQLite's built-in `SUM` works on 64-bit integers or doubles, which cannot hold
+/// wei amounts above ~9.2e18 exactly. NULL inputs are ignored and an empty input
+/// sums to "0".
+struct DecimalSum;
+
+impl Aggregate<DecimalAmount, String> for DecimalSum {
+    fn init(&self, _: &mut Context<'_>) -> Result<DecimalAmount> {
+        Ok(DecimalAmount::zero())
+    }
+
+    fn step(&self, ctx: &mut Context<'_>, total: &mut DecimalAmount) -> Result<()> {
+        if let Some(amount) = ctx.get::<Option<String>>(0)? {
+            let amount: DecimalAmount = amount
+                .parse()
+                .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))?;
+            *total = total.add(&amount);
+        }
+        Ok(())
+    }
+
+    fn finalize(&self, _: &mut Context<'_>, total: Option<DecimalAmount>) -> Result<String> {
+        Ok(total.unwrap_or_else(DecimalAmount::zero).to_string())
+    }
+}
@@ -1,8 +1,15 @@
+mod functions;
 pub mod operations;
+pub mod pool;
+pub mod query;
 pub mod schema;
 
 #[cfg(test)]
 mod tests;
 
-pub use operations::{Database, DbError, TransactionRow, NetFlowRow};
-pub use schema::{initialize_schema, run_migrations};
\ No newline at end of file
+pub use operations::{
+    BackfillStateRow, Database, DbError, DbStats, FlowSeriesPoint, TransactionRow, NetFlowAudit, NetFlowRow, NetFlowSnapshotRow, RebuildSummary, SkippedBlockRow,
+    MAX_FLOW_SERIES_BUCKETS, TRANSACTION_STREAM_POLL_INTERVAL,
+};
+pub use query::TransactionFilter;
+pub use schema::{initialize_schema, run_migrations, SynchronousMode};
\ No newline at end of file
@@ -1,7 +1,21 @@
-use rusqlite::{Connection, params};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params, params_from_iter};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
-use crate::database::schema::{initialize_schema, run_migrations};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use crate::blockchain::normalize_address;
+use crate::database::functions::register_functions;
+use crate::database::pool::{ReadPool, DEFAULT_CHECKOUT_TIMEOUT, DEFAULT_READ_POOL_SIZE};
+use crate::database::query::{QueryBuilder, TransactionFilter};
+use crate::database::schema::{
+    amount_sort_key, backfill_amount_keys, backfill_net_flow_keys, get_metadata, get_synchronous, initialize_schema,
+    net_flow_sort_key, recorded_schema_version, run_migrations, set_metadata,
+    set_synchronous, SynchronousMode, AMOUNT_STORAGE_KEY, FINALIZED_BLOCK_KEY, SCHEMA_VERSION, SEED_INFLOW_KEY,
+    SEED_OUTFLOW_KEY,
+};
+use crate::models::AmountStorage;
 
 #[derive(Error, Debug)]
 pub enum DbError {
@@ -11,40 +25,183 @@ pub enum DbError {
     Operation(String),
     #[error("Transaction not found")]
     NotFound,
+    #[error("No database connection became free within {timeout_ms}ms")]
+    PoolExhausted { timeout_ms: u64 },
 }
 
+/// How often [`Database::stream_transactions_since`] polls for new rows
+pub const TRANSACTION_STREAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Rows read per poll by [`Database::stream_transactions_since`]
+const TRANSACTION_STREAM_BATCH_SIZE: u32 = 500;
+
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    amount_storage: AmountStorage,
+    /// Read-only connections for long scans, `None` for in-memory databases
+    read_pool: Option<ReadPool>,
 }
 
 impl Database {
     /// Create a new database connection and initialize schema
+    ///
+    /// Uses the amount storage mode recorded in the database, or wei for a new database.
     pub fn new(db_path: &str) -> Result<Self, DbError> {
-        let conn = Connection::open(db_path)?;
-        
-        // Initialize schema
-        initialize_schema(&conn)?;
-        run_migrations(&conn)?;
-        
+        Self::open(Connection::open(db_path)?, None)
+    }
+
+    /// Create a new database connection that stores amounts in the given mode
+    ///
+    /// Fails if the database already holds amounts stored in a different mode.
+    pub fn new_with_amount_storage(db_path: &str, amount_storage: AmountStorage) -> Result<Self, DbError> {
+        Self::open(Connection::open(db_path)?, Some(amount_storage))
+    }
+
+    /// Open an existing database read-only, without creating or migrating anything
+    ///
+    /// Fails if the indexer has not migrated the database to the current
+    /// [`SCHEMA_VERSION`](crate::database::schema::SCHEMA_VERSION), or if it
+    /// stores amounts in a mode other than `amount_storage`. Every write through
+    /// the returned database fails.
+    pub fn open_read_only(db_path: &str, amount_storage: AmountStorage) -> Result<Self, DbError> {
+        let conn = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        match recorded_schema_version(&conn)? {
+            Some(SCHEMA_VERSION) => {}
+            recorded => {
+                return Err(DbError::Operation(format!(
+                    "Database schema is at version {}, expected {}; run the indexer once to migrate it",
+                    recorded.map_or_else(|| "none".to_string(), |version| version.to_string()),
+                    SCHEMA_VERSION
+                )))
+            }
+        }
+        register_functions(&conn)?;
+
+        let amount_storage = Self::resolve_amount_storage(&conn, Some(amount_storage), false)?;
+        let path = conn.path().filter(|path| !path.is_empty()).map(str::to_string);
+
         Ok(Database {
             conn: Arc::new(Mutex::new(conn)),
+            amount_storage,
+            read_pool: path.map(|path| ReadPool::new(path, DEFAULT_READ_POOL_SIZE, DEFAULT_CHECKOUT_TIMEOUT)),
         })
     }
 
     /// Create an in-memory database for testing
     pub fn new_in_memory() -> Result<Self, DbError> {
-        let conn = Connection::open_in_memory()?;
-        
+        Self::open(Connection::open_in_memory()?, None)
+    }
+
+    /// Create an in-memory database storing amounts in the given mode
+    pub fn new_in_memory_with_amount_storage(amount_storage: AmountStorage) -> Result<Self, DbError> {
+        Self::open(Connection::open_in_memory()?, Some(amount_storage))
+    }
+
+    fn open(conn: Connection, requested_storage: Option<AmountStorage>) -> Result<Self, DbError> {
         // Initialize schema
         initialize_schema(&conn)?;
         run_migrations(&conn)?;
-        
+        register_functions(&conn)?;
+
+        let amount_storage = Self::resolve_amount_storage(&conn, requested_storage, true)?;
+        backfill_amount_keys(&conn, amount_storage)?;
+        backfill_net_flow_keys(&conn, amount_storage)?;
+        let path = conn.path().filter(|path| !path.is_empty()).map(str::to_string);
+
         Ok(Database {
             conn: Arc::new(Mutex::new(conn)),
+            amount_storage,
+            read_pool: path.map(|path| ReadPool::new(path, DEFAULT_READ_POOL_SIZE, DEFAULT_CHECKOUT_TIMEOUT)),
         })
     }
 
+    /// Set `PRAGMA synchronous` on the write connection
+    pub fn set_synchronous(&self, mode: SynchronousMode) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        set_synchronous(&conn, mode)?;
+        Ok(())
+    }
+
+    /// Current `PRAGMA synchronous` of the write connection, `None` for `EXTRA`
+    pub fn synchronous(&self) -> Result<Option<SynchronousMode>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        Ok(get_synchronous(&conn)?)
+    }
+
+    /// Determine the amount storage mode, recording it in schema metadata on first use when `record` is set
+    fn resolve_amount_storage(
+        conn: &Connection,
+        requested: Option<AmountStorage>,
+        record: bool,
+    ) -> Result<AmountStorage, DbError> {
+        let recorded = match get_metadata(conn, AMOUNT_STORAGE_KEY)? {
+            Some(value) => Some(value.parse::<AmountStorage>().map_err(DbError::Operation)?),
+            None => {
+                // Databases created before the mode was recorded always stored wei
+                let has_transactions: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM transactions)",
+                    [],
+                    |row| row.get(0),
+                )?;
+                has_transactions.then_some(AmountStorage::Wei)
+            }
+        };
+
+        match (recorded, requested) {
+            (Some(recorded), Some(requested)) if recorded != requested => Err(DbError::Operation(format!(
+                "Database stores amounts as {} but {} storage was requested",
+                recorded, requested
+            ))),
+            (recorded, requested) => {
+                let amount_storage = requested.or(recorded).unwrap_or_default();
+                if record && (requested.is_some() || recorded.is_some()) {
+                    set_metadata(conn, AMOUNT_STORAGE_KEY, amount_storage.as_str())?;
+                }
+                Ok(amount_storage)
+            }
+        }
+    }
+
+    /// Size the read-only connection pool and how long a checkout may wait
+    ///
+    /// A checkout that times out fails with [`DbError::PoolExhausted`]. Has no
+    /// effect on in-memory databases, which have no separate read connections.
+    pub fn with_read_pool(mut self, max_size: usize, checkout_timeout: std::time::Duration) -> Self {
+        if let Some(pool) = self.read_pool.take() {
+            self.read_pool = Some(ReadPool::new(pool.path(), max_size, checkout_timeout));
+        }
+        self
+    }
+
+    /// Mode in which transfer amounts are stored
+    pub fn amount_storage(&self) -> AmountStorage {
+        self.amount_storage
+    }
+
+    /// Validate a stored-representation amount before it is written, returning its canonical form
+    fn normalize_amount(&self, amount: &str) -> Result<String, DbError> {
+        self.amount_storage.normalize_stored(amount)
+            .map_err(|e| DbError::Operation(format!("Invalid transfer amount: {}", e)))
+    }
+
+    /// Convert a transfer's wei amount into the configured storage representation
+    ///
+    /// Malformed or negative amounts are rejected here, before anything is
+    /// written, rather than when the net flow is next calculated.
+    fn stored_transfer_amount(&self, wei: &str) -> Result<String, DbError> {
+        let wei = AmountStorage::Wei.normalize_stored(wei)
+            .map_err(|e| DbError::Operation(format!("Invalid transfer amount: {}", e)))?;
+        self.amount_storage.wei_to_stored(&wei)
+            .map_err(|e| DbError::Operation(format!("Failed to convert transfer amount: {}", e)))
+    }
+
     /// Store a transaction in the database
+    ///
+    /// `amount` is in the configured storage representation; anything that is
+    /// not a non-negative amount in that representation is rejected.
     pub fn store_transaction(
         &self,
         block_number: u64,
@@ -56,12 +213,23 @@ impl Database {
         timestamp: u64,
         direction: &str,
     ) -> Result<(), DbError> {
+        let amount = &self.normalize_amount(amount)?;
         let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
         
         conn.execute(
-            "INSERT INTO transactions (block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction],
+            "INSERT INTO transactions (block_number, transaction_hash, log_index, from_address, to_address, amount, amount_u128, timestamp, direction)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                block_number,
+                transaction_hash,
+                log_index,
+                from_address,
+                to_address,
+                amount,
+                amount_sort_key(amount, self.amount_storage),
+                timestamp,
+                direction
+            ],
         )?;
         
         Ok(())
@@ -72,7 +240,7 @@ impl Database {
         let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
         
         let mut stmt = conn.prepare(
-            "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at
+            "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at, transaction_index
              FROM transactions WHERE transaction_hash = ?1 AND log_index = ?2"
         )?;
         
@@ -88,6 +256,7 @@ impl Database {
                 timestamp: row.get(7)?,
                 direction: row.get(8)?,
                 created_at: row.get(9)?,
+                transaction_index: row.get(10)?,
             })
         }).map_err(|e| match e {
             rusqlite::Error::QueryReturnedNoRows => DbError::NotFound,
@@ -98,35 +267,15 @@ impl Database {
     }
 
     /// Get transactions by block number
+    ///
+    /// Rows are ordered by `log_index`, with ties broken by insertion order
+    /// (`id`), so the same block always comes back in the same order.
     pub fn get_transactions_by_block(&self, block_number: u64) -> Result<Vec<TransactionRow>, DbError> {
-        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at
-             FROM transactions WHERE block_number = ?1 ORDER BY log_index"
-        )?;
-        
-        let rows = stmt.query_map(params![block_number], |row| {
-            Ok(TransactionRow {
-                id: row.get(0)?,
-                block_number: row.get(1)?,
-                transaction_hash: row.get(2)?,
-                log_index: row.get(3)?,
-                from_address: row.get(4)?,
-                to_address: row.get(5)?,
-                amount: row.get(6)?,
-                timestamp: row.get(7)?,
-                direction: row.get(8)?,
-                created_at: row.get(9)?,
-            })
-        })?;
-        
-        let mut transactions = Vec::new();
-        for row in rows {
-            transactions.push(row?);
-        }
-        
-        Ok(transactions)
+        self.run_transaction_query(
+            QueryBuilder::new(TRANSACTIONS_SELECT)
+                .and_where("block_number = ?", [Value::Integer(block_number as i64)])
+                .order_by("log_index, id"),
+        )
     }
 
     /// Update a transaction (for testing purposes)
@@ -186,6 +335,56 @@ impl Database {
         Ok(())
     }
 
+    /// Record a new backfill of `from_block..=to_block`, replacing any earlier one
+    pub fn start_backfill(&self, from_block: u64, to_block: u64) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO backfill_state (id, from_block, to_block, last_completed_block, updated_at)
+             VALUES (1, ?1, ?2, NULL, strftime('%s', 'now'))",
+            params![from_block, to_block],
+        )?;
+
+        Ok(())
+    }
+
+    /// Record that the current backfill has written or skipped every block through `block_number`
+    ///
+    /// Does nothing when no backfill has been started. The live last processed
+    /// block is not touched.
+    pub fn set_backfill_progress(&self, block_number: u64) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        conn.execute(
+            "UPDATE backfill_state SET last_completed_block = ?1, updated_at = strftime('%s', 'now') WHERE id = 1",
+            params![block_number],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the range and progress of the latest backfill, if one was ever started
+    pub fn get_backfill_state(&self) -> Result<Option<BackfillStateRow>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let state = conn
+            .query_row(
+                "SELECT from_block, to_block, last_completed_block, updated_at FROM backfill_state WHERE id = 1",
+                [],
+                |row| {
+                    Ok(BackfillStateRow {
+                        from_block: row.get(0)?,
+                        to_block: row.get(1)?,
+                        last_completed_block: row.get(2)?,
+                        updated_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(state)
+    }
+
     /// Get current net flow data
     pub fn get_net_flow_data(&self) -> Result<NetFlowRow, DbError> {
         let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
@@ -209,29 +408,938 @@ impl Database {
         Ok(row)
     }
 
-    /// Get transaction count
-    pub fn get_transaction_count(&self) -> Result<u64, DbError> {
-        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
-        
-        let count: u64 = conn.query_row(
-            "SELECT COUNT(*) FROM transactions",
-            [],
-            |row| row.get(0),
-        )?;
-        
-        Ok(count)
+    /// Calculate the net flow of transactions within an inclusive block range
+    /// Unlike `get_net_flow_data`, the totals only cover the given blocks
+    pub fn net_flow_between(&self, from_block: u64, to_block: u64) -> Result<NetFlowRow, DbError> {
+        if from_block > to_block {
+            return Err(DbError::Operation(format!(
+                "Invalid block range: from_block {} is greater than to_block {}",
+                from_block, to_block
+            )));
+        }
+
+        self.with_read_connection(|conn| {
+            let (total_inflow, total_outflow) = Self::sum_transaction_flows(
+                conn,
+                "block_number BETWEEN ?1 AND ?2",
+                params![from_block, to_block],
+            )?;
+
+            let last_updated: u64 = conn.query_row(
+                "SELECT last_updated FROM net_flows WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )?;
+
+            Ok(NetFlowRow {
+                id: 1,
+                net_flow: total_inflow.sub(&total_outflow).to_string(),
+                total_inflow: total_inflow.to_string(),
+                total_outflow: total_outflow.to_string(),
+                last_processed_block: to_block,
+                last_updated,
+            })
+        })
+    }
+
+    /// Calculate net-flow totals over transfers whose block timestamp falls within
+    /// the last `duration_seconds` before `now_ts` (inclusive)
+    ///
+    /// Windows are based on block timestamps, not on when the indexer stored the transfer.
+    pub fn net_flow_last(&self, duration_seconds: u64, now_ts: u64) -> Result<NetFlowRow, DbError> {
+        let since = now_ts.saturating_sub(duration_seconds);
+
+        self.with_read_connection(|conn| {
+            let (total_inflow, total_outflow) = Self::sum_transaction_flows(
+                conn,
+                "timestamp >= ?1 AND timestamp <= ?2",
+                params![since, now_ts],
+            )?;
+
+            let (last_processed_block, last_updated): (u64, u64) = conn.query_row(
+                "SELECT last_processed_block, last_updated FROM net_flows WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+
+            Ok(NetFlowRow {
+                id: 1,
+                net_flow: total_inflow.sub(&total_outflow).to_string(),
+                total_inflow: total_inflow.to_string(),
+                total_outflow: total_outflow.to_string(),
+                last_processed_block,
+                last_updated,
+            })
+        })
+    }
+
+    /// Cumulative inflow and outflow at the end of each `interval_seconds` bucket of block time
+    ///
+    /// Buckets are aligned to multiples of `interval_seconds`, keyed by their start,
+    /// and cover `from_ts..=to_ts`. Each total includes every transfer up to the end
+    /// of its bucket, including those before `from_ts`, so both series never
+    /// decrease and empty buckets repeat the previous totals.
+    pub fn flow_series(&self, interval_seconds: u64, from_ts: u64, to_ts: u64) -> Result<Vec<FlowSeriesPoint>, DbError> {
+        if interval_seconds == 0 {
+            return Err(DbError::Operation("Flow series interval must be greater than 0".to_string()));
+        }
+        if from_ts > to_ts {
+            return Err(DbError::Operation(format!(
+                "Invalid time range: from {} is greater than to {}",
+                from_ts, to_ts
+            )));
+        }
+        let first_bucket = from_ts - from_ts % interval_seconds;
+        let last_bucket = to_ts - to_ts % interval_seconds;
+        let bucket_count = (last_bucket - first_bucket) / interval_seconds + 1;
+        if bucket_count > MAX_FLOW_SERIES_BUCKETS {
+            return Err(DbError::Operation(format!(
+                "Flow series spans {} buckets, more than the maximum of {}",
+                bucket_count, MAX_FLOW_SERIES_BUCKETS
+            )));
+        }
+
+        self.with_read_connection(|conn| {
+            // Baseline and buckets come from one snapshot
+            let tx = conn.unchecked_transaction()?;
+            let (mut inflow, mut outflow) =
+                Self::sum_transaction_flows(&tx, "timestamp < ?1", params![first_bucket])?;
+
+            let sql = format!(
+                "SELECT timestamp - timestamp % ?1 AS bucket,
+                        {sum}(CASE WHEN direction = 'inflow' THEN amount END),
+                        {sum}(CASE WHEN direction = 'outflow' THEN amount END)
+                 FROM transactions WHERE pending = 0 AND timestamp >= ?2 AND timestamp <= ?3
+                 GROUP BY bucket ORDER BY bucket",
+                sum = crate::database::functions::DECIMAL_SUM,
+            );
+            let last_timestamp = last_bucket.saturating_add(interval_seconds - 1);
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt.query_map(params![interval_seconds, first_bucket, last_timestamp], |row| {
+                Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+            let parse = |amount: &str| {
+                crate::models::NetFlowCalculator::parse_decimal(amount)
+                    .map_err(|e| DbError::Operation(format!("Flow series calculation failed: {}", e)))
+            };
+            let mut bucket_flows = std::collections::BTreeMap::new();
+            for row in rows {
+                let (bucket, bucket_inflow, bucket_outflow) = row?;
+                bucket_flows.insert(bucket, (parse(&bucket_inflow)?, parse(&bucket_outflow)?));
+            }
+            drop(stmt);
+            tx.finish()?;
+
+            let mut series = Vec::with_capacity(bucket_count as usize);
+            for index in 0..bucket_count {
+                let bucket = first_bucket + index * interval_seconds;
+                let (bucket_inflow, bucket_outflow) = bucket_flows
+                    .remove(&bucket)
+                    .unwrap_or_else(|| (crate::models::DecimalAmount::zero(), crate::models::DecimalAmount::zero()));
+                inflow = inflow.add(&bucket_inflow);
+                outflow = outflow.add(&bucket_outflow);
+                series.push(FlowSeriesPoint {
+                    bucket,
+                    inflow: bucket_inflow.to_string(),
+                    outflow: bucket_outflow.to_string(),
+                    cumulative_inflow: inflow.to_string(),
+                    cumulative_outflow: outflow.to_string(),
+                });
+            }
+
+            Ok(series)
+        })
+    }
+
+    /// Recompute cumulative net-flow totals from the transactions table
+    ///
+    /// A baseline recorded by [`seed_net_flow`](Self::seed_net_flow) is included.
+    /// The stored `net_flows` row is not modified. File databases are read through a
+    /// separate read-only connection, so under WAL the scan does not block the
+    /// monitor's writes; `last_processed_block` and `last_updated` come from the same
+    /// snapshot.
+    pub fn recompute_net_flow(&self) -> Result<NetFlowRow, DbError> {
+        self.with_read_connection(|conn| {
+            // Read totals and cursor from one snapshot
+            let tx = conn.unchecked_transaction()?;
+            let (total_inflow, total_outflow) = Self::sum_net_flow_totals(&tx)?;
+            let (last_processed_block, last_updated): (u64, u64) = tx.query_row(
+                "SELECT last_processed_block, last_updated FROM net_flows WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            tx.finish()?;
+
+            Ok(NetFlowRow {
+                id: 1,
+                net_flow: total_inflow.sub(&total_outflow).to_string(),
+                total_inflow: total_inflow.to_string(),
+                total_outflow: total_outflow.to_string(),
+                last_processed_block,
+                last_updated,
+            })
+        })
+    }
+
+    /// Compare the stored `net_flows` totals against a fresh sum of the transactions table
+    ///
+    /// Nothing is modified. Drift means the incremental updates diverged from the
+    /// stored transfers (e.g. a string-compared or lost batch update); every drift
+    /// in the returned [`NetFlowAudit`] is `"0"` when the two agree. A baseline
+    /// recorded by [`seed_net_flow`](Self::seed_net_flow) is not drift. Both sides
+    /// are read from one snapshot, like [`Self::recompute_net_flow`].
+    pub fn audit_net_flow(&self) -> Result<NetFlowAudit, DbError> {
+        self.with_read_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let (recomputed_inflow, recomputed_outflow) = Self::sum_transaction_flows(&tx, "1 = 1", &[])?;
+            let (seed_inflow, seed_outflow) = Self::seed_baseline(&tx)?;
+            let (stored_inflow, stored_outflow): (String, String) = tx.query_row(
+                "SELECT total_inflow, total_outflow FROM net_flows WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            tx.finish()?;
+
+            let parse = |amount: &str| {
+                crate::models::NetFlowCalculator::parse_decimal(amount)
+                    .map_err(|e| DbError::Operation(format!("Net flow audit failed: {}", e)))
+            };
+            let inflow_drift = parse(&stored_inflow)?.sub(&seed_inflow).sub(&recomputed_inflow);
+            let outflow_drift = parse(&stored_outflow)?.sub(&seed_outflow).sub(&recomputed_outflow);
+
+            Ok(NetFlowAudit {
+                net_flow_drift: inflow_drift.sub(&outflow_drift).to_string(),
+                inflow_drift: inflow_drift.to_string(),
+                outflow_drift: outflow_drift.to_string(),
+                stored_inflow,
+                stored_outflow,
+                recomputed_inflow: recomputed_inflow.to_string(),
+                recomputed_outflow: recomputed_outflow.to_string(),
+            })
+        })
+    }
+
+    /// Distinct counterparties of `watched_address` with their summed amounts in wei
+    ///
+    /// For `"inflow"` the counterparties are the senders of transfers into the watched
+    /// address; for `"outflow"` they are the recipients of transfers out of it. Results
+    /// are ordered by total amount, largest first, and capped at `limit`. Pending
+    /// transfers are left out, as they are left out of `net_flows`.
+    pub fn counterparties(&self, watched_address: &str, direction: &str, limit: u32) -> Result<Vec<(String, String)>, DbError> {
+        let (watched_column, counterparty_column) = match direction {
+            "inflow" => ("to_address", "from_address"),
+            "outflow" => ("from_address", "to_address"),
+            other => {
+                return Err(DbError::Operation(format!(
+                    "Invalid direction '{}': expected 'inflow' or 'outflow'",
+                    other
+                )))
+            }
+        };
+
+        // Stored addresses are bare lowercase hex
+        let watched_address = normalize_address(watched_address);
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let sql = format!(
+            "SELECT {counterparty}, {sum}(amount) FROM transactions
+             WHERE direction = ?1 AND {watched} = ?2 AND pending = 0
+             GROUP BY {counterparty}",
+            counterparty = counterparty_column,
+            watched = watched_column,
+            sum = crate::database::functions::DECIMAL_SUM,
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![direction, watched_address], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut totals = Vec::new();
+        for row in rows {
+            let (address, total) = row?;
+            let total = crate::models::NetFlowCalculator::parse_decimal(&total)
+                .map_err(|e| DbError::Operation(format!("Counterparty sum failed: {}", e)))?;
+            totals.push((address, total));
+        }
+
+        totals.sort_by(|(a_address, a_total), (b_address, b_total)| {
+            b_total.cmp(a_total).then_with(|| a_address.cmp(b_address))
+        });
+        totals.truncate(limit as usize);
+
+        totals
+            .into_iter()
+            .map(|(address, total)| {
+                let wei = self.amount_storage.stored_to_wei(&total.to_string())
+                    .map_err(|e| DbError::Operation(format!("Failed to convert counterparty amount: {}", e)))?;
+                Ok((address, wei))
+            })
+            .collect()
+    }
+
+    /// Run a read-only query on a pooled connection when the database is a file
+    ///
+    /// In-memory databases cannot be shared between connections, so they fall back
+    /// to the shared connection.
+    fn with_read_connection<T>(&self, f: impl FnOnce(&Connection) -> Result<T, DbError>) -> Result<T, DbError> {
+        match &self.read_pool {
+            Some(pool) => {
+                let conn = pool.checkout()?;
+                f(&conn)
+            }
+            None => {
+                let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+                f(&conn)
+            }
+        }
+    }
+
+    /// Sum inflow and outflow amounts exactly over the counted transactions matching the SQL `filter`
+    ///
+    /// Pending transfers, still inside the confirmation window, are left out like
+    /// they are left out of `net_flows`.
+    fn sum_transaction_flows(
+        conn: &Connection,
+        filter: &str,
+        query_params: &[&dyn rusqlite::ToSql],
+    ) -> Result<(crate::models::DecimalAmount, crate::models::DecimalAmount), DbError> {
+        Self::sum_flows(conn, &format!("pending = 0 AND ({})", filter), query_params)
+    }
+
+    /// Cumulative inflow and outflow as `net_flows` should hold them: every counted
+    /// transaction plus the baseline recorded by [`seed_net_flow`](Self::seed_net_flow)
+    fn sum_net_flow_totals(
+        conn: &Connection,
+    ) -> Result<(crate::models::DecimalAmount, crate::models::DecimalAmount), DbError> {
+        let (seed_inflow, seed_outflow) = Self::seed_baseline(conn)?;
+        let (total_inflow, total_outflow) = Self::sum_transaction_flows(conn, "1 = 1", &[])?;
+        Ok((total_inflow.add(&seed_inflow), total_outflow.add(&seed_outflow)))
+    }
+
+    /// Inflow and outflow seeded beyond the counted transactions, zero if never seeded
+    fn seed_baseline(conn: &Connection) -> Result<(crate::models::DecimalAmount, crate::models::DecimalAmount), DbError> {
+        let read = |key| -> Result<crate::models::DecimalAmount, DbError> {
+            match get_metadata(conn, key)? {
+                Some(amount) => crate::models::NetFlowCalculator::parse_decimal(&amount)
+                    .map_err(|e| DbError::Operation(format!("Invalid recorded seed {}: {}", key, e))),
+                None => Ok(crate::models::DecimalAmount::zero()),
+            }
+        };
+        Ok((read(SEED_INFLOW_KEY)?, read(SEED_OUTFLOW_KEY)?))
+    }
+
+    /// Sum inflow and outflow amounts exactly over the transactions matching the SQL `filter`
+    ///
+    /// Both totals come from a single aggregate query evaluated inside SQLite with
+    /// the exact `decimal_sum` function, so no rows are loaded into memory no matter
+    /// how many transactions match.
+    fn sum_flows(
+        conn: &Connection,
+        filter: &str,
+        query_params: &[&dyn rusqlite::ToSql],
+    ) -> Result<(crate::models::DecimalAmount, crate::models::DecimalAmount), DbError> {
+        let sql = format!(
+            "SELECT {sum}(CASE WHEN direction = 'inflow' THEN amount END),
+                    {sum}(CASE WHEN direction = 'outflow' THEN amount END)
+             FROM transactions WHERE {filter}",
+            sum = crate::database::functions::DECIMAL_SUM,
+            filter = filter,
+        );
+
+        let (total_inflow, total_outflow): (String, String) = conn.query_row(&sql, query_params, |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+
+        let parse = |amount: &str| {
+            crate::models::NetFlowCalculator::parse_decimal(amount)
+                .map_err(|e| DbError::Operation(format!("Net flow calculation failed: {}", e)))
+        };
+        Ok((parse(&total_inflow)?, parse(&total_outflow)?))
+    }
+
+    /// Seed the cumulative net-flow totals from a known baseline
+    /// Only permitted while the net_flows row is still zero-initialized
+    ///
+    /// The amounts are whole wei, whatever the amount storage mode; a decimal-mode
+    /// database stores them converted to POL like transfer amounts. What the seeded
+    /// totals hold beyond the transactions already counted is recorded as the
+    /// baseline, so totals recomputed from the transactions keep it.
+    pub fn seed_net_flow(&self, total_inflow: &str, total_outflow: &str, last_processed_block: u64) -> Result<(), DbError> {
+        self.seed_net_flow_with_force(total_inflow, total_outflow, last_processed_block, false)
+    }
+
+    /// Seed the cumulative net-flow totals, optionally overwriting existing non-zero data
+    pub fn seed_net_flow_with_force(
+        &self,
+        total_inflow: &str,
+        total_outflow: &str,
+        last_processed_block: u64,
+        force: bool,
+    ) -> Result<(), DbError> {
+        // Validate amounts and derive the net flow before touching the database
+        let normalize = |amount: &str| {
+            AmountStorage::Wei
+                .normalize_stored(amount)
+                .and_then(|wei| self.amount_storage.wei_to_stored(&wei))
+                .map_err(|e| DbError::Operation(format!("Invalid seed amount: {}", e)))
+        };
+        let total_inflow = normalize(total_inflow)?;
+        let total_outflow = normalize(total_outflow)?;
+        let net_flow = crate::models::NetFlowCalculator::calculate_net(&total_inflow, &total_outflow)
+            .map_err(|e| DbError::Operation(format!("Invalid seed amount: {}", e)))?;
+
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let tx = conn.unchecked_transaction()?;
+
+        if !force {
+            let (current_inflow, current_outflow, current_block): (String, String, u64) = tx.query_row(
+                "SELECT total_inflow, total_outflow, last_processed_block FROM net_flows WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+
+            let is_zero = |amount: &str| {
+                crate::models::NetFlowCalculator::parse_decimal(amount).is_ok_and(|amount| amount.is_zero())
+            };
+            if !is_zero(&current_inflow) || !is_zero(&current_outflow) || current_block != 0 {
+                return Err(DbError::Operation(
+                    "Refusing to seed net flow over existing non-zero data (use force to overwrite)".to_string()
+                ));
+            }
+        }
+
+        tx.execute(
+            "UPDATE net_flows SET total_inflow = ?1, total_outflow = ?2, net_flow = ?3, last_processed_block = ?4,
+             last_updated = strftime('%s', 'now') WHERE id = 1",
+            params![total_inflow, total_outflow, net_flow, last_processed_block],
+        )?;
+
+        let (counted_inflow, counted_outflow) = Self::sum_transaction_flows(&tx, "1 = 1", &[])?;
+        let parse = |amount: &str| {
+            crate::models::NetFlowCalculator::parse_decimal(amount)
+                .map_err(|e| DbError::Operation(format!("Invalid seed amount: {}", e)))
+        };
+        set_metadata(&tx, SEED_INFLOW_KEY, &parse(&total_inflow)?.sub(&counted_inflow).to_string())?;
+        set_metadata(&tx, SEED_OUTFLOW_KEY, &parse(&total_outflow)?.sub(&counted_outflow).to_string())?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record a block that was skipped after failing to process
+    /// Re-recording an already skipped block updates its error and increments its skip count
+    pub fn record_skipped_block(&self, block_number: u64, error: &str) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        conn.execute(
+            "INSERT INTO skipped_blocks (block_number, error) VALUES (?1, ?2)
+             ON CONFLICT(block_number) DO UPDATE SET
+                error = excluded.error,
+                skip_count = skip_count + 1,
+                reprocess_requested = 0,
+                skipped_at = strftime('%s', 'now')",
+            params![block_number, error],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get all skipped blocks ordered by block number
+    pub fn get_skipped_blocks(&self) -> Result<Vec<SkippedBlockRow>, DbError> {
+        self.query_skipped_blocks("SELECT block_number, error, skip_count, reprocess_requested, skipped_at
+             FROM skipped_blocks ORDER BY block_number")
+    }
+
+    /// Get skipped blocks that have been queued for reprocessing
+    pub fn get_reprocess_requested_blocks(&self) -> Result<Vec<SkippedBlockRow>, DbError> {
+        self.query_skipped_blocks("SELECT block_number, error, skip_count, reprocess_requested, skipped_at
+             FROM skipped_blocks WHERE reprocess_requested = 1 ORDER BY block_number")
+    }
+
+    fn query_skipped_blocks(&self, sql: &str) -> Result<Vec<SkippedBlockRow>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SkippedBlockRow {
+                block_number: row.get(0)?,
+                error: row.get(1)?,
+                skip_count: row.get(2)?,
+                reprocess_requested: row.get(3)?,
+                skipped_at: row.get(4)?,
+            })
+        })?;
+
+        let mut skipped_blocks = Vec::new();
+        for row in rows {
+            skipped_blocks.push(row?);
+        }
+
+        Ok(skipped_blocks)
+    }
+
+    /// Get the number of skipped blocks awaiting successful reprocessing
+    pub fn get_skipped_block_count(&self) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let count: u64 = conn.query_row("SELECT COUNT(*) FROM skipped_blocks", [], |row| row.get(0))?;
+
+        Ok(count)
+    }
+
+    /// Queue skipped blocks for reprocessing, either a single block or all of them
+    /// Returns the number of blocks queued
+    pub fn request_skipped_block_reprocess(&self, block_number: Option<u64>) -> Result<usize, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let queued = match block_number {
+            Some(block_number) => conn.execute(
+                "UPDATE skipped_blocks SET reprocess_requested = 1 WHERE block_number = ?1",
+                params![block_number],
+            )?,
+            None => conn.execute("UPDATE skipped_blocks SET reprocess_requested = 1", [])?,
+        };
+
+        Ok(queued)
+    }
+
+    /// Remove a skipped block once it has been processed successfully
+    pub fn remove_skipped_block(&self, block_number: u64) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let rows_affected = conn.execute(
+            "DELETE FROM skipped_blocks WHERE block_number = ?1",
+            params![block_number],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(DbError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Record the hash of a processed block, replacing any previously stored hash
+    pub fn record_block_hash(&self, block_number: u64, block_hash: &str) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        conn.execute(
+            "INSERT INTO block_hashes (block_number, block_hash) VALUES (?1, ?2)
+             ON CONFLICT(block_number) DO UPDATE SET
+                block_hash = excluded.block_hash,
+                recorded_at = strftime('%s', 'now')",
+            params![block_number, block_hash],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the stored hash of a processed block, if one was recorded
+    pub fn get_block_hash(&self, block_number: u64) -> Result<Option<String>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let block_hash = conn
+            .query_row(
+                "SELECT block_hash FROM block_hashes WHERE block_number = ?1",
+                params![block_number],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(block_hash)
+    }
+
+    /// Record the current net-flow totals as the snapshot taken after `block_number`
+    ///
+    /// The net flow is also stored as a `net_flow_sort_key`, a TEXT key whose
+    /// order matches the signed value, so snapshots can be filtered by value from
+    /// an index.
+    pub fn record_net_flow_snapshot(&self, block_number: u64) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let (total_inflow, total_outflow, net_flow): (String, String, String) = conn.query_row(
+            "SELECT total_inflow, total_outflow, net_flow FROM net_flows WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        conn.execute(
+            "INSERT INTO net_flow_snapshots (block_number, total_inflow, total_outflow, net_flow, net_flow_sort_key)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                block_number,
+                total_inflow,
+                total_outflow,
+                net_flow,
+                net_flow_sort_key(&net_flow, self.amount_storage),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get up to `limit` snapshots whose net flow was below `max_net_flow_wei`, lowest first
+    ///
+    /// `get_net_flow_snapshots_below("0", ..)` finds the blocks after which more
+    /// POL had left Binance than entered it. Served from the `net_flow_sort_key` index.
+    pub fn get_net_flow_snapshots_below(&self, max_net_flow_wei: &str, limit: u32) -> Result<Vec<NetFlowSnapshotRow>, DbError> {
+        let max_key = net_flow_sort_key(max_net_flow_wei, AmountStorage::Wei)
+            .ok_or_else(|| DbError::Operation(format!("Invalid net flow: {}", max_net_flow_wei)))?;
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(NET_FLOW_SNAPSHOTS_BELOW_QUERY)?;
+        let rows = stmt.query_map(params![max_key, limit], |row| {
+            Ok(NetFlowSnapshotRow {
+                id: row.get(0)?,
+                block_number: row.get(1)?,
+                total_inflow: row.get(2)?,
+                total_outflow: row.get(3)?,
+                net_flow: row.get(4)?,
+                recorded_at: row.get(5)?,
+            })
+        })?;
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            snapshots.push(row?);
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Get up to `limit` net-flow snapshots in the order they were recorded
+    pub fn get_net_flow_snapshots(&self, limit: u32) -> Result<Vec<NetFlowSnapshotRow>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, block_number, total_inflow, total_outflow, net_flow, recorded_at
+             FROM net_flow_snapshots ORDER BY id LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(NetFlowSnapshotRow {
+                id: row.get(0)?,
+                block_number: row.get(1)?,
+                total_inflow: row.get(2)?,
+                total_outflow: row.get(3)?,
+                net_flow: row.get(4)?,
+                recorded_at: row.get(5)?,
+            })
+        })?;
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            snapshots.push(row?);
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Get stored block hashes within an inclusive block range, ordered by block number
+    pub fn get_block_hashes_in_range(&self, from_block: u64, to_block: u64) -> Result<Vec<(u64, String)>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT block_number, block_hash FROM block_hashes
+             WHERE block_number BETWEEN ?1 AND ?2 ORDER BY block_number",
+        )?;
+        let rows = stmt.query_map(params![from_block, to_block], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut block_hashes = Vec::new();
+        for row in rows {
+            block_hashes.push(row?);
+        }
+
+        Ok(block_hashes)
+    }
+
+    /// Refetch each stored block hash in the inclusive range from the chain and
+    /// return the block numbers whose stored hash no longer matches
+    ///
+    /// Blocks without a stored hash are not checked.
+    pub async fn verify_against_chain(
+        &self,
+        rpc: &crate::blockchain::RpcClient,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<u64>, crate::error::IndexerError> {
+        if from > to {
+            return Err(DbError::Operation(format!(
+                "Invalid block range: from ({}) is greater than to ({})",
+                from, to
+            )).into());
+        }
+
+        let mut mismatched = Vec::new();
+        for (block_number, stored_hash) in self.get_block_hashes_in_range(from, to)? {
+            let block = rpc.get_block_with_retry(block_number).await?;
+            if !block.hash.eq_ignore_ascii_case(&stored_hash) {
+                log::warn!(
+                    "Block {} hash mismatch: stored {}, chain {}",
+                    block_number, stored_hash, block.hash
+                );
+                mismatched.push(block_number);
+            }
+        }
+
+        Ok(mismatched)
+    }
+
+    /// Get transaction count
+    pub fn get_transaction_count(&self) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        
+        let count: u64 = conn.query_row(
+            "SELECT COUNT(*) FROM transactions",
+            [],
+            |row| row.get(0),
+        )?;
+        
+        Ok(count)
+    }
+
+    /// Number of stored transactions in an inclusive block range, pending ones included
+    ///
+    /// Answered from `idx_transactions_block` without reading the rows, so it is
+    /// cheap enough to run before backfilling or pruning a range.
+    pub fn count_transactions_in_range(&self, from_block: u64, to_block: u64) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let count: u64 = conn.query_row(
+            "SELECT COUNT(*) FROM transactions WHERE block_number BETWEEN ?1 AND ?2",
+            params![from_block, to_block],
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+
+    /// Lowest and highest block numbers with a stored transaction, `None` when there are none
+    pub fn block_range(&self) -> Result<Option<(u64, u64)>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let (min, max): (Option<u64>, Option<u64>) = conn.query_row(
+            "SELECT MIN(block_number), MAX(block_number) FROM transactions",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(min.zip(max))
+    }
+
+    /// Net flow, transaction counts and indexed block range read under a single lock
+    ///
+    /// Equivalent to calling `get_net_flow_data`, `get_transaction_count`,
+    /// `block_range` and `get_skipped_block_count`, but consistent with each other
+    /// and without taking the lock four times.
+    pub fn get_stats(&self) -> Result<DbStats, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let net_flow = conn.query_row(
+            "SELECT id, total_inflow, total_outflow, net_flow, last_processed_block, last_updated
+             FROM net_flows WHERE id = 1",
+            [],
+            |row| {
+                Ok(NetFlowRow {
+                    id: row.get(0)?,
+                    total_inflow: row.get(1)?,
+                    total_outflow: row.get(2)?,
+                    net_flow: row.get(3)?,
+                    last_processed_block: row.get(4)?,
+                    last_updated: row.get(5)?,
+                })
+            },
+        )?;
+
+        let (transaction_count, inflow_count, outflow_count, min, max, skipped_blocks): (
+            u64,
+            u64,
+            u64,
+            Option<u64>,
+            Option<u64>,
+            u64,
+        ) = conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(direction = 'inflow'), 0),
+                    COALESCE(SUM(direction = 'outflow'), 0),
+                    MIN(block_number),
+                    MAX(block_number),
+                    (SELECT COUNT(*) FROM skipped_blocks)
+             FROM transactions",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+        )?;
+
+        Ok(DbStats {
+            net_flow,
+            transaction_count,
+            inflow_count,
+            outflow_count,
+            block_range: min.zip(max),
+            skipped_blocks,
+        })
+    }
+
+    /// Get recent transactions with pagination
+    pub fn get_recent_transactions(&self, limit: u32, offset: u32) -> Result<Vec<TransactionRow>, DbError> {
+        self.run_transaction_query(
+            QueryBuilder::new(TRANSACTIONS_SELECT)
+                .order_by("created_at DESC, id DESC")
+                .limit(limit)
+                .offset(offset),
+        )
+    }
+
+    /// Get up to `limit` transactions with an id below `before_id`, newest first
+    ///
+    /// Keyset pagination: pass the id of the last row of a page as `before_id` for
+    /// the next one, which costs the same at any depth, unlike an offset. With
+    /// `direction` only transactions stored with that direction are returned.
+    pub fn recent_transactions_before_id(
+        &self,
+        before_id: i64,
+        direction: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, DbError> {
+        self.run_transaction_query(
+            QueryBuilder::new(TRANSACTIONS_SELECT)
+                .and_where("id < ?", [Value::Integer(before_id)])
+                .and_where_opt("direction = ?", direction.map(str::to_string))
+                .order_by("id DESC")
+                .limit(limit),
+        )
+    }
+
+    /// Get up to `limit` transactions with an id greater than `since_id`, oldest first
+    pub fn get_transactions_since(&self, since_id: i64, limit: u32) -> Result<Vec<TransactionRow>, DbError> {
+        self.run_transaction_query(
+            QueryBuilder::new(TRANSACTIONS_SELECT)
+                .and_where("id > ?", [Value::Integer(since_id)])
+                .order_by("id")
+                .limit(limit),
+        )
+    }
+
+    /// Stream every transaction with an id greater than `since_id`, then each new one as it is stored
+    ///
+    /// SQLite has no change feed, so a background task polls [`get_transactions_since`](Self::get_transactions_since)
+    /// every [`TRANSACTION_STREAM_POLL_INTERVAL`] and forwards the rows in id order. A failed poll is
+    /// yielded as an error and retried on the next one. The task stops once the stream is dropped.
+    /// Must be called from within a Tokio runtime.
+    pub fn stream_transactions_since(
+        self: &Arc<Self>,
+        since_id: i64,
+    ) -> impl Stream<Item = Result<TransactionRow, DbError>> {
+        let database = Arc::clone(self);
+        let (sender, receiver) = tokio::sync::mpsc::channel(TRANSACTION_STREAM_BATCH_SIZE as usize);
+        tokio::spawn(async move {
+            let mut last_id = since_id;
+            loop {
+                let batch_size = match database.get_transactions_since(last_id, TRANSACTION_STREAM_BATCH_SIZE) {
+                    Ok(rows) => {
+                        let batch_size = rows.len();
+                        for row in rows {
+                            last_id = row.id;
+                            if sender.send(Ok(row)).await.is_err() {
+                                return;
+                            }
+                        }
+                        batch_size
+                    }
+                    Err(e) => {
+                        if sender.send(Err(e)).await.is_err() {
+                            return;
+                        }
+                        0
+                    }
+                };
+                // A full batch means more rows are waiting, so only pause once caught up
+                if batch_size < TRANSACTION_STREAM_BATCH_SIZE as usize {
+                    tokio::select! {
+                        _ = tokio::time::sleep(TRANSACTION_STREAM_POLL_INTERVAL) => {}
+                        _ = sender.closed() => return,
+                    }
+                }
+            }
+        });
+        ReceiverStream::new(receiver)
+    }
+
+    /// Get up to `limit` transactions of at least `min_amount_wei`, largest first
+    ///
+    /// Served from the `amount_u128` index rather than parsing every amount.
+    /// Amounts above `u128::MAX` share one index key, so ties are ordered by their
+    /// full stored value, and for a minimum above `u128::MAX` the page is filtered
+    /// by the full value, so it may hold fewer than `limit` rows.
+    pub fn get_transactions_above(&self, min_amount_wei: &str, limit: u32) -> Result<Vec<TransactionRow>, DbError> {
+        let min_key = amount_sort_key(min_amount_wei, AmountStorage::Wei)
+            .ok_or_else(|| DbError::Operation(format!("Invalid minimum amount: {}", min_amount_wei)))?;
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(TRANSACTIONS_ABOVE_QUERY)?;
+        let rows = stmt.query_map(params![min_key, limit], |row| {
+            Ok(TransactionRow {
+                id: row.get(0)?,
+                block_number: row.get(1)?,
+                transaction_hash: row.get(2)?,
+                log_index: row.get(3)?,
+                from_address: row.get(4)?,
+                to_address: row.get(5)?,
+                amount: row.get(6)?,
+                timestamp: row.get(7)?,
+                direction: row.get(8)?,
+                created_at: row.get(9)?,
+                transaction_index: row.get(10)?,
+            })
+        })?;
+
+        let mut transactions = Vec::new();
+        for row in rows {
+            transactions.push(row?);
+        }
+
+        // Only oversized amounts can tie on the key; order them by exact value, and
+        // drop those below an oversized minimum, which shares their key
+        let compare = crate::models::NetFlowCalculator::compare_amounts;
+        transactions.sort_by(|a, b| compare(&b.amount, &a.amount).unwrap_or(std::cmp::Ordering::Equal));
+        transactions.retain(|tx| {
+            self.amount_storage
+                .stored_to_wei(&tx.amount)
+                .and_then(|wei| compare(&wei, min_amount_wei))
+                .map_or(true, |ordering| ordering != std::cmp::Ordering::Less)
+        });
+
+        Ok(transactions)
+    }
+
+    /// Transactions matching `filter` in chain order, at most `limit` of them
+    ///
+    /// The `WHERE` clause is composed with [`QueryBuilder`], so filter values are
+    /// always bound as parameters. Addresses are normalized like stored ones, so
+    /// they match either side of the transfer with or without a `0x` prefix.
+    pub fn query_transactions(&self, filter: &TransactionFilter, limit: u32) -> Result<Vec<TransactionRow>, DbError> {
+        let mut query = QueryBuilder::new(TRANSACTIONS_SELECT);
+        if let Some(address) = filter.address.as_deref().map(normalize_address) {
+            query = query.and_where(
+                "(from_address = ? OR to_address = ?)",
+                [Value::Text(address.clone()), Value::Text(address)],
+            );
+        }
+        self.run_transaction_query(
+            query
+                .and_where_opt("direction = ?", filter.direction.clone())
+                .and_where_opt("block_number >= ?", filter.from_block.map(|block| block as i64))
+                .and_where_opt("block_number <= ?", filter.to_block.map(|block| block as i64))
+                .order_by("block_number, log_index, id")
+                .limit(limit),
+        )
     }
 
-    /// Get recent transactions with pagination
-    pub fn get_recent_transactions(&self, limit: u32, offset: u32) -> Result<Vec<TransactionRow>, DbError> {
+    /// Run a query built on [`TRANSACTIONS_SELECT`] and collect its rows
+    fn run_transaction_query(&self, query: QueryBuilder) -> Result<Vec<TransactionRow>, DbError> {
+        let (sql, values) = query.build();
         let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at
-             FROM transactions ORDER BY created_at DESC, id DESC LIMIT ?1 OFFSET ?2"
-        )?;
-        
-        let rows = stmt.query_map(params![limit, offset], |row| {
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
             Ok(TransactionRow {
                 id: row.get(0)?,
                 block_number: row.get(1)?,
@@ -243,17 +1351,51 @@ impl Database {
                 timestamp: row.get(7)?,
                 direction: row.get(8)?,
                 created_at: row.get(9)?,
+                transaction_index: row.get(10)?,
             })
         })?;
-        
+
         let mut transactions = Vec::new();
         for row in rows {
             transactions.push(row?);
         }
-        
+
         Ok(transactions)
     }
 
+    /// Invoke `f` for every stored transaction in insertion (id) order
+    ///
+    /// Rows are read from the statement one at a time, so memory stays bounded
+    /// regardless of table size. The database lock is held for the whole scan, so
+    /// `f` must not call back into this `Database`.
+    pub fn for_each_transaction<F: FnMut(TransactionRow)>(&self, mut f: F) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at, transaction_index
+             FROM transactions ORDER BY id"
+        )?;
+
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            f(TransactionRow {
+                id: row.get(0)?,
+                block_number: row.get(1)?,
+                transaction_hash: row.get(2)?,
+                log_index: row.get(3)?,
+                from_address: row.get(4)?,
+                to_address: row.get(5)?,
+                amount: row.get(6)?,
+                timestamp: row.get(7)?,
+                direction: row.get(8)?,
+                created_at: row.get(9)?,
+                transaction_index: row.get(10)?,
+            });
+        }
+
+        Ok(())
+    }
+
     /// Update net-flow data atomically with a new inflow amount
     pub fn update_net_flow_inflow(&self, amount: &str) -> Result<(), DbError> {
         let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
@@ -335,7 +1477,8 @@ impl Database {
         match direction {
             crate::models::TransferDirection::ToBinance => self.update_net_flow_inflow(amount),
             crate::models::TransferDirection::FromBinance => self.update_net_flow_outflow(amount),
-            crate::models::TransferDirection::NotRelevant => Ok(()), // No update needed for irrelevant transfers
+            // No update needed for irrelevant or internal transfers
+            crate::models::TransferDirection::NotRelevant | crate::models::TransferDirection::Internal => Ok(()),
         }
     }
 
@@ -349,20 +1492,26 @@ impl Database {
         let direction_str = match transfer.direction {
             crate::models::TransferDirection::ToBinance => "inflow",
             crate::models::TransferDirection::FromBinance => "outflow",
+            crate::models::TransferDirection::Internal => "internal",
             crate::models::TransferDirection::NotRelevant => return Ok(()), // Don't store irrelevant transfers
         };
+
+        // Transfers carry wei amounts; convert to the configured storage representation
+        let amount = self.stored_transfer_amount(&transfer.amount)?;
         
         // Store the transaction
         tx.execute(
-            "INSERT INTO transactions (block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO transactions (block_number, transaction_hash, log_index, transaction_index, from_address, to_address, amount, amount_u128, timestamp, direction)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 transfer.block_number,
                 transfer.transaction_hash,
                 transfer.log_index,
+                transfer.transaction_index,
                 transfer.from_address,
                 transfer.to_address,
-                transfer.amount,
+                amount,
+                amount_sort_key(&amount, self.amount_storage),
                 transfer.timestamp,
                 direction_str
             ],
@@ -379,7 +1528,7 @@ impl Database {
                 )?;
                 
                 // Calculate new inflow
-                let new_inflow = crate::models::NetFlowCalculator::add_inflow(&current_inflow, &transfer.amount)
+                let new_inflow = crate::models::NetFlowCalculator::add_inflow(&current_inflow, &amount)
                     .map_err(|e| DbError::Operation(format!("Failed to calculate new inflow: {}", e)))?;
                 
                 // Get current outflow to recalculate net flow
@@ -408,7 +1557,7 @@ impl Database {
                 )?;
                 
                 // Calculate new outflow
-                let new_outflow = crate::models::NetFlowCalculator::add_outflow(&current_outflow, &transfer.amount)
+                let new_outflow = crate::models::NetFlowCalculator::add_outflow(&current_outflow, &amount)
                     .map_err(|e| DbError::Operation(format!("Failed to calculate new outflow: {}", e)))?;
                 
                 // Get current inflow to recalculate net flow
@@ -428,6 +1577,9 @@ impl Database {
                     params![new_outflow, new_net_flow],
                 )?;
             },
+            crate::models::TransferDirection::Internal => {
+                // Moves funds between Binance addresses, so the totals are unchanged
+            }
             crate::models::TransferDirection::NotRelevant => {
                 // This case is already handled above, but included for completeness
             }
@@ -436,6 +1588,471 @@ impl Database {
         tx.commit()?;
         Ok(())
     }
+
+    /// Insert transfers whose amounts are already in the storage representation
+    ///
+    /// With `skip_existing`, a transfer whose `(transaction_hash, log_index)` is
+    /// already stored is left alone instead of failing the insert. Returns the
+    /// transfers that were inserted.
+    fn insert_stored_transfers(
+        &self,
+        conn: &Connection,
+        stored: Vec<crate::models::ProcessedTransfer>,
+        pending: bool,
+        skip_existing: bool,
+    ) -> Result<Vec<crate::models::ProcessedTransfer>, DbError> {
+        let mut insert = conn.prepare(if skip_existing {
+            "INSERT INTO transactions (block_number, transaction_hash, log_index, transaction_index, from_address, to_address, amount, amount_u128, timestamp, direction, pending)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(transaction_hash, log_index) DO NOTHING"
+        } else {
+            "INSERT INTO transactions (block_number, transaction_hash, log_index, transaction_index, from_address, to_address, amount, amount_u128, timestamp, direction, pending)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+        })?;
+        let mut inserted = Vec::with_capacity(stored.len());
+        for transfer in stored {
+            let direction_str = match transfer.direction {
+                crate::models::TransferDirection::ToBinance => "inflow",
+                crate::models::TransferDirection::Internal => "internal",
+                _ => "outflow",
+            };
+            let rows = insert.execute(params![
+                transfer.block_number,
+                transfer.transaction_hash,
+                transfer.log_index,
+                transfer.transaction_index,
+                transfer.from_address,
+                transfer.to_address,
+                transfer.amount,
+                amount_sort_key(&transfer.amount, self.amount_storage),
+                transfer.timestamp,
+                direction_str,
+                pending
+            ])?;
+            // Zero rows means the row already existed
+            if rows > 0 {
+                inserted.push(transfer);
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Journal the POL logs of a block so derived tables can be rebuilt from them
+    ///
+    /// Logs already journaled, identified by `(transaction_hash, log_index)`, are
+    /// left as they are, so reprocessing a block is harmless.
+    pub fn store_raw_logs(&self, block_timestamp: u64, logs: &[crate::models::RawLog]) -> Result<(), DbError> {
+        if logs.is_empty() {
+            return Ok(());
+        }
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO raw_logs (block_number, block_timestamp, transaction_hash, log_index, transaction_index, address, topics, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            )?;
+            for log in logs {
+                let topics = serde_json::to_string(&log.topics)
+                    .map_err(|e| DbError::Operation(format!("Failed to encode log topics: {}", e)))?;
+                insert.execute(params![
+                    log.block_number,
+                    block_timestamp,
+                    log.transaction_hash,
+                    log.log_index,
+                    log.transaction_index,
+                    log.address,
+                    topics,
+                    log.data
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Number of journaled raw logs
+    pub fn get_raw_log_count(&self) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let count: u64 = conn.query_row("SELECT COUNT(*) FROM raw_logs", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    /// Rebuild `transactions` and the net-flow totals from the journaled raw logs
+    ///
+    /// Every journaled log is decoded and classified again with `detector`, so
+    /// fixing a classification bug and rebuilding corrects the stored history.
+    /// Zero-amount transfers are dropped unless `track_zero_value_transfers` is set,
+    /// as when indexing. Only the transactions of journaled blocks are replaced;
+    /// rows from blocks indexed before journaling was enabled are kept, and the
+    /// net-flow totals are recomputed over all confirmed transactions plus any
+    /// seeded baseline. Transfers
+    /// of a block that held pending transfers, or that lies above the finalized
+    /// height, are stored as pending again so they wait for confirmation.
+    /// The rebuild runs in one transaction: either the derived data is replaced
+    /// entirely or left untouched. Logs the detector cannot decode are counted
+    /// and skipped. Net-flow snapshots and the last processed block are kept.
+    pub fn rebuild_from_raw_logs(
+        &self,
+        detector: &crate::blockchain::TransferDetector,
+        track_zero_value_transfers: bool,
+    ) -> Result<RebuildSummary, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let journaled: Vec<(crate::models::RawLog, u64)> = {
+            let mut stmt = tx.prepare(
+                "SELECT block_number, block_timestamp, transaction_hash, log_index, transaction_index, address, topics, data
+                 FROM raw_logs ORDER BY block_number, log_index, id"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                let topics: String = row.get(6)?;
+                Ok((
+                    crate::models::RawLog {
+                        address: row.get(5)?,
+                        topics: serde_json::from_str(&topics).unwrap_or_default(),
+                        data: row.get(7)?,
+                        block_number: row.get(0)?,
+                        transaction_hash: row.get(2)?,
+                        log_index: row.get(3)?,
+                        transaction_index: row.get(4)?,
+                        removed: false,
+                    },
+                    row.get(1)?,
+                ))
+            })?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        let mut summary = RebuildSummary { logs_replayed: journaled.len() as u64, ..RebuildSummary::default() };
+        let mut stored = Vec::new();
+        for (log, block_timestamp) in &journaled {
+            let transfer = match detector.decode_transfer_log(log) {
+                Ok(transfer) => transfer,
+                Err(e) => {
+                    log::warn!("Skipping journaled log {}:{}: {}", log.transaction_hash, log.log_index, e);
+                    summary.undecodable_logs += 1;
+                    continue;
+                }
+            };
+            if transfer.direction == crate::models::TransferDirection::NotRelevant
+                || (!track_zero_value_transfers && crate::blockchain::block_processor::is_zero_amount(&transfer.amount))
+            {
+                continue;
+            }
+            let amount = self.stored_transfer_amount(&transfer.amount)?;
+            stored.push(crate::models::ProcessedTransfer { amount, timestamp: *block_timestamp, ..transfer });
+        }
+        summary.transfers_stored = stored.len() as u64;
+
+        // Blocks still awaiting confirmation stay pending, as they were when written
+        let finalized = Self::finalized_block(&tx)?;
+        let pending_blocks: HashSet<u64> = {
+            let mut stmt = tx.prepare("SELECT DISTINCT block_number FROM transactions WHERE pending = 1")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        let (pending, counted): (Vec<_>, Vec<_>) = stored.into_iter().partition(|transfer| {
+            pending_blocks.contains(&transfer.block_number)
+                || finalized.is_some_and(|finalized| transfer.block_number > finalized)
+        });
+
+        tx.execute("DELETE FROM transactions WHERE block_number IN (SELECT DISTINCT block_number FROM raw_logs)", [])?;
+        self.insert_stored_transfers(&tx, counted, false, false)?;
+        self.insert_stored_transfers(&tx, pending, true, false)?;
+
+        let (total_inflow, total_outflow) = Self::sum_net_flow_totals(&tx)?;
+        tx.execute(
+            "UPDATE net_flows SET total_inflow = ?1, total_outflow = ?2, net_flow = ?3, last_updated = strftime('%s', 'now') WHERE id = 1",
+            params![
+                total_inflow.to_string(),
+                total_outflow.to_string(),
+                total_inflow.sub(&total_outflow).to_string()
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// Store a batch of processed transfers and update net-flow data in one transaction
+    ///
+    /// Irrelevant transfers are skipped. The net-flow totals are read once, folded
+    /// with [`NetFlowCalculator::apply_batch`](crate::models::NetFlowCalculator::apply_batch)
+    /// and written once, so either every transfer is stored or none is.
+    pub fn store_transfers_and_update_net_flow(&self, transfers: &[crate::models::ProcessedTransfer]) -> Result<(), DbError> {
+        let stored = self.relevant_stored_transfers(transfers)?;
+        if stored.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let inserted = self.insert_stored_transfers(&tx, stored, false, false)?;
+        Self::add_to_net_flow(&tx, &inserted)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store the transfers not stored yet, counting them in net flow; returns how many were stored
+    ///
+    /// Transfers are matched on transaction hash and log index, so storing a
+    /// block's transfers again leaves the ones already there untouched and only
+    /// the new ones change the totals.
+    pub fn store_missing_transfers(&self, transfers: &[crate::models::ProcessedTransfer]) -> Result<usize, DbError> {
+        let stored = self.relevant_stored_transfers(transfers)?;
+        if stored.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let inserted = self.insert_stored_transfers(&tx, stored, false, true)?;
+        if inserted.is_empty() {
+            return Ok(0);
+        }
+        Self::add_to_net_flow(&tx, &inserted)?;
+
+        tx.commit()?;
+        Ok(inserted.len())
+    }
+
+    /// Fold stored-representation transfers into the `net_flows` totals
+    fn add_to_net_flow(conn: &Connection, stored: &[crate::models::ProcessedTransfer]) -> Result<(), DbError> {
+        let current = conn.query_row(
+            "SELECT id, total_inflow, total_outflow, net_flow, last_processed_block, last_updated FROM net_flows WHERE id = 1",
+            [],
+            |row| {
+                Ok(NetFlowRow {
+                    id: row.get(0)?,
+                    total_inflow: row.get(1)?,
+                    total_outflow: row.get(2)?,
+                    net_flow: row.get(3)?,
+                    last_processed_block: row.get(4)?,
+                    last_updated: row.get(5)?,
+                })
+            },
+        )?;
+
+        let updated = crate::models::NetFlowCalculator::apply_batch(&current, stored)
+            .map_err(|e| DbError::Operation(format!("Failed to calculate net flow: {}", e)))?;
+
+        conn.execute(
+            "UPDATE net_flows SET total_inflow = ?1, total_outflow = ?2, net_flow = ?3, last_updated = strftime('%s', 'now') WHERE id = 1",
+            params![updated.total_inflow, updated.total_outflow, updated.net_flow],
+        )?;
+        Ok(())
+    }
+
+    /// Store transfers as pending: recorded, but not counted in `net_flows` yet
+    ///
+    /// Used for blocks still inside the confirmation window. They are counted by
+    /// [`finalize_pending_transfers`](Self::finalize_pending_transfers) once
+    /// confirmed, or removed by [`discard_block`](Self::discard_block)
+    /// if their block is reorganized away.
+    pub fn store_pending_transfers(&self, transfers: &[crate::models::ProcessedTransfer]) -> Result<(), DbError> {
+        let stored = self.relevant_stored_transfers(transfers)?;
+        if stored.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+        self.insert_stored_transfers(&tx, stored, true, false)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store the transfers not stored yet as pending; returns how many were stored
+    ///
+    /// The pending counterpart of [`store_missing_transfers`](Self::store_missing_transfers).
+    pub fn store_missing_pending_transfers(&self, transfers: &[crate::models::ProcessedTransfer]) -> Result<usize, DbError> {
+        let stored = self.relevant_stored_transfers(transfers)?;
+        if stored.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+        let inserted = self.insert_stored_transfers(&tx, stored, true, true)?;
+        tx.commit()?;
+        Ok(inserted.len())
+    }
+
+    /// Store the transfers of a block processed on demand and record its hash; returns how many were stored
+    ///
+    /// Transfers already stored are left alone. With `pending` set the new ones
+    /// wait for [`finalize_pending_transfers`](Self::finalize_pending_transfers)
+    /// like any block inside the confirmation window. The hash lets the monitor
+    /// detect a reorg of the block later; a skipped block has none to record.
+    pub fn store_processed_block(
+        &self,
+        block_number: u64,
+        block_hash: &str,
+        transfers: &[crate::models::ProcessedTransfer],
+        pending: bool,
+    ) -> Result<usize, DbError> {
+        let stored = if pending {
+            self.store_missing_pending_transfers(transfers)?
+        } else {
+            self.store_missing_transfers(transfers)?
+        };
+        if !block_hash.is_empty() {
+            self.record_block_hash(block_number, block_hash)?;
+        }
+        Ok(stored)
+    }
+
+    /// Count every pending transfer at or below `up_to_block` in `net_flows`, returning how many were promoted
+    ///
+    /// Also records `up_to_block` as finalized, so
+    /// [`get_unfinalized_blocks`](Self::get_unfinalized_blocks) stops returning the
+    /// blocks up to it.
+    pub fn finalize_pending_transfers(&self, up_to_block: u64) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+
+        if Self::finalized_block(&tx)?.is_none_or(|finalized| finalized < up_to_block) {
+            set_metadata(&tx, FINALIZED_BLOCK_KEY, &up_to_block.to_string())?;
+        }
+
+        let (inflow, outflow) = Self::sum_flows(&tx, "pending = 1 AND block_number <= ?1", params![up_to_block])?;
+        let promoted = tx.execute(
+            "UPDATE transactions SET pending = 0 WHERE pending = 1 AND block_number <= ?1",
+            params![up_to_block],
+        )?;
+        if promoted == 0 {
+            tx.commit()?;
+            return Ok(0);
+        }
+
+        Self::shift_net_flow(&tx, &inflow, &outflow)?;
+
+        tx.commit()?;
+        Ok(promoted as u64)
+    }
+
+    /// Add `inflow` and `outflow` to the `net_flows` totals; negative amounts take transfers back out
+    fn shift_net_flow(
+        conn: &Connection,
+        inflow: &crate::models::DecimalAmount,
+        outflow: &crate::models::DecimalAmount,
+    ) -> Result<(), DbError> {
+        let (total_inflow, total_outflow): (String, String) = conn.query_row(
+            "SELECT total_inflow, total_outflow FROM net_flows WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let parse = |amount: &str| {
+            crate::models::NetFlowCalculator::parse_decimal(amount)
+                .map_err(|e| DbError::Operation(format!("Failed to calculate net flow: {}", e)))
+        };
+        let total_inflow = parse(&total_inflow)?.add(inflow);
+        let total_outflow = parse(&total_outflow)?.add(outflow);
+        conn.execute(
+            "UPDATE net_flows SET total_inflow = ?1, total_outflow = ?2, net_flow = ?3, last_updated = strftime('%s', 'now') WHERE id = 1",
+            params![total_inflow.to_string(), total_outflow.to_string(), total_inflow.sub(&total_outflow).to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a reorganized block: delete its transfers and journaled logs, returning how many transfers were removed
+    ///
+    /// Transfers already counted, such as those of a block written outside the
+    /// confirmation window, are subtracted from `net_flows` again. Everything goes
+    /// in one transaction, so the canonical block journals its own logs and a
+    /// rebuild never replays the orphaned ones.
+    pub fn discard_block(&self, block_number: u64) -> Result<usize, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let (inflow, outflow) = Self::sum_transaction_flows(&tx, "block_number = ?1", params![block_number])?;
+        Self::shift_net_flow(&tx, &inflow.negated(), &outflow.negated())?;
+        let removed = tx.execute("DELETE FROM transactions WHERE block_number = ?1", params![block_number])?;
+        tx.execute("DELETE FROM raw_logs WHERE block_number = ?1", params![block_number])?;
+
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Highest block recorded by [`finalize_pending_transfers`](Self::finalize_pending_transfers)
+    fn finalized_block(conn: &Connection) -> Result<Option<u64>, DbError> {
+        Ok(get_metadata(conn, FINALIZED_BLOCK_KEY)?.and_then(|block| block.parse().ok()))
+    }
+
+    /// Blocks at or below `up_to_block` to check for a reorg before finalizing, with their recorded hash, lowest first
+    ///
+    /// These are every block with a recorded hash above the finalized height,
+    /// whether or not it holds transfers, so a block that was empty when written
+    /// is still checked, plus any block holding pending transfers. Until something
+    /// has been finalized only the blocks with pending transfers are returned.
+    pub fn get_unfinalized_blocks(&self, up_to_block: u64) -> Result<Vec<(u64, Option<String>)>, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let finalized = Self::finalized_block(&conn)?.unwrap_or(up_to_block);
+
+        let mut stmt = conn.prepare(
+            "SELECT block_number, MAX(block_hash) FROM (
+                SELECT block_number, block_hash FROM block_hashes WHERE block_number > ?2 AND block_number <= ?1
+                UNION ALL
+                SELECT t.block_number, h.block_hash FROM transactions t
+                LEFT JOIN block_hashes h ON h.block_number = t.block_number
+                WHERE t.pending = 1 AND t.block_number <= ?1
+             ) GROUP BY block_number ORDER BY block_number",
+        )?;
+        let rows = stmt.query_map(params![up_to_block, finalized], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut blocks = Vec::new();
+        for row in rows {
+            blocks.push(row?);
+        }
+
+        Ok(blocks)
+    }
+
+    /// Number of stored transfers not yet counted in `net_flows`
+    pub fn get_pending_transfer_count(&self) -> Result<u64, DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        let count: u64 = conn.query_row("SELECT COUNT(*) FROM transactions WHERE pending = 1", [], |row| row.get(0))?;
+
+        Ok(count)
+    }
+
+    /// Refresh query planner statistics with `ANALYZE`, and with `vacuum` also rebuild the file
+    ///
+    /// `VACUUM` reclaims the space left by deleted rows (after pruning, say) but
+    /// rewrites the whole database; the write connection stays locked until it
+    /// finishes, so block processing and every other write wait for it.
+    pub fn maintenance(&self, vacuum: bool) -> Result<(), DbError> {
+        let conn = self.conn.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        conn.execute_batch("ANALYZE")?;
+        if vacuum {
+            // In WAL mode the rewritten pages land in the log; checkpoint to shrink the file
+            conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+        }
+
+        Ok(())
+    }
+
+    /// Relevant transfers with amounts converted from wei to the storage representation
+    fn relevant_stored_transfers(
+        &self,
+        transfers: &[crate::models::ProcessedTransfer],
+    ) -> Result<Vec<crate::models::ProcessedTransfer>, DbError> {
+        let mut stored = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            if transfer.direction == crate::models::TransferDirection::NotRelevant {
+                continue;
+            }
+            let amount = self.stored_transfer_amount(&transfer.amount)?;
+            stored.push(crate::models::ProcessedTransfer { amount, ..transfer.clone() });
+        }
+        Ok(stored)
+    }
 }
 
 /// Represents a row from the transactions table
@@ -451,6 +2068,129 @@ pub struct TransactionRow {
     pub timestamp: u64,
     pub direction: String,
     pub created_at: u64,
+    /// Position of the transaction within its block; 0 for rows stored before it was recorded
+    pub transaction_index: u32,
+}
+
+/// Aggregate figures returned by [`Database::get_stats`]
+#[derive(Debug, Clone)]
+pub struct DbStats {
+    pub net_flow: NetFlowRow,
+    pub transaction_count: u64,
+    pub inflow_count: u64,
+    pub outflow_count: u64,
+    /// Lowest and highest block with a stored transaction
+    pub block_range: Option<(u64, u64)>,
+    pub skipped_blocks: u64,
+}
+
+/// Represents a row from the skipped_blocks table
+#[derive(Debug, Clone)]
+pub struct SkippedBlockRow {
+    pub block_number: u64,
+    pub error: String,
+    pub skip_count: u32,
+    pub reprocess_requested: bool,
+    pub skipped_at: u64,
+}
+
+/// Represents the row of the backfill_state table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillStateRow {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Highest block written or skipped so far, `None` before the first one
+    pub last_completed_block: Option<u64>,
+    pub updated_at: u64,
+}
+
+impl BackfillStateRow {
+    /// First block a resumed backfill should process
+    pub fn resume_from(&self) -> u64 {
+        self.last_completed_block.map_or(self.from_block, |block| block + 1)
+    }
+
+    /// Whether every block in the range was written or skipped
+    pub fn is_complete(&self) -> bool {
+        self.last_completed_block == Some(self.to_block)
+    }
+}
+
+/// Represents a row from the net_flow_snapshots table
+#[derive(Debug, Clone)]
+pub struct NetFlowSnapshotRow {
+    pub id: i64,
+    pub block_number: u64,
+    pub total_inflow: String,
+    pub total_outflow: String,
+    pub net_flow: String,
+    pub recorded_at: u64,
+}
+
+/// Most buckets a single [`Database::flow_series`] call may return
+pub const MAX_FLOW_SERIES_BUCKETS: u64 = 10_000;
+
+/// Running inflow and outflow totals at the end of one flow-series bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowSeriesPoint {
+    /// Start of the bucket, in seconds since the Unix epoch
+    pub bucket: u64,
+    /// Inflow within this bucket alone
+    pub inflow: String,
+    /// Outflow within this bucket alone
+    pub outflow: String,
+    pub cumulative_inflow: String,
+    pub cumulative_outflow: String,
+}
+
+/// Snapshots below a `net_flow_sort_key`, lowest net flow first
+pub(crate) const NET_FLOW_SNAPSHOTS_BELOW_QUERY: &str =
+    "SELECT id, block_number, total_inflow, total_outflow, net_flow, recorded_at
+     FROM net_flow_snapshots WHERE net_flow_sort_key < ?1 ORDER BY net_flow_sort_key, id LIMIT ?2";
+
+/// Columns of a [`TransactionRow`], for queries composed with [`QueryBuilder`]
+const TRANSACTIONS_SELECT: &str =
+    "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at, transaction_index
+     FROM transactions";
+
+/// Transactions at or above an `amount_u128` key, largest first
+pub(crate) const TRANSACTIONS_ABOVE_QUERY: &str =
+    "SELECT id, block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction, created_at, transaction_index
+     FROM transactions WHERE amount_u128 >= ?1 ORDER BY amount_u128 DESC, id DESC LIMIT ?2";
+
+/// Outcome of [`Database::rebuild_from_raw_logs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RebuildSummary {
+    /// Journaled logs read back
+    pub logs_replayed: u64,
+    /// Transfers stored after classification
+    pub transfers_stored: u64,
+    /// Journaled logs the detector could not decode
+    pub undecodable_logs: u64,
+}
+
+/// Outcome of [`Database::audit_net_flow`], amounts in the stored unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetFlowAudit {
+    pub stored_inflow: String,
+    pub stored_outflow: String,
+    /// Inflow summed from the confirmed transactions
+    pub recomputed_inflow: String,
+    /// Outflow summed from the confirmed transactions
+    pub recomputed_outflow: String,
+    /// Stored minus seeded minus recomputed inflow
+    pub inflow_drift: String,
+    /// Stored minus seeded minus recomputed outflow
+    pub outflow_drift: String,
+    /// Stored minus recomputed net flow
+    pub net_flow_drift: String,
+}
+
+impl NetFlowAudit {
+    /// Whether the stored totals match the transactions exactly
+    pub fn is_consistent(&self) -> bool {
+        self.inflow_drift == "0" && self.outflow_drift == "0"
+    }
 }
 
 /// Represents a row from the net_flows table
@@ -462,4 +2202,29 @@ pub struct NetFlowRow {
     pub net_flow: String,
     pub last_processed_block: u64,
     pub last_updated: u64,
+}
+
+impl NetFlowRow {
+    /// Net flow as a signed integer in the stored unit
+    ///
+    /// Fails when the stored value is fractional (POL storage) or outside `i128`.
+    pub fn net_flow_signed(&self) -> Result<i128, crate::models::CalculationError> {
+        self.net_flow.trim().parse().map_err(|e| {
+            crate::models::CalculationError::InvalidDecimal(format!("net flow {}: {}", self.net_flow, e))
+        })
+    }
+
+    /// True when more POL went to Binance than came out of it
+    pub fn is_net_inflow(&self) -> bool {
+        self.flow_direction() == "inflow"
+    }
+
+    /// Sign of the net flow: `inflow`, `outflow`, or `neutral` when it is zero or unparsable
+    pub fn flow_direction(&self) -> &'static str {
+        match crate::models::NetFlowCalculator::parse_decimal(&self.net_flow) {
+            Ok(net_flow) if net_flow.is_negative() => "outflow",
+            Ok(net_flow) if !net_flow.is_zero() => "inflow",
+            _ => "neutral",
+        }
+    }
 }
\ No newline at end of file
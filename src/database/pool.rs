@@ -0,0 +1,131 @@
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use crate::database::functions::register_functions;
+use crate::database::DbError;
+
+/// Default number of read-only connections kept by a [`ReadPool`]
+pub const DEFAULT_READ_POOL_SIZE: usize = 10;
+
+/// Default time to wait for a free read-only connection
+pub const DEFAULT_CHECKOUT_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// Bounded pool of read-only connections to a database file
+///
+/// At most `max_size` connections are open at once. A checkout waits up to the
+/// checkout timeout for one to be returned and then fails with
+/// [`DbError::PoolExhausted`], which callers can retry instead of blocking
+/// forever.
+pub struct ReadPool {
+    path: String,
+    max_size: usize,
+    checkout_timeout: Duration,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+struct PoolState {
+    idle: Vec<Connection>,
+    open: usize,
+}
+
+impl ReadPool {
+    pub fn new(path: impl Into<String>, max_size: usize, checkout_timeout: Duration) -> Self {
+        Self {
+            path: path.into(),
+            max_size: max_size.max(1),
+            checkout_timeout,
+            state: Mutex::new(PoolState { idle: Vec::new(), open: 0 }),
+            returned: Condvar::new(),
+        }
+    }
+
+    /// Path of the database file the connections are opened on
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Maximum number of connections open at once
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Take a connection, opening one if the pool is below its size
+    pub fn checkout(&self) -> Result<PooledConnection<'_>, DbError> {
+        let state = self.state.lock().map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+        let (mut state, wait) = self
+            .returned
+            .wait_timeout_while(state, self.checkout_timeout, |state| {
+                state.idle.is_empty() && state.open >= self.max_size
+            })
+            .map_err(|_| DbError::Operation("Failed to acquire lock".to_string()))?;
+
+        if let Some(conn) = state.idle.pop() {
+            return Ok(PooledConnection { pool: self, conn: Some(conn) });
+        }
+        if wait.timed_out() && state.open >= self.max_size {
+            return Err(DbError::PoolExhausted {
+                timeout_ms: self.checkout_timeout.as_millis() as u64,
+            });
+        }
+
+        // Reserve the slot before opening so concurrent checkouts respect the limit
+        state.open += 1;
+        drop(state);
+        match self.open_connection() {
+            Ok(conn) => Ok(PooledConnection { pool: self, conn: Some(conn) }),
+            Err(e) => {
+                self.release_slot();
+                Err(e)
+            }
+        }
+    }
+
+    fn open_connection(&self) -> Result<Connection, DbError> {
+        let conn = Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(self.checkout_timeout)?;
+        register_functions(&conn)?;
+        Ok(conn)
+    }
+
+    fn release_slot(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.open -= 1;
+        }
+        self.returned.notify_one();
+    }
+
+    fn give_back(&self, conn: Connection) {
+        if let Ok(mut state) = self.state.lock() {
+            state.idle.push(conn);
+        }
+        self.returned.notify_one();
+    }
+}
+
+/// A connection checked out of a [`ReadPool`], returned to it on drop
+pub struct PooledConnection<'a> {
+    pool: &'a ReadPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.give_back(conn);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use rusqlite::types::Value;
+
+/// Composes a `SELECT` with a dynamic `WHERE` clause from bound parameters only
+///
+/// SQL fragments are `&'static str`, so they can only come from the source code;
+/// every caller-supplied value goes through a `?` placeholder and is bound by
+/// SQLite, never spliced into the statement text.
+pub(crate) struct QueryBuilder {
+    select: &'static str,
+    conditions: Vec<&'static str>,
+    params: Vec<Value>,
+    order_by: Option<&'static str>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl QueryBuilder {
+    /// Start from `select`, a `SELECT ... FROM ...` without a `WHERE` clause
+    pub(crate) fn new(select: &'static str) -> Self {
+        Self {
+            select,
+            conditions: Vec::new(),
+            params: Vec::new(),
+            order_by: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// AND `condition` into the `WHERE` clause, binding `values` to its `?` placeholders in order
+    pub(crate) fn and_where(mut self, condition: &'static str, values: impl IntoIterator<Item = Value>) -> Self {
+        let start = self.params.len();
+        self.params.extend(values);
+        debug_assert_eq!(
+            condition.matches('?').count(),
+            self.params.len() - start,
+            "placeholder count does not match the bound values in `{}`",
+            condition
+        );
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Like [`and_where`](Self::and_where), skipped when `value` is `None`
+    pub(crate) fn and_where_opt<T: Into<Value>>(self, condition: &'static str, value: Option<T>) -> Self {
+        match value {
+            Some(value) => self.and_where(condition, [value.into()]),
+            None => self,
+        }
+    }
+
+    pub(crate) fn order_by(mut self, order_by: &'static str) -> Self {
+        self.order_by = Some(order_by);
+        self
+    }
+
+    pub(crate) fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` rows; only applied together with a [`limit`](Self::limit)
+    pub(crate) fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The statement text and the parameters to bind to it
+    pub(crate) fn build(self) -> (String, Vec<Value>) {
+        let mut sql = self.select.to_string();
+        let mut params = self.params;
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        if let Some(order_by) = self.order_by {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(order_by);
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(" LIMIT ?");
+            params.push(Value::Integer(limit as i64));
+            if let Some(offset) = self.offset {
+                sql.push_str(" OFFSET ?");
+                params.push(Value::Integer(offset as i64));
+            }
+        }
+        (sql, params)
+    }
+}
+
+/// Optional filters for [`Database::query_transactions`](crate::database::Database::query_transactions)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionFilter {
+    /// Transactions sent from or to this address
+    pub address: Option<String>,
+    /// "inflow" or "outflow"
+    pub direction: Option<String>,
+    /// Lowest block number, inclusive
+    pub from_block: Option<u64>,
+    /// Highest block number, inclusive
+    pub to_block: Option<u64>,
+}
+
@@ -1,24 +1,127 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
-/// Initialize the database schema with required tables
-pub fn initialize_schema(conn: &Connection) -> Result<()> {
-    // Create transactions table for raw transaction storage
+use crate::models::AmountStorage;
+
+/// Schema metadata key recording how transaction amounts are stored
+pub const AMOUNT_STORAGE_KEY: &str = "amount_storage";
+
+/// Schema metadata key recording the highest block whose transfers have been finalized
+pub const FINALIZED_BLOCK_KEY: &str = "finalized_block";
+
+/// Schema metadata keys recording the inflow and outflow seeded into `net_flows`
+/// beyond what the counted transactions add up to
+pub const SEED_INFLOW_KEY: &str = "seed_inflow";
+pub const SEED_OUTFLOW_KEY: &str = "seed_outflow";
+
+/// Schema metadata key recording the version [`run_migrations`] brought the database to
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Version written by [`run_migrations`]; bump it whenever a migration is added
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Digits in `u128::MAX`, the width of every `amount_u128` key
+const AMOUNT_KEY_WIDTH: usize = 39;
+
+/// SQLite `synchronous` setting, trading durability for write throughput
+///
+/// `FULL` syncs on every commit, so a committed block survives power loss.
+/// `NORMAL` syncs less often; in WAL mode the last commits may be rolled back
+/// after a power loss, but the database is never corrupted. `OFF` leaves
+/// syncing to the OS and can corrupt the database if the machine crashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SynchronousMode {
+    Off,
+    Normal,
+    #[default]
+    Full,
+}
+
+impl SynchronousMode {
+    /// Value passed to `PRAGMA synchronous`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SynchronousMode::Off => "OFF",
+            SynchronousMode::Normal => "NORMAL",
+            SynchronousMode::Full => "FULL",
+        }
+    }
+}
+
+impl fmt::Display for SynchronousMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SynchronousMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_uppercase().as_str() {
+            "OFF" => Ok(SynchronousMode::Off),
+            "NORMAL" => Ok(SynchronousMode::Normal),
+            "FULL" => Ok(SynchronousMode::Full),
+            _ => Err(format!("Unknown synchronous mode '{}' (expected OFF, NORMAL or FULL)", value)),
+        }
+    }
+}
+
+/// Apply `mode` to the connection with `PRAGMA synchronous`
+pub fn set_synchronous(conn: &Connection, mode: SynchronousMode) -> Result<()> {
+    conn.pragma_update(None, "synchronous", mode.as_str())
+}
+
+/// Read the connection's `PRAGMA synchronous` setting
+pub fn get_synchronous(conn: &Connection) -> Result<Option<SynchronousMode>> {
+    let level: i64 = conn.pragma_query_value(None, "synchronous", |row| row.get(0))?;
+    Ok(match level {
+        0 => Some(SynchronousMode::Off),
+        1 => Some(SynchronousMode::Normal),
+        2 => Some(SynchronousMode::Full),
+        // EXTRA (3) is stricter than FULL and cannot be configured here
+        _ => None,
+    })
+}
+
+/// Columns of the `transactions` table, in declaration order
+const TRANSACTIONS_COLUMNS: &str = "id, block_number, transaction_hash, log_index, transaction_index, \
+    from_address, to_address, amount, amount_u128, timestamp, direction, created_at";
+
+/// Create the `transactions` table under `table`
+fn create_transactions_table(conn: &Connection, table: &str) -> Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS transactions (
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             block_number INTEGER NOT NULL,
             transaction_hash TEXT NOT NULL,
             log_index INTEGER NOT NULL,
+            transaction_index INTEGER NOT NULL DEFAULT 0,
             from_address TEXT NOT NULL,
             to_address TEXT NOT NULL,
             amount TEXT NOT NULL,
+            amount_u128 TEXT,
             timestamp INTEGER NOT NULL,
-            direction TEXT NOT NULL CHECK (direction IN ('inflow', 'outflow')),
+            direction TEXT NOT NULL CHECK (direction IN ('inflow', 'outflow', 'internal')),
             created_at INTEGER DEFAULT (strftime('%s', 'now')),
+            pending INTEGER NOT NULL DEFAULT 0,
             UNIQUE(transaction_hash, log_index)
         )",
+            table
+        ),
         [],
     )?;
+    Ok(())
+}
+
+/// Initialize the database schema with required tables
+pub fn initialize_schema(conn: &Connection) -> Result<()> {
+    // Create transactions table for raw transaction storage
+    create_transactions_table(conn, "transactions")?;
 
     // Create net_flows table for cumulative net-flow tracking
     conn.execute(
@@ -33,6 +136,80 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Create skipped_blocks table recording blocks that failed processing
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS skipped_blocks (
+            block_number INTEGER PRIMARY KEY,
+            error TEXT NOT NULL,
+            skip_count INTEGER NOT NULL DEFAULT 1,
+            reprocess_requested INTEGER NOT NULL DEFAULT 0,
+            skipped_at INTEGER DEFAULT (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+
+    // Create block_hashes table for verifying stored data against the chain
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS block_hashes (
+            block_number INTEGER PRIMARY KEY,
+            block_hash TEXT NOT NULL,
+            recorded_at INTEGER DEFAULT (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+
+    // Create raw_logs table journaling decoded POL logs so derived data can be rebuilt
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS raw_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            block_number INTEGER NOT NULL,
+            block_timestamp INTEGER NOT NULL,
+            transaction_hash TEXT NOT NULL,
+            log_index INTEGER NOT NULL,
+            transaction_index INTEGER NOT NULL DEFAULT 0,
+            address TEXT NOT NULL,
+            topics TEXT NOT NULL,
+            data TEXT NOT NULL,
+            UNIQUE(transaction_hash, log_index)
+        )",
+        [],
+    )?;
+
+    // Create net_flow_snapshots table recording cumulative totals after each block
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS net_flow_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            block_number INTEGER NOT NULL,
+            total_inflow TEXT NOT NULL,
+            total_outflow TEXT NOT NULL,
+            net_flow TEXT NOT NULL,
+            net_flow_sort_key TEXT,
+            recorded_at INTEGER DEFAULT (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+
+    // Create backfill_state table tracking the latest backfill, apart from the live cursor
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS backfill_state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            from_block INTEGER NOT NULL,
+            to_block INTEGER NOT NULL,
+            last_completed_block INTEGER,
+            updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+
+    // Create schema_metadata table for database-wide settings
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_metadata (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
     // Create indexes for performance
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_transactions_block ON transactions(block_number)",
@@ -49,6 +226,16 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_net_flow_snapshots_block ON net_flow_snapshots(block_number)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_raw_logs_block ON raw_logs(block_number, log_index)",
+        [],
+    )?;
+
     // Initialize net_flows table with default values if empty
     conn.execute(
         "INSERT OR IGNORE INTO net_flows (id, total_inflow, total_outflow, net_flow, last_processed_block)
@@ -59,9 +246,229 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Read a value from the schema_metadata table
+pub fn get_metadata(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM schema_metadata WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Write a value to the schema_metadata table
+pub fn set_metadata(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schema_metadata (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
 /// Run database migrations (for future schema updates)
 pub fn run_migrations(conn: &Connection) -> Result<()> {
     // Check current schema version and apply migrations as needed
-    // For now, just ensure the schema is initialized
-    initialize_schema(conn)
+    initialize_schema(conn)?;
+
+    // Databases created before amounts were indexed lack the sort key column
+    let has_amount_key: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info('transactions') WHERE name = 'amount_u128')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_amount_key {
+        conn.execute("ALTER TABLE transactions ADD COLUMN amount_u128 TEXT", [])?;
+    }
+
+    // Databases created before transaction positions were recorded default them to 0
+    let has_transaction_index: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info('transactions') WHERE name = 'transaction_index')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_transaction_index {
+        conn.execute("ALTER TABLE transactions ADD COLUMN transaction_index INTEGER NOT NULL DEFAULT 0", [])?;
+    }
+
+    // Databases created before internal transfers only accept inflows and outflows.
+    // SQLite cannot alter a CHECK constraint, so the table is rebuilt with the new one.
+    let table_sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'transactions'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !table_sql.contains("'internal'") {
+        let tx = conn.unchecked_transaction()?;
+        create_transactions_table(&tx, "transactions_migrated")?;
+        tx.execute(
+            &format!(
+                "INSERT INTO transactions_migrated ({columns}) SELECT {columns} FROM transactions",
+                columns = TRANSACTIONS_COLUMNS
+            ),
+            [],
+        )?;
+        tx.execute("DROP TABLE transactions", [])?;
+        tx.execute("ALTER TABLE transactions_migrated RENAME TO transactions", [])?;
+        tx.commit()?;
+        // Recreate the indexes dropped with the old table
+        initialize_schema(conn)?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_amount_u128 ON transactions(amount_u128)",
+        [],
+    )?;
+
+    // Databases created before confirmation tracking count every stored transfer
+    let has_pending: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info('transactions') WHERE name = 'pending')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_pending {
+        conn.execute("ALTER TABLE transactions ADD COLUMN pending INTEGER NOT NULL DEFAULT 0", [])?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_pending ON transactions(pending, block_number)",
+        [],
+    )?;
+
+    // Databases created before snapshots had a signed net-flow key lack the column,
+    // and older ones named it `net_flow_i128` although it holds a TEXT key
+    let snapshot_column_exists = |name: &str| -> Result<bool> {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('net_flow_snapshots') WHERE name = ?1)",
+            [name],
+            |row| row.get(0),
+        )
+    };
+    if snapshot_column_exists("net_flow_i128")? {
+        conn.execute("ALTER TABLE net_flow_snapshots RENAME COLUMN net_flow_i128 TO net_flow_sort_key", [])?;
+    } else if !snapshot_column_exists("net_flow_sort_key")? {
+        conn.execute("ALTER TABLE net_flow_snapshots ADD COLUMN net_flow_sort_key TEXT", [])?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_net_flow_snapshots_net_flow ON net_flow_snapshots(net_flow_sort_key)",
+        [],
+    )?;
+
+    set_metadata(conn, SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_string())?;
+    Ok(())
+}
+
+/// Schema version recorded by [`run_migrations`], `None` if they never ran on the database
+///
+/// Only reads, so it works on a read-only connection.
+pub fn recorded_schema_version(conn: &Connection) -> Result<Option<u32>> {
+    let has_metadata: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_metadata')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_metadata {
+        return Ok(None);
+    }
+    Ok(get_metadata(conn, SCHEMA_VERSION_KEY)?.and_then(|version| version.parse().ok()))
+}
+
+/// Index key for a stored amount, kept in the `amount_u128` column
+///
+/// SQLite integers are 64-bit, so the wei value is stored as TEXT zero-padded to
+/// the width of `u128::MAX`, which makes string order match numeric order.
+/// Fractions of a wei are truncated, and amounts above `u128::MAX` get the
+/// all-nines key so they sort above everything else; the full value always stays
+/// in `amount`. Returns `None` for amounts that are not valid decimals.
+pub fn amount_sort_key(stored_amount: &str, amount_storage: AmountStorage) -> Option<String> {
+    let wei = amount_storage.stored_to_wei(stored_amount).ok()?;
+    let integer_part = wei.split('.').next().unwrap_or_default();
+    match integer_part.parse::<u128>() {
+        Ok(value) => Some(format!("{:0width$}", value, width = AMOUNT_KEY_WIDTH)),
+        Err(_) if !integer_part.is_empty() && integer_part.bytes().all(|b| b.is_ascii_digit()) => {
+            Some("9".repeat(AMOUNT_KEY_WIDTH))
+        }
+        Err(_) => None,
+    }
+}
+
+/// Index key for a stored signed net flow, kept in the `net_flow_sort_key` column
+///
+/// The wei value is clamped to the `i128` range, offset by 2^127 so negative
+/// totals sort below positive ones, then stored as TEXT zero-padded like
+/// [`amount_sort_key`], so string order matches numeric order. Fractions of a wei are truncated
+/// and totals outside the `i128` range get the key of its bounds. Returns `None`
+/// for values that are not valid decimals.
+pub fn net_flow_sort_key(stored_net_flow: &str, amount_storage: AmountStorage) -> Option<String> {
+    let (negative, magnitude) = match stored_net_flow.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, stored_net_flow),
+    };
+    let wei = amount_storage.stored_to_wei(magnitude).ok()?;
+    let integer_part = wei.split('.').next().unwrap_or_default();
+    if integer_part.is_empty() || !integer_part.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value = match integer_part.parse::<i128>() {
+        Ok(magnitude) if negative => -magnitude,
+        Ok(magnitude) => magnitude,
+        Err(_) if negative => i128::MIN,
+        Err(_) => i128::MAX,
+    };
+    Some(format!("{:0width$}", (value as u128) ^ (1 << 127), width = AMOUNT_KEY_WIDTH))
+}
+
+/// Fill in `net_flow_sort_key` for snapshots recorded before the column existed
+pub fn backfill_net_flow_keys(conn: &Connection, amount_storage: AmountStorage) -> Result<usize> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, net_flow FROM net_flow_snapshots WHERE net_flow_sort_key IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut updated = 0;
+    {
+        let mut update = tx.prepare("UPDATE net_flow_snapshots SET net_flow_sort_key = ?1 WHERE id = ?2")?;
+        for (id, net_flow) in rows {
+            if let Some(key) = net_flow_sort_key(&net_flow, amount_storage) {
+                update.execute(params![key, id])?;
+                updated += 1;
+            }
+        }
+    }
+    tx.commit()?;
+
+    Ok(updated)
+}
+
+/// Fill in `amount_u128` for rows written before the column existed
+pub fn backfill_amount_keys(conn: &Connection, amount_storage: AmountStorage) -> Result<usize> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, amount FROM transactions WHERE amount_u128 IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut updated = 0;
+    {
+        let mut update = tx.prepare("UPDATE transactions SET amount_u128 = ?1 WHERE id = ?2")?;
+        for (id, amount) in rows {
+            if let Some(key) = amount_sort_key(&amount, amount_storage) {
+                update.execute(params![key, id])?;
+                updated += 1;
+            }
+        }
+    }
+    tx.commit()?;
+
+    Ok(updated)
 }
\ No newline at end of file
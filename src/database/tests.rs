@@ -460,6 +460,7 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabcdef1234567890".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1111111111111111111111111111111111111111".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance address
             amount: "1500000000000000000000".to_string(), // 1500 POL in wei
@@ -496,6 +497,7 @@ mod tests {
             block_number: 12346,
             transaction_hash: "0xfedcba0987654321".to_string(),
             log_index: 1,
+            transaction_index: 0,
             from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(), // Binance address
             to_address: "0x2222222222222222222222222222222222222222".to_string(),
             amount: "800000000000000000000".to_string(), // 800 POL in wei
@@ -528,6 +530,7 @@ mod tests {
             block_number: 12347,
             transaction_hash: "0x1234567890abcdef".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1111111111111111111111111111111111111111".to_string(),
             to_address: "0x2222222222222222222222222222222222222222".to_string(),
             amount: "500000000000000000000".to_string(),
@@ -564,6 +567,7 @@ mod tests {
                 block_number: 12345,
                 transaction_hash: "0xhash1".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0x1111111111111111111111111111111111111111".to_string(),
                 to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
                 amount: "1000000000000000000000".to_string(), // 1000 POL
@@ -574,6 +578,7 @@ mod tests {
                 block_number: 12346,
                 transaction_hash: "0xhash2".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(),
                 to_address: "0x2222222222222222222222222222222222222222".to_string(),
                 amount: "600000000000000000000".to_string(), // 600 POL
@@ -584,6 +589,7 @@ mod tests {
                 block_number: 12347,
                 transaction_hash: "0xhash3".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0x3333333333333333333333333333333333333333".to_string(),
                 to_address: "0x505e71695e9bc45943c58adec1650577bca68fd9".to_string(),
                 amount: "2000000000000000000000".to_string(), // 2000 POL
@@ -650,6 +656,7 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xbadtransfer".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1111111111111111111111111111111111111111".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             amount: "invalid_amount".to_string(),
@@ -673,4 +680,1775 @@ mod tests {
         let count = db.get_transaction_count().expect("Failed to get transaction count");
         assert_eq!(count, 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_seed_net_flow_on_fresh_database() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+
+        db.seed_net_flow("5000", "1500", 50000000).expect("Failed to seed net flow");
+
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "5000");
+        assert_eq!(net_flow.total_outflow, "1500");
+        assert_eq!(net_flow.net_flow, "3500");
+        assert_eq!(net_flow.last_processed_block, 50000000);
+
+        let last_block = db.get_last_processed_block().expect("Failed to get last processed block");
+        assert_eq!(last_block, 50000000);
+
+        // Subsequent transfers accumulate on top of the seeded totals
+        db.update_net_flow_inflow("500").expect("Failed to update inflow");
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "5500");
+        assert_eq!(net_flow.net_flow, "4000");
+    }
+
+    #[test]
+    fn test_seed_net_flow_converts_wei_in_decimal_mode() {
+        use crate::models::AmountStorage;
+
+        let db = Database::new_in_memory_with_amount_storage(AmountStorage::Decimal)
+            .expect("Failed to create database");
+
+        db.seed_net_flow("5000000000000000000", "1500000000000000000", 100).expect("Failed to seed net flow");
+        assert!(db.seed_net_flow_with_force("1.5", "0", 100, true).is_err());
+
+        // Later transfers are added in the same unit as the seed
+        db.update_net_flow_inflow("0.5").expect("Failed to update inflow");
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "5.5");
+        assert_eq!(net_flow.total_outflow, "1.5");
+        assert_eq!(net_flow.net_flow, "4");
+    }
+
+    #[test]
+    fn test_recompute_net_flow_includes_seeded_baseline() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        db.seed_net_flow("5000", "1500", 100).expect("Failed to seed net flow");
+        let transfer = crate::models::ProcessedTransfer {
+            block_number: 101,
+            transaction_hash: "0xafterseed".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(),
+            to_address: "0x2222222222222222222222222222222222222222".to_string(),
+            amount: "300".to_string(),
+            timestamp: 1640995300,
+            direction: crate::models::TransferDirection::FromBinance,
+        };
+        db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+
+        let recomputed = db.recompute_net_flow().expect("Failed to recompute net flow");
+        let stored = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(recomputed.total_inflow, "5000");
+        assert_eq!(recomputed.total_outflow, "1800");
+        assert_eq!(recomputed.net_flow, stored.net_flow);
+    }
+
+    #[test]
+    fn test_seed_net_flow_rejects_existing_data_without_force() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        db.update_net_flow_inflow("1000").expect("Failed to update inflow");
+
+        let result = db.seed_net_flow("5000", "1500", 50000000);
+        assert!(matches!(result, Err(DbError::Operation(_))));
+
+        // Existing data must be left untouched
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "1000");
+        assert_eq!(net_flow.net_flow, "1000");
+
+        // Forcing the seed overwrites the existing totals
+        db.seed_net_flow_with_force("5000", "1500", 50000000, true).expect("Failed to force seed");
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "5000");
+        assert_eq!(net_flow.net_flow, "3500");
+    }
+
+    #[test]
+    fn test_seed_net_flow_rejects_invalid_amounts() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+
+        assert!(db.seed_net_flow("not_a_number", "0", 100).is_err());
+        assert!(db.seed_net_flow("100", "-5", 100).is_err());
+        // Wei amounts must be whole numbers
+        assert!(db.seed_net_flow("1.5", "0", 100).is_err());
+
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!(net_flow.total_inflow, "0");
+        assert_eq!(net_flow.last_processed_block, 0);
+
+        // Accepted amounts are stored in canonical form
+        db.seed_net_flow("007", "00", 100).expect("Failed to seed net flow");
+        let net_flow = db.get_net_flow_data().expect("Failed to get net flow");
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.total_outflow.as_str()), ("7", "0"));
+        assert_eq!(net_flow.net_flow, "7");
+    }
+
+    #[test]
+    fn test_net_flow_between_excludes_out_of_range_blocks() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+
+        let transfers = [
+            (99, "0xbefore", "5000000000000000000000", crate::models::TransferDirection::ToBinance),
+            (100, "0xstart", "1500000000000000000000", crate::models::TransferDirection::ToBinance),
+            (105, "0xmiddle", "400000000000000000001", crate::models::TransferDirection::FromBinance),
+            (110, "0xend", "250000000000000000000", crate::models::TransferDirection::ToBinance),
+            (111, "0xafter", "9000000000000000000000", crate::models::TransferDirection::FromBinance),
+        ];
+
+        for (block_number, hash, amount, direction) in transfers {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number,
+                transaction_hash: hash.to_string(),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: amount.to_string(),
+                timestamp: 1640995200,
+                direction,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+
+        let ranged = db.net_flow_between(100, 110).expect("Failed to get ranged net flow");
+        assert_eq!(ranged.total_inflow, "1750000000000000000000");
+        assert_eq!(ranged.total_outflow, "400000000000000000001");
+        assert_eq!(ranged.net_flow, "1349999999999999999999");
+        assert_eq!(ranged.last_processed_block, 110);
+
+        // A single-block range only includes that block
+        let single = db.net_flow_between(105, 105).expect("Failed to get single block net flow");
+        assert_eq!(single.total_inflow, "0");
+        assert_eq!(single.net_flow, "-400000000000000000001");
+
+        // An empty range yields zero totals
+        let empty = db.net_flow_between(200, 300).expect("Failed to get empty range net flow");
+        assert_eq!(empty.net_flow, "0");
+
+        assert!(matches!(db.net_flow_between(110, 100), Err(DbError::Operation(_))));
+    }
+
+    #[test]
+    fn test_skipped_block_lifecycle() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        assert_eq!(db.get_skipped_block_count().unwrap(), 0);
+
+        db.record_skipped_block(500, "RPC error: timeout").expect("Failed to record skipped block");
+        db.record_skipped_block(400, "RPC error: timeout").expect("Failed to record skipped block");
+        db.record_skipped_block(500, "RPC error: header not found").expect("Failed to re-record skipped block");
+
+        let skipped = db.get_skipped_blocks().expect("Failed to get skipped blocks");
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].block_number, 400);
+        assert_eq!(skipped[1].block_number, 500);
+        assert_eq!(skipped[1].skip_count, 2);
+        assert_eq!(skipped[1].error, "RPC error: header not found");
+        assert_eq!(db.get_skipped_block_count().unwrap(), 2);
+
+        assert_eq!(db.request_skipped_block_reprocess(Some(500)).unwrap(), 1);
+        assert_eq!(db.request_skipped_block_reprocess(Some(600)).unwrap(), 0);
+        let requested = db.get_reprocess_requested_blocks().unwrap();
+        assert_eq!(requested.len(), 1);
+        assert_eq!(requested[0].block_number, 500);
+
+        assert_eq!(db.request_skipped_block_reprocess(None).unwrap(), 2);
+
+        db.remove_skipped_block(500).expect("Failed to remove skipped block");
+        assert_eq!(db.get_skipped_block_count().unwrap(), 1);
+        assert!(matches!(db.remove_skipped_block(500), Err(DbError::NotFound)));
+    }
+
+    #[test]
+    fn test_amount_storage_modes_agree_after_conversion() {
+        use crate::models::AmountStorage;
+
+        let transfers = [
+            ("0xinflow", "1500000000000000000001", crate::models::TransferDirection::ToBinance),
+            ("0xoutflow", "250500000000000000000", crate::models::TransferDirection::FromBinance),
+        ];
+
+        let wei_db = Database::new_in_memory_with_amount_storage(AmountStorage::Wei).expect("Failed to create wei database");
+        let decimal_db = Database::new_in_memory_with_amount_storage(AmountStorage::Decimal)
+            .expect("Failed to create decimal database");
+
+        for (hash, amount, direction) in transfers {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 100,
+                transaction_hash: hash.to_string(),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: amount.to_string(),
+                timestamp: 1640995200,
+                direction,
+            };
+            wei_db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store wei transfer");
+            decimal_db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store decimal transfer");
+        }
+
+        let wei_tx = wei_db.get_transaction("0xinflow", 0).unwrap();
+        let decimal_tx = decimal_db.get_transaction("0xinflow", 0).unwrap();
+        assert_eq!(wei_tx.amount, "1500000000000000000001");
+        assert_eq!(decimal_tx.amount, "1500.000000000000000001");
+        assert_ne!(wei_tx.amount, decimal_tx.amount);
+
+        let wei_flow = wei_db.get_net_flow_data().unwrap();
+        let decimal_flow = decimal_db.get_net_flow_data().unwrap();
+        assert_eq!(decimal_flow.net_flow, "1249.500000000000000001");
+        for (wei_total, decimal_total) in [
+            (&wei_flow.total_inflow, &decimal_flow.total_inflow),
+            (&wei_flow.total_outflow, &decimal_flow.total_outflow),
+            (&wei_flow.net_flow, &decimal_flow.net_flow),
+        ] {
+            assert_eq!(&AmountStorage::Decimal.stored_to_wei(decimal_total).unwrap(), wei_total);
+        }
+    }
+
+    #[test]
+    fn test_amount_storage_mode_mismatch_rejected() {
+        use crate::models::AmountStorage;
+
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+
+        let db = Database::new_with_amount_storage(path, AmountStorage::Decimal).expect("Failed to create database");
+        assert_eq!(db.amount_storage(), AmountStorage::Decimal);
+        drop(db);
+
+        // Reopening without an explicit mode adopts the recorded one
+        let db = Database::new(path).expect("Failed to reopen database");
+        assert_eq!(db.amount_storage(), AmountStorage::Decimal);
+        drop(db);
+
+        let result = Database::new_with_amount_storage(path, AmountStorage::Wei);
+        assert!(matches!(result, Err(DbError::Operation(message)) if message.contains("decimal")));
+    }
+
+    #[tokio::test]
+    async fn test_verify_against_chain_flags_changed_block_hash() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for (block_hex, chain_hash) in [("0x64", "0xaaa"), ("0x65", "0xreorged")] {
+            Mock::given(method("POST"))
+                .and(body_partial_json(serde_json::json!({
+                    "method": "eth_getBlockByNumber",
+                    "params": [block_hex, true]
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "number": block_hex,
+                        "hash": chain_hash,
+                        "timestamp": "0x61cf9980",
+                        "transactions": []
+                    }
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        db.record_block_hash(100, "0xAAA").expect("Failed to record block hash");
+        db.record_block_hash(101, "0xbbb").expect("Failed to record block hash");
+        assert_eq!(db.get_block_hash(101).unwrap().as_deref(), Some("0xbbb"));
+        assert_eq!(db.get_block_hash(102).unwrap(), None);
+
+        let rpc = crate::blockchain::RpcClient::new(server.uri());
+        let mismatched = db.verify_against_chain(&rpc, 100, 105).await.expect("Verification failed");
+        assert_eq!(mismatched, vec![101]);
+
+        assert!(db.verify_against_chain(&rpc, 105, 100).await.is_err());
+    }
+
+    #[test]
+    fn test_net_flow_last_excludes_transfers_outside_window() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        let now = 1_700_000_000u64;
+        let hour = 3_600u64;
+
+        // One transfer every 6 hours across the last 48 hours: 8 transfers, alternating direction
+        for i in 0..8u64 {
+            let direction = if i % 2 == 0 {
+                crate::models::TransferDirection::ToBinance
+            } else {
+                crate::models::TransferDirection::FromBinance
+            };
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 1000 + i,
+                transaction_hash: format!("0xrolling{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: format!("{}", (i + 1) * 100),
+                timestamp: now - 47 * hour + i * 6 * hour,
+                direction,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+
+        // The 24h window holds transfers 4..=7 (timestamps now-23h .. now-5h)
+        let rolling = db.net_flow_last(24 * hour, now).expect("Failed to get rolling net flow");
+        assert_eq!(rolling.total_inflow, "1200"); // 500 + 700
+        assert_eq!(rolling.total_outflow, "1400"); // 600 + 800
+        assert_eq!(rolling.net_flow, "-200");
+        assert_eq!(rolling.last_processed_block, db.get_last_processed_block().unwrap());
+
+        // The full 48h window matches the cumulative totals
+        let all = db.net_flow_last(48 * hour, now).expect("Failed to get rolling net flow");
+        assert_eq!(all.net_flow, db.get_net_flow_data().unwrap().net_flow);
+    }
+
+    #[test]
+    fn test_for_each_transaction_streams_rows_in_order() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        let row_count = 2_500u64;
+
+        for i in 0..row_count {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 5_000 + i / 10,
+                transaction_hash: format!("0xstream{}", i),
+                log_index: (i % 10) as u32,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: "1".to_string(),
+                timestamp: 1_700_000_000 + i,
+                direction: crate::models::TransferDirection::ToBinance,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+
+        let mut visited = 0u64;
+        let mut last_id = 0i64;
+        db.for_each_transaction(|row| {
+            assert!(row.id > last_id, "rows must arrive in id order");
+            assert_eq!(row.transaction_hash, format!("0xstream{}", visited));
+            last_id = row.id;
+            visited += 1;
+        })
+        .expect("Failed to stream transactions");
+
+        assert_eq!(visited, row_count);
+
+        // An empty table never invokes the callback
+        let empty = Database::new_in_memory().expect("Failed to create test database");
+        empty.for_each_transaction(|_| panic!("no rows expected")).unwrap();
+    }
+
+    #[test]
+    fn test_recompute_net_flow_matches_rust_side_sum() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("aggregate.db");
+        let path = path.to_str().unwrap();
+
+        // Readers never block the writer under WAL
+        rusqlite::Connection::open(path)
+            .unwrap()
+            .query_row("PRAGMA journal_mode=WAL", [], |row| row.get::<_, String>(0))
+            .unwrap();
+        let db = Database::new(path).expect("Failed to create test database");
+
+        // Amounts well above i64::MAX so integer or floating point sums would be wrong
+        for i in 0..3_000u64 {
+            let direction = if i % 3 == 0 {
+                crate::models::TransferDirection::FromBinance
+            } else {
+                crate::models::TransferDirection::ToBinance
+            };
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 10_000 + i,
+                transaction_hash: format!("0xaggregate{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: format!("{}123456789012345678901", i + 1),
+                timestamp: 1_700_000_000 + i,
+                direction,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+        db.set_last_processed_block(12_999).unwrap();
+
+        let mut inflow = crate::models::DecimalAmount::zero();
+        let mut outflow = crate::models::DecimalAmount::zero();
+        db.for_each_transaction(|row| {
+            let amount: crate::models::DecimalAmount = row.amount.parse().unwrap();
+            match row.direction.as_str() {
+                "inflow" => inflow = inflow.add(&amount),
+                _ => outflow = outflow.add(&amount),
+            }
+        })
+        .unwrap();
+
+        // Hold a write transaction on another connection while recomputing
+        let writer = rusqlite::Connection::open(path).unwrap();
+        writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+        let recomputed = db.recompute_net_flow().expect("Failed to recompute net flow");
+        writer.execute_batch("ROLLBACK").unwrap();
+
+        assert_eq!(recomputed.total_inflow, inflow.to_string());
+        assert_eq!(recomputed.total_outflow, outflow.to_string());
+        assert_eq!(recomputed.net_flow, inflow.sub(&outflow).to_string());
+        assert_eq!(recomputed.last_processed_block, 12_999);
+
+        // The incremental totals agree with the aggregate, as do range queries
+        let stored = db.get_net_flow_data().unwrap();
+        assert_eq!(stored.total_inflow, recomputed.total_inflow);
+        assert_eq!(stored.net_flow, recomputed.net_flow);
+        assert_eq!(db.net_flow_between(0, u64::MAX >> 1).unwrap().net_flow, recomputed.net_flow);
+
+        // An empty database recomputes to zero
+        let empty = Database::new_in_memory().unwrap().recompute_net_flow().unwrap();
+        assert_eq!((empty.total_inflow.as_str(), empty.net_flow.as_str()), ("0", "0"));
+    }
+
+    #[test]
+    fn test_get_transactions_since_pages_forward_by_id() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+
+        for i in 0..5u64 {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 7_000 - i,
+                transaction_hash: format!("0xsince{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: "1".to_string(),
+                timestamp: 1_700_000_000 + i,
+                direction: crate::models::TransferDirection::ToBinance,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+
+        // Ordered by insertion id regardless of block number
+        let first = db.get_transactions_since(0, 2).unwrap();
+        let hashes: Vec<&str> = first.iter().map(|row| row.transaction_hash.as_str()).collect();
+        assert_eq!(hashes, vec!["0xsince0", "0xsince1"]);
+
+        let rest = db.get_transactions_since(first[1].id, 10).unwrap();
+        assert_eq!(rest.len(), 3);
+        assert_eq!(rest[0].transaction_hash, "0xsince2");
+
+        assert!(db.get_transactions_since(rest[2].id, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_store_transfers_batch_matches_individual_stores() {
+        let batched = Database::new_in_memory().expect("Failed to create test database");
+        let individual = Database::new_in_memory().expect("Failed to create test database");
+
+        let transfers: Vec<crate::models::ProcessedTransfer> = (0..6u64)
+            .map(|i| crate::models::ProcessedTransfer {
+                block_number: 8_000,
+                transaction_hash: format!("0xbatch{}", i),
+                log_index: i as u32,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: format!("{}000000000000000000", i + 1),
+                timestamp: 1_700_000_000,
+                direction: match i % 3 {
+                    0 => crate::models::TransferDirection::ToBinance,
+                    1 => crate::models::TransferDirection::FromBinance,
+                    _ => crate::models::TransferDirection::NotRelevant,
+                },
+            })
+            .collect();
+
+        batched.store_transfers_and_update_net_flow(&transfers).expect("Failed to store batch");
+        for transfer in &transfers {
+            individual.store_transfer_and_update_net_flow(transfer).expect("Failed to store transfer");
+        }
+
+        let (batch_flow, single_flow) = (batched.get_net_flow_data().unwrap(), individual.get_net_flow_data().unwrap());
+        assert_eq!(batch_flow.total_inflow, single_flow.total_inflow);
+        assert_eq!(batch_flow.total_outflow, single_flow.total_outflow);
+        assert_eq!(batch_flow.net_flow, single_flow.net_flow);
+        assert_eq!(batched.get_transaction_count().unwrap(), 4);
+
+        // A duplicate in the batch rolls back every row and the totals
+        let duplicate = vec![transfers[0].clone(), transfers[0].clone()];
+        let fresh = Database::new_in_memory().expect("Failed to create test database");
+        assert!(fresh.store_transfers_and_update_net_flow(&duplicate).is_err());
+        assert_eq!(fresh.get_transaction_count().unwrap(), 0);
+        assert_eq!(fresh.get_net_flow_data().unwrap().total_inflow, "0");
+    }
+
+    #[test]
+    fn test_counterparties_groups_and_sums_per_address() {
+        use crate::models::{AmountStorage, ProcessedTransfer, TransferDirection};
+
+        let watched = "f977814e90da44bfa03b6295a0616a897441acec";
+        let alice = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let bob = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+        let carol = "cccccccccccccccccccccccccccccccccccccccc";
+        let other_watched = "e7804c37c13166ff0b37f5ae0bb07a3aebb6e245";
+
+        let transfers = [
+            (alice, watched, "1000000000000000000", TransferDirection::ToBinance),
+            (alice, watched, "2500000000000000000", TransferDirection::ToBinance),
+            (bob, watched, "9000000000000000000", TransferDirection::ToBinance),
+            (watched, carol, "4000000000000000000", TransferDirection::FromBinance),
+            (watched, alice, "1", TransferDirection::FromBinance),
+            // A different watched address must not leak into the results
+            (carol, other_watched, "7000000000000000000", TransferDirection::ToBinance),
+        ];
+
+        for storage in [AmountStorage::Wei, AmountStorage::Decimal] {
+            let db = Database::new_in_memory_with_amount_storage(storage).expect("Failed to create test database");
+            for (i, (from, to, amount, direction)) in transfers.iter().enumerate() {
+                db.store_transfer_and_update_net_flow(&ProcessedTransfer {
+                    block_number: 9_000 + i as u64,
+                    transaction_hash: format!("0xcp{}", i),
+                    log_index: 0,
+                    transaction_index: 0,
+                    from_address: from.to_string(),
+                    to_address: to.to_string(),
+                    amount: amount.to_string(),
+                    timestamp: 1_700_000_000,
+                    direction: direction.clone(),
+                })
+                .expect("Failed to store transfer");
+            }
+            // A transfer still inside the confirmation window is not counted yet
+            db.store_pending_transfers(&[ProcessedTransfer {
+                block_number: 9_100,
+                transaction_hash: "0xcppending".to_string(),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: carol.to_string(),
+                to_address: watched.to_string(),
+                amount: "50000000000000000000".to_string(),
+                timestamp: 1_700_000_000,
+                direction: TransferDirection::ToBinance,
+            }])
+            .expect("Failed to store pending transfer");
+
+            // Senders into the watched address, largest total first, summed in wei;
+            // the address is matched with or without a 0x prefix, in any case
+            let inflow = db.counterparties(&format!("0x{}", watched), "inflow", 10).unwrap();
+            assert_eq!(
+                inflow,
+                vec![
+                    (bob.to_string(), "9000000000000000000".to_string()),
+                    (alice.to_string(), "3500000000000000000".to_string()),
+                ]
+            );
+
+            let outflow = db.counterparties(&watched.to_uppercase(), "outflow", 10).unwrap();
+            assert_eq!(
+                outflow,
+                vec![
+                    (carol.to_string(), "4000000000000000000".to_string()),
+                    (alice.to_string(), "1".to_string()),
+                ]
+            );
+
+            assert_eq!(db.counterparties(watched, "inflow", 1).unwrap().len(), 1);
+            assert!(db.counterparties("0x0000000000000000000000000000000000000000", "inflow", 10).unwrap().is_empty());
+            assert!(db.counterparties(watched, "sideways", 10).is_err());
+        }
+    }
+
+    #[test]
+    fn test_net_flow_row_sign() {
+        use crate::database::NetFlowRow;
+
+        let row = |net_flow: &str| NetFlowRow {
+            id: 1,
+            total_inflow: "0".to_string(),
+            total_outflow: "0".to_string(),
+            net_flow: net_flow.to_string(),
+            last_processed_block: 0,
+            last_updated: 0,
+        };
+
+        let positive = row("170141183460469231731687303715884105727");
+        assert_eq!(positive.net_flow_signed().unwrap(), i128::MAX);
+        assert!(positive.is_net_inflow());
+        assert_eq!(positive.flow_direction(), "inflow");
+
+        let negative = row("-750");
+        assert_eq!(negative.net_flow_signed().unwrap(), -750);
+        assert!(!negative.is_net_inflow());
+        assert_eq!(negative.flow_direction(), "outflow");
+
+        let zero = row("0");
+        assert_eq!(zero.net_flow_signed().unwrap(), 0);
+        assert!(!zero.is_net_inflow());
+        assert_eq!(zero.flow_direction(), "neutral");
+
+        // POL storage keeps fractions, which have no integer form but still have a sign
+        let fractional = row("-0.5");
+        assert!(fractional.net_flow_signed().is_err());
+        assert_eq!(fractional.flow_direction(), "outflow");
+        assert!(row("not a number").net_flow_signed().is_err());
+    }
+
+    #[test]
+    fn test_amount_range_query_uses_numeric_index() {
+        use crate::database::operations::TRANSACTIONS_ABOVE_QUERY;
+
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        let db = Database::new(path).expect("Failed to create database");
+
+        // String order would put "9" above "10" and "1000"
+        let amounts = [
+            "9",
+            "1000000000000000000000",
+            "10",
+            "340282366920938463463374607431768211456", // u128::MAX + 1
+            "999999999999999999",
+            "2000000000000000000",
+        ];
+        for (log_index, amount) in amounts.iter().enumerate() {
+            db.store_transaction(100, "0xrange", log_index as u32, "0xfrom", "0xto", amount, 1_700_000_000, "inflow")
+                .expect("Failed to store transaction");
+        }
+
+        let above: Vec<String> = db
+            .get_transactions_above("10", 10)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.amount)
+            .collect();
+        assert_eq!(
+            above,
+            vec![
+                "340282366920938463463374607431768211456",
+                "1000000000000000000000",
+                "2000000000000000000",
+                "999999999999999999",
+                "10",
+            ]
+        );
+
+        let top_two: Vec<String> = db.get_transactions_above("0", 2).unwrap().into_iter().map(|tx| tx.amount).collect();
+        assert_eq!(top_two, vec!["340282366920938463463374607431768211456", "1000000000000000000000"]);
+        assert!(db.get_transactions_above("not a number", 10).is_err());
+
+        // Minimums past u128::MAX share the oversized key but still compare exactly
+        let oversized = |min: &str| db.get_transactions_above(min, 10).unwrap().len();
+        assert_eq!(oversized("340282366920938463463374607431768211456"), 1);
+        assert_eq!(oversized("340282366920938463463374607431768211457"), 0);
+
+        let conn = rusqlite::Connection::open(path).unwrap();
+        let plan: Vec<String> = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", TRANSACTIONS_ABOVE_QUERY))
+            .unwrap()
+            .query_map(rusqlite::params!["0", 10], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(
+            plan.iter().any(|detail| detail.contains("USING INDEX idx_transactions_amount_u128")),
+            "query plan does not use the amount index: {:?}",
+            plan
+        );
+        assert!(!plan.iter().any(|detail| detail.contains("TEMP B-TREE")), "query plan sorts: {:?}", plan);
+    }
+
+    #[test]
+    fn test_amount_index_migration_backfills_existing_rows() {
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+
+        // A transactions table from before amounts were indexed
+        {
+            let conn = rusqlite::Connection::open(path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE transactions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    block_number INTEGER NOT NULL,
+                    transaction_hash TEXT NOT NULL,
+                    log_index INTEGER NOT NULL,
+                    from_address TEXT NOT NULL,
+                    to_address TEXT NOT NULL,
+                    amount TEXT NOT NULL,
+                    timestamp INTEGER NOT NULL,
+                    direction TEXT NOT NULL CHECK (direction IN ('inflow', 'outflow')),
+                    created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                    UNIQUE(transaction_hash, log_index)
+                );
+                INSERT INTO transactions (block_number, transaction_hash, log_index, from_address, to_address, amount, timestamp, direction)
+                VALUES (1, '0xold', 0, '0xfrom', '0xto', '15', 0, 'inflow'),
+                       (1, '0xold', 1, '0xfrom', '0xto', '200', 0, 'outflow'),
+                       (1, '0xold', 2, '0xfrom', '0xto', '3', 0, 'outflow');",
+            )
+            .unwrap();
+        }
+
+        let db = Database::new(path).expect("Failed to migrate database");
+        let amounts: Vec<String> = db.get_transactions_above("10", 10).unwrap().into_iter().map(|tx| tx.amount).collect();
+        assert_eq!(amounts, vec!["200", "15"]);
+
+        // Rows from before transaction positions were recorded read back as index 0
+        let rows = db.get_transactions_by_block(1).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|tx| tx.transaction_index == 0));
+
+        // The rebuilt table keeps its ids and accepts internal transfers
+        assert_eq!(rows.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        db.store_transaction(2, "0xinternal", 0, "0xfrom", "0xto", "5", 0, "internal").unwrap();
+        assert_eq!(db.get_transaction("0xinternal", 0).unwrap().id, 4);
+    }
+
+    #[test]
+    fn test_net_flow_snapshots_copy_current_totals() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+
+        db.record_net_flow_snapshot(10).unwrap();
+        db.update_net_flow_inflow("500").unwrap();
+        db.update_net_flow_outflow("200").unwrap();
+        db.record_net_flow_snapshot(11).unwrap();
+
+        let snapshots = db.get_net_flow_snapshots(10).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].block_number, 10);
+        assert_eq!(snapshots[0].net_flow, "0");
+        assert_eq!(snapshots[1].block_number, 11);
+        assert_eq!(snapshots[1].total_inflow, "500");
+        assert_eq!(snapshots[1].total_outflow, "200");
+        assert_eq!(snapshots[1].net_flow, "300");
+        assert_eq!(db.get_net_flow_snapshots(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_synchronous_pragma_is_applied() {
+        use crate::database::SynchronousMode;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("synchronous.db");
+        let db = Database::new(path.to_str().unwrap()).expect("Failed to create database");
+        assert_eq!(db.synchronous().unwrap(), Some(SynchronousMode::Full));
+
+        db.set_synchronous(SynchronousMode::Normal).unwrap();
+        assert_eq!(db.synchronous().unwrap(), Some(SynchronousMode::Normal));
+        // Writes still go through under the relaxed setting
+        db.set_last_processed_block(42).unwrap();
+        assert_eq!(db.get_last_processed_block().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_exhausted_read_pool_times_out_with_retryable_error() {
+        use crate::database::pool::ReadPool;
+        use crate::error::{DatabaseError, IndexerError};
+        use std::time::{Duration, Instant};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pool.db");
+        Database::new(path.to_str().unwrap()).expect("Failed to create database");
+
+        let pool = ReadPool::new(path.to_str().unwrap(), 1, Duration::from_millis(50));
+        let held = pool.checkout().unwrap();
+
+        let started = Instant::now();
+        let error = pool.checkout().err().expect("second checkout should time out");
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(matches!(error, DbError::PoolExhausted { timeout_ms: 50 }), "unexpected error: {:?}", error);
+
+        let error = IndexerError::from(error);
+        assert!(matches!(error, IndexerError::Database(DatabaseError::PoolExhausted(_))));
+        assert!(error.is_recoverable());
+
+        // Returning the connection frees the slot again
+        drop(held);
+        let conn = pool.checkout().unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_get_transactions_by_block_orders_by_log_index_then_id() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+
+        // Stored out of log_index order, with two transactions sharing log index 1
+        for (hash, log_index) in [("0xc", 2), ("0xb", 1), ("0xa", 1), ("0xd", 0)] {
+            db.store_transaction(
+                500,
+                hash,
+                log_index,
+                "0x1111111111111111111111111111111111111111",
+                "0x2222222222222222222222222222222222222222",
+                "1",
+                1640995200,
+                "inflow",
+            ).expect("Failed to store transaction");
+        }
+
+        for _ in 0..3 {
+            let hashes: Vec<String> = db
+                .get_transactions_by_block(500)
+                .expect("Failed to get transactions by block")
+                .into_iter()
+                .map(|tx| tx.transaction_hash)
+                .collect();
+            assert_eq!(hashes, vec!["0xd", "0xb", "0xa", "0xc"]);
+        }
+    }
+
+    #[test]
+    fn test_query_builder_binds_every_value() {
+        use crate::database::query::QueryBuilder;
+        use rusqlite::types::Value;
+
+        let (sql, params) = QueryBuilder::new("SELECT id FROM transactions")
+            .and_where("(from_address = ? OR to_address = ?)", [Value::from("0xabc".to_string()), Value::from("0xabc".to_string())])
+            .and_where_opt("direction = ?", None::<String>)
+            .and_where_opt("block_number >= ?", Some(10i64))
+            .order_by("block_number, log_index")
+            .limit(5)
+            .offset(20)
+            .build();
+
+        assert_eq!(
+            sql,
+            "SELECT id FROM transactions WHERE (from_address = ? OR to_address = ?) AND block_number >= ? \
+             ORDER BY block_number, log_index LIMIT ? OFFSET ?"
+        );
+        assert_eq!(
+            params,
+            vec![
+                Value::Text("0xabc".to_string()),
+                Value::Text("0xabc".to_string()),
+                Value::Integer(10),
+                Value::Integer(5),
+                Value::Integer(20),
+            ]
+        );
+
+        let (sql, params) = QueryBuilder::new("SELECT id FROM transactions").build();
+        assert_eq!(sql, "SELECT id FROM transactions");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_query_transactions_filters() {
+        use crate::database::TransactionFilter;
+
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let watched = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+        for (block, hash, from, to, direction) in [
+            (100, "0xa", "1111111111111111111111111111111111111111", "f977814e90da44bfa03b6295a0616a897441acec", "inflow"),
+            (101, "0xb", "f977814e90da44bfa03b6295a0616a897441acec", "2222222222222222222222222222222222222222", "outflow"),
+            (102, "0xc", "3333333333333333333333333333333333333333", "4444444444444444444444444444444444444444", "inflow"),
+        ] {
+            db.store_transaction(block, hash, 0, from, to, "1", 1640995200, direction).unwrap();
+        }
+        let hashes = |filter: TransactionFilter| -> Vec<String> {
+            db.query_transactions(&filter, 10).unwrap().into_iter().map(|tx| tx.transaction_hash).collect()
+        };
+
+        assert_eq!(hashes(TransactionFilter::default()), vec!["0xa", "0xb", "0xc"]);
+        // Stored addresses are bare lowercase hex; the filter may carry a prefix and any case
+        assert_eq!(hashes(TransactionFilter { address: Some(watched.to_string()), ..Default::default() }), vec!["0xa", "0xb"]);
+        assert_eq!(
+            hashes(TransactionFilter { address: Some(watched[2..].to_lowercase()), ..Default::default() }),
+            vec!["0xa", "0xb"]
+        );
+        assert_eq!(hashes(TransactionFilter { direction: Some("inflow".to_string()), ..Default::default() }), vec!["0xa", "0xc"]);
+        assert_eq!(
+            hashes(TransactionFilter { from_block: Some(101), to_block: Some(102), ..Default::default() }),
+            vec!["0xb", "0xc"]
+        );
+        assert_eq!(db.query_transactions(&TransactionFilter::default(), 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_query_transactions_parameterizes_filter_values() {
+        use crate::database::TransactionFilter;
+
+        let db = Database::new_in_memory().expect("Failed to create database");
+        db.store_transaction(
+            100,
+            "0xa",
+            0,
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "1",
+            1640995200,
+            "inflow",
+        ).unwrap();
+
+        for malicious in ["'; DROP TABLE transactions; --", "' OR '1'='1"] {
+            let filter = TransactionFilter {
+                address: Some(malicious.to_string()),
+                direction: Some(malicious.to_string()),
+                ..Default::default()
+            };
+            assert!(db.query_transactions(&filter, 10).unwrap().is_empty());
+        }
+
+        // The table survived and still holds its row
+        assert_eq!(db.get_transaction_count().unwrap(), 1);
+        assert_eq!(db.query_transactions(&TransactionFilter::default(), 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_malformed_amounts_are_rejected_before_insert() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let binance = "0xf977814e90da44bfa03b6295a0616a897441acec";
+        let external = "0x1111111111111111111111111111111111111111";
+
+        for amount in ["not-a-number", "-5", "1.5", "0x10", ""] {
+            let result = db.store_transaction(1, "0xbad", 0, external, binance, amount, 0, "inflow");
+            assert!(matches!(result, Err(DbError::Operation(_))), "amount {:?} was accepted", amount);
+
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 1,
+                transaction_hash: "0xbad".to_string(),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: external.to_string(),
+                to_address: binance.to_string(),
+                amount: amount.to_string(),
+                timestamp: 0,
+                direction: crate::models::TransferDirection::ToBinance,
+            };
+            let result = db.store_transfers_and_update_net_flow(&[transfer]);
+            assert!(matches!(result, Err(DbError::Operation(_))), "amount {:?} was accepted", amount);
+        }
+        assert_eq!(db.get_transaction_count().unwrap(), 0);
+
+        // Accepted amounts are stored in canonical form
+        db.store_transaction(1, "0xgood", 0, external, binance, "0042", 0, "inflow").unwrap();
+        assert_eq!(db.get_transaction("0xgood", 0).unwrap().amount, "42");
+    }
+
+    #[test]
+    fn test_block_range() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        assert_eq!(db.block_range().unwrap(), None);
+
+        let binance = "0xf977814e90da44bfa03b6295a0616a897441acec";
+        let external = "0x1111111111111111111111111111111111111111";
+        for (block_number, hash) in [(250u64, "0xb"), (120, "0xa"), (980, "0xc")] {
+            db.store_transaction(block_number, hash, 0, external, binance, "1", 0, "inflow").unwrap();
+        }
+        assert_eq!(db.block_range().unwrap(), Some((120, 980)));
+    }
+
+    #[test]
+    fn test_internal_transfers_are_stored_without_moving_net_flow() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let internal = |hash: &str| crate::models::ProcessedTransfer {
+            block_number: 500,
+            transaction_hash: hash.to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            to_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(),
+            amount: "1000".to_string(),
+            timestamp: 0,
+            direction: crate::models::TransferDirection::Internal,
+        };
+
+        db.store_transfer_and_update_net_flow(&internal("0xinternal1")).unwrap();
+        db.store_transfers_and_update_net_flow(&[internal("0xinternal2")]).unwrap();
+
+        assert_eq!(db.get_transaction("0xinternal1", 0).unwrap().direction, "internal");
+        assert_eq!(db.get_transaction("0xinternal2", 0).unwrap().direction, "internal");
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "0");
+        assert_eq!(net_flow.total_outflow, "0");
+        assert_eq!(net_flow.net_flow, "0");
+    }
+
+    #[test]
+    fn test_rebuild_from_raw_logs_restores_derived_data() {
+        use crate::blockchain::TransferDetector;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let binance = "0xf977814e90da44bfa03b6295a0616a897441acec";
+        let other_binance = "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245";
+        let external = "0x1111111111111111111111111111111111111111";
+        let log = |block_number: u64, log_index: u32, from: &str, to: &str, amount: &str| {
+            MockPolygonNode::transfer_log(&ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0xjournal{}{}", block_number, log_index),
+                log_index,
+                transaction_index: 0,
+                from_address: from.to_string(),
+                to_address: to.to_string(),
+                amount: amount.to_string(),
+                timestamp: 0,
+                direction: TransferDirection::NotRelevant,
+            })
+        };
+
+        db.store_raw_logs(1_000, &[log(10, 0, external, binance, "700"), log(10, 1, external, external, "5")]).unwrap();
+        db.store_raw_logs(1_002, &[log(11, 0, binance, external, "200"), log(11, 1, binance, other_binance, "50")]).unwrap();
+        // Journaling a block again keeps a single copy of each log
+        db.store_raw_logs(1_002, &[log(11, 0, binance, external, "200")]).unwrap();
+        assert_eq!(db.get_raw_log_count().unwrap(), 4);
+
+        // Corrupt the derived tables
+        db.store_transaction(10, "0xbogus", 0, external, binance, "123", 0, "inflow").unwrap();
+        db.update_net_flow_inflow("999999").unwrap();
+
+        let summary = db.rebuild_from_raw_logs(&TransferDetector::new(), false).unwrap();
+        assert_eq!(summary, crate::database::RebuildSummary { logs_replayed: 4, transfers_stored: 2, undecodable_logs: 0 });
+
+        let rows = db.get_transactions_by_block(10).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].direction.as_str(), rows[0].amount.as_str(), rows[0].timestamp), ("inflow", "700", 1_000));
+        let rows = db.get_transactions_by_block(11).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].direction.as_str(), rows[0].timestamp), ("outflow", 1_002));
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.total_outflow.as_str(), net_flow.net_flow.as_str()), ("700", "200", "500"));
+
+        // Rebuilding with a different classification reclassifies the same logs
+        let detector = TransferDetector::new().with_classify_internal_transfers(true);
+        let summary = db.rebuild_from_raw_logs(&detector, false).unwrap();
+        assert_eq!(summary.transfers_stored, 3);
+        assert_eq!(db.get_transaction("0xjournal111", 1).unwrap().direction, "internal");
+        assert_eq!(db.get_net_flow_data().unwrap().net_flow, "500");
+    }
+
+    #[test]
+    fn test_open_read_only_serves_reads_without_writing() {
+        use crate::models::AmountStorage;
+
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        Database::new_with_amount_storage(path, AmountStorage::Wei).unwrap().update_net_flow_inflow("700").unwrap();
+
+        let db = Database::open_read_only(path, AmountStorage::Wei).unwrap();
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "700");
+        assert!(db.update_net_flow_inflow("1").is_err());
+        assert!(matches!(
+            Database::open_read_only(path, AmountStorage::Decimal),
+            Err(DbError::Operation(message)) if message.contains("amounts as wei")
+        ));
+    }
+
+    #[test]
+    fn test_open_read_only_rejects_an_unmigrated_database() {
+        use crate::database::schema::{set_metadata, SCHEMA_VERSION_KEY};
+        use crate::models::AmountStorage;
+
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        let conn = rusqlite::Connection::open(path).unwrap();
+        conn.execute("CREATE TABLE transactions (id INTEGER PRIMARY KEY)", []).unwrap();
+
+        let result = Database::open_read_only(path, AmountStorage::Wei);
+        assert!(matches!(result, Err(DbError::Operation(message)) if message.contains("version none")));
+        // Nothing was created or migrated
+        let tables: u32 = conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'", [], |row| row.get(0)).unwrap();
+        assert_eq!(tables, 1);
+
+        // A database migrated by an older indexer records an older version
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        drop(Database::new(path).unwrap());
+        let conn = rusqlite::Connection::open(path).unwrap();
+        set_metadata(&conn, SCHEMA_VERSION_KEY, "0").unwrap();
+        let result = Database::open_read_only(path, AmountStorage::Wei);
+        assert!(matches!(result, Err(DbError::Operation(message)) if message.contains("version 0")));
+    }
+
+    #[test]
+    fn test_rebuild_keeps_transactions_from_blocks_indexed_before_journaling() {
+        use crate::blockchain::TransferDetector;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let binance = "0xf977814e90da44bfa03b6295a0616a897441acec";
+        let external = "0x1111111111111111111111111111111111111111";
+        let log = |log_index: u32, amount: &str| {
+            MockPolygonNode::transfer_log(&ProcessedTransfer {
+                block_number: 20,
+                transaction_hash: format!("0xjournal20{}", log_index),
+                log_index,
+                transaction_index: 0,
+                from_address: external.to_string(),
+                to_address: binance.to_string(),
+                amount: amount.to_string(),
+                timestamp: 0,
+                direction: TransferDirection::NotRelevant,
+            })
+        };
+
+        // Block 5 was indexed before journaling was enabled
+        let bare = |address: &str| crate::blockchain::normalize_address(address);
+        db.store_transaction(5, "0xunjournaled", 0, &bare(external), &bare(binance), "300", 500, "inflow").unwrap();
+        db.store_transaction(5, "0xunjournaled", 1, &bare(binance), &bare(external), "100", 500, "outflow").unwrap();
+        db.store_raw_logs(2_000, &[log(0, "40"), log(1, "0")]).unwrap();
+
+        let summary = db.rebuild_from_raw_logs(&TransferDetector::new(), false).unwrap();
+        assert_eq!(summary.transfers_stored, 1);
+        assert_eq!(db.get_transactions_by_block(5).unwrap().len(), 2);
+        assert_eq!(db.get_transactions_by_block(20).unwrap().len(), 1);
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.total_outflow.as_str(), net_flow.net_flow.as_str()), ("340", "100", "240"));
+
+        // Zero-amount transfers are rebuilt only when tracked
+        let summary = db.rebuild_from_raw_logs(&TransferDetector::new(), true).unwrap();
+        assert_eq!(summary.transfers_stored, 2);
+        assert_eq!(db.get_transactions_by_block(20).unwrap().len(), 2);
+        assert_eq!(db.get_transactions_by_block(5).unwrap().len(), 2);
+        assert_eq!(db.get_net_flow_data().unwrap().net_flow, "240");
+    }
+
+    #[test]
+    fn test_negative_net_flow_snapshots_query_uses_signed_index() {
+        use crate::database::operations::NET_FLOW_SNAPSHOTS_BELOW_QUERY;
+
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        let db = Database::new(path).expect("Failed to create database");
+
+        // Net flow after each block: 500, -250, -1000000000000000000000, -9, 41
+        db.update_net_flow_inflow("500").unwrap();
+        db.record_net_flow_snapshot(1).unwrap();
+        db.update_net_flow_outflow("750").unwrap();
+        db.record_net_flow_snapshot(2).unwrap();
+        db.update_net_flow_outflow("999999999999999999750").unwrap();
+        db.record_net_flow_snapshot(3).unwrap();
+        db.update_net_flow_inflow("999999999999999999991").unwrap();
+        db.record_net_flow_snapshot(4).unwrap();
+        db.update_net_flow_inflow("50").unwrap();
+        db.record_net_flow_snapshot(5).unwrap();
+
+        // String order would put "-9" below "-250"
+        let negative: Vec<(u64, String)> = db
+            .get_net_flow_snapshots_below("0", 10)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| (snapshot.block_number, snapshot.net_flow))
+            .collect();
+        assert_eq!(
+            negative,
+            vec![
+                (3, "-1000000000000000000000".to_string()),
+                (2, "-250".to_string()),
+                (4, "-9".to_string()),
+            ]
+        );
+        let below_minus_ten: Vec<u64> = db
+            .get_net_flow_snapshots_below("-10", 10)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.block_number)
+            .collect();
+        assert_eq!(below_minus_ten, vec![3, 2]);
+        assert_eq!(db.get_net_flow_snapshots_below("1000", 10).unwrap().len(), 5);
+        assert!(db.get_net_flow_snapshots_below("minus ten", 10).is_err());
+
+        let conn = rusqlite::Connection::open(path).unwrap();
+        let plan: Vec<String> = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", NET_FLOW_SNAPSHOTS_BELOW_QUERY))
+            .unwrap()
+            .query_map(rusqlite::params!["0", 10], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(
+            plan.iter().any(|detail| detail.contains("USING INDEX idx_net_flow_snapshots_net_flow")),
+            "query plan does not use the net-flow index: {:?}",
+            plan
+        );
+        assert!(!plan.iter().any(|detail| detail.contains("TEMP B-TREE")), "query plan sorts: {:?}", plan);
+    }
+
+    #[test]
+    fn test_net_flow_key_migration_backfills_existing_snapshots() {
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        {
+            let db = Database::new(path).expect("Failed to create database");
+            db.update_net_flow_outflow("300").unwrap();
+            db.record_net_flow_snapshot(7).unwrap();
+        }
+        {
+            // Simulate a database from before the signed key existed
+            let conn = rusqlite::Connection::open(path).unwrap();
+            conn.execute("DROP INDEX idx_net_flow_snapshots_net_flow", []).unwrap();
+            conn.execute("ALTER TABLE net_flow_snapshots DROP COLUMN net_flow_sort_key", []).unwrap();
+        }
+
+        let db = Database::new(path).expect("Failed to reopen database");
+        let negative = db.get_net_flow_snapshots_below("0", 10).unwrap();
+        assert_eq!(negative.len(), 1);
+        assert_eq!((negative[0].block_number, negative[0].net_flow.as_str()), (7, "-300"));
+    }
+
+    #[test]
+    fn test_net_flow_key_migration_renames_the_old_column() {
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let path = temp_file.path().to_str().unwrap();
+        {
+            let db = Database::new(path).expect("Failed to create database");
+            db.update_net_flow_outflow("300").unwrap();
+            db.record_net_flow_snapshot(7).unwrap();
+        }
+        {
+            // Simulate a database from when the key column was named net_flow_i128
+            let conn = rusqlite::Connection::open(path).unwrap();
+            conn.execute("ALTER TABLE net_flow_snapshots RENAME COLUMN net_flow_sort_key TO net_flow_i128", []).unwrap();
+        }
+
+        let db = Database::new(path).expect("Failed to reopen database");
+        db.update_net_flow_inflow("100").unwrap();
+        db.record_net_flow_snapshot(8).unwrap();
+        let negative = db.get_net_flow_snapshots_below("0", 10).unwrap();
+        let blocks: Vec<u64> = negative.iter().map(|snapshot| snapshot.block_number).collect();
+        assert_eq!(blocks, vec![7, 8]);
+    }
+
+    #[test]
+    fn test_flow_series_accumulates_per_bucket() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let store = |log_index: u32, timestamp: u64, amount: &str, direction: &str| {
+            db.store_transaction(1, "0xseries", log_index, "0xfrom", "0xto", amount, timestamp, direction)
+                .expect("Failed to store transaction");
+        };
+        // Before the range: counted in the baseline
+        store(0, 3_500, "1000", "inflow");
+        // Bucket 3600
+        store(1, 3_600, "18446744073709551616", "inflow"); // 2^64, past SQLite's integer range
+        store(2, 7_199, "300", "outflow");
+        // Bucket 7200 is empty
+        // Bucket 10800
+        store(3, 10_800, "5", "inflow");
+        store(4, 14_399, "45", "outflow");
+        // After the range
+        store(5, 14_400, "999", "inflow");
+
+        let series = db.flow_series(3_600, 4_000, 14_000).unwrap();
+        let points: Vec<(u64, &str, &str)> = series
+            .iter()
+            .map(|point| (point.bucket, point.cumulative_inflow.as_str(), point.cumulative_outflow.as_str()))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (3_600, "18446744073709552616", "300"),
+                (7_200, "18446744073709552616", "300"),
+                (10_800, "18446744073709552621", "345"),
+            ]
+        );
+
+        // Per-bucket flows exclude the baseline and are zero for empty buckets
+        let flows: Vec<(&str, &str)> = series.iter().map(|point| (point.inflow.as_str(), point.outflow.as_str())).collect();
+        assert_eq!(flows, vec![("18446744073709551616", "300"), ("0", "0"), ("5", "45")]);
+
+        // Both series never decrease
+        for pair in series.windows(2) {
+            for (before, after) in [
+                (&pair[0].cumulative_inflow, &pair[1].cumulative_inflow),
+                (&pair[0].cumulative_outflow, &pair[1].cumulative_outflow),
+            ] {
+                let before: u128 = before.parse().unwrap();
+                let after: u128 = after.parse().unwrap();
+                assert!(after >= before);
+            }
+        }
+
+        // The last point covering every transfer matches the running totals
+        let all = db.flow_series(3_600, 0, 14_400).unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[0].cumulative_inflow, "1000");
+        assert_eq!(all[4].cumulative_inflow, "18446744073709553620");
+        assert_eq!(all[4].cumulative_outflow, "345");
+
+        assert!(db.flow_series(0, 0, 10).is_err());
+        assert!(db.flow_series(60, 10, 0).is_err());
+        assert!(db.flow_series(1, 0, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_get_stats_matches_individual_queries() {
+        let db = Database::new_in_memory().expect("Failed to create database");
+        let empty = db.get_stats().unwrap();
+        assert_eq!(empty.transaction_count, 0);
+        assert_eq!((empty.inflow_count, empty.outflow_count), (0, 0));
+        assert_eq!(empty.block_range, None);
+
+        let transfer = |block_number: u64, hash: &str, direction| crate::models::ProcessedTransfer {
+            block_number,
+            transaction_hash: hash.to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "1000".to_string(),
+            timestamp: 0,
+            direction,
+        };
+        use crate::models::TransferDirection::{FromBinance, ToBinance};
+        db.store_transfer_and_update_net_flow(&transfer(120, "0xa", ToBinance)).unwrap();
+        db.store_transfer_and_update_net_flow(&transfer(250, "0xb", ToBinance)).unwrap();
+        db.store_transfer_and_update_net_flow(&transfer(980, "0xc", FromBinance)).unwrap();
+        db.set_last_processed_block(990).unwrap();
+        db.record_skipped_block(991, "boom").unwrap();
+
+        let stats = db.get_stats().unwrap();
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!(stats.net_flow.net_flow, net_flow.net_flow);
+        assert_eq!(stats.net_flow.total_inflow, net_flow.total_inflow);
+        assert_eq!(stats.net_flow.total_outflow, net_flow.total_outflow);
+        assert_eq!(stats.net_flow.last_processed_block, db.get_last_processed_block().unwrap());
+        assert_eq!(stats.transaction_count, db.get_transaction_count().unwrap());
+        assert_eq!((stats.inflow_count, stats.outflow_count), (2, 1));
+        assert_eq!(stats.block_range, db.block_range().unwrap());
+        assert_eq!(stats.skipped_blocks, db.get_skipped_block_count().unwrap());
+    }
+
+    #[test]
+    fn test_pending_transfers_are_counted_only_once_finalized() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+
+        let transfer = |block_number: u64, direction| crate::models::ProcessedTransfer {
+            block_number,
+            transaction_hash: format!("0xpending{}", block_number),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "2000000000000000000".to_string(),
+            timestamp: 1_700_000_000,
+            direction,
+        };
+        db.store_pending_transfers(&[transfer(9_000, crate::models::TransferDirection::ToBinance)]).unwrap();
+        db.store_pending_transfers(&[transfer(9_001, crate::models::TransferDirection::FromBinance)]).unwrap();
+
+        assert_eq!(db.get_pending_transfer_count().unwrap(), 2);
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "0");
+        assert_eq!(db.recompute_net_flow().unwrap().total_inflow, "0");
+        assert_eq!(db.get_unfinalized_blocks(9_000).unwrap().len(), 1);
+
+        assert_eq!(db.finalize_pending_transfers(9_000).unwrap(), 1);
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "2000000000000000000");
+        assert_eq!(net_flow.total_outflow, "0");
+        assert_eq!(db.recompute_net_flow().unwrap().total_inflow, "2000000000000000000");
+
+        // A reorged block's pending transfers are dropped without touching the totals
+        assert_eq!(db.discard_block(9_001).unwrap(), 1);
+        assert_eq!(db.get_pending_transfer_count().unwrap(), 0);
+        assert_eq!(db.finalize_pending_transfers(9_001).unwrap(), 0);
+        assert_eq!(db.get_net_flow_data().unwrap().total_outflow, "0");
+        assert_eq!(db.get_transaction_count().unwrap(), 1);
+
+        // A reorged block's counted transfers are taken back out of the totals
+        assert_eq!(db.discard_block(9_000).unwrap(), 1);
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.net_flow.as_str()), ("0", "0"));
+        assert_eq!(db.get_transaction_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_discarded_block_is_not_replayed_by_rebuild() {
+        use crate::blockchain::TransferDetector;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        let transfer = |block_number: u64| ProcessedTransfer {
+            block_number,
+            transaction_hash: "0xreincluded".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "1111111111111111111111111111111111111111".to_string(),
+            to_address: "f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "700".to_string(),
+            timestamp: 1_000,
+            direction: TransferDirection::ToBinance,
+        };
+        db.store_raw_logs(1_000, &[MockPolygonNode::transfer_log(&transfer(50))]).unwrap();
+        db.store_pending_transfers(&[transfer(50)]).unwrap();
+
+        // The transaction is re-included in block 51 of the canonical chain
+        assert_eq!(db.discard_block(50).unwrap(), 1);
+        assert_eq!(db.get_raw_log_count().unwrap(), 0);
+        db.store_raw_logs(1_002, &[MockPolygonNode::transfer_log(&transfer(51))]).unwrap();
+        db.store_transfers_and_update_net_flow(&[transfer(51)]).unwrap();
+
+        db.rebuild_from_raw_logs(&TransferDetector::new(), false).unwrap();
+        assert!(db.get_transactions_by_block(50).unwrap().is_empty());
+        assert_eq!(db.get_transactions_by_block(51).unwrap().len(), 1);
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "700");
+    }
+
+    #[test]
+    fn test_rebuild_keeps_unconfirmed_transfers_pending() {
+        use crate::blockchain::TransferDetector;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        let transfer = |block_number: u64, transaction_hash: &str, amount: &str| ProcessedTransfer {
+            block_number,
+            transaction_hash: transaction_hash.to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "1111111111111111111111111111111111111111".to_string(),
+            to_address: "f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: amount.to_string(),
+            timestamp: 1_000,
+            direction: TransferDirection::ToBinance,
+        };
+        let confirmed = transfer(40, "0xconfirmed", "300");
+        let unconfirmed = transfer(50, "0xunconfirmed", "700");
+        db.store_raw_logs(1_000, &[MockPolygonNode::transfer_log(&confirmed)]).unwrap();
+        db.store_transfers_and_update_net_flow(&[confirmed]).unwrap();
+        db.finalize_pending_transfers(45).unwrap();
+        db.store_raw_logs(1_000, &[MockPolygonNode::transfer_log(&unconfirmed)]).unwrap();
+        db.store_pending_transfers(&[unconfirmed]).unwrap();
+
+        db.rebuild_from_raw_logs(&TransferDetector::new(), false).unwrap();
+        assert_eq!(db.get_pending_transfer_count().unwrap(), 1);
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "300");
+
+        // Block 50 is reorganized; its canonical replacement holds a smaller inflow
+        assert_eq!(db.discard_block(50).unwrap(), 1);
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "300");
+        db.store_pending_transfers(&[transfer(50, "0xcanonical", "200")]).unwrap();
+        db.finalize_pending_transfers(50).unwrap();
+
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!((net_flow.total_inflow.as_str(), net_flow.net_flow.as_str()), ("500", "500"));
+        assert_eq!(db.recompute_net_flow().unwrap().total_inflow, "500");
+    }
+
+    #[test]
+    fn test_rebuild_keeps_seeded_baseline() {
+        use crate::blockchain::TransferDetector;
+        use crate::models::{ProcessedTransfer, TransferDirection};
+        use crate::testing::MockPolygonNode;
+
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        db.seed_net_flow("5000", "1500", 40).unwrap();
+        let transfer = ProcessedTransfer {
+            block_number: 41,
+            transaction_hash: "0xafterseed".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "1111111111111111111111111111111111111111".to_string(),
+            to_address: "f977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "700".to_string(),
+            timestamp: 1_000,
+            direction: TransferDirection::ToBinance,
+        };
+        db.store_raw_logs(1_000, &[MockPolygonNode::transfer_log(&transfer)]).unwrap();
+        db.store_transfers_and_update_net_flow(&[transfer]).unwrap();
+
+        db.rebuild_from_raw_logs(&TransferDetector::new(), false).unwrap();
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "5700");
+        assert_eq!(net_flow.total_outflow, "1500");
+        assert_eq!(net_flow.net_flow, "4200");
+    }
+
+    #[test]
+    fn test_unfinalized_blocks_include_blocks_without_transfers() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        db.finalize_pending_transfers(100).unwrap();
+        for block_number in [99, 101, 102, 104] {
+            db.record_block_hash(block_number, &format!("0x{:x}", block_number)).unwrap();
+        }
+
+        assert_eq!(
+            db.get_unfinalized_blocks(102).unwrap(),
+            vec![(101, Some("0x65".to_string())), (102, Some("0x66".to_string()))]
+        );
+
+        // Finalizing moves the height; it never moves back
+        db.finalize_pending_transfers(101).unwrap();
+        db.finalize_pending_transfers(90).unwrap();
+        assert_eq!(db.get_unfinalized_blocks(102).unwrap(), vec![(102, Some("0x66".to_string()))]);
+    }
+
+    #[test]
+    fn test_maintenance_keeps_data() {
+        let temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        let db = Database::new(temp_file.path().to_str().unwrap()).expect("Failed to create database");
+
+        for i in 0..20u64 {
+            db.store_transfer_and_update_net_flow(&crate::models::ProcessedTransfer {
+                block_number: 10_000 + i,
+                transaction_hash: format!("0xmaint{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: "1000000000000000000".to_string(),
+                timestamp: 1_700_000_000,
+                direction: crate::models::TransferDirection::ToBinance,
+            })
+            .expect("Failed to store transfer");
+        }
+        let before = db.get_net_flow_data().unwrap();
+
+        db.maintenance(false).expect("ANALYZE failed");
+        db.maintenance(true).expect("VACUUM failed");
+
+        assert_eq!(db.get_transaction_count().unwrap(), 20);
+        let after = db.get_net_flow_data().unwrap();
+        assert_eq!(after.total_inflow, before.total_inflow);
+        assert_eq!(after.last_processed_block, before.last_processed_block);
+        assert_eq!(db.recompute_net_flow().unwrap().total_inflow, "20000000000000000000");
+    }
+
+    #[test]
+    fn test_count_transactions_in_range_counts_only_the_sub_range() {
+        let db = Database::new_in_memory().unwrap();
+        for (block_number, log_index) in [(100, 0), (101, 0), (101, 1), (102, 0), (105, 0), (110, 0)] {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0x{:064x}", block_number),
+                log_index,
+                transaction_index: 0,
+                from_address: "0xsender".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: "1000".to_string(),
+                timestamp: 1640995200 + block_number,
+                direction: crate::models::TransferDirection::ToBinance,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).unwrap();
+        }
+
+        assert_eq!(db.count_transactions_in_range(101, 105).unwrap(), 4);
+        assert_eq!(db.count_transactions_in_range(100, 110).unwrap(), 6);
+        assert_eq!(db.count_transactions_in_range(103, 104).unwrap(), 0);
+        assert_eq!(db.count_transactions_in_range(110, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rewriting_a_partially_stored_block_counts_each_transfer_once() {
+        use crate::models::{ProcessedTransfer, TransferDirection};
+
+        let db = Database::new_in_memory().unwrap();
+        let transfer = |log_index: u32, amount: &str, direction: TransferDirection| ProcessedTransfer {
+            block_number: 9_100,
+            transaction_hash: format!("0xpartial{}", log_index),
+            log_index,
+            transaction_index: 0,
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: amount.to_string(),
+            timestamp: 1_700_000_000,
+            direction,
+        };
+        let block = vec![
+            transfer(0, "100", TransferDirection::ToBinance),
+            transfer(1, "40", TransferDirection::FromBinance),
+            transfer(2, "7", TransferDirection::ToBinance),
+        ];
+
+        // A first attempt stored part of the block before failing
+        db.store_transfers_and_update_net_flow(&block[..1]).unwrap();
+        assert!(db.store_transfers_and_update_net_flow(&block).is_err());
+
+        assert_eq!(db.store_missing_transfers(&block).unwrap(), 2);
+        assert_eq!(db.store_missing_transfers(&block).unwrap(), 0);
+
+        let stored: Vec<u32> = db.get_transactions_by_block(9_100).unwrap().iter().map(|tx| tx.log_index).collect();
+        assert_eq!(stored, vec![0, 1, 2]);
+        let net_flow = db.get_net_flow_data().unwrap();
+        assert_eq!(net_flow.total_inflow, "107");
+        assert_eq!(net_flow.total_outflow, "40");
+        assert_eq!(net_flow.net_flow, "67");
+
+        // Pending writes skip existing rows the same way
+        let pending = vec![transfer(3, "5", TransferDirection::ToBinance)];
+        assert_eq!(db.store_missing_pending_transfers(&[block[0].clone(), pending[0].clone()]).unwrap(), 1);
+        assert_eq!(db.get_pending_transfer_count().unwrap(), 1);
+        assert_eq!(db.get_net_flow_data().unwrap().total_inflow, "107");
+    }
+
+    #[tokio::test]
+    async fn test_stream_transactions_since_yields_rows_stored_after_subscribing() {
+        use tokio_stream::StreamExt;
+
+        let db = std::sync::Arc::new(Database::new_in_memory().expect("Failed to create test database"));
+        let transfer = |i: u64| crate::models::ProcessedTransfer {
+            block_number: 8_000 + i,
+            transaction_hash: format!("0xstream{}", i),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+            amount: "1".to_string(),
+            timestamp: 1_700_000_000 + i,
+            direction: crate::models::TransferDirection::ToBinance,
+        };
+        db.store_transfer_and_update_net_flow(&transfer(0)).unwrap();
+        let first_id = db.get_transactions_since(0, 1).unwrap()[0].id;
+
+        let stream = db.stream_transactions_since(first_id);
+        tokio::pin!(stream);
+        for i in 1..=3 {
+            db.store_transfer_and_update_net_flow(&transfer(i)).unwrap();
+        }
+
+        let mut hashes = Vec::new();
+        for _ in 0..3 {
+            let row = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+                .await
+                .expect("No row within 5s")
+                .expect("Stream ended")
+                .unwrap();
+            hashes.push(row.transaction_hash);
+        }
+        // Rows at or below the starting id are not replayed
+        assert_eq!(hashes, vec!["0xstream1", "0xstream2", "0xstream3"]);
+
+        db.store_transfer_and_update_net_flow(&transfer(4)).unwrap();
+        let row = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(row.transaction_hash, "0xstream4");
+    }
+
+    #[test]
+    fn test_recent_transactions_before_id_pages_without_gaps_or_repeats() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+
+        for i in 0..23u64 {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 9_000 + i,
+                transaction_hash: format!("0xkeyset{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: "1".to_string(),
+                timestamp: 1_700_000_000 + i,
+                direction: if i % 3 == 0 {
+                    crate::models::TransferDirection::FromBinance
+                } else {
+                    crate::models::TransferDirection::ToBinance
+                },
+            };
+            db.store_transfer_and_update_net_flow(&transfer).expect("Failed to store transfer");
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = i64::MAX;
+        loop {
+            let page = db.recent_transactions_before_id(cursor, None, 5).unwrap();
+            if page.is_empty() {
+                break;
+            }
+            assert!(page.len() <= 5);
+            assert!(page.windows(2).all(|pair| pair[0].id > pair[1].id));
+            cursor = page.last().unwrap().id;
+            seen.extend(page.into_iter().map(|row| row.id));
+        }
+
+        let mut all: Vec<i64> = db.get_transactions_since(0, 100).unwrap().into_iter().map(|row| row.id).collect();
+        all.reverse();
+        assert_eq!(seen, all);
+
+        // The direction filter pages the same way over the matching rows only
+        let mut outflows = Vec::new();
+        let mut cursor = i64::MAX;
+        loop {
+            let page = db.recent_transactions_before_id(cursor, Some("outflow"), 3).unwrap();
+            let Some(last) = page.last() else { break };
+            cursor = last.id;
+            assert!(page.iter().all(|row| row.direction == "outflow"));
+            outflows.extend(page.into_iter().map(|row| row.transaction_hash));
+        }
+        let expected: Vec<String> = (0..23u64).rev().filter(|i| i % 3 == 0).map(|i| format!("0xkeyset{}", i)).collect();
+        assert_eq!(outflows, expected);
+    }
+
+    #[test]
+    fn test_backfill_state_is_kept_apart_from_the_live_cursor() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        assert_eq!(db.get_backfill_state().unwrap(), None);
+        // Progress without a started backfill is ignored
+        db.set_backfill_progress(5).unwrap();
+        assert_eq!(db.get_backfill_state().unwrap(), None);
+
+        db.set_last_processed_block(9_000).unwrap();
+        db.start_backfill(100, 200).unwrap();
+        let state = db.get_backfill_state().unwrap().unwrap();
+        assert_eq!((state.from_block, state.to_block, state.last_completed_block), (100, 200, None));
+        assert_eq!(state.resume_from(), 100);
+
+        db.set_backfill_progress(150).unwrap();
+        let state = db.get_backfill_state().unwrap().unwrap();
+        assert_eq!(state.resume_from(), 151);
+        assert!(!state.is_complete());
+        assert_eq!(db.get_last_processed_block().unwrap(), 9_000);
+
+        db.set_backfill_progress(200).unwrap();
+        assert!(db.get_backfill_state().unwrap().unwrap().is_complete());
+
+        // A new backfill replaces the old one
+        db.start_backfill(300, 400).unwrap();
+        let state = db.get_backfill_state().unwrap().unwrap();
+        assert_eq!((state.from_block, state.last_completed_block), (300, None));
+    }
+
+    #[test]
+    fn test_audit_net_flow_reports_exact_drift() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        for (i, (amount, direction)) in [
+            ("123456789012345678901", crate::models::TransferDirection::ToBinance),
+            ("5000000000000000000", crate::models::TransferDirection::FromBinance),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let transfer = crate::models::ProcessedTransfer {
+                block_number: 100 + i as u64,
+                transaction_hash: format!("0xaudit{}", i),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0x1111111111111111111111111111111111111111".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: amount.to_string(),
+                timestamp: 1_700_000_000,
+                direction,
+            };
+            db.store_transfer_and_update_net_flow(&transfer).unwrap();
+        }
+
+        let audit = db.audit_net_flow().unwrap();
+        assert!(audit.is_consistent());
+        assert_eq!(audit.net_flow_drift, "0");
+        assert_eq!(audit.recomputed_inflow, "123456789012345678901");
+
+        // Move the stored totals without a matching transaction
+        db.update_net_flow_inflow("1000000000000000007").unwrap();
+        db.update_net_flow_outflow("250").unwrap();
+
+        let audit = db.audit_net_flow().unwrap();
+        assert!(!audit.is_consistent());
+        assert_eq!(audit.stored_inflow, "124456789012345678908");
+        assert_eq!(audit.recomputed_inflow, "123456789012345678901");
+        assert_eq!(audit.inflow_drift, "1000000000000000007");
+        assert_eq!(audit.outflow_drift, "250");
+        assert_eq!(audit.net_flow_drift, "999999999999999757");
+    }
+
+    #[test]
+    fn test_audit_net_flow_of_seeded_database_has_no_drift() {
+        let db = Database::new_in_memory().expect("Failed to create test database");
+        db.seed_net_flow("5000", "1500", 100).unwrap();
+
+        let audit = db.audit_net_flow().unwrap();
+        assert!(audit.is_consistent());
+        assert_eq!(audit.net_flow_drift, "0");
+        assert_eq!(audit.stored_inflow, "5000");
+        assert_eq!(audit.recomputed_inflow, "0");
+    }
+}
@@ -34,12 +34,16 @@ pub enum RpcError {
     #[error("JSON parsing failed: {0}")]
     Json(#[from] serde_json::Error),
     
-    #[error("RPC method error: code={code}, message={message}")]
-    Method { code: i32, message: String },
+    #[error("RPC method error: code={code}, message={message}{}", format_rpc_error_data(.data))]
+    Method { code: i32, message: String, data: Option<String> },
     
     #[error("Invalid response format: {0}")]
     InvalidResponse(String),
     
+    /// A JSON-RPC response carrying neither `result` nor `error`
+    #[error("Response has neither a result nor an error")]
+    EmptyResponse,
+    
     #[error("Timeout after {seconds} seconds")]
     Timeout { seconds: u64 },
     
@@ -49,13 +53,30 @@ pub enum RpcError {
     #[error("Block not found: {block_number}")]
     BlockNotFound { block_number: u64 },
     
+    #[error("Response body exceeded the {limit_bytes} byte limit")]
+    ResponseTooLarge { limit_bytes: u64 },
+    
     #[error("Connection failed: {0}")]
     Connection(String),
     
+    #[error("Server error: HTTP {status}")]
+    ServerError { status: u16 },
+    
+    #[error("Request rejected: HTTP {status}")]
+    ClientError { status: u16 },
+    
     #[error("Authentication failed")]
     Authentication,
 }
 
+/// Format the optional JSON-RPC error `data` as a display suffix
+fn format_rpc_error_data(data: &Option<String>) -> String {
+    match data {
+        Some(data) => format!(", data={}", data),
+        None => String::new(),
+    }
+}
+
 /// Database-related errors
 #[derive(Error, Debug)]
 pub enum DatabaseError {
@@ -85,6 +106,29 @@ pub enum DatabaseError {
     
     #[error("Constraint violation: {0}")]
     Constraint(String),
+    
+    #[error("Connection pool exhausted: {0}")]
+    PoolExhausted(String),
+}
+
+impl DatabaseError {
+    /// Classify a SQLite error so the retry layer can tell transient contention
+    /// (busy/locked, retryable) from constraint violations (never retryable)
+    pub fn classify(err: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+
+        match &err {
+            rusqlite::Error::SqliteFailure(failure, message) => {
+                let detail = message.clone().unwrap_or_else(|| failure.to_string());
+                match failure.code {
+                    ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => DatabaseError::Lock(detail),
+                    ErrorCode::ConstraintViolation => DatabaseError::Constraint(detail),
+                    _ => DatabaseError::Connection(err),
+                }
+            }
+            _ => DatabaseError::Connection(err),
+        }
+    }
 }
 
 /// Block processing errors
@@ -204,6 +248,23 @@ pub enum SystemError {
     
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
+    
+    #[error("{attempts} attempts: {}; last error: {last_error}", format_error_counts(.distinct_errors))]
+    RetryExhausted {
+        attempts: u32,
+        last_error: Box<IndexerError>,
+        /// Distinct error kinds seen across attempts with their counts, in first-seen order
+        distinct_errors: Vec<(String, u32)>,
+    },
+}
+
+/// Format error kind counts as "3×Timeout, 2×Connection"
+fn format_error_counts(counts: &[(String, u32)]) -> String {
+    counts
+        .iter()
+        .map(|(kind, count)| format!("{}×{}", count, kind))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// Result type alias for convenience
@@ -223,9 +284,87 @@ pub enum ErrorSeverity {
 }
 
 impl IndexerError {
+    /// Short name of the underlying error variant (e.g. "Timeout", "Connection")
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndexerError::Rpc(error) => match error {
+                RpcError::Http(_) => "Http",
+                RpcError::Json(_) => "Json",
+                RpcError::Method { .. } => "Method",
+                RpcError::InvalidResponse(_) => "InvalidResponse",
+                RpcError::EmptyResponse => "EmptyResponse",
+                RpcError::Timeout { .. } => "Timeout",
+                RpcError::RateLimit { .. } => "RateLimit",
+                RpcError::BlockNotFound { .. } => "BlockNotFound",
+                RpcError::ResponseTooLarge { .. } => "ResponseTooLarge",
+                RpcError::Connection(_) => "Connection",
+                RpcError::ServerError { .. } => "ServerError",
+                RpcError::ClientError { .. } => "ClientError",
+                RpcError::Authentication => "Authentication",
+            },
+            IndexerError::Database(error) => match error {
+                DatabaseError::Connection(_) => "Connection",
+                DatabaseError::Transaction(_) => "Transaction",
+                DatabaseError::Query(_) => "Query",
+                DatabaseError::Integrity(_) => "Integrity",
+                DatabaseError::Lock(_) => "Lock",
+                DatabaseError::Migration(_) => "Migration",
+                DatabaseError::Backup(_) => "Backup",
+                DatabaseError::NotFound(_) => "NotFound",
+                DatabaseError::Constraint(_) => "Constraint",
+                DatabaseError::PoolExhausted(_) => "PoolExhausted",
+            },
+            IndexerError::Processing(error) => match error {
+                ProcessingError::BlockParsing(_) => "BlockParsing",
+                ProcessingError::TransactionParsing(_) => "TransactionParsing",
+                ProcessingError::LogParsing(_) => "LogParsing",
+                ProcessingError::AddressValidation(_) => "AddressValidation",
+                ProcessingError::AmountParsing(_) => "AmountParsing",
+                ProcessingError::EventSignature { .. } => "EventSignature",
+                ProcessingError::InsufficientData { .. } => "InsufficientData",
+                ProcessingError::InvalidDirection => "InvalidDirection",
+                ProcessingError::Overflow(_) => "Overflow",
+            },
+            IndexerError::Config(error) => match error {
+                ConfigError::MissingEnvVar(_) => "MissingEnvVar",
+                ConfigError::InvalidValue { .. } => "InvalidValue",
+                ConfigError::FileNotFound(_) => "FileNotFound",
+                ConfigError::Parsing(_) => "Parsing",
+                ConfigError::InvalidUrl(_) => "InvalidUrl",
+                ConfigError::InvalidPort(_) => "InvalidPort",
+            },
+            IndexerError::Network(error) => match error {
+                NetworkError::Timeout => "Timeout",
+                NetworkError::DnsResolution(_) => "DnsResolution",
+                NetworkError::Tls(_) => "Tls",
+                NetworkError::Unreachable => "Unreachable",
+                NetworkError::ConnectionRefused => "ConnectionRefused",
+                NetworkError::TooManyRedirects => "TooManyRedirects",
+            },
+            IndexerError::Validation(error) => match error {
+                ValidationError::InvalidAddress(_) => "InvalidAddress",
+                ValidationError::InvalidBlockNumber(_) => "InvalidBlockNumber",
+                ValidationError::InvalidTransactionHash(_) => "InvalidTransactionHash",
+                ValidationError::InvalidAmount(_) => "InvalidAmount",
+                ValidationError::InvalidTimestamp(_) => "InvalidTimestamp",
+                ValidationError::OutOfRange(_) => "OutOfRange",
+            },
+            IndexerError::System(error) => match error {
+                SystemError::FileSystem(_) => "FileSystem",
+                SystemError::OutOfMemory => "OutOfMemory",
+                SystemError::ThreadPanic(_) => "ThreadPanic",
+                SystemError::Signal(_) => "Signal",
+                SystemError::ResourceExhausted(_) => "ResourceExhausted",
+                SystemError::PermissionDenied(_) => "PermissionDenied",
+                SystemError::RetryExhausted { .. } => "RetryExhausted",
+            },
+        }
+    }
+
     /// Get the severity level of an error
     pub fn severity(&self) -> ErrorSeverity {
         match self {
+            IndexerError::System(SystemError::RetryExhausted { last_error, .. }) => last_error.severity(),
             IndexerError::Database(DatabaseError::Connection(_)) => ErrorSeverity::Critical,
             IndexerError::Database(DatabaseError::Migration(_)) => ErrorSeverity::Critical,
             IndexerError::Config(_) => ErrorSeverity::Critical,
@@ -252,18 +391,25 @@ impl IndexerError {
     /// Check if the error is recoverable (can be retried)
     pub fn is_recoverable(&self) -> bool {
         match self {
+            IndexerError::System(SystemError::RetryExhausted { last_error, .. }) => last_error.is_recoverable(),
             IndexerError::Rpc(RpcError::Timeout { .. }) => true,
             IndexerError::Rpc(RpcError::RateLimit { .. }) => true,
             IndexerError::Rpc(RpcError::Connection(_)) => true,
+            // Some providers briefly answer with just `jsonrpc` and `id`
+            IndexerError::Rpc(RpcError::EmptyResponse) => true,
+            IndexerError::Rpc(RpcError::ServerError { status }) => crate::retry::DEFAULT_RETRYABLE_STATUSES.contains(status),
             IndexerError::Network(NetworkError::Timeout) => true,
             IndexerError::Network(NetworkError::ConnectionRefused) => true,
             IndexerError::Database(DatabaseError::Lock(_)) => true,
+            IndexerError::Database(DatabaseError::PoolExhausted(_)) => true,
             IndexerError::System(SystemError::ResourceExhausted(_)) => true,
             
             // Non-recoverable errors
+            IndexerError::Database(DatabaseError::Constraint(_)) => false,
             IndexerError::Config(_) => false,
             IndexerError::Validation(_) => false,
             IndexerError::Rpc(RpcError::Authentication) => false,
+            IndexerError::Rpc(RpcError::ClientError { .. }) => false,
             IndexerError::System(SystemError::PermissionDenied(_)) => false,
             
             _ => false,
@@ -277,12 +423,14 @@ impl IndexerError {
         }
 
         match self {
+            IndexerError::System(SystemError::RetryExhausted { last_error, .. }) => last_error.retry_delay(),
             IndexerError::Rpc(RpcError::RateLimit { seconds }) => Some(*seconds),
             IndexerError::Rpc(RpcError::Timeout { .. }) => Some(5),
             IndexerError::Rpc(RpcError::Connection(_)) => Some(10),
             IndexerError::Network(NetworkError::Timeout) => Some(5),
             IndexerError::Network(NetworkError::ConnectionRefused) => Some(15),
             IndexerError::Database(DatabaseError::Lock(_)) => Some(1),
+            IndexerError::Database(DatabaseError::PoolExhausted(_)) => Some(1),
             IndexerError::System(SystemError::ResourceExhausted(_)) => Some(30),
             _ => Some(5),
         }
@@ -303,8 +451,12 @@ impl From<crate::blockchain::rpc_client::RpcError> for RpcError {
                         if let Some(code_end) = msg[code_start + 6..].find(',') {
                             if let Ok(code) = msg[code_start + 6..code_start + 6 + code_end].parse::<i32>() {
                                 if let Some(msg_start) = msg.find("Message: ") {
-                                    let message = msg[msg_start + 9..].to_string();
-                                    return RpcError::Method { code, message };
+                                    let rest = &msg[msg_start + 9..];
+                                    let (message, data) = match rest.split_once(", Data: ") {
+                                        Some((message, data)) => (message.to_string(), Some(data.to_string())),
+                                        None => (rest.to_string(), None),
+                                    };
+                                    return RpcError::Method { code, message, data };
                                 }
                             }
                         }
@@ -319,17 +471,17 @@ impl From<crate::blockchain::rpc_client::RpcError> for RpcError {
 impl From<crate::database::DbError> for DatabaseError {
     fn from(err: crate::database::DbError) -> Self {
         match err {
-            crate::database::DbError::Connection(e) => DatabaseError::Connection(e),
+            crate::database::DbError::Connection(e) => DatabaseError::classify(e),
             crate::database::DbError::Operation(msg) => DatabaseError::Query(msg),
             crate::database::DbError::NotFound => DatabaseError::NotFound("Record not found".to_string()),
+            err @ crate::database::DbError::PoolExhausted { .. } => DatabaseError::PoolExhausted(err.to_string()),
         }
     }
 }
 
 impl From<crate::blockchain::ProcessError> for ProcessingError {
     fn from(err: crate::blockchain::ProcessError) -> Self {
-        // This will need to be implemented based on the actual ProcessError definition
-        ProcessingError::BlockParsing(format!("{:?}", err))
+        ProcessingError::BlockParsing(err.to_string())
     }
 }
 
@@ -391,11 +543,39 @@ mod tests {
         assert_eq!(non_recoverable.retry_delay(), None);
     }
 
+    #[test]
+    fn test_sqlite_busy_and_locked_are_recoverable() {
+        for code in [rusqlite::ffi::SQLITE_BUSY, rusqlite::ffi::SQLITE_LOCKED] {
+            let sqlite_error = rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(code),
+                Some("database is locked".to_string()),
+            );
+            let error = IndexerError::from(crate::database::DbError::Connection(sqlite_error));
+
+            assert!(matches!(error, IndexerError::Database(DatabaseError::Lock(_))));
+            assert!(error.is_recoverable());
+            assert_eq!(error.retry_delay(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_sqlite_constraint_violation_is_not_recoverable() {
+        let db = crate::database::Database::new_in_memory().unwrap();
+        let store = || db.store_transaction(1, "0xdup", 0, "0xfrom", "0xto", "1", 1640995200, "inflow");
+        store().unwrap();
+
+        let error = IndexerError::from(store().unwrap_err());
+        assert!(matches!(error, IndexerError::Database(DatabaseError::Constraint(_))));
+        assert!(!error.is_recoverable());
+        assert_eq!(error.retry_delay(), None);
+    }
+
     #[test]
     fn test_error_display() {
         let error = IndexerError::Rpc(RpcError::Method {
             code: -32601,
             message: "Method not found".to_string(),
+            data: None,
         });
         assert_eq!(format!("{}", error), "RPC error: RPC method error: code=-32601, message=Method not found");
     }
@@ -5,17 +5,95 @@ use crate::error::{IndexerError, ErrorSeverity};
 use crate::logging::{LogContext, ErrorLogger, PerformanceMonitor};
 use crate::retry::{RetryConfig, RetryManager};
 
+/// An error that has not recurred for this long starts a new run in [`ErrorRecoveryManager::record_occurrence`]
+pub const ERROR_RUN_RESET_AFTER: Duration = Duration::from_secs(300);
+
 /// Advanced error recovery strategies for different types of failures
 pub struct ErrorRecoveryManager {
     /// Track error patterns to identify systemic issues
     error_patterns: std::sync::Mutex<HashMap<String, ErrorPattern>>,
     /// Configuration for different recovery strategies
     recovery_configs: HashMap<String, RecoveryStrategy>,
+    /// Endpoint that alerts are also posted to
+    alert_webhook: Option<AlertWebhook>,
+}
+
+/// Posts alerts as JSON to a webhook endpoint
+///
+/// Delivery runs on its own task with its own HTTP client, request timeout and
+/// attempt budget, independent of the [`RetryManager`] and recovery strategies,
+/// so a slow or hung endpoint never holds up the caller raising the alert. An
+/// alert that cannot be delivered within the attempts is dropped with a warning.
+#[derive(Debug, Clone)]
+pub struct AlertWebhook {
+    client: reqwest::Client,
+    url: String,
+    attempts: u32,
+}
+
+impl AlertWebhook {
+    /// Post to `url`, giving each request `timeout` and trying at most `attempts` times
+    pub fn new(url: impl Into<String>, timeout: Duration, attempts: u32) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            url: url.into(),
+            attempts: attempts.max(1),
+        }
+    }
+
+    /// Deliver `message` in the background, returning whether it was accepted
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn deliver(&self, message: &str) -> tokio::task::JoinHandle<bool> {
+        let webhook = self.clone();
+        let payload = serde_json::json!({ "alert": message });
+        tokio::spawn(async move {
+            let mut last_error = String::new();
+            for attempt in 1..=webhook.attempts {
+                match webhook.client.post(&webhook.url).json(&payload).send().await {
+                    Ok(response) if response.status().is_success() => {
+                        log::debug!("Delivered alert to webhook on attempt {}", attempt);
+                        return true;
+                    }
+                    Ok(response) => last_error = format!("HTTP {}", response.status()),
+                    Err(e) => last_error = e.to_string(),
+                }
+            }
+            log::warn!(
+                "Dropping alert after {} failed webhook attempt(s): {}",
+                webhook.attempts, last_error
+            );
+            false
+        })
+    }
+}
+
+/// Key errors are grouped under, e.g. "Rpc::Timeout"
+///
+/// Details such as block numbers are left out, so the number of patterns is
+/// bounded by the number of error variants.
+fn pattern_key(error: &IndexerError) -> String {
+    let category = match error {
+        IndexerError::Rpc(_) => "Rpc",
+        IndexerError::Database(_) => "Database",
+        IndexerError::Processing(_) => "Processing",
+        IndexerError::Config(_) => "Config",
+        IndexerError::Network(_) => "Network",
+        IndexerError::Validation(_) => "Validation",
+        IndexerError::System(_) => "System",
+    };
+    format!("{}::{}", category, error.kind())
 }
 
 #[derive(Debug, Clone)]
 struct ErrorPattern {
     count: u32,
+    /// Occurrences since the error last went [`ERROR_RUN_RESET_AFTER`] without recurring
+    run_count: u32,
     first_occurrence: Instant,
     last_occurrence: Instant,
     error_type: String,
@@ -125,17 +203,25 @@ impl ErrorRecoveryManager {
         Self {
             error_patterns: std::sync::Mutex::new(HashMap::new()),
             recovery_configs,
+            alert_webhook: None,
         }
     }
+
+    /// Also post every alert to `webhook`, see [`AlertWebhook`]
+    pub fn with_alert_webhook(mut self, webhook: AlertWebhook) -> Self {
+        self.alert_webhook = Some(webhook);
+        self
+    }
     
     /// Record an error occurrence and analyze patterns
     pub fn record_error(&self, error: &IndexerError, context: &str) {
-        let error_type = format!("{:?}", error);
+        let error_type = pattern_key(error);
         let now = Instant::now();
         
         if let Ok(mut patterns) = self.error_patterns.lock() {
             let pattern = patterns.entry(error_type.clone()).or_insert(ErrorPattern {
                 count: 0,
+                run_count: 0,
                 first_occurrence: now,
                 last_occurrence: now,
                 error_type: error_type.clone(),
@@ -162,6 +248,38 @@ impl ErrorRecoveryManager {
         }
     }
     
+    /// Count an occurrence of `error` without logging it, returning its position in the current run
+    ///
+    /// A run is the stretch of occurrences of one error type without a gap of
+    /// [`ERROR_RUN_RESET_AFTER`], so the first error of a later outage counts as 1
+    /// again. Lets callers sample their own logging of a repeating error; see
+    /// [`ErrorLogger::log_sampled_error`].
+    pub fn record_occurrence(&self, error: &IndexerError) -> u32 {
+        self.record_occurrence_at(error, Instant::now())
+    }
+
+    fn record_occurrence_at(&self, error: &IndexerError, now: Instant) -> u32 {
+        let error_type = pattern_key(error);
+
+        let Ok(mut patterns) = self.error_patterns.lock() else {
+            return 1;
+        };
+        let pattern = patterns.entry(error_type.clone()).or_insert(ErrorPattern {
+            count: 0,
+            run_count: 0,
+            first_occurrence: now,
+            last_occurrence: now,
+            error_type,
+        });
+        if now.saturating_duration_since(pattern.last_occurrence) >= ERROR_RUN_RESET_AFTER {
+            pattern.run_count = 0;
+        }
+        pattern.count += 1;
+        pattern.run_count += 1;
+        pattern.last_occurrence = now;
+        pattern.run_count
+    }
+
     /// Get recovery strategy for a specific error type
     pub fn get_recovery_strategy(&self, error: &IndexerError) -> RecoveryStrategy {
         let strategy_key = match error {
@@ -246,10 +364,7 @@ impl ErrorRecoveryManager {
                 Ok(())
             }
             RecoveryAction::SendAlert(message) => {
-                let context = LogContext::new("error_recovery", "send_alert")
-                    .with_metadata("alert_message", serde_json::json!(message));
-                context.warn(&format!("ALERT: {}", message));
-                // In a real implementation, this would send alerts to monitoring systems
+                self.send_alert(message);
                 Ok(())
             }
             RecoveryAction::HealthCheck => {
@@ -262,6 +377,21 @@ impl ErrorRecoveryManager {
         }
     }
     
+    /// Raise an alert with the monitoring system
+    pub fn send_alert(&self, message: &str) {
+        let context = LogContext::new("error_recovery", "send_alert")
+            .with_metadata("alert_message", serde_json::json!(message));
+        context.warn(&format!("ALERT: {}", message));
+        if let Some(webhook) = &self.alert_webhook {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                // Fire and forget: delivery retries and gives up on its own task
+                drop(webhook.deliver(message));
+            } else {
+                log::warn!("Dropping alert webhook delivery outside of a Tokio runtime");
+            }
+        }
+    }
+    
     /// Get error pattern statistics
     pub fn get_error_statistics(&self) -> Result<Vec<ErrorStatistic>, IndexerError> {
         let patterns = self.error_patterns.lock().map_err(|_| {
@@ -289,7 +419,7 @@ impl ErrorRecoveryManager {
     
     /// Check if an error type is showing concerning patterns
     pub fn is_error_pattern_concerning(&self, error: &IndexerError) -> bool {
-        let error_type = format!("{:?}", error);
+        let error_type = pattern_key(error);
         
         if let Ok(patterns) = self.error_patterns.lock() {
             if let Some(pattern) = patterns.get(&error_type) {
@@ -443,6 +573,41 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_occurrence_runs_restart_after_a_quiet_period() {
+        let manager = ErrorRecoveryManager::new();
+        let error = IndexerError::Rpc(RpcError::Timeout { seconds: 30 });
+        let start = Instant::now();
+
+        assert_eq!(manager.record_occurrence_at(&error, start), 1);
+        assert_eq!(manager.record_occurrence_at(&error, start + Duration::from_secs(60)), 2);
+        assert_eq!(manager.record_occurrence_at(&error, start + Duration::from_secs(120)), 3);
+
+        // A later outage starts counting from 1 again
+        let later = start + Duration::from_secs(120) + ERROR_RUN_RESET_AFTER;
+        assert_eq!(manager.record_occurrence_at(&error, later), 1);
+        assert_eq!(manager.record_occurrence_at(&error, later + Duration::from_secs(60)), 2);
+
+        // The lifetime count behind the statistics keeps growing
+        let statistics = manager.get_error_statistics().unwrap();
+        assert_eq!(statistics[0].count, 5);
+    }
+
+    #[test]
+    fn test_errors_are_grouped_by_variant_not_details() {
+        let manager = ErrorRecoveryManager::new();
+        for block_number in 0..100 {
+            manager.record_occurrence(&IndexerError::Rpc(RpcError::BlockNotFound { block_number }));
+        }
+        manager.record_error(&IndexerError::Network(crate::error::NetworkError::Timeout), "test_context");
+
+        let statistics = manager.get_error_statistics().unwrap();
+        assert_eq!(statistics.len(), 2);
+        assert_eq!(statistics[0].error_type, "Rpc::BlockNotFound");
+        assert_eq!(statistics[0].count, 100);
+        assert_eq!(statistics[1].error_type, "Network::Timeout");
+    }
+
     #[test]
     fn test_concerning_pattern_detection() {
         let manager = ErrorRecoveryManager::new();
@@ -485,4 +650,46 @@ mod tests {
             assert!(statistics[0].count >= statistics[1].count);
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_alert_webhook_retries_a_timed_out_delivery_without_blocking() {
+        use wiremock::matchers::{body_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        crate::testing::capture_logs();
+        let server = MockServer::start().await;
+        let payload = serde_json::json!({ "alert": "webhook retry test alert" });
+        // The first delivery hangs past the timeout, the retry is accepted
+        Mock::given(method("POST"))
+            .and(body_json(&payload))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(1)))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_json(&payload))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = ErrorRecoveryManager::new()
+            .with_alert_webhook(AlertWebhook::new(server.uri(), Duration::from_millis(200), 2));
+        let started = Instant::now();
+        manager.send_alert("webhook retry test alert");
+        // Recovery carries on while the webhook is still hanging
+        let error = IndexerError::Rpc(RpcError::Timeout { seconds: 30 });
+        manager.record_error(&error, "test_context");
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        let delivered = || !crate::testing::captured_logs(log::Level::Debug, "Delivered alert to webhook on attempt 2").is_empty();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !delivered() && Instant::now() < deadline {
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert!(delivered());
+        assert!(crate::testing::captured_logs(log::Level::Warn, "Dropping alert after").is_empty());
+        server.verify().await;
+    }
+}
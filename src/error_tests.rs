@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::error::{IndexerError, RpcError, DatabaseError, ProcessingError, ConfigError, ErrorSeverity};
-    use crate::retry::{RetryConfig, RetryManager, CircuitBreaker};
+    use crate::retry::{RetryConfig, RetryManager, CircuitBreaker, DEFAULT_RETRYABLE_STATUSES};
     use crate::logging::{LogContext, ErrorLogger};
 
     #[test]
@@ -135,6 +135,7 @@ mod tests {
             max_delay_seconds: 30,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test", config);
@@ -156,6 +157,7 @@ mod tests {
             max_delay_seconds: 20,
             backoff_multiplier: 3.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test", config);
@@ -187,6 +189,7 @@ mod tests {
             max_delay_seconds: 5,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
         
         let enhanced_manager = EnhancedRetryManager::new("test_operation", retry_config);
@@ -210,6 +213,7 @@ mod tests {
             max_delay_seconds: 5,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
         
         let enhanced_manager = EnhancedRetryManager::new("test_operation", retry_config);
@@ -319,6 +323,7 @@ mod tests {
         let rpc_error = RpcError::Method {
             code: -32601,
             message: "Method not found".to_string(),
+            data: None,
         };
         let indexer_error = IndexerError::Rpc(rpc_error);
         
@@ -326,4 +331,15 @@ mod tests {
         assert!(error_string.contains("-32601"));
         assert!(error_string.contains("Method not found"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_error_kind_names_the_variant() {
+        assert_eq!(IndexerError::Rpc(RpcError::Timeout { seconds: 30 }).kind(), "Timeout");
+        assert_eq!(IndexerError::Rpc(RpcError::Connection("refused".to_string())).kind(), "Connection");
+        assert_eq!(IndexerError::Database(DatabaseError::Lock("busy".to_string())).kind(), "Lock");
+        assert_eq!(IndexerError::Network(crate::error::NetworkError::Unreachable).kind(), "Unreachable");
+        // Details of the error never leak into its kind
+        let method_error = RpcError::Method { code: -32000, message: "header not found".to_string(), data: None };
+        assert_eq!(IndexerError::Rpc(method_error).kind(), "Method");
+    }
+}
@@ -8,6 +8,10 @@ pub mod error_tests;
 pub mod logging;
 pub mod retry;
 pub mod config;
+pub mod build_info;
+pub mod telemetry;
+#[cfg(test)]
+pub mod testing;
 
 pub use blockchain::RpcClient;
 pub use error::{IndexerError, Result};
@@ -1,7 +1,8 @@
 use log::{info, warn, error, debug, trace};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Structured logging context for the indexer
 pub struct LogContext {
@@ -183,12 +184,52 @@ impl ErrorLogger {
         }
 
         let message = format!("Error occurred: {}", error);
+        Self::log_at_severity(&log_context, severity, &message);
+    }
+
+    /// Log `error`, the `occurrence`th of its current run, unless sampling suppresses it
+    ///
+    /// `occurrence` comes from [`ErrorRecoveryManager::record_occurrence`](crate::error_recovery::ErrorRecoveryManager::record_occurrence),
+    /// which restarts at 1 once the error stops recurring. The first occurrence is always logged. After that only every
+    /// `sample_rate`th is, as a summary of how often the error repeated and how
+    /// many occurrences were suppressed since the last one logged. A `sample_rate`
+    /// of 0 or 1 logs every occurrence. Returns whether anything was logged.
+    pub fn log_sampled_error(
+        error: &crate::error::IndexerError,
+        context: Option<LogContext>,
+        occurrence: u32,
+        sample_rate: u32,
+    ) -> bool {
+        if sample_rate <= 1 || occurrence <= 1 {
+            Self::log_error(error, context);
+            return true;
+        }
+        if !occurrence.is_multiple_of(sample_rate) {
+            return false;
+        }
+
+        let last_logged = if occurrence == sample_rate { 1 } else { occurrence - sample_rate };
+        let suppressed = occurrence - last_logged - 1;
+        let log_context = context
+            .unwrap_or_else(|| LogContext::new("error", "unknown"))
+            .with_metadata("error_type", json!(format!("{:?}", error)))
+            .with_metadata("occurrences", json!(occurrence))
+            .with_metadata("suppressed", json!(suppressed));
+
+        let message = format!(
+            "Error repeated {} times ({} suppressed since last logged): {}",
+            occurrence, suppressed, error
+        );
+        Self::log_at_severity(&log_context, error.severity(), &message);
+        true
+    }
 
+    fn log_at_severity(log_context: &LogContext, severity: crate::error::ErrorSeverity, message: &str) {
         match severity {
-            crate::error::ErrorSeverity::Critical => log_context.error(&message),
-            crate::error::ErrorSeverity::High => log_context.error(&message),
-            crate::error::ErrorSeverity::Medium => log_context.warn(&message),
-            crate::error::ErrorSeverity::Low => log_context.info(&message),
+            crate::error::ErrorSeverity::Critical => log_context.error(message),
+            crate::error::ErrorSeverity::High => log_context.error(message),
+            crate::error::ErrorSeverity::Medium => log_context.warn(message),
+            crate::error::ErrorSeverity::Low => log_context.info(message),
         }
     }
 
@@ -228,6 +269,15 @@ impl MetricsLogger {
         context.info(&format!("Block {} processed with {} transfers", block_number, transfer_count));
     }
 
+    pub fn log_block_skipped(block_number: u64, error: &str, total_skipped: u64) {
+        let context = LogContext::new("metrics", "block_skipped")
+            .with_block_number(block_number)
+            .with_metadata("error", json!(error))
+            .with_metadata("total_skipped", json!(total_skipped));
+
+        context.warn(&format!("Block {} skipped, {} skipped blocks pending reprocess", block_number, total_skipped));
+    }
+
     pub fn log_net_flow_update(direction: &str, amount: &str, new_net_flow: &str) {
         let context = LogContext::new("metrics", "net_flow_update")
             .with_metadata("direction", json!(direction))
@@ -237,7 +287,13 @@ impl MetricsLogger {
         context.info(&format!("Net flow updated: {} {} POL, new net flow: {}", direction, amount, new_net_flow));
     }
 
+    /// Log an RPC call, or only record it when RPC calls are aggregated, see [`Self::aggregate_rpc_calls`]
     pub fn log_rpc_call(method: &str, duration_ms: u64, success: bool) {
+        if let Some(aggregator) = RPC_CALL_AGGREGATOR.get() {
+            aggregator.record(method, duration_ms, success);
+            return;
+        }
+
         let context = LogContext::new("metrics", "rpc_call")
             .with_metadata("method", json!(method))
             .with_duration_ms(duration_ms)
@@ -250,6 +306,15 @@ impl MetricsLogger {
         }
     }
 
+    /// Stop logging RPC calls one by one and log a summary of them every `flush_interval`
+    ///
+    /// Applies process-wide from the first call on; later calls start another flush
+    /// task for the same aggregator. Must be called from within a Tokio runtime.
+    pub fn aggregate_rpc_calls(flush_interval: Duration) -> tokio::task::JoinHandle<()> {
+        let aggregator = RPC_CALL_AGGREGATOR.get_or_init(|| Arc::new(RpcCallAggregator::default()));
+        Arc::clone(aggregator).spawn_flush(flush_interval)
+    }
+
     pub fn log_database_operation(operation: &str, duration_ms: u64, rows_affected: Option<usize>) {
         let mut context = LogContext::new("metrics", "database_operation")
             .with_metadata("operation", json!(operation))
@@ -286,6 +351,91 @@ impl MetricsLogger {
     }
 }
 
+/// RPC calls aggregated by [`MetricsLogger::aggregate_rpc_calls`], unset while calls are logged one by one
+static RPC_CALL_AGGREGATOR: OnceLock<Arc<RpcCallAggregator>> = OnceLock::new();
+
+/// RPC call counts and durations per method, accumulated between summary flushes
+#[derive(Debug, Default)]
+pub struct RpcCallAggregator {
+    calls: Mutex<HashMap<String, MethodCalls>>,
+}
+
+#[derive(Debug, Default)]
+struct MethodCalls {
+    durations_ms: Vec<u64>,
+    failed: u64,
+}
+
+impl RpcCallAggregator {
+    pub fn record(&self, method: &str, duration_ms: u64, success: bool) {
+        if let Ok(mut calls) = self.calls.lock() {
+            let method_calls = calls.entry(method.to_string()).or_default();
+            method_calls.durations_ms.push(duration_ms);
+            if !success {
+                method_calls.failed += 1;
+            }
+        }
+    }
+
+    /// Log one summary line of the calls recorded since the last flush and forget them
+    ///
+    /// Logs nothing and returns `false` when no calls were recorded.
+    pub fn flush(&self, interval: Duration) -> bool {
+        let calls = match self.calls.lock() {
+            Ok(mut calls) => std::mem::take(&mut *calls),
+            Err(_) => return false,
+        };
+        if calls.is_empty() {
+            return false;
+        }
+
+        let mut methods: Vec<_> = calls.into_iter().collect();
+        methods.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut summaries = Vec::with_capacity(methods.len());
+        let mut metadata = serde_json::Map::new();
+        for (method, mut method_calls) in methods {
+            method_calls.durations_ms.sort_unstable();
+            let count = method_calls.durations_ms.len();
+            let p50 = percentile(&method_calls.durations_ms, 50);
+            let p95 = percentile(&method_calls.durations_ms, 95);
+            summaries.push(format!(
+                "{} {} calls ({} failed, p50 {}ms, p95 {}ms)",
+                method, count, method_calls.failed, p50, p95
+            ));
+            metadata.insert(method, json!({
+                "calls": count,
+                "failed": method_calls.failed,
+                "p50_ms": p50,
+                "p95_ms": p95,
+            }));
+        }
+
+        let context = LogContext::new("metrics", "rpc_call_summary")
+            .with_metadata("interval_seconds", json!(interval.as_secs()))
+            .with_metadata("methods", Value::Object(metadata));
+        context.info(&format!("RPC calls in the last {:?}: {}", interval, summaries.join("; ")));
+        true
+    }
+
+    /// Flush every `interval` on a background task, starting one interval from now
+    pub fn spawn_flush(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+                self.flush(interval);
+            }
+        })
+    }
+}
+
+/// Nearest-rank `p`th percentile of ascending `sorted_ms`, which must not be empty
+fn percentile(sorted_ms: &[u64], p: usize) -> u64 {
+    let rank = (sorted_ms.len() * p).div_ceil(100).max(1);
+    sorted_ms[rank - 1]
+}
+
 /// Initialize structured logging for the application
 pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize env_logger with custom format
@@ -369,6 +519,30 @@ mod tests {
         ErrorLogger::log_error(&error, Some(context));
     }
 
+    #[test]
+    fn test_sampled_error_logging_suppresses_repeats() {
+        crate::testing::capture_logs();
+
+        let error = crate::error::IndexerError::Rpc(crate::error::RpcError::Connection(
+            "sampling test: connection refused".to_string(),
+        ));
+        let manager = crate::error_recovery::ErrorRecoveryManager::new();
+
+        let logged = (0..100)
+            .filter(|_| {
+                let occurrence = manager.record_occurrence(&error);
+                ErrorLogger::log_sampled_error(&error, None, occurrence, 25)
+            })
+            .count();
+
+        let lines = crate::testing::captured_logs(log::Level::Error, "sampling test: connection refused");
+        assert_eq!(logged, 5);
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].contains("Error occurred"));
+        assert!(lines[1].contains("Error repeated 25 times (23 suppressed since last logged)"));
+        assert!(lines[4].contains("Error repeated 100 times (24 suppressed since last logged)"));
+    }
+
     #[test]
     fn test_metrics_logging() {
         // These should not panic
@@ -394,4 +568,27 @@ mod tests {
         assert_eq!(parsed["message"], "test message");
         assert_eq!(parsed["key"], "value");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_rpc_call_aggregator_flushes_one_summary_per_interval() {
+        crate::testing::capture_logs();
+        let aggregator = Arc::new(RpcCallAggregator::default());
+        for duration_ms in [40, 10, 30, 20, 100] {
+            aggregator.record("aggregator_test_getLogs", duration_ms, true);
+        }
+        aggregator.record("aggregator_test_blockNumber", 5, false);
+
+        let summaries = || crate::testing::captured_logs(log::Level::Info, "aggregator_test_getLogs");
+        let flusher = Arc::clone(&aggregator).spawn_flush(Duration::from_millis(100));
+        assert!(summaries().is_empty());
+
+        // Later intervals have nothing new to report
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        flusher.abort();
+
+        let summaries = summaries();
+        assert_eq!(summaries.len(), 1);
+        assert!(summaries[0].contains("aggregator_test_blockNumber 1 calls (1 failed, p50 5ms, p95 5ms)"));
+        assert!(summaries[0].contains("aggregator_test_getLogs 5 calls (0 failed, p50 30ms, p95 100ms)"));
+    }
+}
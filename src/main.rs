@@ -7,20 +7,41 @@ mod error_recovery;
 mod logging;
 mod retry;
 mod config;
+mod build_info;
+mod telemetry;
+#[cfg(test)]
+mod testing;
 
 #[cfg(test)]
 mod error_tests;
 
+use clap::Parser;
 use log::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
-use blockchain::{RpcClient, BlockProcessor, BlockMonitor, BlockMonitorConfig};
+use api::ApiServer;
+use blockchain::{RpcClient, BlockProcessor, BlockMonitor, BlockMonitorConfig, TransferDetector};
 use database::Database;
 use error::IndexerError;
-use logging::{LogContext, ErrorLogger};
+use error_recovery::AlertWebhook;
+use logging::{LogContext, ErrorLogger, MetricsLogger};
 use config::AppConfig;
 
+/// Command-line flags of the indexer; everything else comes from the configuration
+#[derive(Parser)]
+#[command(name = "indexer", version = build_info::LONG_VERSION)]
+struct Args {
+    /// Serve only the read-only HTTP API over an existing database, without RPC access or the block monitor
+    #[arg(long)]
+    api_only: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
     // Display welcome banner
     print_startup_banner();
     
@@ -30,7 +51,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Err(e);
     }
     
-    let context = LogContext::new("main", "startup");
+    let context = LogContext::new("main", "startup")
+        .with_metadata("version", serde_json::json!(build_info::VERSION))
+        .with_metadata("git_commit", serde_json::json!(build_info::GIT_COMMIT));
     context.info("Starting Polygon POL Token Indexer");
     
     // Load configuration with enhanced error handling
@@ -45,11 +68,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Log configuration
     let config_context = LogContext::new("main", "configuration")
-        .with_metadata("rpc_endpoint", serde_json::json!(config.rpc.endpoint))
+        .with_metadata("rpc_endpoint", serde_json::json!(config.rpc.primary_endpoint()))
         .with_metadata("database_path", serde_json::json!(config.database.path))
         .with_metadata("poll_interval_seconds", serde_json::json!(config.processing.poll_interval_seconds));
     config_context.info("Configuration loaded successfully");
     
+    // Export block-processing traces when enabled; kept alive until shutdown
+    let _tracing_guard = match telemetry::init_tracing(&config.logging) {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("Failed to initialize OpenTelemetry tracing: {}", e);
+            return Err(e.into());
+        }
+    };
+    
+    if args.api_only || config.api.api_only {
+        return run_api_only(config).await;
+    }
+    
     // Initialize components with enhanced error handling
     let context = LogContext::new("main", "initialization");
     context.info("Initializing components...");
@@ -68,7 +104,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let context = LogContext::new("main", "monitoring");
     context.info("Starting block monitoring...");
     
-    match components.block_monitor.start().await {
+    // Serve the HTTP API alongside the monitor; both stop on the monitor's shutdown signal
+    let api_server = components.api_server.map(|server| {
+        let shutdown_signal = Arc::clone(&components.block_monitor.shutdown_signal);
+        tokio::spawn(async move {
+            if let Err(e) = server.start_with_shutdown_signal(shutdown_signal).await {
+                log::error!("HTTP API server failed: {}", e);
+            }
+        })
+    });
+    
+    let result = components.block_monitor.start().await;
+    
+    // Stop the API server as well and let its in-flight requests drain
+    components.block_monitor.shutdown();
+    if let Some(api_server) = api_server {
+        if let Err(e) = api_server.await {
+            log::error!("HTTP API server task failed: {}", e);
+        }
+    }
+    
+    match result {
         Ok(()) => {
             context.info("Block monitor stopped normally");
         }
@@ -86,6 +142,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     context.info("Shutdown requested");
                     return Ok(());
                 }
+                e @ blockchain::MonitorError::SkipLimitReached { .. } => {
+                    IndexerError::System(error::SystemError::ResourceExhausted(e.to_string()))
+                }
             };
             ErrorLogger::log_error(&error, Some(LogContext::new("main", "monitoring")));
             return Err(error.into());
@@ -111,9 +170,48 @@ fn print_startup_banner() {
     println!();
 }
 
+/// Serve the API read-only over the existing database, until Ctrl-C
+///
+/// Nothing connects to RPC and no blocks are processed, so this can run against a
+/// read replica of another indexer's database. The database is opened read-only
+/// and never migrated; the indexer writing it must have migrated it already.
+async fn run_api_only(config: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let context = LogContext::new("main", "api_only")
+        .with_metadata("database_path", serde_json::json!(config.database.path))
+        .with_metadata("port", serde_json::json!(config.api.port));
+    if !std::path::Path::new(&config.database.path).exists() {
+        let error = IndexerError::Config(error::ConfigError::InvalidValue {
+            key: "database.path".to_string(),
+            value: format!("{} (API-only mode needs an existing database)", config.database.path),
+        });
+        ErrorLogger::log_error(&error, Some(context));
+        return Err(error.into());
+    }
+    
+    let database = Database::open_read_only(&config.database.path, config.processing.amount_storage)
+        .map_err(IndexerError::from)?
+        .with_read_pool(
+            config.database.connection_pool_size as usize,
+            Duration::from_millis(config.database.busy_timeout_ms as u64),
+        );
+    let server = ApiServer::new(Arc::new(database), config.api.port)
+        .with_max_staleness_seconds(config.api.readiness_max_staleness_seconds)
+        .with_shutdown_drain_timeout(Duration::from_secs(config.api.shutdown_drain_timeout_seconds))
+        .with_admin_ui(config.api.admin_ui_enabled)
+        .with_pretty_json(config.api.pretty_json)
+        .with_address_labels(config.api.include_address_labels)
+        .with_read_only(true);
+    
+    context.info("Starting HTTP API in API-only mode; the block monitor is not running");
+    server.start().await?;
+    context.info("Polygon POL Token Indexer API stopped");
+    Ok(())
+}
+
 /// Components structure
 struct AppComponents {
     block_monitor: BlockMonitor,
+    api_server: Option<ApiServer>,
 }
 
 /// Initialize all application components
@@ -122,11 +220,26 @@ async fn initialize_components(config: AppConfig) -> Result<AppComponents, Index
     
     // Initialize RPC client with timeout configuration
     context.debug("Initializing RPC client");
-    let rpc_client = RpcClient::new_with_config(config.rpc.endpoint, config.rpc.timeout_seconds);
+    // Live monitoring and backfill share one budget of in-flight RPC requests
+    let request_limiter = Arc::new(Semaphore::new(config.rpc.max_concurrent_requests));
+    let mut rpc_client = RpcClient::new_with_pool_config(
+        config.rpc.primary_endpoint().to_string(),
+        config.rpc.timeout_seconds,
+        config.rpc.pool_max_idle_per_host,
+        config.rpc.pool_idle_timeout_seconds,
+    )
+    .with_compression(config.rpc.enable_compression)
+    .with_max_response_bytes(config.rpc.max_response_bytes)
+    .with_request_limiter(Arc::clone(&request_limiter))
+    .with_endpoint_name(config.rpc.primary_endpoint_name());
+    for endpoint in config.rpc.failover_endpoints() {
+        rpc_client = rpc_client.with_fallback_endpoint(&endpoint.name, &endpoint.url);
+    }
     
     // Test RPC connection
     context.debug("Testing RPC connection");
-    match rpc_client.get_latest_block_number_with_retry().await {
+    let probe_timeout = Duration::from_secs(config.rpc.startup_probe_timeout_seconds);
+    match rpc_client.probe_latest_block_number(probe_timeout).await {
         Ok(block_number) => {
             let test_context = LogContext::new("components", "rpc_test")
                 .with_block_number(block_number);
@@ -139,14 +252,47 @@ async fn initialize_components(config: AppConfig) -> Result<AppComponents, Index
         }
     }
     
+    // Re-probe the live-polling endpoints so a recovered one is promoted again
+    if config.rpc.health_check_interval_seconds > 0 && rpc_client.endpoint_pool().len() > 1 {
+        context.debug("Starting RPC endpoint health checker");
+        rpc_client.spawn_health_checker(
+            Duration::from_secs(config.rpc.health_check_interval_seconds),
+            Duration::from_secs(config.rpc.timeout_seconds),
+        );
+    }
+    
     // Initialize database
     context.debug("Initializing database");
-    let database = Database::new(&config.database.path)
+    let database = Database::new_with_amount_storage(&config.database.path, config.processing.amount_storage)
+        .map_err(|e| IndexerError::from(e))?;
+    database.set_synchronous(config.database.synchronous)
         .map_err(|e| IndexerError::from(e))?;
+    let database = database.with_read_pool(
+        config.database.connection_pool_size as usize,
+        Duration::from_millis(config.database.busy_timeout_ms as u64),
+    );
     
     // Initialize block processor
     context.debug("Initializing block processor");
-    let block_processor = BlockProcessor::new(rpc_client.clone());
+    let build_block_processor = |client: RpcClient| {
+        BlockProcessor::new(client)
+            .with_min_block(config.processing.min_block)
+            .with_track_zero_value_transfers(config.processing.track_zero_value_transfers)
+            .with_max_transfers_per_block(config.processing.max_transfers_per_block)
+            .with_on_amount_overflow(config.processing.on_amount_overflow)
+            .with_max_timestamp_skew(config.processing.max_timestamp_skew_seconds)
+            .with_transfer_detector(
+                TransferDetector::new()
+                    .with_drop_self_transfers(config.processing.drop_self_transfers)
+                    .with_classify_internal_transfers(config.processing.classify_internal_transfers)
+                    .with_strict_topic_padding(config.processing.strict_topic_padding)
+                    .with_classification_trace(config.processing.trace_classification),
+            )
+    };
+    let block_processor = build_block_processor(rpc_client.clone());
+    // Separate processor for on-demand processing through the API
+    let api_block_processor = build_block_processor(rpc_client.clone());
+    let endpoint_pool = rpc_client.endpoint_pool();
     
     // Initialize block monitor with configuration
     context.debug("Initializing block monitor");
@@ -155,16 +301,81 @@ async fn initialize_components(config: AppConfig) -> Result<AppComponents, Index
         max_retries: config.rpc.max_retries,
         retry_delay_seconds: config.rpc.retry_delay_seconds,
         max_retry_delay_seconds: config.rpc.max_retry_delay_seconds,
+        fail_fast: config.processing.fail_fast,
+        ordered_commits: config.processing.ordered_commits,
+        pipeline_buffer_size: config.processing.pipeline_buffer_size,
+        initial_lookback_blocks: config.processing.initial_lookback_blocks,
+        min_confirmations: config.processing.min_confirmations,
+        snapshot_every_n_blocks: config.processing.snapshot_every_n_blocks,
+        max_consecutive_skips: config.processing.max_consecutive_skips,
     };
     
-    let block_monitor = BlockMonitor::new(
+    let mut block_monitor = BlockMonitor::new(
         rpc_client,
         block_processor,
         database,
         Some(monitor_config),
-    );
+    )
+    .with_require_archive_for_backfill(config.rpc.require_archive_for_backfill)
+    .with_raw_log_journal(config.processing.journal_raw_logs)
+    .with_error_sample_rate(config.logging.error_sample_rate)
+    .with_skip_duplicate_transfers(config.processing.skip_duplicate_transfers)
+    .with_status_cache_ttl(Duration::from_millis(config.rpc.status_cache_ttl_ms));
+    
+    if config.metrics.flush_interval_seconds > 0 {
+        MetricsLogger::aggregate_rpc_calls(Duration::from_secs(config.metrics.flush_interval_seconds));
+    }
+    if let Some(threshold) = config.alerts.net_flow_threshold()? {
+        block_monitor = block_monitor.with_net_flow_alert(threshold);
+    }
+    if let Some(url) = &config.alerts.webhook_url {
+        block_monitor = block_monitor.with_alert_webhook(AlertWebhook::new(
+            url.as_str(),
+            Duration::from_millis(config.alerts.webhook_timeout_ms),
+            config.alerts.webhook_attempts,
+        ));
+    }
+    if config.database.maintenance_interval_hours > 0 {
+        block_monitor = block_monitor.with_scheduled_maintenance(
+            std::time::Duration::from_secs(config.database.maintenance_interval_hours * 3600),
+            config.database.maintenance_vacuum,
+        );
+    }
+    
+    // Backfill and deep history go to the archive endpoint when one is configured
+    if let Some(archive_endpoint) = config.rpc.archive_endpoint() {
+        context.debug("Initializing archive block processor");
+        let archive_client = RpcClient::new_with_pool_config(
+            archive_endpoint.to_string(),
+            config.rpc.timeout_seconds,
+            config.rpc.pool_max_idle_per_host,
+            config.rpc.pool_idle_timeout_seconds,
+        )
+        .with_compression(config.rpc.enable_compression)
+        .with_max_response_bytes(config.rpc.max_response_bytes)
+        .with_request_limiter(Arc::clone(&request_limiter));
+        block_monitor = block_monitor.with_archive_block_processor(
+            build_block_processor(archive_client),
+        );
+    }
+    
+    // The API server reads the same database the monitor writes
+    let api_server = config.api.enabled.then(|| {
+        ApiServer::new(block_monitor.database(), config.api.port)
+            .with_max_staleness_seconds(config.api.readiness_max_staleness_seconds)
+            .with_shutdown_drain_timeout(Duration::from_secs(config.api.shutdown_drain_timeout_seconds))
+            .with_admin_ui(config.api.admin_ui_enabled)
+            .with_pretty_json(config.api.pretty_json)
+            .with_address_labels(config.api.include_address_labels)
+            .with_min_confirmations(config.processing.min_confirmations)
+            .with_pipeline_occupancy(block_monitor.pipeline_occupancy())
+            .with_block_processor(api_block_processor)
+            .with_endpoint_pool(endpoint_pool)
+            .with_error_recovery_manager(block_monitor.error_recovery_manager())
+    });
     
     Ok(AppComponents {
         block_monitor,
+        api_server,
     })
 }
\ No newline at end of file
@@ -18,15 +18,101 @@ static BINANCE_ADDRESS_SET: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     BINANCE_ADDRESSES.iter().copied().collect()
 });
 
+/// Decides whether an address belongs to the watched exchange
+///
+/// Addresses are passed normalized: lowercase hex without the `0x` prefix.
+/// Implementations beyond exact lists, such as bloom filters or deployer-family
+/// rules, plug into [`AddressClassifier::new`].
+pub trait AddressMatcher: Send + Sync {
+    fn matches(&self, normalized_address: &str) -> bool;
+}
+
+/// Matches exactly the built-in [`BINANCE_ADDRESSES`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatcher;
+
+impl AddressMatcher for ExactMatcher {
+    fn matches(&self, normalized_address: &str) -> bool {
+        BINANCE_ADDRESS_SET.contains(normalized_address)
+    }
+}
+
+/// Matches any address in a caller-supplied set
+#[derive(Debug, Clone, Default)]
+pub struct SetMatcher {
+    addresses: HashSet<String>,
+}
+
+impl SetMatcher {
+    /// Build a matcher from addresses in any case, with or without the `0x` prefix
+    pub fn new<I, S>(addresses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            addresses: addresses
+                .into_iter()
+                .map(|address| AddressClassifier::normalize_address(address.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+impl AddressMatcher for SetMatcher {
+    fn matches(&self, normalized_address: &str) -> bool {
+        self.addresses.contains(normalized_address)
+    }
+}
+
 /// Address classifier for determining if addresses are Binance-related
-pub struct AddressClassifier;
+///
+/// The associated functions use the built-in Binance list; an instance classifies
+/// with whichever [`AddressMatcher`] it was built with.
+pub struct AddressClassifier {
+    matcher: Box<dyn AddressMatcher>,
+    /// Classify transfers between two matched addresses as internal instead of not relevant
+    classify_internal: bool,
+}
+
+impl Default for AddressClassifier {
+    fn default() -> Self {
+        Self::new(Box::new(ExactMatcher))
+    }
+}
 
 impl AddressClassifier {
+    /// Create a classifier that treats addresses accepted by `matcher` as Binance
+    pub fn new(matcher: Box<dyn AddressMatcher>) -> Self {
+        Self { matcher, classify_internal: false }
+    }
+
+    /// Choose whether transfers between two different matched addresses are
+    /// classified as [`TransferDirection::Internal`] (off by default, when they are not relevant)
+    pub fn with_internal_transfers(mut self, classify_internal: bool) -> Self {
+        self.classify_internal = classify_internal;
+        self
+    }
+
+    /// Check if an address is accepted by this classifier's matcher
+    pub fn matches(&self, address: &str) -> bool {
+        self.matcher.matches(&Self::normalize_address(address))
+    }
+
+    /// Classify a transfer with this classifier's matcher
+    pub fn classify(&self, from_address: &str, to_address: &str) -> TransferDirection {
+        let (from, to) = (Self::normalize_address(from_address), Self::normalize_address(to_address));
+        let (from_matches, to_matches) = (self.matcher.matches(&from), self.matcher.matches(&to));
+        if self.classify_internal && from_matches && to_matches && from != to {
+            return TransferDirection::Internal;
+        }
+        Self::direction(from_matches, to_matches)
+    }
+
     /// Check if an address is a Binance address
     /// Performs case-insensitive comparison by converting to lowercase
     pub fn is_binance_address(address: &str) -> bool {
-        let normalized_address = Self::normalize_address(address);
-        BINANCE_ADDRESS_SET.contains(normalized_address.as_str())
+        ExactMatcher.matches(&Self::normalize_address(address))
     }
 
     /// Classify a transfer based on from/to addresses
@@ -36,9 +122,10 @@ impl AddressClassifier {
     /// - 7.2: Correctly identify transfers TO Binance (inflows)
     /// - 7.3: Correctly identify transfers FROM Binance (outflows)
     pub fn classify_transfer(from_address: &str, to_address: &str) -> TransferDirection {
-        let from_is_binance = Self::is_binance_address(from_address);
-        let to_is_binance = Self::is_binance_address(to_address);
+        Self::direction(Self::is_binance_address(from_address), Self::is_binance_address(to_address))
+    }
 
+    fn direction(from_is_binance: bool, to_is_binance: bool) -> TransferDirection {
         match (from_is_binance, to_is_binance) {
             (false, true) => TransferDirection::ToBinance,   // Inflow to Binance
             (true, false) => TransferDirection::FromBinance, // Outflow from Binance
@@ -229,4 +316,71 @@ mod tests {
             TransferDirection::NotRelevant
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_exact_matcher_matches_current_behavior() {
+        let classifier = AddressClassifier::default();
+        let addresses = [
+            "0xF977814e90dA44bFA03b6295A0616a897441aceC",
+            "f977814e90da44bfa03b6295a0616a897441acec",
+            "0x1234567890abcdef1234567890abcdef12345678",
+            "",
+        ];
+
+        for address in addresses {
+            assert_eq!(classifier.matches(address), AddressClassifier::is_binance_address(address));
+        }
+        for (from, to) in [(addresses[2], addresses[0]), (addresses[0], addresses[2]), (addresses[0], addresses[1])] {
+            assert_eq!(classifier.classify(from, to), AddressClassifier::classify_transfer(from, to));
+        }
+    }
+
+    #[test]
+    fn test_set_matcher() {
+        let classifier = AddressClassifier::new(Box::new(SetMatcher::new(["0xAAAA000000000000000000000000000000000001"])));
+
+        assert!(classifier.matches("0xaaaa000000000000000000000000000000000001"));
+        assert!(!classifier.matches(BINANCE_ADDRESSES[0]));
+        assert_eq!(
+            classifier.classify("0x1234567890abcdef1234567890abcdef12345678", "aaaa000000000000000000000000000000000001"),
+            TransferDirection::ToBinance
+        );
+    }
+
+    #[test]
+    fn test_internal_transfers() {
+        let binance_a = "0xF977814e90dA44bFA03b6295A0616a897441aceC";
+        let binance_b = "0xe7804c37c13166fF0b37F5aE0BB07A3aEbb6e245";
+        let user = "0x1234567890abcdef1234567890abcdef12345678";
+
+        // Off by default
+        assert_eq!(AddressClassifier::default().classify(binance_a, binance_b), TransferDirection::NotRelevant);
+
+        let classifier = AddressClassifier::default().with_internal_transfers(true);
+        assert_eq!(classifier.classify(binance_a, binance_b), TransferDirection::Internal);
+        // A self-transfer is not internal movement between addresses
+        assert_eq!(classifier.classify(binance_a, &binance_a.to_lowercase()), TransferDirection::NotRelevant);
+        assert_eq!(classifier.classify(user, binance_a), TransferDirection::ToBinance);
+        assert_eq!(classifier.classify(binance_b, user), TransferDirection::FromBinance);
+    }
+
+    #[test]
+    fn test_custom_matcher_classifies_by_rule() {
+        /// Deposit addresses sharing a deployer prefix
+        struct PrefixMatcher(&'static str);
+
+        impl AddressMatcher for PrefixMatcher {
+            fn matches(&self, normalized_address: &str) -> bool {
+                normalized_address.starts_with(self.0)
+            }
+        }
+
+        let classifier = AddressClassifier::new(Box::new(PrefixMatcher("dead")));
+        let user = "0x1234567890abcdef1234567890abcdef12345678";
+
+        assert_eq!(classifier.classify(user, "0xDEAD000000000000000000000000000000000001"), TransferDirection::ToBinance);
+        assert_eq!(classifier.classify("0xdead00000000000000000000000000000000beef", user), TransferDirection::FromBinance);
+        assert_eq!(classifier.classify("0xdead01", "0xdead02"), TransferDirection::NotRelevant);
+        assert_eq!(classifier.classify(user, BINANCE_ADDRESSES[0]), TransferDirection::NotRelevant);
+    }
+}
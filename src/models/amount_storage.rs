@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::{CalculationError, DecimalAmount};
+
+/// Number of decimals used by the POL token
+pub const POL_DECIMALS: usize = 18;
+
+/// How transfer amounts are persisted in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmountStorage {
+    /// Raw integer wei amounts, exactly as decoded from the chain
+    #[default]
+    Wei,
+    /// Human-readable POL amounts (wei divided by 10^18)
+    Decimal,
+}
+
+impl AmountStorage {
+    /// Name recorded in the schema metadata and accepted in configuration
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AmountStorage::Wei => "wei",
+            AmountStorage::Decimal => "decimal",
+        }
+    }
+
+    /// Check that `stored` is a non-negative amount in this representation and return its canonical form
+    ///
+    /// Wei amounts must be integers; decimal amounts may have at most
+    /// [`POL_DECIMALS`] fractional digits. Leading and trailing zeros are dropped,
+    /// so `"007"` becomes `"7"` and `"1.50"` becomes `"1.5"`.
+    pub fn normalize_stored(self, stored: &str) -> Result<String, CalculationError> {
+        let amount: DecimalAmount = stored.parse()?;
+        if amount.is_negative() {
+            return Err(CalculationError::InvalidDecimal(format!("{} is negative", stored)));
+        }
+        if self == AmountStorage::Wei && !amount.is_integer() {
+            return Err(CalculationError::InvalidDecimal(format!("{} is not a whole number of wei", stored)));
+        }
+        let normalized = amount.to_string();
+        self.stored_to_wei(&normalized)?;
+        Ok(normalized)
+    }
+
+    /// Convert a wei amount into this storage representation
+    pub fn wei_to_stored(self, wei: &str) -> Result<String, CalculationError> {
+        match self {
+            AmountStorage::Wei => Ok(wei.to_string()),
+            AmountStorage::Decimal => {
+                let amount: DecimalAmount = wei.parse()?;
+                Ok(amount.scaled_down(POL_DECIMALS).to_string())
+            }
+        }
+    }
+
+    /// Convert an amount stored in this representation back into wei
+    pub fn stored_to_wei(self, stored: &str) -> Result<String, CalculationError> {
+        match self {
+            AmountStorage::Wei => Ok(stored.to_string()),
+            AmountStorage::Decimal => {
+                let amount: DecimalAmount = stored.parse()?;
+                let wei = amount.scaled_up(POL_DECIMALS);
+                if !wei.is_integer() {
+                    return Err(CalculationError::InvalidDecimal(format!(
+                        "{} has more than {} fractional digits",
+                        stored, POL_DECIMALS
+                    )));
+                }
+                Ok(wei.to_string())
+            }
+        }
+    }
+
+    /// Convert an amount stored in this representation into POL units
+    ///
+    /// Inflows, outflows and signed net flows format alike: a negative amount keeps
+    /// its sign (`-1.5`) and an amount below one POL keeps its leading fractional
+    /// zeros, so one wei is `0.000000000000000001` and exactly one POL is `1`.
+    pub fn stored_to_pol(self, stored: &str) -> Result<String, CalculationError> {
+        let amount: DecimalAmount = stored.parse()?;
+        Ok(match self {
+            AmountStorage::Wei => amount.scaled_down(POL_DECIMALS),
+            AmountStorage::Decimal => amount,
+        }
+        .to_string())
+    }
+}
+
+impl fmt::Display for AmountStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AmountStorage {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "wei" => Ok(AmountStorage::Wei),
+            "decimal" => Ok(AmountStorage::Decimal),
+            _ => Err(format!("Unknown amount storage mode '{}' (expected 'wei' or 'decimal')", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        let storage = AmountStorage::Decimal;
+        assert_eq!(storage.wei_to_stored("1500000000000000000000").unwrap(), "1500");
+        assert_eq!(storage.wei_to_stored("1000500000000000000001").unwrap(), "1000.500000000000000001");
+        assert_eq!(storage.stored_to_wei("1000.500000000000000001").unwrap(), "1000500000000000000001");
+        assert!(storage.stored_to_wei("0.0000000000000000001").is_err());
+    }
+
+    #[test]
+    fn test_wei_is_passthrough() {
+        assert_eq!(AmountStorage::Wei.wei_to_stored("1000000000000000000").unwrap(), "1000000000000000000");
+        assert_eq!(AmountStorage::Wei.stored_to_wei("1000000000000000000").unwrap(), "1000000000000000000");
+    }
+
+    #[test]
+    fn test_stored_to_pol() {
+        assert_eq!(AmountStorage::Wei.stored_to_pol("1500000000000000000").unwrap(), "1.5");
+        assert_eq!(AmountStorage::Decimal.stored_to_pol("1.50").unwrap(), "1.5");
+        assert!(AmountStorage::Wei.stored_to_pol("not-a-number").is_err());
+    }
+
+    #[test]
+    fn test_stored_to_pol_signed_and_sub_unit_amounts() {
+        // Net flows can be negative; the sign precedes the integer part
+        assert_eq!(AmountStorage::Wei.stored_to_pol("-1500000000000000000").unwrap(), "-1.5");
+        assert_eq!(AmountStorage::Wei.stored_to_pol("-1").unwrap(), "-0.000000000000000001");
+        assert_eq!(AmountStorage::Decimal.stored_to_pol("-1.500").unwrap(), "-1.5");
+        // One wei keeps every leading fractional zero
+        assert_eq!(AmountStorage::Wei.stored_to_pol("1").unwrap(), "0.000000000000000001");
+        assert_eq!(AmountStorage::Decimal.stored_to_pol("0.000000000000000001").unwrap(), "0.000000000000000001");
+        // Exactly one POL has no fractional part
+        assert_eq!(AmountStorage::Wei.stored_to_pol("1000000000000000000").unwrap(), "1");
+        assert_eq!(AmountStorage::Wei.stored_to_pol("-1000000000000000000").unwrap(), "-1");
+        assert_eq!(AmountStorage::Wei.stored_to_pol("0").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_normalize_stored() {
+        assert_eq!(AmountStorage::Wei.normalize_stored("007").unwrap(), "7");
+        assert_eq!(AmountStorage::Wei.normalize_stored("0").unwrap(), "0");
+        assert!(AmountStorage::Wei.normalize_stored("1.5").is_err());
+        assert!(AmountStorage::Wei.normalize_stored("-1").is_err());
+        assert!(AmountStorage::Wei.normalize_stored("0x10").is_err());
+        assert!(AmountStorage::Wei.normalize_stored("").is_err());
+
+        assert_eq!(AmountStorage::Decimal.normalize_stored("1.50").unwrap(), "1.5");
+        assert!(AmountStorage::Decimal.normalize_stored("0.0000000000000000001").is_err());
+        assert!(AmountStorage::Decimal.normalize_stored("-0.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!("wei".parse::<AmountStorage>().unwrap(), AmountStorage::Wei);
+        assert_eq!("Decimal".parse::<AmountStorage>().unwrap(), AmountStorage::Decimal);
+        assert!("gwei".parse::<AmountStorage>().is_err());
+    }
+}
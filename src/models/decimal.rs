@@ -0,0 +1,313 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::CalculationError;
+
+/// Exact, arbitrary-precision decimal amount used for token arithmetic.
+///
+/// Amounts are stored as a sign, a base-10 magnitude and a scale (number of
+/// fractional digits), so wei-denominated integers of any size and decimal
+/// POL values are added and subtracted without floating point rounding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalAmount {
+    negative: bool,
+    /// Magnitude digits, most significant first, without leading zeros (empty for zero)
+    digits: Vec<u8>,
+    /// Number of digits after the decimal point
+    scale: usize,
+}
+
+impl DecimalAmount {
+    /// The zero amount
+    pub fn zero() -> Self {
+        Self {
+            negative: false,
+            digits: Vec::new(),
+            scale: 0,
+        }
+    }
+
+    /// Check whether the amount is zero
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Check whether the amount is strictly negative
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Add two amounts
+    pub fn add(&self, other: &Self) -> Self {
+        let scale = self.scale.max(other.scale);
+        let lhs = self.magnitude_at_scale(scale);
+        let rhs = other.magnitude_at_scale(scale);
+
+        if self.negative == other.negative {
+            return Self::normalized(self.negative, add_magnitudes(&lhs, &rhs), scale);
+        }
+
+        match compare_magnitudes(&lhs, &rhs) {
+            Ordering::Equal => Self::zero(),
+            Ordering::Greater => Self::normalized(self.negative, sub_magnitudes(&lhs, &rhs), scale),
+            Ordering::Less => Self::normalized(other.negative, sub_magnitudes(&rhs, &lhs), scale),
+        }
+    }
+
+    /// Subtract `other` from this amount
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.negated())
+    }
+
+    /// Check whether the amount has no fractional part
+    pub fn is_integer(&self) -> bool {
+        self.scale == 0
+    }
+
+    /// Divide the amount by `10^places` exactly
+    pub fn scaled_down(&self, places: usize) -> Self {
+        let little_endian: Vec<u8> = self.digits.iter().rev().copied().collect();
+        Self::normalized(self.negative, little_endian, self.scale + places)
+    }
+
+    /// Multiply the amount by `10^places` exactly
+    pub fn scaled_up(&self, places: usize) -> Self {
+        let shift = places.min(self.scale);
+        let mut little_endian: Vec<u8> = vec![0; places - shift];
+        little_endian.extend(self.digits.iter().rev());
+        Self::normalized(self.negative, little_endian, self.scale - shift)
+    }
+
+    /// Return the amount with its sign flipped
+    pub fn negated(&self) -> Self {
+        Self {
+            negative: !self.negative && !self.is_zero(),
+            digits: self.digits.clone(),
+            scale: self.scale,
+        }
+    }
+
+    /// Magnitude digits (least significant first) rescaled to `scale` fractional digits
+    fn magnitude_at_scale(&self, scale: usize) -> Vec<u8> {
+        let mut digits: Vec<u8> = vec![0; scale - self.scale];
+        digits.extend(self.digits.iter().rev());
+        digits
+    }
+
+    /// Build an amount from least-significant-first digits, trimming redundant zeros
+    fn normalized(negative: bool, mut little_endian: Vec<u8>, mut scale: usize) -> Self {
+        // Drop trailing fractional zeros
+        let mut leading_fraction_zeros = 0;
+        while leading_fraction_zeros < scale
+            && leading_fraction_zeros < little_endian.len()
+            && little_endian[leading_fraction_zeros] == 0
+        {
+            leading_fraction_zeros += 1;
+        }
+        little_endian.drain(..leading_fraction_zeros);
+        scale -= leading_fraction_zeros;
+
+        // Drop leading integer zeros
+        while little_endian.last() == Some(&0) {
+            little_endian.pop();
+        }
+
+        if little_endian.is_empty() {
+            return Self::zero();
+        }
+
+        little_endian.reverse();
+        Self {
+            negative,
+            digits: little_endian,
+            scale,
+        }
+    }
+}
+
+impl FromStr for DecimalAmount {
+    type Err = CalculationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CalculationError::InvalidDecimal(value.to_string());
+
+        let (negative, unsigned) = match value.as_bytes().first() {
+            Some(b'-') => (true, &value[1..]),
+            Some(b'+') => (false, &value[1..]),
+            _ => (false, value),
+        };
+
+        let (integer_part, fraction_part) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (unsigned, ""),
+        };
+
+        if integer_part.is_empty() && fraction_part.is_empty() {
+            return Err(invalid());
+        }
+        if !integer_part.bytes().chain(fraction_part.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let little_endian: Vec<u8> = integer_part
+            .bytes()
+            .chain(fraction_part.bytes())
+            .rev()
+            .map(|b| b - b'0')
+            .collect();
+
+        Ok(Self::normalized(negative, little_endian, fraction_part.len()))
+    }
+}
+
+impl fmt::Display for DecimalAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+
+        let digits: String = self.digits.iter().map(|d| char::from(b'0' + d)).collect();
+        let sign = if self.negative { "-" } else { "" };
+
+        if self.scale == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else if digits.len() > self.scale {
+            let (integer, fraction) = digits.split_at(digits.len() - self.scale);
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        } else {
+            write!(f, "{}0.{:0>width$}", sign, digits, width = self.scale)
+        }
+    }
+}
+
+impl PartialOrd for DecimalAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DecimalAmount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (negative, _) => {
+                let scale = self.scale.max(other.scale);
+                let ordering = compare_magnitudes(
+                    &self.magnitude_at_scale(scale),
+                    &other.magnitude_at_scale(scale),
+                );
+                if negative { ordering.reverse() } else { ordering }
+            }
+        }
+    }
+}
+
+/// Compare two least-significant-first magnitudes
+fn compare_magnitudes(lhs: &[u8], rhs: &[u8]) -> Ordering {
+    let significant = |digits: &[u8]| digits.len() - digits.iter().rev().take_while(|d| **d == 0).count();
+    let (lhs_len, rhs_len) = (significant(lhs), significant(rhs));
+
+    lhs_len.cmp(&rhs_len).then_with(|| lhs[..lhs_len].iter().rev().cmp(rhs[..rhs_len].iter().rev()))
+}
+
+/// Add two least-significant-first magnitudes
+fn add_magnitudes(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(lhs.len().max(rhs.len()) + 1);
+    let mut carry = 0;
+
+    for i in 0..lhs.len().max(rhs.len()) {
+        let sum = lhs.get(i).copied().unwrap_or(0) + rhs.get(i).copied().unwrap_or(0) + carry;
+        result.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+
+    result
+}
+
+/// Subtract `rhs` from `lhs` (least-significant-first); requires `lhs >= rhs`
+fn sub_magnitudes(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(lhs.len());
+    let mut borrow = 0;
+
+    for (i, &digit) in lhs.iter().enumerate() {
+        let mut digit = digit as i8 - rhs.get(i).copied().unwrap_or(0) as i8 - borrow;
+        if digit < 0 {
+            digit += 10;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        result.push(digit as u8);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: &str) -> DecimalAmount {
+        value.parse().expect("valid decimal")
+    }
+
+    #[test]
+    fn test_parse_and_display_normalization() {
+        assert_eq!(amount("0").to_string(), "0");
+        assert_eq!(amount("-0.000").to_string(), "0");
+        assert_eq!(amount("007").to_string(), "7");
+        assert_eq!(amount("2500.0").to_string(), "2500");
+        assert_eq!(amount("0.050").to_string(), "0.05");
+        assert_eq!(amount("-12.340").to_string(), "-12.34");
+        assert_eq!(amount(".5").to_string(), "0.5");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        for value in ["", "-", ".", "abc", "1.2.3", "1e18", "NaN", "inf", " 1"] {
+            assert!(value.parse::<DecimalAmount>().is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        assert_eq!(amount("0.1").add(&amount("0.2")).to_string(), "0.3");
+        assert_eq!(amount("750.75").add(&amount("249.25")).to_string(), "1000");
+        assert_eq!(amount("500").sub(&amount("1500")).to_string(), "-1000");
+        assert_eq!(amount("-5").add(&amount("5")).to_string(), "0");
+        assert_eq!(amount("-1.5").sub(&amount("2.25")).to_string(), "-3.75");
+
+        // Values beyond f64 and u128 precision stay exact
+        let large = amount("115792089237316195423570985008687907853269984665640564039457584007913129639935");
+        assert_eq!(
+            large.add(&amount("1")).to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        );
+        assert_eq!(amount("1000000000000000000001").sub(&amount("1")).to_string(), "1000000000000000000000");
+    }
+
+    #[test]
+    fn test_power_of_ten_scaling() {
+        assert_eq!(amount("1500000000000000000000").scaled_down(18).to_string(), "1500");
+        assert_eq!(amount("1").scaled_down(18).to_string(), "0.000000000000000001");
+        assert_eq!(amount("-2500").scaled_down(3).to_string(), "-2.5");
+        assert_eq!(amount("0.000000000000000001").scaled_up(18).to_string(), "1");
+        assert_eq!(amount("1000.5").scaled_up(18).to_string(), "1000500000000000000000");
+        assert_eq!(amount("0.5").scaled_up(0).to_string(), "0.5");
+        assert!(amount("1.25").scaled_up(2).is_integer());
+        assert!(!amount("1.25").scaled_up(1).is_integer());
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(amount("10") > amount("9.99"));
+        assert!(amount("-10") < amount("-9.99"));
+        assert!(amount("-1") < amount("0"));
+        assert_eq!(amount("1.50").cmp(&amount("1.5")), Ordering::Equal);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod transaction;
 pub mod net_flow;
 pub mod address_classifier;
+pub mod decimal;
+pub mod amount_storage;
 
 pub use transaction::{ProcessedTransfer, RawLog, TransferDirection};
 pub use net_flow::{NetFlowData, NetFlowCalculator, CalculationError};
-pub use address_classifier::{AddressClassifier, BINANCE_ADDRESSES};
\ No newline at end of file
+pub use decimal::DecimalAmount;
+pub use amount_storage::{AmountStorage, POL_DECIMALS};
+pub use address_classifier::{AddressClassifier, AddressMatcher, ExactMatcher, SetMatcher, BINANCE_ADDRESSES};
\ No newline at end of file
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::cmp::Ordering;
+
+use crate::database::NetFlowRow;
+use crate::models::{DecimalAmount, ProcessedTransfer, TransferDirection};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NetFlowData {
@@ -29,27 +32,60 @@ impl NetFlowCalculator {
     pub fn add_inflow(current: &str, amount: &str) -> Result<String, CalculationError> {
         let current_val = Self::parse_decimal(current)?;
         let amount_val = Self::parse_decimal(amount)?;
-        Ok((current_val + amount_val).to_string())
+        Ok(current_val.add(&amount_val).to_string())
     }
 
     /// Add an outflow amount to the current total outflow
     pub fn add_outflow(current: &str, amount: &str) -> Result<String, CalculationError> {
         let current_val = Self::parse_decimal(current)?;
         let amount_val = Self::parse_decimal(amount)?;
-        Ok((current_val + amount_val).to_string())
+        Ok(current_val.add(&amount_val).to_string())
     }
 
     /// Calculate net flow (inflow - outflow)
     pub fn calculate_net(inflow: &str, outflow: &str) -> Result<String, CalculationError> {
         let inflow_val = Self::parse_decimal(inflow)?;
         let outflow_val = Self::parse_decimal(outflow)?;
-        Ok((inflow_val - outflow_val).to_string())
+        Ok(inflow_val.sub(&outflow_val).to_string())
     }
 
-    /// Parse decimal string to f64 for calculations
-    /// Note: In production, consider using a decimal library for exact precision
-    fn parse_decimal(value: &str) -> Result<f64, CalculationError> {
-        f64::from_str(value).map_err(|_| CalculationError::InvalidDecimal(value.to_string()))
+    /// Fold a batch of transfers into `current` and return the new totals
+    ///
+    /// Inflows and outflows are summed exactly and the net flow is recomputed once
+    /// for the whole batch. Irrelevant transfers are ignored; block and timestamp
+    /// bookkeeping is left to the caller.
+    pub fn apply_batch(current: &NetFlowRow, transfers: &[ProcessedTransfer]) -> Result<NetFlowRow, CalculationError> {
+        let mut inflow = Self::parse_decimal(&current.total_inflow)?;
+        let mut outflow = Self::parse_decimal(&current.total_outflow)?;
+
+        for transfer in transfers {
+            match transfer.direction {
+                TransferDirection::ToBinance => inflow = inflow.add(&Self::parse_decimal(&transfer.amount)?),
+                TransferDirection::FromBinance => outflow = outflow.add(&Self::parse_decimal(&transfer.amount)?),
+                TransferDirection::NotRelevant | TransferDirection::Internal => {}
+            }
+        }
+
+        Ok(NetFlowRow {
+            total_inflow: inflow.to_string(),
+            total_outflow: outflow.to_string(),
+            net_flow: inflow.sub(&outflow).to_string(),
+            ..current.clone()
+        })
+    }
+
+    /// Compare two decimal amounts numerically
+    ///
+    /// The comparison is exact at any length, so uint256 wei amounts past
+    /// `u128::MAX` and signed net flows order correctly; comparing the strings
+    /// themselves would put `"100"` below `"20"`.
+    pub fn compare_amounts(a: &str, b: &str) -> Result<Ordering, CalculationError> {
+        Ok(Self::parse_decimal(a)?.cmp(&Self::parse_decimal(b)?))
+    }
+
+    /// Parse a decimal string into an exact amount for calculations
+    pub fn parse_decimal(value: &str) -> Result<DecimalAmount, CalculationError> {
+        value.parse()
     }
 }
 
@@ -159,6 +195,87 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_apply_batch_matches_one_by_one_application() {
+        let current = NetFlowRow {
+            id: 1,
+            total_inflow: "1000".to_string(),
+            total_outflow: "250.5".to_string(),
+            net_flow: "749.5".to_string(),
+            last_processed_block: 42,
+            last_updated: 1640995200,
+        };
+        let transfer = |amount: &str, direction: TransferDirection| ProcessedTransfer {
+            block_number: 43,
+            transaction_hash: "0xbatch".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0xfrom".to_string(),
+            to_address: "0xto".to_string(),
+            amount: amount.to_string(),
+            timestamp: 1640995300,
+            direction,
+        };
+        let transfers = vec![
+            transfer("115792089237316195423570985008687907853269984665640564039457584007913129639935", TransferDirection::ToBinance),
+            transfer("0.25", TransferDirection::FromBinance),
+            transfer("999", TransferDirection::NotRelevant),
+            transfer("1500000000000000000", TransferDirection::FromBinance),
+            transfer("3.75", TransferDirection::ToBinance),
+        ];
+
+        let batched = NetFlowCalculator::apply_batch(&current, &transfers).expect("Failed to apply batch");
+
+        let (mut inflow, mut outflow) = (current.total_inflow.clone(), current.total_outflow.clone());
+        for transfer in &transfers {
+            match transfer.direction {
+                TransferDirection::ToBinance => inflow = NetFlowCalculator::add_inflow(&inflow, &transfer.amount).unwrap(),
+                TransferDirection::FromBinance => outflow = NetFlowCalculator::add_outflow(&outflow, &transfer.amount).unwrap(),
+                TransferDirection::NotRelevant | TransferDirection::Internal => {}
+            }
+        }
+
+        assert_eq!(batched.total_inflow, inflow);
+        assert_eq!(batched.total_outflow, outflow);
+        assert_eq!(batched.net_flow, NetFlowCalculator::calculate_net(&inflow, &outflow).unwrap());
+        assert_eq!(batched.last_processed_block, 42);
+
+        // An empty batch leaves the totals untouched
+        let unchanged = NetFlowCalculator::apply_batch(&current, &[]).unwrap();
+        assert_eq!(unchanged.total_inflow, "1000");
+        assert_eq!(unchanged.net_flow, "749.5");
+
+        // A malformed amount fails the whole batch
+        let invalid = vec![transfer("1e18", TransferDirection::ToBinance)];
+        assert!(NetFlowCalculator::apply_batch(&current, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_compare_amounts_numerically() {
+        assert_eq!(NetFlowCalculator::compare_amounts("100", "20").unwrap(), Ordering::Greater);
+        assert_eq!(NetFlowCalculator::compare_amounts("20", "100").unwrap(), Ordering::Less);
+        assert_eq!(NetFlowCalculator::compare_amounts("0100", "100").unwrap(), Ordering::Equal);
+
+        // 40 digits, past u128::MAX, differing only in the last digit
+        let larger = "1234567890123456789012345678901234567891";
+        let smaller = "1234567890123456789012345678901234567890";
+        assert_eq!(NetFlowCalculator::compare_amounts(larger, smaller).unwrap(), Ordering::Greater);
+        assert_eq!(NetFlowCalculator::compare_amounts(smaller, larger).unwrap(), Ordering::Less);
+        assert_eq!(NetFlowCalculator::compare_amounts(larger, larger).unwrap(), Ordering::Equal);
+        // Also above the fewer-digit u128::MAX
+        assert_eq!(
+            NetFlowCalculator::compare_amounts(smaller, &u128::MAX.to_string()).unwrap(),
+            Ordering::Greater
+        );
+
+        // Signed and fractional amounts
+        assert_eq!(NetFlowCalculator::compare_amounts("-5", "-40").unwrap(), Ordering::Greater);
+        assert_eq!(NetFlowCalculator::compare_amounts("1.5", "1.25").unwrap(), Ordering::Greater);
+
+        assert!(NetFlowCalculator::compare_amounts("12a", "1").is_err());
+        assert!(NetFlowCalculator::compare_amounts("1", "").is_err());
+    }
+
     #[test]
     fn test_calculation_error_display() {
         let error = CalculationError::InvalidDecimal("not_a_number".to_string());
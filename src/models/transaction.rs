@@ -5,6 +5,9 @@ pub struct ProcessedTransfer {
     pub block_number: u64,
     pub transaction_hash: String,
     pub log_index: u32,
+    /// Position of the transaction within its block
+    #[serde(default)]
+    pub transaction_index: u32,
     pub from_address: String,
     pub to_address: String,
     pub amount: String,  // Decimal string representation for precision
@@ -20,6 +23,12 @@ pub struct RawLog {
     pub block_number: u64,
     pub transaction_hash: String,
     pub log_index: u32,
+    /// Position of the emitting transaction within its block
+    #[serde(default)]
+    pub transaction_index: u32,
+    /// Set by the node when the log was removed by a chain reorganization
+    #[serde(default)]
+    pub removed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -27,6 +36,7 @@ pub enum TransferDirection {
     ToBinance,    // Inflow to Binance
     FromBinance,  // Outflow from Binance
     NotRelevant,  // Transfer not involving Binance
+    Internal,     // Transfer between two Binance addresses; stored but moves no net flow
 }
 #
 [cfg(test)]
@@ -40,6 +50,7 @@ mod tests {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1234567890abcdef".to_string(),
             to_address: "0xfedcba0987654321".to_string(),
             amount: "1000000000000000000".to_string(), // 1 POL in wei
@@ -69,6 +80,8 @@ mod tests {
             block_number: 54321,
             transaction_hash: "0xdef456".to_string(),
             log_index: 2,
+            transaction_index: 0,
+            removed: false,
         };
 
         // Test serialization
@@ -118,6 +131,7 @@ mod tests {
                 block_number: 1,
                 transaction_hash: "0x123".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0x111".to_string(),
                 to_address: "0x222".to_string(),
                 amount: "100".to_string(),
@@ -1,8 +1,11 @@
 use std::time::Duration;
 use tokio::time::sleep;
-use crate::error::IndexerError;
+use crate::error::{IndexerError, RpcError, SystemError};
 use crate::logging::{LogContext, ErrorLogger, PerformanceMonitor};
 
+/// HTTP statuses from an RPC endpoint that are worth retrying by default
+pub const DEFAULT_RETRYABLE_STATUSES: [u16; 4] = [500, 502, 503, 504];
+
 /// Configuration for retry behavior
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -16,6 +19,8 @@ pub struct RetryConfig {
     pub backoff_multiplier: f64,
     /// Whether to add jitter to prevent thundering herd
     pub jitter: bool,
+    /// Server error statuses that are retried; other statuses fail immediately
+    pub retryable_statuses: Vec<u16>,
 }
 
 impl Default for RetryConfig {
@@ -26,11 +31,24 @@ impl Default for RetryConfig {
             max_delay_seconds: 60,
             backoff_multiplier: 2.0,
             jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         }
     }
 }
 
 impl RetryConfig {
+    /// Whether `error` should be retried under this configuration
+    ///
+    /// HTTP server errors are retried only for the configured statuses; every other
+    /// error falls back to [`IndexerError::is_recoverable`].
+    pub fn should_retry(&self, error: &IndexerError) -> bool {
+        match error {
+            IndexerError::System(SystemError::RetryExhausted { last_error, .. }) => self.should_retry(last_error),
+            IndexerError::Rpc(RpcError::ServerError { status }) => self.retryable_statuses.contains(status),
+            _ => error.is_recoverable(),
+        }
+    }
+
     /// Create a configuration for RPC operations
     pub fn for_rpc() -> Self {
         Self {
@@ -39,6 +57,7 @@ impl RetryConfig {
             max_delay_seconds: 30,
             backoff_multiplier: 2.0,
             jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         }
     }
 
@@ -50,6 +69,7 @@ impl RetryConfig {
             max_delay_seconds: 10,
             backoff_multiplier: 1.5,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         }
     }
 
@@ -61,6 +81,7 @@ impl RetryConfig {
             max_delay_seconds: 120,
             backoff_multiplier: 2.0,
             jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         }
     }
 
@@ -72,6 +93,39 @@ impl RetryConfig {
             max_delay_seconds: 5,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+        }
+    }
+}
+
+/// Tracks the errors seen across retry attempts so the final error carries their history
+#[derive(Debug, Default)]
+struct AttemptHistory {
+    attempts: u32,
+    distinct_errors: Vec<(String, u32)>,
+}
+
+impl AttemptHistory {
+    fn record(&mut self, error: &IndexerError) {
+        self.attempts += 1;
+        let kind = error.kind();
+        match self.distinct_errors.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, count)) => *count += 1,
+            None => self.distinct_errors.push((kind.to_string(), 1)),
+        }
+    }
+
+    /// Wrap the last error with the aggregated attempt context
+    fn into_error(self, last_error: Option<IndexerError>) -> IndexerError {
+        match last_error {
+            Some(last_error) => IndexerError::System(crate::error::SystemError::RetryExhausted {
+                attempts: self.attempts,
+                last_error: Box::new(last_error),
+                distinct_errors: self.distinct_errors,
+            }),
+            None => IndexerError::System(crate::error::SystemError::ResourceExhausted(
+                "All retry attempts exhausted".to_string()
+            )),
         }
     }
 }
@@ -98,6 +152,7 @@ impl RetryManager {
     {
         let monitor = PerformanceMonitor::new(&format!("retry_{}", self.operation_name));
         let mut last_error = None;
+        let mut history = AttemptHistory::default();
 
         for attempt in 1..=self.config.max_attempts {
             let attempt_monitor = PerformanceMonitor::new(&format!("{}_attempt_{}", self.operation_name, attempt));
@@ -116,9 +171,10 @@ impl RetryManager {
                 }
                 Err(error) => {
                     attempt_monitor.finish_with_result::<(), &IndexerError>(&Err(&error));
+                    history.record(&error);
                     
                     // Check if error is recoverable
-                    if !error.is_recoverable() {
+                    if !self.config.should_retry(&error) {
                         let context = LogContext::new("retry", &self.operation_name)
                             .with_retry_count(attempt)
                             .with_metadata("reason", serde_json::json!("non_recoverable"));
@@ -154,15 +210,11 @@ impl RetryManager {
         }
 
         // All retries exhausted
-        let final_error = last_error.unwrap_or_else(|| {
-            IndexerError::System(crate::error::SystemError::ResourceExhausted(
-                "All retry attempts exhausted".to_string()
-            ))
-        });
+        let final_error = history.into_error(last_error);
 
         let context = LogContext::new("retry", &self.operation_name)
             .with_metadata("max_attempts", serde_json::json!(self.config.max_attempts));
-        context.error(&format!("All {} retry attempts failed: {}", self.config.max_attempts, final_error));
+        context.error(&format!("All retry attempts failed for {}: {}", self.operation_name, final_error));
 
         Err(final_error)
     }
@@ -180,6 +232,7 @@ impl RetryManager {
     {
         let monitor = PerformanceMonitor::new(&format!("retry_with_handler_{}", self.operation_name));
         let mut last_error = None;
+        let mut history = AttemptHistory::default();
 
         for attempt in 1..=self.config.max_attempts {
             match operation().await {
@@ -194,6 +247,8 @@ impl RetryManager {
                     return Ok(result);
                 }
                 Err(error) => {
+                    history.record(&error);
+
                     // Check custom error handler
                     if !error_handler(&error, attempt) {
                         let context = LogContext::new("retry", &self.operation_name)
@@ -217,11 +272,7 @@ impl RetryManager {
             }
         }
 
-        Err(last_error.unwrap_or_else(|| {
-            IndexerError::System(crate::error::SystemError::ResourceExhausted(
-                "All retry attempts exhausted".to_string()
-            ))
-        }))
+        Err(history.into_error(last_error))
     }
 
     /// Calculate delay for the given attempt number
@@ -450,6 +501,7 @@ mod tests {
             max_delay_seconds: 10,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test_operation", config);
@@ -470,6 +522,7 @@ mod tests {
             max_delay_seconds: 10,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test_operation", config);
@@ -484,6 +537,49 @@ mod tests {
         // Should fail immediately without retries for non-recoverable errors
     }
 
+    #[tokio::test]
+    async fn test_retry_manager_aggregates_errors_across_attempts() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let config = RetryConfig {
+            max_attempts: 5,
+            initial_delay_seconds: 0,
+            max_delay_seconds: 0,
+            backoff_multiplier: 1.0,
+            jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+        };
+
+        let retry_manager = RetryManager::new("test_operation", config);
+        let calls = AtomicU32::new(0);
+
+        // Alternate between timeouts and connection failures
+        let result = retry_manager.execute(|| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call % 2 == 0 {
+                    Err::<i32, IndexerError>(IndexerError::Rpc(crate::error::RpcError::Timeout { seconds: 30 }))
+                } else {
+                    Err::<i32, IndexerError>(IndexerError::Rpc(crate::error::RpcError::Connection("refused".to_string())))
+                }
+            }
+        }).await;
+
+        let error = result.unwrap_err();
+        match &error {
+            IndexerError::System(crate::error::SystemError::RetryExhausted { attempts, last_error, distinct_errors }) => {
+                assert_eq!(*attempts, 5);
+                assert_eq!(distinct_errors, &vec![("Timeout".to_string(), 3), ("Connection".to_string(), 2)]);
+                assert!(matches!(**last_error, IndexerError::Rpc(crate::error::RpcError::Timeout { .. })));
+            }
+            other => panic!("Expected RetryExhausted, got {:?}", other),
+        }
+
+        assert!(error.to_string().contains("5 attempts: 3×Timeout, 2×Connection"));
+        // Recoverability follows the last underlying error
+        assert!(error.is_recoverable());
+    }
+
     #[tokio::test]
     async fn test_circuit_breaker_normal_operation() {
         let circuit_breaker = CircuitBreaker::new(3, 10);
@@ -532,6 +628,7 @@ mod tests {
             max_delay_seconds: 30,
             backoff_multiplier: 2.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test", config);
@@ -553,6 +650,7 @@ mod tests {
             max_delay_seconds: 20,
             backoff_multiplier: 3.0,
             jitter: false,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
         };
 
         let retry_manager = RetryManager::new("test", config);
@@ -561,4 +659,21 @@ mod tests {
         // 5 * 3^4 = 5 * 81 = 405, but capped at 20
         assert_eq!(delay5.as_secs(), 20);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_retry_eligibility_follows_configured_statuses() {
+        let server_error = |status| IndexerError::Rpc(RpcError::ServerError { status });
+
+        let config = RetryConfig::for_rpc();
+        for status in [500, 502, 503, 504] {
+            assert!(config.should_retry(&server_error(status)), "{} should be retried", status);
+        }
+        assert!(!config.should_retry(&server_error(501)));
+        assert!(!config.should_retry(&IndexerError::Rpc(RpcError::ClientError { status: 400 })));
+        assert!(config.should_retry(&IndexerError::Rpc(RpcError::Timeout { seconds: 30 })));
+
+        let config = RetryConfig { retryable_statuses: vec![503], ..RetryConfig::for_rpc() };
+        assert!(config.should_retry(&server_error(503)));
+        assert!(!config.should_retry(&server_error(500)));
+    }
+}
@@ -0,0 +1,77 @@
+//! OpenTelemetry trace export for block processing
+//!
+//! Block processing is instrumented with `tracing` spans: `process_single_block`
+//! per block, with `rpc_fetch`, `decode` and `db_write` children. Without a
+//! subscriber they cost next to nothing. Built with the `otel` feature and with
+//! `logging.otel_enabled` set, [`init_tracing`] exports them over OTLP.
+
+use crate::config::LoggingConfig;
+
+/// Keeps trace export running; dropping it flushes and shuts down the exporter
+#[derive(Debug)]
+pub struct TracingGuard {
+    #[cfg(feature = "otel")]
+    provider: opentelemetry_sdk::trace::TracerProvider,
+}
+
+#[cfg(feature = "otel")]
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            log::warn!("Failed to flush OpenTelemetry traces: {}", e);
+        }
+    }
+}
+
+/// Name traces are reported under
+pub const SERVICE_NAME: &str = "polygon-pol-indexer";
+
+/// Start exporting block-processing spans to `config.otel_endpoint`, if enabled
+///
+/// Must be called from within a Tokio runtime. Returns `None` when export is
+/// disabled.
+#[cfg(feature = "otel")]
+pub fn init_tracing(config: &LoggingConfig) -> Result<Option<TracingGuard>, opentelemetry::trace::TraceError> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    if !config.otel_enabled {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(config.otel_endpoint.clone())
+        .build()?;
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new(vec![opentelemetry::KeyValue::new(
+            "service.name",
+            SERVICE_NAME,
+        )]))
+        .build();
+
+    tracing::subscriber::set_global_default(subscriber(&provider))
+        .map_err(|e| opentelemetry::trace::TraceError::Other(Box::new(e)))?;
+    log::info!("Exporting OpenTelemetry traces to {}", config.otel_endpoint);
+
+    Ok(Some(TracingGuard { provider }))
+}
+
+/// Without the `otel` feature there is nothing to export to
+#[cfg(not(feature = "otel"))]
+pub fn init_tracing(config: &LoggingConfig) -> Result<Option<TracingGuard>, std::convert::Infallible> {
+    if config.otel_enabled {
+        log::warn!("logging.otel_enabled is set, but this build lacks the `otel` feature; traces are not exported");
+    }
+    Ok(None)
+}
+
+/// Subscriber forwarding `tracing` spans to `provider`
+#[cfg(feature = "otel")]
+pub fn subscriber(provider: &opentelemetry_sdk::trace::TracerProvider) -> impl tracing::Subscriber + Send + Sync {
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)))
+}
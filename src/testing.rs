@@ -0,0 +1,333 @@
+//! Test helpers shared across the crate's unit tests
+
+use std::ops::RangeInclusive;
+use std::sync::{Mutex, Once};
+
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::blockchain::transfer_detector::{POL_TOKEN_ADDRESS, TRANSFER_EVENT_SIGNATURE};
+use crate::blockchain::RpcClient;
+use crate::models::{ProcessedTransfer, RawLog};
+
+/// Block timestamp of block 0; block `n` is `n` seconds later
+pub const GENESIS_TIMESTAMP: u64 = 1_700_000_000;
+
+/// Builder for a wiremock-backed Polygon JSON-RPC node
+///
+/// Blocks that were not configured with [`with_block`](Self::with_block) exist
+/// but are empty, so any block up to the latest one can be fetched.
+#[derive(Default)]
+pub struct MockPolygonNode {
+    latest_block: u64,
+    blocks: Vec<(u64, Vec<ProcessedTransfer>)>,
+    logs: Vec<(RangeInclusive<u64>, Vec<RawLog>)>,
+    failures: u64,
+}
+
+impl MockPolygonNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block number returned by `eth_blockNumber`
+    pub fn with_latest_block(mut self, block_number: u64) -> Self {
+        self.latest_block = block_number;
+        self
+    }
+
+    /// Serve `transfers` as POL Transfer logs of `block_number`
+    pub fn with_block(mut self, block_number: u64, transfers: Vec<ProcessedTransfer>) -> Self {
+        self.blocks.push((block_number, transfers));
+        self
+    }
+
+    /// Serve `logs` for an `eth_getLogs` request covering exactly `range`
+    pub fn with_logs(mut self, range: RangeInclusive<u64>, logs: Vec<RawLog>) -> Self {
+        self.logs.push((range, logs));
+        self
+    }
+
+    /// Answer the first `times` requests, whatever the method, with HTTP 500
+    pub fn fail_times(mut self, times: u64) -> Self {
+        self.failures = times;
+        self
+    }
+
+    /// Hash reported for `block_number`
+    pub fn block_hash(block_number: u64) -> String {
+        format!("0x{:064x}", block_number)
+    }
+
+    /// Transfer log emitted by the POL token for `transfer`
+    pub fn transfer_log(transfer: &ProcessedTransfer) -> RawLog {
+        RawLog {
+            address: POL_TOKEN_ADDRESS.to_string(),
+            topics: vec![
+                TRANSFER_EVENT_SIGNATURE.to_string(),
+                address_topic(&transfer.from_address),
+                address_topic(&transfer.to_address),
+            ],
+            data: format!("0x{:0>64}", decimal_to_hex(&transfer.amount)),
+            block_number: transfer.block_number,
+            transaction_hash: transfer.transaction_hash.clone(),
+            log_index: transfer.log_index,
+            transaction_index: transfer.transaction_index,
+            removed: false,
+        }
+    }
+
+    /// Start the mock node, returning the server and a client pointed at it
+    pub async fn start(self) -> (MockServer, RpcClient) {
+        let server = MockServer::start().await;
+
+        if self.failures > 0 {
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(500))
+                .up_to_n_times(self.failures)
+                .with_priority(1)
+                .mount(&server)
+                .await;
+        }
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_blockNumber"})))
+            .respond_with(rpc_result(json!(hex(self.latest_block))))
+            .mount(&server)
+            .await;
+
+        for (block_number, transfers) in &self.blocks {
+            Mock::given(method("POST"))
+                .and(body_partial_json(json!({
+                    "method": "eth_getBlockByNumber", "params": [hex(*block_number), true]
+                })))
+                .respond_with(rpc_result(block_json(*block_number)))
+                .with_priority(2)
+                .mount(&server)
+                .await;
+
+            let logs: Vec<RawLog> = transfers
+                .iter()
+                .map(|transfer| Self::transfer_log(&ProcessedTransfer { block_number: *block_number, ..transfer.clone() }))
+                .collect();
+            mount_logs(&server, *block_number..=*block_number, &logs).await;
+        }
+
+        for (range, logs) in &self.logs {
+            mount_logs(&server, range.clone(), logs).await;
+        }
+
+        // Anything not configured above is an empty block
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_getBlockByNumber"})))
+            .respond_with(move |request: &wiremock::Request| {
+                let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                let block_number = body["params"][0]
+                    .as_str()
+                    .and_then(|n| u64::from_str_radix(n.trim_start_matches("0x"), 16).ok())
+                    .unwrap_or(0);
+                rpc_result(block_json(block_number))
+            })
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_getLogs"})))
+            .respond_with(rpc_result(json!([])))
+            .mount(&server)
+            .await;
+
+        let rpc_client = RpcClient::new(server.uri());
+        (server, rpc_client)
+    }
+}
+
+async fn mount_logs(server: &MockServer, range: RangeInclusive<u64>, logs: &[RawLog]) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({
+            "method": "eth_getLogs",
+            "params": [{"fromBlock": hex(*range.start()), "toBlock": hex(*range.end())}]
+        })))
+        .respond_with(rpc_result(Value::Array(logs.iter().map(log_json).collect())))
+        .with_priority(2)
+        .mount(server)
+        .await;
+}
+
+fn rpc_result(result: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": result}))
+}
+
+fn block_json(block_number: u64) -> Value {
+    json!({
+        "number": hex(block_number),
+        "hash": MockPolygonNode::block_hash(block_number),
+        "timestamp": hex(GENESIS_TIMESTAMP + block_number),
+        "transactions": []
+    })
+}
+
+fn log_json(log: &RawLog) -> Value {
+    json!({
+        "address": log.address,
+        "topics": log.topics,
+        "data": log.data,
+        "blockNumber": hex(log.block_number),
+        "transactionHash": log.transaction_hash,
+        "logIndex": hex(log.log_index as u64),
+        "transactionIndex": hex(log.transaction_index as u64),
+        "removed": log.removed
+    })
+}
+
+/// Hex digits of a decimal wei amount of any size, so amounts above `u128::MAX` can be served
+fn decimal_to_hex(amount: &str) -> String {
+    // Hex digits, least significant first
+    let mut digits: Vec<u32> = vec![0];
+    for c in amount.chars() {
+        let mut carry = c.to_digit(10).expect("mock transfer amounts must be decimal wei");
+        for digit in digits.iter_mut() {
+            let value = *digit * 10 + carry;
+            *digit = value % 16;
+            carry = value / 16;
+        }
+        while carry > 0 {
+            digits.push(carry % 16);
+            carry /= 16;
+        }
+    }
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits.iter().rev().map(|digit| char::from_digit(*digit, 16).unwrap_or('0')).collect()
+}
+
+fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x"))
+}
+
+fn hex(value: u64) -> String {
+    format!("0x{:x}", value)
+}
+
+/// Debug-and-above log records captured since [`capture_logs`] was first called
+static CAPTURED_LOGS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            if let Ok(mut logs) = CAPTURED_LOGS.lock() {
+                logs.push((record.level(), record.args().to_string()));
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the process-wide capturing logger used by [`captured_logs`]
+///
+/// Tests run in parallel and share the logger, so assertions should match on
+/// messages unique to the test.
+pub fn capture_logs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&CapturingLogger).expect("no other logger may be installed in tests");
+        log::set_max_level(log::LevelFilter::Debug);
+    });
+}
+
+/// Captured messages at `level` that contain `needle`
+pub fn captured_logs(level: log::Level, needle: &str) -> Vec<String> {
+    CAPTURED_LOGS
+        .lock()
+        .map(|logs| {
+            logs.iter()
+                .filter(|(record_level, message)| *record_level == level && message.contains(needle))
+                .map(|(_, message)| message.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::transfer_detector::BINANCE_ADDRESSES;
+    use crate::blockchain::{BlockProcessor, LogFilter};
+    use crate::models::TransferDirection;
+
+    fn transfer(log_index: u32, from: &str, to: &str, amount: &str) -> ProcessedTransfer {
+        ProcessedTransfer {
+            block_number: 0,
+            transaction_hash: format!("0xmock{}", log_index),
+            log_index,
+            transaction_index: 0,
+            from_address: from.to_string(),
+            to_address: to.to_string(),
+            amount: amount.to_string(),
+            timestamp: 0,
+            direction: TransferDirection::NotRelevant,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_node_serves_configured_block() {
+        let external = "0x1111111111111111111111111111111111111111";
+        let (server, rpc_client) = MockPolygonNode::new()
+            .with_latest_block(250)
+            .with_block(
+                200,
+                vec![
+                    transfer(0, external, BINANCE_ADDRESSES[0], "1500000000000000000"),
+                    transfer(1, BINANCE_ADDRESSES[0], external, "250000000000000000"),
+                ],
+            )
+            .fail_times(1)
+            .start()
+            .await;
+
+        // The injected failure is retried away
+        assert_eq!(rpc_client.get_latest_block_number_with_retry().await.unwrap(), 250);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        let processor = BlockProcessor::new(rpc_client.clone());
+        let (hash, transfers) = processor.process_block_with_hash(200).await.unwrap();
+        assert_eq!(hash, MockPolygonNode::block_hash(200));
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].direction, TransferDirection::ToBinance);
+        assert_eq!(transfers[0].amount, "1500000000000000000");
+        assert_eq!(transfers[0].block_number, 200);
+        assert_eq!(transfers[1].direction, TransferDirection::FromBinance);
+        assert_eq!(transfers[1].timestamp, GENESIS_TIMESTAMP + 200);
+
+        // Unconfigured blocks are empty
+        let (hash, transfers) = processor.process_block_with_hash(201).await.unwrap();
+        assert_eq!(hash, MockPolygonNode::block_hash(201));
+        assert!(transfers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_node_serves_logs_for_range() {
+        let log = MockPolygonNode::transfer_log(&ProcessedTransfer {
+            block_number: 12,
+            ..transfer(3, "0xaaaa", "0xbbbb", "42")
+        });
+        let (_server, rpc_client) = MockPolygonNode::new().with_logs(10..=20, vec![log.clone()]).start().await;
+
+        let filter = |from: u64, to: u64| LogFilter {
+            from_block: hex(from),
+            to_block: hex(to),
+            address: None,
+            topics: None,
+        };
+        assert_eq!(rpc_client.get_logs(filter(10, 20)).await.unwrap(), vec![log]);
+        assert!(rpc_client.get_logs(filter(10, 11)).await.unwrap().is_empty());
+    }
+}
@@ -97,6 +97,13 @@ async fn test_complete_monitoring_workflow() {
         max_retries: 2,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 5,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database.clone(), Some(config));
@@ -140,6 +147,7 @@ async fn test_workflow_error_recovery() {
         block_number: 1000,
         transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0x1111111111111111111111111111111111111111".to_string(),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         amount: "1000000000000000000".to_string(),
@@ -213,6 +221,10 @@ async fn test_data_consistency_workflow() {
                 expected_outflow += transfer.amount.parse::<u128>().unwrap();
                 transaction_count += 1;
             }
+            TransferDirection::Internal => {
+                // Stored without moving the net flow
+                transaction_count += 1;
+            }
             TransferDirection::NotRelevant => {
                 // Should not be stored
             }
@@ -336,6 +348,7 @@ fn create_transfer(
         block_number,
         transaction_hash: format!("0x{:064x}", block_number),
         log_index: 0,
+        transaction_index: 0,
         from_address,
         to_address,
         amount: amount.to_string(),
@@ -108,7 +108,7 @@ async fn test_complete_system_with_live_polygon_network() {
         match &transfer.direction {
             TransferDirection::ToBinance => manual_inflow += amount,
             TransferDirection::FromBinance => manual_outflow += amount,
-            TransferDirection::NotRelevant => {}
+            TransferDirection::NotRelevant | TransferDirection::Internal => {}
         }
     }
     
@@ -479,6 +479,7 @@ async fn test_comprehensive_requirements_verification() {
                 block_number: 100,
                 transaction_hash: "0xtest1".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0xsender".to_string(),
                 to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance address
                 amount: "1000000000000000000".to_string(), // 1 POL
@@ -489,6 +490,7 @@ async fn test_comprehensive_requirements_verification() {
                 block_number: 101,
                 transaction_hash: "0xtest2".to_string(),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance address
                 to_address: "0xreceiver".to_string(),
                 amount: "500000000000000000".to_string(), // 0.5 POL
@@ -4,8 +4,9 @@ use axum::{
     Router,
 };
 use polygon_pol_indexer::api::AppState;
+use polygon_pol_indexer::blockchain::WatchedAddress;
 use polygon_pol_indexer::database::Database;
-use polygon_pol_indexer::models::{ProcessedTransfer, TransferDirection};
+use polygon_pol_indexer::models::{AmountStorage, ProcessedTransfer, TransferDirection};
 use serde_json::Value;
 use std::sync::Arc;
 use tower::util::ServiceExt;
@@ -20,9 +21,10 @@ async fn setup_test_database() -> Arc<Database> {
             block_number: 100,
             transaction_hash: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
             log_index: 0,
-            from_address: "0xsender1".to_string(),
-            to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance
-            amount: "1000.5".to_string(),
+            transaction_index: 0,
+            from_address: "5e4de15e4de15e4de15e4de15e4de15e4de15e4d".to_string(),
+            to_address: "f977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance
+            amount: "100050".to_string(),
             timestamp: 1640995200, // 2022-01-01 00:00:00 UTC
             direction: TransferDirection::ToBinance,
         },
@@ -30,9 +32,10 @@ async fn setup_test_database() -> Arc<Database> {
             block_number: 101,
             transaction_hash: "0xfedcba0987654321fedcba0987654321fedcba09".to_string(),
             log_index: 1,
-            from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(), // Binance
-            to_address: "0xreceiver1".to_string(),
-            amount: "500.25".to_string(),
+            transaction_index: 0,
+            from_address: "e7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(), // Binance
+            to_address: "9ec0ed9ec0ed9ec0ed9ec0ed9ec0ed9ec0ed9ec0".to_string(),
+            amount: "50025".to_string(),
             timestamp: 1640995260, // 2022-01-01 00:01:00 UTC
             direction: TransferDirection::FromBinance,
         },
@@ -40,9 +43,10 @@ async fn setup_test_database() -> Arc<Database> {
             block_number: 102,
             transaction_hash: "0xabcdef1234567890abcdef1234567890abcdef12".to_string(),
             log_index: 0,
-            from_address: "0xsender2".to_string(),
-            to_address: "0x505e71695e9bc45943c58adec1650577bca68fd9".to_string(), // Binance
-            amount: "2500.0".to_string(),
+            transaction_index: 0,
+            from_address: "5e4de25e4de25e4de25e4de25e4de25e4de25e4d".to_string(),
+            to_address: "505e71695e9bc45943c58adec1650577bca68fd9".to_string(), // Binance
+            amount: "250000".to_string(),
             timestamp: 1640995320, // 2022-01-01 00:02:00 UTC
             direction: TransferDirection::ToBinance,
         },
@@ -62,18 +66,40 @@ async fn setup_test_database() -> Arc<Database> {
 
 /// Helper function to create a test router
 fn create_test_router(database: Arc<Database>) -> Router {
-    use axum::routing::get;
-    use polygon_pol_indexer::api::http::{get_net_flow, get_status, get_transactions};
+    create_test_router_with_state(AppState::new(database))
+}
+
+/// Helper function to create a test router from a prepared application state
+fn create_test_router_with_state(app_state: AppState) -> Router {
+    use axum::routing::{get, post};
+    use polygon_pol_indexer::api::http::{
+        get_address_counterparties, get_admin_ui, get_error_statistics, get_flow_series, get_net_flow, get_net_flow_range, get_net_flow_rolling, get_ready, get_status,
+        get_net_flow_audit, get_transactions, get_version, get_watched_addresses, post_admin_process_block, post_admin_reprocess, pretty_json,
+    };
     use tower::ServiceBuilder;
     use tower_http::cors::CorsLayer;
 
-    let app_state = AppState { database };
-
     Router::new()
+        .route("/", get(get_admin_ui))
         .route("/net-flow", get(get_net_flow))
+        .route("/net-flow/range", get(get_net_flow_range))
+        .route("/net-flow/rolling", get(get_net_flow_rolling))
+        .route("/flow/series", get(get_flow_series))
         .route("/status", get(get_status))
+        .route("/ready", get(get_ready))
+        .route("/version", get(get_version))
+        .route("/config/watched-addresses", get(get_watched_addresses))
+        .route("/diagnostics/errors", get(get_error_statistics))
+        .route("/diagnostics/net-flow-audit", get(get_net_flow_audit))
         .route("/transactions", get(get_transactions))
-        .layer(ServiceBuilder::new().layer(CorsLayer::permissive()))
+        .route("/address/:address/counterparties", get(get_address_counterparties))
+        .route("/admin/reprocess", post(post_admin_reprocess))
+        .route("/admin/process-block/:block_number", post(post_admin_process_block))
+        .layer(
+            ServiceBuilder::new()
+                .layer(CorsLayer::permissive())
+                .layer(axum::middleware::from_fn_with_state(app_state.clone(), pretty_json)),
+        )
         .with_state(app_state)
 }
 
@@ -104,12 +130,95 @@ async fn test_get_net_flow_endpoint() {
     assert!(json.get("last_updated").is_some());
 
     // Verify values based on test data
-    assert_eq!(json["total_inflow"], "3500.5"); // 1000.5 + 2500.0
-    assert_eq!(json["total_outflow"], "500.25");
-    assert_eq!(json["net_flow"], "3000.25"); // 3500.5 - 500.25
+    assert_eq!(json["total_inflow"], "350050"); // 100050 + 250000
+    assert_eq!(json["total_outflow"], "50025");
+    assert_eq!(json["net_flow"], "300025"); // 350050 - 50025
+    assert_eq!(json["direction"], "inflow");
+    assert_eq!(json["last_processed_block"], 102);
+}
+
+#[tokio::test]
+async fn test_get_net_flow_reports_freshness() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri("/net-flow")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    // The test data was just written, so last_updated is close to now
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let last_updated = json["last_updated"].as_u64().expect("last_updated should be a timestamp");
+    assert!(last_updated <= now && now - last_updated < 60);
+
+    let age_seconds = json["age_seconds"].as_u64().expect("age_seconds should be a non-negative integer");
+    assert!(age_seconds < 60);
     assert_eq!(json["last_processed_block"], 102);
 }
 
+#[tokio::test]
+async fn test_get_version_endpoint() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri("/version")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!json["git_commit"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_watched_addresses_defaults_to_binance() {
+    use polygon_pol_indexer::blockchain::transfer_detector::BINANCE_ADDRESSES;
+
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri("/config/watched-addresses")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    let mut expected: Vec<String> = BINANCE_ADDRESSES.iter().map(|a| a.to_lowercase()).collect();
+    expected.sort();
+
+    assert_eq!(json["count"], expected.len());
+    let addresses = json["addresses"].as_array().unwrap();
+    let returned: Vec<&str> = addresses.iter().map(|a| a["address"].as_str().unwrap()).collect();
+    assert_eq!(returned, expected);
+    assert!(addresses.iter().all(|a| a["label"] == "Binance"));
+}
+
 #[tokio::test]
 async fn test_get_status_endpoint() {
     let database = setup_test_database().await;
@@ -134,6 +243,7 @@ async fn test_get_status_endpoint() {
     assert!(json.get("last_processed_block").is_some());
     assert!(json.get("total_transactions").is_some());
     assert!(json.get("last_updated").is_some());
+    assert!(json.get("seconds_since_last_block").is_some());
     assert!(json.get("database_status").is_some());
 
     // Verify values
@@ -141,6 +251,8 @@ async fn test_get_status_endpoint() {
     assert_eq!(json["last_processed_block"], 102);
     assert_eq!(json["total_transactions"], 3);
     assert_eq!(json["database_status"], "connected");
+    assert_eq!(json["indexed_from"], 100);
+    assert_eq!(json["indexed_to"], 102);
 }
 
 #[tokio::test]
@@ -250,6 +362,81 @@ async fn test_get_transactions_endpoint_with_offset() {
     assert_eq!(transactions.len(), 2); // Should return 2 transactions (offset 1, limit 2)
 }
 
+#[tokio::test]
+async fn test_get_transactions_endpoint_with_since_id() {
+    let database = setup_test_database().await;
+    let first_id = database.get_transactions_since(0, 1).unwrap()[0].id;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri(format!("/transactions?since_id={}&limit=1", first_id))
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    // Rows after since_id come back oldest first, one page at a time
+    let transactions = json["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["id"], first_id + 1);
+    assert_eq!(json["has_more"], true);
+}
+
+#[tokio::test]
+async fn test_get_transactions_endpoint_pages_with_before_id_cursor() {
+    let database = setup_test_database().await;
+    let total = database.get_transaction_count().unwrap() as usize;
+    let app = create_test_router(database);
+
+    let mut ids = Vec::new();
+    let mut uri = "/transactions?limit=2".to_string();
+    loop {
+        let request = Request::builder().uri(uri.as_str()).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        ids.extend(json["transactions"].as_array().unwrap().iter().map(|tx| tx["id"].as_i64().unwrap()));
+
+        match json["next_before_id"].as_i64() {
+            Some(next) => {
+                assert_eq!(json["has_more"], true);
+                uri = format!("/transactions?limit=2&before_id={}", next);
+            }
+            None => break,
+        }
+    }
+
+    // Newest first, every row exactly once
+    assert_eq!(ids.len(), total);
+    assert!(ids.windows(2).all(|pair| pair[0] > pair[1]));
+
+    // Keyset parameters cannot be mixed with an offset
+    let request = Request::builder()
+        .uri("/transactions?offset=1&direction=inflow")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .uri("/transactions?direction=sideways")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_get_transactions_endpoint_invalid_limit_zero() {
     let database = setup_test_database().await;
@@ -349,6 +536,7 @@ async fn test_endpoints_with_empty_database() {
     assert_eq!(json["total_inflow"], "0");
     assert_eq!(json["total_outflow"], "0");
     assert_eq!(json["net_flow"], "0");
+    assert_eq!(json["direction"], "neutral");
     assert_eq!(json["last_processed_block"], 0);
 }
 
@@ -375,6 +563,28 @@ async fn test_status_endpoint_with_empty_database() {
     assert_eq!(json["last_processed_block"], 0);
     assert_eq!(json["total_transactions"], 0);
     assert_eq!(json["database_status"], "connected");
+    assert!(json["indexed_from"].is_null());
+    assert!(json["indexed_to"].is_null());
+    // No block monitor in this process, so no pipeline to report
+    assert!(json.get("pipeline").is_none());
+}
+
+#[tokio::test]
+async fn test_status_reports_pipeline_occupancy() {
+    use polygon_pol_indexer::blockchain::PipelineOccupancy;
+
+    let database = Arc::new(Database::new_in_memory().expect("Failed to create test database"));
+    let occupancy = Arc::new(PipelineOccupancy::default());
+    let app = create_test_router_with_state(AppState::new(database).with_pipeline_occupancy(occupancy));
+
+    let request = Request::builder().uri("/status").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["pipeline"]["buffered_blocks"], 0);
+    assert_eq!(json["pipeline"]["capacity"], 0);
 }
 
 #[tokio::test]
@@ -438,4 +648,595 @@ async fn test_invalid_endpoint() {
     let response = app.oneshot(request).await.unwrap();
 
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_ready_endpoint_flips_to_unavailable_after_staleness_threshold() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let database = setup_test_database().await;
+    let last_updated = database.get_net_flow_data().unwrap().last_updated;
+
+    // Injected clock starting at the moment the last block was processed
+    let now = Arc::new(AtomicU64::new(last_updated));
+    let clock_now = Arc::clone(&now);
+    let app_state = AppState::new(database)
+        .with_clock(Arc::new(move || clock_now.load(Ordering::SeqCst)))
+        .with_max_staleness_seconds(60);
+    let app = create_test_router_with_state(app_state);
+
+    let request = Request::builder().uri("/ready").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["ready"], true);
+    assert_eq!(json["seconds_since_last_block"], 0);
+    assert_eq!(json["max_staleness_seconds"], 60);
+
+    // Exactly at the threshold the service is still ready
+    now.store(last_updated + 60, Ordering::SeqCst);
+    let request = Request::builder().uri("/ready").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Past the threshold readiness fails
+    now.store(last_updated + 61, Ordering::SeqCst);
+    let request = Request::builder().uri("/ready").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "stale");
+
+    // The status endpoint reports the same gauge
+    let request = Request::builder().uri("/status").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["seconds_since_last_block"], 61);
+}
+
+#[tokio::test]
+async fn test_get_net_flow_range_endpoint() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    // Blocks 101..=102 exclude the 100050 inflow at block 100
+    let request = Request::builder()
+        .uri("/net-flow/range?from=101&to=102")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["from_block"], 101);
+    assert_eq!(json["to_block"], 102);
+    assert_eq!(json["total_inflow"], "250000");
+    assert_eq!(json["total_outflow"], "50025");
+    assert_eq!(json["net_flow"], "199975");
+}
+
+#[tokio::test]
+async fn test_get_net_flow_range_endpoint_rejects_inverted_range() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri("/net-flow/range?from=102&to=100")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "INVALID_PARAM");
+}
+
+#[tokio::test]
+async fn test_admin_reprocess_endpoint() {
+    let database = setup_test_database().await;
+    database.record_skipped_block(150, "RPC error: header not found").unwrap();
+    database.record_skipped_block(151, "RPC error: header not found").unwrap();
+    let app = create_test_router(Arc::clone(&database));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/reprocess?block=150")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["queued"], 1);
+    assert_eq!(json["block"], 150);
+    assert_eq!(database.get_reprocess_requested_blocks().unwrap().len(), 1);
+
+    // Unknown blocks are reported as not found
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/reprocess?block=999")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Without a block parameter every skipped block is queued
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/reprocess")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["queued"], 2);
+    assert!(json["block"].is_null());
+}
+
+#[tokio::test]
+async fn test_get_net_flow_rolling_endpoint() {
+    // Test data timestamps are 1640995200..=1640995320; a 120s window ending at
+    // 1640995330 excludes the first transfer only
+    let database = setup_test_database().await;
+    let app = create_test_router_with_state(
+        AppState::new(database).with_clock(Arc::new(|| 1_640_995_330)),
+    );
+
+    let request = Request::builder()
+        .uri("/net-flow/rolling?window=120")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["window_seconds"], 120);
+    assert_eq!(json["from_timestamp"], 1_640_995_210u64);
+    assert_eq!(json["total_inflow"], "250000");
+    assert_eq!(json["total_outflow"], "50025");
+    assert_eq!(json["net_flow"], "199975");
+
+    let request = Request::builder()
+        .uri("/net-flow/rolling?window=0")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_address_counterparties_endpoint() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .uri("/address/0xF977814e90dA44bFA03b6295A0616a897441aceC/counterparties?direction=inflow")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["direction"], "inflow");
+    let counterparties = json["counterparties"].as_array().unwrap();
+    assert_eq!(counterparties.len(), 1);
+    assert_eq!(counterparties[0]["address"], "5e4de15e4de15e4de15e4de15e4de15e4de15e4d");
+    assert_eq!(counterparties[0]["total_amount"], "100050");
+
+    // Unknown directions are rejected
+    let request = Request::builder()
+        .uri("/address/0xf977814e90da44bfa03b6295a0616a897441acec/counterparties?direction=both")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_ui_page() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let content_type = response.headers()["content-type"].to_str().unwrap().to_string();
+    assert!(content_type.starts_with("text/html"), "unexpected content type {}", content_type);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    for endpoint in ["\"/status\"", "\"/net-flow\"", "\"/transactions?limit="] {
+        assert!(html.contains(endpoint), "page does not fetch {}", endpoint);
+    }
+}
+
+#[tokio::test]
+async fn test_get_flow_series_endpoint() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    // One-minute buckets over the three seeded transfers
+    let request = Request::builder()
+        .uri("/flow/series?interval=60&from=1640995200&to=1640995379")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["interval_seconds"], 60);
+    assert_eq!(
+        json["points"],
+        serde_json::json!([
+            {"bucket": 1640995200, "cumulative_inflow": "100050", "cumulative_outflow": "0"},
+            {"bucket": 1640995260, "cumulative_inflow": "100050", "cumulative_outflow": "50025"},
+            {"bucket": 1640995320, "cumulative_inflow": "350050", "cumulative_outflow": "50025"},
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_get_flow_series_endpoint_rejects_invalid_parameters() {
+    let database = setup_test_database().await;
+
+    for uri in [
+        "/flow/series?interval=0&from=0&to=10",
+        "/flow/series?from=10&to=0",
+        "/flow/series?interval=1&from=0&to=100000",
+    ] {
+        let app = create_test_router(Arc::clone(&database));
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+async fn get_json(app: Router, uri: &str) -> Value {
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_units_wei_is_the_default() {
+    let app = create_test_router(setup_test_database().await);
+
+    for uri in ["/net-flow", "/net-flow?units=wei"] {
+        let json = get_json(app.clone(), uri).await;
+        assert_eq!(json["total_inflow"], "350050");
+        assert_eq!(json["total_outflow"], "50025");
+        assert_eq!(json["net_flow"], "300025");
+        assert!(json.get("net_flow_pol").is_none());
+    }
+
+    for uri in ["/transactions", "/transactions?units=wei"] {
+        let json = get_json(app.clone(), uri).await;
+        let newest = &json["transactions"][0];
+        assert_eq!(newest["amount"], "250000");
+        assert!(newest.get("amount_pol").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_units_pol_returns_decimal_amounts_only() {
+    let app = create_test_router(setup_test_database().await);
+
+    let json = get_json(app.clone(), "/net-flow?units=pol").await;
+    assert_eq!(json["total_inflow_pol"], "0.00000000000035005");
+    assert_eq!(json["total_outflow_pol"], "0.000000000000050025");
+    assert_eq!(json["net_flow_pol"], "0.000000000000300025");
+    assert!(json.get("total_inflow").is_none());
+    assert!(json.get("net_flow").is_none());
+    assert_eq!(json["direction"], "inflow");
+
+    let json = get_json(app, "/transactions?units=pol").await;
+    let newest = &json["transactions"][0];
+    assert_eq!(newest["amount_pol"], "0.00000000000025");
+    assert!(newest.get("amount").is_none());
+}
+
+#[tokio::test]
+async fn test_units_pol_formats_one_wei_negative_net_flow() {
+    let database = Arc::new(Database::new_in_memory().unwrap());
+    for (block_number, amount, direction) in [
+        (100, "1000000000000000000", TransferDirection::ToBinance),
+        (101, "1000000000000000001", TransferDirection::FromBinance),
+    ] {
+        database
+            .store_transfer_and_update_net_flow(&ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0xunits{}", block_number),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: "0xsender1".to_string(),
+                to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
+                amount: amount.to_string(),
+                timestamp: 1640995200,
+                direction,
+            })
+            .unwrap();
+    }
+
+    let json = get_json(create_test_router(database), "/net-flow?units=pol").await;
+    assert_eq!(json["total_inflow_pol"], "1");
+    assert_eq!(json["total_outflow_pol"], "1.000000000000000001");
+    assert_eq!(json["net_flow_pol"], "-0.000000000000000001");
+    assert_eq!(json["direction"], "outflow");
+}
+
+#[tokio::test]
+async fn test_units_both_returns_wei_and_pol() {
+    let database = Arc::new(Database::new_in_memory_with_amount_storage(AmountStorage::Decimal).unwrap());
+    database
+        .store_transfer_and_update_net_flow(&ProcessedTransfer {
+            block_number: 100,
+            transaction_hash: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
+            log_index: 0,
+            transaction_index: 0,
+            from_address: "0xe7804c37c13166ff0b37f5ae0bb07a3aebb6e245".to_string(), // Binance
+            to_address: "0xreceiver1".to_string(),
+            amount: "1500000000000000000".to_string(),
+            timestamp: 1640995200,
+            direction: TransferDirection::FromBinance,
+        })
+        .unwrap();
+    let app = create_test_router(database);
+
+    // Amounts stored as decimal POL still come back as exact wei
+    let json = get_json(app.clone(), "/net-flow?units=both").await;
+    assert_eq!(json["total_outflow"], "1500000000000000000");
+    assert_eq!(json["total_outflow_pol"], "1.5");
+    assert_eq!(json["net_flow"], "-1500000000000000000");
+    assert_eq!(json["net_flow_pol"], "-1.5");
+
+    let json = get_json(app, "/transactions?units=both").await;
+    let transaction = &json["transactions"][0];
+    assert_eq!(transaction["amount"], "1500000000000000000");
+    assert_eq!(transaction["amount_pol"], "1.5");
+}
+
+#[tokio::test]
+async fn test_units_rejects_unknown_unit() {
+    let app = create_test_router(setup_test_database().await);
+
+    let response = app
+        .oneshot(Request::builder().uri("/net-flow?units=gwei").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_process_block_without_processor_is_unavailable() {
+    let database = setup_test_database().await;
+    let app = create_test_router(database);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/process-block/500")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_pretty_query_indents_json_and_compact_is_the_default() {
+    let database = setup_test_database().await;
+
+    let get_body = |app: Router, uri: &'static str| async move {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    };
+
+    let compact = get_body(create_test_router(Arc::clone(&database)), "/net-flow").await;
+    assert!(!compact.contains('\n'));
+    assert!(compact.starts_with("{\"total_inflow\":\"350050\","));
+
+    // age_seconds may tick over between requests
+    let without_age = |body: &str| {
+        let mut value = serde_json::from_str::<Value>(body).unwrap();
+        value.as_object_mut().unwrap().remove("age_seconds");
+        value
+    };
+
+    let pretty = get_body(create_test_router(Arc::clone(&database)), "/net-flow?pretty=true").await;
+    assert!(pretty.starts_with("{\n  \"total_inflow\": \"350050\",\n  \"total_outflow\""));
+    assert_eq!(without_age(&pretty), without_age(&compact));
+
+    // The configured default can be overridden per request
+    let state = AppState::new(Arc::clone(&database)).with_pretty_json(true);
+    assert!(get_body(create_test_router_with_state(state.clone()), "/net-flow").await.contains("\n  \"net_flow\": "));
+    let overridden = get_body(create_test_router_with_state(state), "/net-flow?pretty=false").await;
+    assert!(!overridden.contains('\n'));
+    assert_eq!(without_age(&overridden), without_age(&compact));
+
+    let request = Request::builder().uri("/net-flow?pretty=maybe").body(Body::empty()).unwrap();
+    let response = create_test_router(database).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_diagnostics_errors_lists_recorded_errors_by_count() {
+    use polygon_pol_indexer::error::{IndexerError, RpcError};
+    use polygon_pol_indexer::ErrorRecoveryManager;
+
+    let manager = Arc::new(ErrorRecoveryManager::new());
+    let timeout = IndexerError::Rpc(RpcError::Timeout { seconds: 30 });
+    let auth = IndexerError::Rpc(RpcError::Authentication);
+    let invalid = IndexerError::Rpc(RpcError::InvalidResponse("truncated".to_string()));
+    for (error, times) in [(&auth, 1), (&timeout, 3), (&invalid, 2)] {
+        for _ in 0..times {
+            manager.record_error(error, "test");
+        }
+    }
+
+    let state = AppState::new(setup_test_database().await)
+        .with_clock(Arc::new(|| 1_700_000_000))
+        .with_error_recovery_manager(manager);
+    let request = Request::builder().uri("/diagnostics/errors").body(Body::empty()).unwrap();
+    let response = create_test_router_with_state(state).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let errors = json["errors"].as_array().unwrap();
+    let counts: Vec<u64> = errors.iter().map(|error| error["count"].as_u64().unwrap()).collect();
+    assert_eq!(counts, vec![3, 2, 1]);
+    assert!(errors[0]["error_type"].as_str().unwrap().contains("Timeout"));
+    assert!(errors[2]["error_type"].as_str().unwrap().contains("Authentication"));
+    for error in errors {
+        assert_eq!(error["first_seen"], 1_700_000_000);
+        assert_eq!(error["last_seen"], 1_700_000_000);
+        assert!(error["frequency"].as_f64().unwrap() > 0.0);
+    }
+
+    // Without a block monitor there is nothing to report
+    let request = Request::builder().uri("/diagnostics/errors").body(Body::empty()).unwrap();
+    let response = create_test_router(setup_test_database().await).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_read_only_api_serves_reads_and_forbids_admin_writes() {
+    let database = setup_test_database().await;
+    database.record_skipped_block(150, "RPC error: header not found").unwrap();
+    let app = create_test_router_with_state(AppState::new(Arc::clone(&database)).with_read_only(true));
+
+    let request = Request::builder().uri("/net-flow").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for uri in ["/admin/reprocess?block=150", "/admin/process-block/150"] {
+        let request = Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", uri);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "forbidden");
+    }
+    assert!(database.get_reprocess_requested_blocks().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_transactions_endpoint_labels_watched_addresses() {
+    // Stored addresses are bare lowercase hex; only one of the two exchange
+    // addresses is watched, so the transfer to 505e... runs between two unlabeled addresses
+    let database = Database::new_in_memory().unwrap();
+    for (block_number, from_address, to_address) in [
+        (100, "5e4de15e4de15e4de15e4de15e4de15e4de15e4d", "f977814e90da44bfa03b6295a0616a897441acec"),
+        (102, "5e4de25e4de25e4de25e4de25e4de25e4de25e4d", "505e71695e9bc45943c58adec1650577bca68fd9"),
+    ] {
+        database
+            .store_transfer_and_update_net_flow(&ProcessedTransfer {
+                block_number,
+                transaction_hash: format!("0xlabels{}", block_number),
+                log_index: 0,
+                transaction_index: 0,
+                from_address: from_address.to_string(),
+                to_address: to_address.to_string(),
+                amount: "100050".to_string(),
+                timestamp: 1640995200,
+                direction: TransferDirection::ToBinance,
+            })
+            .unwrap();
+    }
+    let state = AppState::new(Arc::new(database)).with_watched_addresses(vec![WatchedAddress {
+        address: "0xF977814e90dA44bFA03b6295A0616a897441aceC".to_string(),
+        label: "Binance Hot Wallet 1".to_string(),
+    }]);
+
+    let get_json = |app: Router| async move {
+        let request = Request::builder().uri("/transactions").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    let json = get_json(create_test_router_with_state(state.clone())).await;
+    let by_block = |block: u64| {
+        json["transactions"].as_array().unwrap().iter()
+            .find(|tx| tx["block_number"] == block)
+            .unwrap()
+            .clone()
+    };
+
+    let labeled = by_block(100);
+    assert_eq!(labeled["from_label"], Value::Null);
+    assert_eq!(labeled["to_label"], "Binance Hot Wallet 1");
+
+    let unlabeled = by_block(102);
+    assert!(unlabeled.as_object().unwrap().contains_key("from_label"));
+    assert_eq!(unlabeled["from_label"], Value::Null);
+    assert_eq!(unlabeled["to_label"], Value::Null);
+
+    // Disabled labels drop the fields instead of reporting null
+    let json = get_json(create_test_router_with_state(state.with_address_labels(false))).await;
+    assert!(json["transactions"].as_array().unwrap().iter().all(|tx| tx.get("to_label").is_none()));
+}
+
+#[tokio::test]
+async fn test_net_flow_audit_endpoint_reports_drift() {
+    let database = setup_test_database().await;
+    let audit = |database: Arc<Database>| async move {
+        let request = Request::builder().uri("/diagnostics/net-flow-audit").body(Body::empty()).unwrap();
+        let response = create_test_router(database).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    let json = audit(Arc::clone(&database)).await;
+    assert_eq!(json["consistent"], true);
+    assert_eq!(json["recomputed_inflow"], "350050");
+    assert_eq!(json["net_flow_drift"], "0");
+
+    database.update_net_flow_outflow("25").unwrap();
+    let json = audit(database).await;
+    assert_eq!(json["consistent"], false);
+    assert_eq!(json["outflow_drift"], "25");
+    assert_eq!(json["net_flow_drift"], "-25");
+}
@@ -47,6 +47,13 @@ async fn test_block_monitor_initialization() {
         max_retries: 3,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 10,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
@@ -70,6 +77,13 @@ async fn test_block_monitor_state_persistence() {
         max_retries: 1,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 5,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     // Test state persistence directly on database first
@@ -101,6 +115,13 @@ async fn test_block_monitor_graceful_shutdown() {
         max_retries: 1,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 5,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
@@ -159,6 +180,13 @@ async fn test_block_monitor_error_handling() {
         max_retries: 2,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 5,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
@@ -197,6 +225,7 @@ async fn test_block_monitor_database_integration() {
         block_number: 1000,
         transaction_hash: "0xtest123".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0x1111111111111111111111111111111111111111".to_string(),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance address
         amount: "1000000000000000000".to_string(), // 1 POL
@@ -242,6 +271,13 @@ async fn test_block_monitor_configuration_validation() {
         max_retries: 3,
         retry_delay_seconds: 2,
         max_retry_delay_seconds: 30,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let rpc_client = RpcClient::new("http://test".to_string());
@@ -322,6 +358,7 @@ async fn test_complete_monitoring_workflow() {
             block_number: 1001,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1111111111111111111111111111111111111111".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             amount: "2000000000000000000".to_string(), // 2 POL
@@ -332,6 +369,7 @@ async fn test_complete_monitoring_workflow() {
             block_number: 1002,
             transaction_hash: "0xdef456".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             to_address: "0x2222222222222222222222222222222222222222".to_string(),
             amount: "500000000000000000".to_string(), // 0.5 POL
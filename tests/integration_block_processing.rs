@@ -97,6 +97,8 @@ fn create_mock_block_logs() -> Vec<RawLog> {
             block_number: 12345,
             transaction_hash: "0xabc123".to_string(),
             log_index: 0,
+            transaction_index: 0,
+            removed: false,
         },
         // POL transfer: Binance -> Other (Outflow)
         RawLog {
@@ -110,6 +112,8 @@ fn create_mock_block_logs() -> Vec<RawLog> {
             block_number: 12345,
             transaction_hash: "0xdef456".to_string(),
             log_index: 1,
+            transaction_index: 0,
+            removed: false,
         },
         // POL transfer: Other -> Another (Not relevant)
         RawLog {
@@ -123,6 +127,8 @@ fn create_mock_block_logs() -> Vec<RawLog> {
             block_number: 12345,
             transaction_hash: "0x789abc".to_string(),
             log_index: 2,
+            transaction_index: 0,
+            removed: false,
         },
         // Non-POL transfer (different contract)
         RawLog {
@@ -136,6 +142,8 @@ fn create_mock_block_logs() -> Vec<RawLog> {
             block_number: 12345,
             transaction_hash: "0x456def".to_string(),
             log_index: 3,
+            transaction_index: 0,
+            removed: false,
         },
     ]
 }
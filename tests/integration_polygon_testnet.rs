@@ -1,6 +1,6 @@
 use std::time::Duration;
 use tokio::time::timeout;
-use polygon_pol_indexer::blockchain::{RpcClient, BlockProcessor};
+use polygon_pol_indexer::blockchain::{BlockProcessor, BlockTransaction, RpcClient};
 use polygon_pol_indexer::database::Database;
 use polygon_pol_indexer::models::{ProcessedTransfer, TransferDirection};
 
@@ -54,14 +54,18 @@ async fn test_real_block_fetching() {
                 println!("Successfully fetched block {}: {} transactions", 
                     target_block, block.transactions.len());
                 
-                assert_eq!(block.number, target_block);
+                // Block fields are hex quantities as returned by the node
+                let parse_hex = |value: &str| u64::from_str_radix(value.trim_start_matches("0x"), 16).unwrap();
+                assert_eq!(parse_hex(&block.number), target_block);
                 assert!(!block.hash.is_empty());
-                assert!(block.timestamp > 0);
+                assert!(parse_hex(&block.timestamp) > 0);
                 
-                // Verify block structure
+                // Verify block structure; some providers return only transaction hashes
                 for tx in &block.transactions {
-                    assert!(!tx.hash.is_empty());
-                    assert!(!tx.from.is_empty());
+                    assert!(!tx.hash().is_empty());
+                    if let BlockTransaction::Full(transaction) = tx {
+                        assert!(!transaction.from.is_empty());
+                    }
                 }
             }
             Ok(Err(e)) => {
@@ -94,13 +98,16 @@ async fn test_real_pol_transfer_detection() {
         let mut binance_transfers = 0;
         
         for block_num in start_block..=end_block {
-            let result = timeout(Duration::from_secs(10), processor.extract_pol_transfers(block_num)).await;
+            let result = timeout(Duration::from_secs(10), processor.process_block_with_logs(block_num)).await;
             
             match result {
-                Ok(Ok(transfers)) => {
-                    total_transfers += transfers.len();
+                Ok(Ok(processed)) => {
+                    total_transfers += processed.logs.len();
                     
-                    for transfer in transfers {
+                    for log in &processed.logs {
+                        let Ok(transfer) = processor.transfer_detector().decode_transfer_log(log) else {
+                            continue;
+                        };
                         println!("Found POL transfer in block {}: {} -> {} ({})", 
                             block_num, transfer.from_address, transfer.to_address, transfer.amount);
                         
@@ -174,28 +181,32 @@ async fn test_real_data_validation() {
     if let Ok(Ok(latest_block)) = latest_result {
         let target_block = latest_block.saturating_sub(5);
         
-        let transfers_result = timeout(Duration::from_secs(15), processor.extract_pol_transfers(target_block)).await;
+        let processed_result = timeout(Duration::from_secs(15), processor.process_block_with_logs(target_block)).await;
         
-        if let Ok(Ok(transfers)) = transfers_result {
-            for transfer in transfers {
+        if let Ok(Ok(processed)) = processed_result {
+            assert!(processed.timestamp > 0, "Timestamp should be greater than 0");
+            for log in &processed.logs {
+                let transfer = processor
+                    .transfer_detector()
+                    .decode_transfer_log(log)
+                    .expect("Journaled POL logs should decode");
+
                 // Validate transfer data structure
                 assert!(!transfer.transaction_hash.is_empty(), "Transaction hash should not be empty");
                 assert!(transfer.transaction_hash.starts_with("0x"), "Transaction hash should start with 0x");
                 assert_eq!(transfer.transaction_hash.len(), 66, "Transaction hash should be 66 characters");
                 
-                assert!(!transfer.from_address.is_empty(), "From address should not be empty");
-                assert!(transfer.from_address.starts_with("0x"), "From address should start with 0x");
-                assert_eq!(transfer.from_address.len(), 42, "From address should be 42 characters");
+                // Decoded addresses are bare lowercase hex
+                assert_eq!(transfer.from_address.len(), 40, "From address should be 40 hex characters");
+                assert!(transfer.from_address.chars().all(|c| c.is_ascii_hexdigit()), "From address should be hex");
                 
-                assert!(!transfer.to_address.is_empty(), "To address should not be empty");
-                assert!(transfer.to_address.starts_with("0x"), "To address should start with 0x");
-                assert_eq!(transfer.to_address.len(), 42, "To address should be 42 characters");
+                assert_eq!(transfer.to_address.len(), 40, "To address should be 40 hex characters");
+                assert!(transfer.to_address.chars().all(|c| c.is_ascii_hexdigit()), "To address should be hex");
                 
                 assert!(!transfer.amount.is_empty(), "Amount should not be empty");
                 assert!(transfer.amount.parse::<u128>().is_ok(), "Amount should be a valid number");
                 
                 assert!(transfer.block_number > 0, "Block number should be greater than 0");
-                assert!(transfer.timestamp > 0, "Timestamp should be greater than 0");
                 
                 println!("Validated transfer: {} POL from {} to {}", 
                     transfer.amount, transfer.from_address, transfer.to_address);
@@ -219,6 +230,7 @@ async fn test_known_pol_transactions() {
             block_number: 40000000, // Realistic Mumbai block number
             transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x742d35cc6634c0532925a3b8d0c9e3e0c0c0c0c0".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance
             amount: "1000000000000000000".to_string(), // 1 POL
@@ -229,6 +241,7 @@ async fn test_known_pol_transactions() {
             block_number: 40000001,
             transaction_hash: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(), // Binance
             to_address: "0x742d35cc6634c0532925a3b8d0c9e3e0c0c0c0c0".to_string(),
             amount: "500000000000000000".to_string(), // 0.5 POL
@@ -1,6 +1,9 @@
+use polygon_pol_indexer::api::http::serve_with_graceful_shutdown;
 use polygon_pol_indexer::api::ApiServer;
 use polygon_pol_indexer::database::Database;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_api_server_creation() {
@@ -22,4 +25,75 @@ async fn test_api_server_with_different_port() {
     
     let server = ApiServer::new(database, 3000);
     assert_eq!(server.port, 3000);
-}
\ No newline at end of file
+}
+/// Start `app` on an ephemeral port; the server stops once `shutdown` is set
+async fn spawn_server(
+    app: axum::Router,
+    shutdown: Arc<std::sync::atomic::AtomicBool>,
+    drain_timeout: Duration,
+) -> (std::net::SocketAddr, tokio::task::JoinHandle<Result<(), polygon_pol_indexer::api::ApiError>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let signal = async move {
+        while !shutdown.load(Ordering::Relaxed) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let handle = tokio::spawn(serve_with_graceful_shutdown(listener, app, signal, drain_timeout));
+    (addr, handle)
+}
+
+fn slow_router(delay: Duration) -> axum::Router {
+    axum::Router::new().route(
+        "/slow",
+        axum::routing::get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_graceful_shutdown_drains_in_flight_requests() {
+    let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let (addr, server) = spawn_server(slow_router(Duration::from_millis(800)), Arc::clone(&shutdown), Duration::from_secs(5)).await;
+
+    let in_flight = tokio::spawn(async move { reqwest::get(format!("http://{}/slow", addr)).await });
+
+    // Shut down while the slow request is being handled
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    shutdown.store(true, Ordering::Relaxed);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // The listener is closed, so new connections are refused
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+
+    // The in-flight request still completes
+    let response = in_flight.await.unwrap().expect("in-flight request should complete");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "done");
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server should stop once drained")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_graceful_shutdown_drain_is_bounded() {
+    let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let (addr, server) = spawn_server(slow_router(Duration::from_secs(30)), Arc::clone(&shutdown), Duration::from_millis(200)).await;
+
+    let in_flight = tokio::spawn(async move { reqwest::get(format!("http://{}/slow", addr)).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.store(true, Ordering::Relaxed);
+
+    // A request outliving the drain timeout does not hold the server open
+    tokio::time::timeout(Duration::from_secs(3), server)
+        .await
+        .expect("server should stop after the drain timeout")
+        .unwrap()
+        .unwrap();
+    in_flight.abort();
+}
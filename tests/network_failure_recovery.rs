@@ -118,6 +118,13 @@ async fn test_block_monitor_retry_logic() {
         max_retries: 3,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 5,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database, Some(config));
@@ -220,6 +227,7 @@ async fn test_database_resilience_during_network_failures() {
         block_number: 1000,
         transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0x1111111111111111111111111111111111111111".to_string(),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         amount: "1000000000000000000".to_string(),
@@ -243,6 +251,7 @@ async fn test_database_resilience_during_network_failures() {
             block_number: 1001,
             transaction_hash: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             to_address: "0x2222222222222222222222222222222222222222".to_string(),
             amount: "500000000000000000".to_string(),
@@ -282,6 +291,13 @@ async fn test_prolonged_network_instability() {
         max_retries: 2,
         retry_delay_seconds: 1,
         max_retry_delay_seconds: 3,
+        fail_fast: false,
+        ordered_commits: true,
+        pipeline_buffer_size: polygon_pol_indexer::blockchain::DEFAULT_PIPELINE_CAPACITY,
+        initial_lookback_blocks: 0,
+        min_confirmations: 0,
+        snapshot_every_n_blocks: 1,
+        max_consecutive_skips: 0,
     };
     
     let monitor = BlockMonitor::new(rpc_client, block_processor, database.clone(), Some(config));
@@ -366,6 +382,7 @@ async fn test_graceful_degradation() {
             block_number: 1000,
             transaction_hash: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x1111111111111111111111111111111111111111".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             amount: "1000000000000000000".to_string(),
@@ -392,6 +409,7 @@ async fn test_graceful_degradation() {
         block_number: 1001,
         transaction_hash: "0x2222222222222222222222222222222222222222222222222222222222222222".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         to_address: "0x3333333333333333333333333333333333333333".to_string(),
         amount: "500000000000000000".to_string(),
@@ -428,6 +446,7 @@ async fn test_recovery_after_extended_outage() {
         block_number: 1000,
         transaction_hash: "0x1000000000000000000000000000000000000000000000000000000000000000".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0x1111111111111111111111111111111111111111".to_string(),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         amount: "1000000000000000000".to_string(),
@@ -452,6 +471,7 @@ async fn test_recovery_after_extended_outage() {
             block_number: 1001,
             transaction_hash: "0x1001000000000000000000000000000000000000000000000000000000000000".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             to_address: "0x2222222222222222222222222222222222222222".to_string(),
             amount: "300000000000000000".to_string(),
@@ -462,6 +482,7 @@ async fn test_recovery_after_extended_outage() {
             block_number: 1002,
             transaction_hash: "0x1002000000000000000000000000000000000000000000000000000000000000".to_string(),
             log_index: 0,
+            transaction_index: 0,
             from_address: "0x3333333333333333333333333333333333333333".to_string(),
             to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
             amount: "2000000000000000000".to_string(),
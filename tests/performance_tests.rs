@@ -270,6 +270,7 @@ async fn test_database_benchmarks() {
         block_number: 1000,
         transaction_hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
         log_index: 0,
+        transaction_index: 0,
         from_address: "0x1111111111111111111111111111111111111111".to_string(),
         to_address: "0xf977814e90da44bfa03b6295a0616a897441acec".to_string(),
         amount: "1000000000000000000".to_string(),
@@ -328,6 +329,7 @@ fn generate_test_transfers(count: usize) -> Vec<ProcessedTransfer> {
                 block_number: 1000 + i as u64,
                 transaction_hash: format!("0x{:064x}", i),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: format!("0x{:040x}", i),
                 to_address: if matches!(direction, TransferDirection::ToBinance) {
                     "0xf977814e90da44bfa03b6295a0616a897441acec".to_string()
@@ -360,6 +362,7 @@ fn generate_test_transfers_with_offset(count: usize, offset: usize) -> Vec<Proce
                 block_number: 1000 + idx as u64,
                 transaction_hash: format!("0x{:064x}", idx),
                 log_index: 0,
+                transaction_index: 0,
                 from_address: format!("0x{:040x}", idx),
                 to_address: if matches!(direction, TransferDirection::ToBinance) {
                     "0xf977814e90da44bfa03b6295a0616a897441acec".to_string()
@@ -389,6 +392,7 @@ fn generate_block_transfers(block_number: u64, count: usize) -> Vec<ProcessedTra
                 block_number,
                 transaction_hash: format!("0x{:064x}", block_number * 1000 + i as u64),
                 log_index: i as u32,
+                transaction_index: 0,
                 from_address: format!("0x{:040x}", block_number + i as u64),
                 to_address: if matches!(direction, TransferDirection::ToBinance) {
                     "0xf977814e90da44bfa03b6295a0616a897441acec".to_string()